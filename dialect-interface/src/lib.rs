@@ -1,9 +1,17 @@
 use std::cell::RefCell;
 use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::rc::Rc;
 
 use tree_sitter::{InputEdit, Point, Tree};
 
+use mongodb_query_language::execution::Execution;
+use mongodb_query_language::schema::{DocumentSchema, SchemaViolation};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::ValueType;
+
 use crate::FileResourceChange::{Full, Range};
 
 struct FileResourceChangeRangePosition {
@@ -44,6 +52,7 @@ pub enum FileResourceChange {
 #[derive(Clone)]
 pub struct FileResource {
     source: String,
+    version: i32,
     tree: RefCell<Tree>,
     parser: Rc<dyn DialectParser>,
 }
@@ -53,6 +62,173 @@ pub trait DialectParser {
     fn reparse(&self, contents: &String, original: RefCell<Tree>) -> RefCell<Tree>;
 }
 
+#[derive(PartialEq, Debug, Clone)]
+pub enum ExtractionWarning {
+    UnresolvedCollection {
+        reference: String,
+        span: Span,
+    },
+    UnsupportedOperator {
+        operator: String,
+        span: Span,
+    },
+    UnresolvedFilter {
+        expression: String,
+        span: Span,
+    },
+    UnresolvedStage {
+        expression: String,
+        span: Span,
+    },
+    UnresolvedUpdate {
+        expression: String,
+        span: Span,
+    },
+    UnknownField {
+        field: String,
+        document: String,
+        span: Span,
+    },
+    MismatchedFieldType {
+        field: String,
+        expected: ValueType,
+        actual: ValueType,
+        span: Span,
+    },
+}
+
+impl ExtractionWarning {
+    pub fn span(&self) -> Span {
+        return match self {
+            ExtractionWarning::UnresolvedCollection { span, .. }
+            | ExtractionWarning::UnsupportedOperator { span, .. }
+            | ExtractionWarning::UnresolvedFilter { span, .. }
+            | ExtractionWarning::UnresolvedStage { span, .. }
+            | ExtractionWarning::UnresolvedUpdate { span, .. }
+            | ExtractionWarning::UnknownField { span, .. }
+            | ExtractionWarning::MismatchedFieldType { span, .. } => *span,
+        };
+    }
+
+    pub fn from_violation(violation: SchemaViolation, document: &str) -> ExtractionWarning {
+        return match violation {
+            SchemaViolation::UnknownField { field, span } => ExtractionWarning::UnknownField {
+                field,
+                document: document.to_string(),
+                span,
+            },
+            SchemaViolation::MismatchedType {
+                field,
+                expected,
+                actual,
+                span,
+            } => ExtractionWarning::MismatchedFieldType {
+                field,
+                expected,
+                actual,
+                span,
+            },
+        };
+    }
+}
+
+impl Display for ExtractionWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ExtractionWarning::UnresolvedCollection { reference, .. } => {
+                write!(f, "could not resolve collection `{}`", reference)
+            }
+            ExtractionWarning::UnsupportedOperator { operator, .. } => {
+                write!(f, "unsupported operator `{}`", operator)
+            }
+            ExtractionWarning::UnresolvedFilter { expression, .. } => {
+                write!(f, "could not resolve filter `{}`", expression)
+            }
+            ExtractionWarning::UnresolvedStage { expression, .. } => {
+                write!(f, "could not resolve stage `{}`", expression)
+            }
+            ExtractionWarning::UnresolvedUpdate { expression, .. } => {
+                write!(f, "could not resolve update `{}`", expression)
+            }
+            ExtractionWarning::UnknownField {
+                field, document, ..
+            } => {
+                write!(f, "field `{}` is not declared in `{}`", field, document)
+            }
+            ExtractionWarning::MismatchedFieldType {
+                field,
+                expected,
+                actual,
+                ..
+            } => {
+                write!(
+                    f,
+                    "field `{}` holds `{}` but is compared with `{}`",
+                    field,
+                    expected.alias(),
+                    actual.alias()
+                )
+            }
+        };
+    }
+}
+
+#[derive(PartialEq, Debug, Default)]
+pub struct Extraction {
+    pub executions: Vec<Execution>,
+    pub warnings: Vec<ExtractionWarning>,
+    pub schemas: HashMap<String, DocumentSchema>,
+}
+
+impl Extraction {
+    pub fn append(&mut self, other: &mut Extraction) {
+        self.executions.append(&mut other.executions);
+        self.warnings.append(&mut other.warnings);
+        self.schemas.extend(other.schemas.drain());
+    }
+}
+
+pub trait DialectExtractor {
+    fn extract(&self, file: &FileResource) -> Result<Extraction, Box<dyn Error + Sync + Send>>;
+
+    fn index(&self, _path: &String, _file: &FileResource) {}
+
+    fn forget(&self, _path: &String) {}
+}
+
+pub struct CombinedExtractor {
+    extractors: Vec<Rc<dyn DialectExtractor>>,
+}
+
+impl CombinedExtractor {
+    pub fn of(extractors: Vec<Rc<dyn DialectExtractor>>) -> Rc<dyn DialectExtractor> {
+        return Rc::new(CombinedExtractor { extractors });
+    }
+}
+
+impl DialectExtractor for CombinedExtractor {
+    fn extract(&self, file: &FileResource) -> Result<Extraction, Box<dyn Error + Sync + Send>> {
+        let mut result = Extraction::default();
+        for extractor in &self.extractors {
+            result.append(&mut extractor.extract(file)?);
+        }
+
+        return Ok(result);
+    }
+
+    fn index(&self, path: &String, file: &FileResource) {
+        for extractor in &self.extractors {
+            extractor.index(path, file);
+        }
+    }
+
+    fn forget(&self, path: &String) {
+        for extractor in &self.extractors {
+            extractor.forget(path);
+        }
+    }
+}
+
 fn resolve_byte_position(contents: &String, points: [&Point; 3]) -> [usize; 3] {
     let mut pos: [usize; 3] = [0, 0, 0];
     let mut row: usize = 0;
@@ -148,18 +324,24 @@ fn apply_changes_to_string(
 }
 
 impl FileResource {
-    pub fn new(contents: &String, parser: Rc<dyn DialectParser>) -> RefCell<FileResource> {
+    pub fn new(
+        contents: &String,
+        version: i32,
+        parser: Rc<dyn DialectParser>,
+    ) -> RefCell<FileResource> {
         let base_tree = parser.full_parse(contents);
 
         return RefCell::new(FileResource {
             source: contents.to_owned(),
+            version,
             tree: base_tree,
             parser: Rc::clone(&parser),
         });
     }
 
-    pub fn update(&mut self, changes: &[FileResourceChange]) -> Box<FileResource> {
+    pub fn update(&mut self, changes: &[FileResourceChange], version: i32) -> Box<FileResource> {
         let (maybe_edit, new_source) = apply_changes_to_string(&self.source, changes);
+        self.version = version;
         match maybe_edit {
             Some(edit) => {
                 self.tree.borrow_mut().edit(&edit);
@@ -178,6 +360,18 @@ impl FileResource {
     pub fn tree(&self) -> RefCell<Tree> {
         return RefCell::clone(&self.tree);
     }
+
+    pub fn source(&self) -> &String {
+        return &self.source;
+    }
+
+    pub fn version(&self) -> i32 {
+        return self.version;
+    }
+
+    pub fn parser(&self) -> Rc<dyn DialectParser> {
+        return Rc::clone(&self.parser);
+    }
 }
 
 #[cfg(test)]
@@ -228,7 +422,7 @@ mod tests {
     #[test]
     fn can_parse_fully_a_file() {
         let java: Rc<dyn DialectParser> = Rc::new(Java::new());
-        let file = FileResource::new(&"class MyClass {}".to_string(), Rc::clone(&java));
+        let file = FileResource::new(&"class MyClass {}".to_string(), 0, Rc::clone(&java));
 
         assert_eq!("class MyClass {}", file.borrow().source);
     }
@@ -236,16 +430,18 @@ mod tests {
     #[test]
     fn can_do_full_edit_of_a_file() {
         let java: Rc<dyn DialectParser> = Rc::new(Java::new());
-        let file = FileResource::new(&"class MyClass {}".to_string(), Rc::clone(&java));
-        file.borrow_mut().update(&[Full("class Y {}".to_string())]);
+        let file = FileResource::new(&"class MyClass {}".to_string(), 0, Rc::clone(&java));
+        file.borrow_mut()
+            .update(&[Full("class Y {}".to_string())], 1);
 
         assert_eq!("class Y {}", file.borrow().source);
+        assert_eq!(1, file.borrow().version());
     }
 
     #[test]
     fn can_do_an_incrementa_edit_of_file() {
         let java: Rc<dyn DialectParser> = Rc::new(Java::new());
-        let file = FileResource::new(&"class MyClass {}".to_string(), Rc::clone(&java));
+        let file = FileResource::new(&"class MyClass {}".to_string(), 0, Rc::clone(&java));
         let start = FileResourceChangeRangePosition { column: 15, row: 0 };
 
         let end = FileResourceChangeRangePosition { column: 14, row: 0 };
@@ -255,7 +451,7 @@ mod tests {
             "private int X;".to_string(),
         );
 
-        file.borrow_mut().update(&[change]);
+        file.borrow_mut().update(&[change], 1);
 
         assert_eq!("class MyClass {private int X;}", file.borrow().source);
     }
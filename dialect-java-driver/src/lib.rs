@@ -1,12 +1,16 @@
-use crate::use_cases::find_one::find_one;
-use dialect_interface::DialectParser;
-use mongodb_query_language::execution::{Execution, ExecutionProcessor};
+use crate::symbol_index::{with_symbols, SymbolIndex};
+use crate::use_cases::aggregate::aggregate;
+use crate::use_cases::ddl::ddl;
+use crate::use_cases::find::find;
+use crate::use_cases::update::update;
+use dialect_interface::{DialectExtractor, DialectParser, Extraction, FileResource};
 use std::cell::RefCell;
 use std::error::Error;
 use std::rc::Rc;
 use tree_sitter::{Parser, Tree};
 
-mod tree_ext;
+pub mod symbol_index;
+pub mod tree_ext;
 mod use_cases;
 
 pub struct Java {
@@ -24,17 +28,41 @@ impl Java {
             parser: RefCell::new(parser),
         });
     }
+
+    pub fn extractor() -> Rc<dyn DialectExtractor> {
+        return Java::extractor_with_symbols(SymbolIndex::shared());
+    }
+
+    pub fn extractor_with_symbols(symbols: Rc<RefCell<SymbolIndex>>) -> Rc<dyn DialectExtractor> {
+        return Rc::new(JavaExtractor { symbols });
+    }
+}
+
+pub struct JavaExtractor {
+    symbols: Rc<RefCell<SymbolIndex>>,
 }
 
-impl ExecutionProcessor for Java {
-    fn process(
-        tree: RefCell<Tree>,
-        code: &String,
-    ) -> Result<Vec<Execution>, Box<dyn Error + Sync + Send>> {
-        let mut result = vec![];
-        result.append(&mut find_one(tree, code)?);
+impl DialectExtractor for JavaExtractor {
+    fn extract(&self, file: &FileResource) -> Result<Extraction, Box<dyn Error + Sync + Send>> {
+        return with_symbols(&self.symbols, || {
+            let mut result = Extraction::default();
+            result.append(&mut find(file.tree(), file.source())?);
+            result.append(&mut aggregate(file.tree(), file.source())?);
+            result.append(&mut update(file.tree(), file.source())?);
+            result.append(&mut ddl(file.tree(), file.source())?);
+
+            Ok(result)
+        });
+    }
+
+    fn index(&self, path: &String, file: &FileResource) {
+        self.symbols
+            .borrow_mut()
+            .index(path, &file.tree().borrow(), file.source());
+    }
 
-        return Ok(result);
+    fn forget(&self, path: &String) {
+        self.symbols.borrow_mut().forget(path);
     }
 }
 
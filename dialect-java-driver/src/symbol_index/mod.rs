@@ -0,0 +1,747 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use mongodb_query_language::schema::DocumentSchema;
+use tree_sitter::{Node, Tree};
+
+use crate::tree_ext::document_schema_from_class::document_schema_of_class;
+use crate::tree_ext::entity_mapping::{entity_mapping, EntityMapping};
+use crate::tree_ext::infer_mongodb_namespace::{
+    infer_database_from_usage, infer_namespace_from_usage,
+};
+use crate::tree_ext::{
+    annotation_element, annotation_named, descendants_of_kind, field_from_expression,
+    optional_node_to_string, simple_type_name,
+};
+
+const TYPE_DECLARATIONS: [&str; 4] = [
+    "class_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "record_declaration",
+];
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Symbol {
+    Class {
+        name: String,
+        supertypes: Vec<String>,
+    },
+    Constant {
+        class: String,
+        name: String,
+        value: String,
+    },
+    Database {
+        class: String,
+        name: String,
+        database: String,
+    },
+    Collection {
+        class: String,
+        name: String,
+        database: Option<String>,
+        collection: String,
+    },
+    Bean {
+        name: String,
+        database: Option<String>,
+        collection: Option<String>,
+    },
+    Entity {
+        mapping: EntityMapping,
+    },
+    Schema {
+        schema: DocumentSchema,
+    },
+}
+
+pub enum SymbolReference {
+    Qualified(String),
+    Bare {
+        classes: Vec<String>,
+        static_imports: Vec<(String, Option<String>)>,
+    },
+}
+
+#[derive(Default)]
+pub struct SymbolIndex {
+    files: BTreeMap<String, Vec<Symbol>>,
+}
+
+thread_local! {
+    static ACTIVE_INDEX: RefCell<Option<Rc<RefCell<SymbolIndex>>>> = const { RefCell::new(None) };
+}
+
+pub fn with_symbols<T>(index: &Rc<RefCell<SymbolIndex>>, extraction: impl FnOnce() -> T) -> T {
+    let previous = ACTIVE_INDEX.with(|active| active.replace(Some(Rc::clone(index))));
+    let result = extraction();
+    ACTIVE_INDEX.with(|active| active.replace(previous));
+
+    return result;
+}
+
+fn lookup<T>(query: impl FnOnce(&SymbolIndex) -> Option<T>) -> Option<T> {
+    return ACTIVE_INDEX.with(|active| {
+        let active = active.borrow();
+        let index = active.as_ref()?.try_borrow().ok()?;
+        query(&index)
+    });
+}
+
+fn enclosing_declarations(node: Node) -> Vec<Node> {
+    let mut declarations = vec![];
+    let mut scope = node.parent();
+    while let Some(declaration) = scope {
+        if TYPE_DECLARATIONS.contains(&declaration.kind()) {
+            declarations.push(declaration);
+        }
+        scope = declaration.parent();
+    }
+
+    return declarations;
+}
+
+pub fn enclosing_class(node: Node, code: &String) -> Option<String> {
+    let declaration = enclosing_declarations(node).into_iter().next()?;
+    return Some(optional_node_to_string(
+        &declaration.child_by_field_name("name"),
+        code,
+    ));
+}
+
+fn static_imports(node: Node, code: &String) -> Vec<(String, Option<String>)> {
+    let mut root = node;
+    while let Some(parent) = root.parent() {
+        root = parent;
+    }
+
+    let mut imports = vec![];
+    descendants_of_kind(root, &["import_declaration"], &mut imports);
+    return imports
+        .into_iter()
+        .filter_map(|import| {
+            let text = optional_node_to_string(&Some(import), code);
+            let path = text
+                .trim()
+                .strip_prefix("import")?
+                .trim_start()
+                .strip_prefix("static ")?
+                .trim()
+                .trim_end_matches(';')
+                .trim()
+                .to_string();
+            let (owner, member) = path.rsplit_once('.')?;
+            Some((
+                simple_type_name(owner),
+                Some(member.to_string()).filter(|member| member != "*"),
+            ))
+        })
+        .collect();
+}
+
+fn symbol_reference(node: Node, code: &String) -> Option<(SymbolReference, String)> {
+    let bare = || SymbolReference::Bare {
+        classes: enclosing_declarations(node)
+            .into_iter()
+            .flat_map(|declaration| {
+                let mut classes = vec![optional_node_to_string(
+                    &declaration.child_by_field_name("name"),
+                    code,
+                )];
+                classes.extend(supertypes(declaration, code));
+                classes
+            })
+            .collect(),
+        static_imports: static_imports(node, code),
+    };
+
+    return match node.kind() {
+        "identifier" => Some((bare(), optional_node_to_string(&Some(node), code))),
+        "field_access" => {
+            let qualifier = optional_node_to_string(&node.child_by_field_name("object"), code);
+            let field = optional_node_to_string(&node.child_by_field_name("field"), code);
+            match simple_type_name(&qualifier).as_str() {
+                "this" => Some((bare(), field)),
+                qualifier => Some((SymbolReference::Qualified(qualifier.to_string()), field)),
+            }
+        }
+        _ => None,
+    };
+}
+
+pub fn resolve_constant(node: Node, code: &String) -> Option<String> {
+    let (reference, name) = symbol_reference(node, code)?;
+    return lookup(|index| index.constant(&reference, &name));
+}
+
+pub fn resolve_database(node: Node, code: &String) -> Option<String> {
+    let (reference, name) = symbol_reference(node, code)?;
+    return lookup(|index| index.database(&reference, &name));
+}
+
+pub fn resolve_collection(node: Node, code: &String) -> Option<(Option<String>, String)> {
+    let (reference, name) = symbol_reference(node, code)?;
+    return lookup(|index| index.collection(&reference, &name));
+}
+
+pub fn resolve_entity(type_name: &str) -> Option<EntityMapping> {
+    let name = simple_type_name(type_name);
+    return lookup(|index| index.entity(&name));
+}
+
+pub fn resolve_schema(type_name: &str) -> Option<DocumentSchema> {
+    let name = simple_type_name(type_name);
+    return lookup(|index| index.schema(&name));
+}
+
+fn is_constant(declaration: Node) -> bool {
+    if declaration.kind() == "constant_declaration" {
+        return true;
+    }
+
+    let mut cursor = declaration.walk();
+    let Some(modifiers) = declaration
+        .named_children(&mut cursor)
+        .find(|child| child.kind() == "modifiers")
+    else {
+        return false;
+    };
+
+    let mut modifiers_cursor = modifiers.walk();
+    let kinds: Vec<&str> = modifiers
+        .children(&mut modifiers_cursor)
+        .map(|modifier| modifier.kind())
+        .collect();
+    return kinds.contains(&"static") && kinds.contains(&"final");
+}
+
+fn supertypes(declaration: Node, code: &String) -> Vec<String> {
+    let mut cursor = declaration.walk();
+    let mut supertypes = vec![];
+    for clause in declaration.named_children(&mut cursor) {
+        let types = match clause.kind() {
+            "superclass" => clause.named_child(0).into_iter().collect(),
+            "super_interfaces" | "extends_interfaces" => match clause.named_child(0) {
+                Some(list) => {
+                    let mut list_cursor = list.walk();
+                    list.named_children(&mut list_cursor).collect()
+                }
+                None => vec![],
+            },
+            _ => vec![],
+        };
+        supertypes.extend(
+            types.into_iter().map(|supertype| {
+                simple_type_name(&optional_node_to_string(&Some(supertype), code))
+            }),
+        );
+    }
+
+    return supertypes;
+}
+
+fn member_symbols(class: &str, name: Node, constant: bool, code: &String) -> Vec<Symbol> {
+    let class = class.to_string();
+    let member = optional_node_to_string(&Some(name), code);
+    let mut symbols = vec![];
+
+    let value = name
+        .parent()
+        .and_then(|declarator| declarator.child_by_field_name("value"))
+        .filter(|_| constant);
+    if let Some(value) = value.and_then(|value| field_from_expression(value, code)) {
+        symbols.push(Symbol::Constant {
+            class: class.clone(),
+            name: member.clone(),
+            value,
+        });
+    }
+
+    symbols.extend(definition_symbols(&class, &member, name, code));
+    return symbols;
+}
+
+fn definition_symbols(class: &str, name: &str, definition: Node, code: &String) -> Vec<Symbol> {
+    let mut symbols = vec![];
+    if let Some(namespace) = infer_namespace_from_usage(definition, code) {
+        symbols.push(Symbol::Collection {
+            class: class.to_string(),
+            name: name.to_string(),
+            database: namespace.database,
+            collection: namespace.collection.unwrap_or_default(),
+        });
+    }
+
+    if let Some(database) = infer_database_from_usage(definition, code) {
+        symbols.push(Symbol::Database {
+            class: class.to_string(),
+            name: name.to_string(),
+            database,
+        });
+    }
+
+    return symbols;
+}
+
+fn bean_symbols(class: &str, method: Node, code: &String) -> Vec<Symbol> {
+    let Some(bean) = annotation_named(method, "Bean", code) else {
+        return vec![];
+    };
+
+    let names = annotation_element(bean, "name", code)
+        .or_else(|| annotation_element(bean, "value", code))
+        .map(|names| match names.kind() {
+            "element_value_array_initializer" => names.named_child(0),
+            _ => Some(names),
+        })
+        .and_then(|name| field_from_expression(name?, code));
+    let name =
+        names.unwrap_or_else(|| optional_node_to_string(&method.child_by_field_name("name"), code));
+
+    let mut returns = vec![];
+    if let Some(body) = method.child_by_field_name("body") {
+        descendants_of_kind(body, &["return_statement"], &mut returns);
+    }
+
+    return returns
+        .into_iter()
+        .filter_map(|statement| statement.named_child(0))
+        .flat_map(|returned| definition_symbols(class, &name, returned, code))
+        .map(|symbol| match symbol {
+            Symbol::Database { name, database, .. } => Symbol::Bean {
+                name,
+                database: Some(database),
+                collection: None,
+            },
+            Symbol::Collection {
+                name,
+                database,
+                collection,
+                ..
+            } => Symbol::Bean {
+                name,
+                database,
+                collection: Some(collection),
+            },
+            symbol => symbol,
+        })
+        .collect();
+}
+
+impl SymbolIndex {
+    pub fn shared() -> Rc<RefCell<SymbolIndex>> {
+        return Rc::new(RefCell::new(SymbolIndex::default()));
+    }
+
+    pub fn index(&mut self, path: &String, tree: &Tree, code: &String) {
+        let mut declarations = vec![];
+        descendants_of_kind(tree.root_node(), &TYPE_DECLARATIONS, &mut declarations);
+
+        let mut symbols = vec![];
+        for declaration in declarations {
+            let class = optional_node_to_string(&declaration.child_by_field_name("name"), code);
+            symbols.push(Symbol::Class {
+                name: class.clone(),
+                supertypes: supertypes(declaration, code),
+            });
+            if matches!(
+                declaration.kind(),
+                "class_declaration" | "record_declaration"
+            ) {
+                symbols.push(Symbol::Entity {
+                    mapping: entity_mapping(declaration, code),
+                });
+                symbols.extend(
+                    document_schema_of_class(tree.root_node(), &class, code)
+                        .map(|schema| Symbol::Schema { schema }),
+                );
+            }
+
+            let Some(body) = declaration.child_by_field_name("body") else {
+                continue;
+            };
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor) {
+                match member.kind() {
+                    "field_declaration" | "constant_declaration" => {
+                        let mut declarators_cursor = member.walk();
+                        for declarator in
+                            member.children_by_field_name("declarator", &mut declarators_cursor)
+                        {
+                            if let Some(name) = declarator.child_by_field_name("name") {
+                                let constant = is_constant(member);
+                                symbols.extend(member_symbols(&class, name, constant, code));
+                            }
+                        }
+                    }
+                    "method_declaration" => symbols.extend(bean_symbols(&class, member, code)),
+                    _ => {}
+                }
+            }
+        }
+
+        self.files.insert(path.clone(), symbols);
+    }
+
+    pub fn forget(&mut self, path: &String) {
+        self.files.remove(path);
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = &Symbol> {
+        return self.files.values().flatten();
+    }
+
+    fn supertypes_of(&self, class: &String, result: &mut Vec<String>) {
+        if result.contains(class) {
+            return;
+        }
+
+        result.push(class.clone());
+        for symbol in self.symbols() {
+            if let Symbol::Class { name, supertypes } = symbol {
+                if name == class {
+                    for supertype in supertypes {
+                        self.supertypes_of(supertype, result);
+                    }
+                }
+            }
+        }
+    }
+
+    fn visible(&self, reference: &SymbolReference, class: &String, member: &str) -> bool {
+        return match reference {
+            SymbolReference::Qualified(qualifier) => {
+                let known_class = self.symbols().any(
+                    |symbol| matches!(symbol, Symbol::Class { name, .. } if name == qualifier),
+                );
+                !known_class || qualifier == class
+            }
+            SymbolReference::Bare {
+                classes,
+                static_imports,
+            } => {
+                let mut scope = vec![];
+                for enclosing in classes {
+                    self.supertypes_of(enclosing, &mut scope);
+                }
+
+                scope.contains(class)
+                    || static_imports.iter().any(|(owner, imported)| {
+                        owner == class && imported.as_deref().unwrap_or(member) == member
+                    })
+            }
+        };
+    }
+
+    fn bean(
+        &self,
+        reference: &SymbolReference,
+        name: &str,
+    ) -> Option<(Option<String>, Option<String>)> {
+        if let SymbolReference::Qualified(_) = reference {
+            return None;
+        }
+
+        return self.symbols().find_map(|symbol| match symbol {
+            Symbol::Bean {
+                name: bean,
+                database,
+                collection,
+            } if bean == name => Some((database.clone(), collection.clone())),
+            _ => None,
+        });
+    }
+
+    pub fn entity(&self, name: &str) -> Option<EntityMapping> {
+        return self.symbols().find_map(|symbol| match symbol {
+            Symbol::Entity { mapping } if mapping.name == name => Some(mapping.clone()),
+            _ => None,
+        });
+    }
+
+    pub fn schema(&self, name: &str) -> Option<DocumentSchema> {
+        return self.symbols().find_map(|symbol| match symbol {
+            Symbol::Schema { schema } if schema.name == name => Some(schema.clone()),
+            _ => None,
+        });
+    }
+
+    pub fn constant(&self, reference: &SymbolReference, name: &str) -> Option<String> {
+        return self.symbols().find_map(|symbol| match symbol {
+            Symbol::Constant {
+                class,
+                name: constant,
+                value,
+            } if constant == name && self.visible(reference, class, name) => Some(value.clone()),
+            _ => None,
+        });
+    }
+
+    pub fn database(&self, reference: &SymbolReference, name: &str) -> Option<String> {
+        return self
+            .symbols()
+            .find_map(|symbol| match symbol {
+                Symbol::Database {
+                    class,
+                    name: database_name,
+                    database,
+                } if database_name == name && self.visible(reference, class, name) => {
+                    Some(database.clone())
+                }
+                _ => None,
+            })
+            .or_else(|| self.bean(reference, name)?.0);
+    }
+
+    pub fn collection(
+        &self,
+        reference: &SymbolReference,
+        name: &str,
+    ) -> Option<(Option<String>, String)> {
+        return self
+            .symbols()
+            .find_map(|symbol| match symbol {
+                Symbol::Collection {
+                    class,
+                    name: collection_name,
+                    database,
+                    collection,
+                } if collection_name == name && self.visible(reference, class, name) => {
+                    Some((database.clone(), collection.clone()))
+                }
+                _ => None,
+            })
+            .or_else(|| {
+                let (database, collection) = self.bean(reference, name)?;
+                Some((database, collection?))
+            });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use dialect_interface::ExtractionWarning;
+    use mongodb_query_language::execution::Execution::FindMany;
+    use mongodb_query_language::execution::ExecutionNamespace;
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::values::{BsonType, ValueType};
+
+    use crate::symbol_index::{with_symbols, SymbolIndex};
+    use crate::use_cases::find::find;
+    use crate::Java;
+
+    #[test]
+    fn resolves_namespaces_and_constants_from_other_files() {
+        let config = r#"
+        @Configuration
+        public class MongoConfig {
+            private final MongoClient client;
+
+            @Bean
+            public MongoDatabase shopDatabase(MongoClient client) {
+                return client.getDatabase("shop");
+            }
+
+            @Bean("orders")
+            public MongoCollection<Document> ordersCollection() {
+                return client.getDatabase("shop").getCollection("orders");
+            }
+        }
+        "#
+        .to_string();
+        let constants = r#"
+        public interface Collections {
+            String ORDERS = "orders";
+            String USERS = "users";
+            String STATUS = "status";
+        }
+        "#
+        .to_string();
+        let repository = r#"
+        public class OrderRepository {
+            private final MongoCollection<Document> orders;
+            private final MongoDatabase shopDatabase;
+
+            public List<Document> pending() {
+                shopDatabase.getCollection(Collections.USERS).find().into(new ArrayList<>());
+                return orders.find(eq(Collections.STATUS, "pending")).into(new ArrayList<>());
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let symbols = SymbolIndex::shared();
+        for (path, code) in [
+            ("Collections.java", &constants),
+            ("MongoConfig.java", &config),
+        ] {
+            symbols
+                .borrow_mut()
+                .index(&path.to_string(), &java.full_parse(code).borrow(), code);
+        }
+
+        let tree = java.full_parse(&repository);
+        let result = with_symbols(&symbols, || find(tree, &repository)).unwrap();
+        let namespaces: Vec<ExecutionNamespace> = result
+            .executions
+            .iter()
+            .map(|execution| execution.namespace().clone())
+            .collect();
+
+        assert_eq!(result.warnings, vec![]);
+        assert_eq!(
+            namespaces,
+            vec![
+                ExecutionNamespace {
+                    database: Some("shop".to_string()),
+                    collection: Some("users".to_string()),
+                    reference_name: "shopDatabase.getCollection(Collections.USERS)".to_string(),
+                },
+                ExecutionNamespace {
+                    database: Some("shop".to_string()),
+                    collection: Some("orders".to_string()),
+                    reference_name: "orders".to_string(),
+                },
+            ]
+        );
+        let FindMany { predicate, .. } = &result.executions[1] else {
+            panic!()
+        };
+        assert_eq!(predicate.field(), Some(&"status".to_string()));
+    }
+
+    #[test]
+    fn limits_bare_names_to_the_enclosing_class_hierarchy_and_static_imports() {
+        let files = [
+            (
+                "Fields.java",
+                r#"
+                public class Fields {
+                    public static final String STATUS = "status";
+                    public static final String AGE = "age";
+                    public static final String NAME = "name";
+                    public String label = "label";
+                }
+                "#,
+            ),
+            (
+                "BaseRepository.java",
+                r#"
+                public abstract class BaseRepository {
+                    protected static final String KIND = "kind";
+                }
+                "#,
+            ),
+            (
+                "Unrelated.java",
+                r#"
+                public class Unrelated {
+                    public static final String CITY = "city";
+                }
+                "#,
+            ),
+        ];
+        let repository = r#"
+        import static com.shop.Fields.STATUS;
+        import static com.shop.Fields.label;
+
+        public class OrderRepository extends BaseRepository {
+            private final MongoCollection<Document> orders;
+
+            public void pending() {
+                orders.find(eq(STATUS, 1)).into(new ArrayList<>());
+                orders.find(eq(KIND, 2)).into(new ArrayList<>());
+                orders.find(eq(AGE, 3)).into(new ArrayList<>());
+                orders.find(eq(CITY, 4)).into(new ArrayList<>());
+                orders.find(eq(label, 5)).into(new ArrayList<>());
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let symbols = SymbolIndex::shared();
+        for (path, code) in files {
+            let code = code.to_string();
+            symbols
+                .borrow_mut()
+                .index(&path.to_string(), &java.full_parse(&code).borrow(), &code);
+        }
+
+        let tree = java.full_parse(&repository);
+        let result = with_symbols(&symbols, || find(tree, &repository)).unwrap();
+        let fields: Vec<Option<&String>> = result
+            .executions
+            .iter()
+            .map(|execution| match execution {
+                FindMany { predicate, .. } => predicate.field(),
+                _ => panic!(),
+            })
+            .collect();
+
+        assert_eq!(
+            fields,
+            vec![Some(&"status".to_string()), Some(&"kind".to_string())]
+        );
+    }
+
+    #[test]
+    fn checks_executions_against_document_classes_from_other_files() {
+        let user = r#"
+        public class User {
+            private ObjectId id;
+            private int age;
+            private Address address;
+        }
+
+        public record Address(String city) {}
+        "#
+        .to_string();
+        let repository = r#"
+        public class UserRepository {
+            public List<User> adults(MongoDatabase database) {
+                var users = database.getCollection("users", User.class);
+                return users
+                    .find(and(eq("age", "18"), eq("nickname", "x"), eq("address.city", "Rome")))
+                    .into(new ArrayList<>());
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let symbols = SymbolIndex::shared();
+        symbols.borrow_mut().index(
+            &"User.java".to_string(),
+            &java.full_parse(&user).borrow(),
+            &user,
+        );
+
+        let tree = java.full_parse(&repository);
+        let result = with_symbols(&symbols, || find(tree, &repository)).unwrap();
+
+        assert_eq!(
+            result.warnings,
+            vec![
+                ExtractionWarning::MismatchedFieldType {
+                    field: "age".to_string(),
+                    expected: ValueType::Of(BsonType::Int),
+                    actual: ValueType::Of(BsonType::String),
+                    span: Span::default(),
+                },
+                ExtractionWarning::UnknownField {
+                    field: "nickname".to_string(),
+                    document: "User".to_string(),
+                    span: Span::default(),
+                },
+            ]
+        );
+        assert_eq!(
+            result.schemas["users"].paths(),
+            vec!["_id", "age", "address", "address.city"]
+        );
+    }
+}
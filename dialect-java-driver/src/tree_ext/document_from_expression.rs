@@ -0,0 +1,229 @@
+use dialect_interface::ExtractionWarning;
+use mongodb_query_language::parser::parse;
+use mongodb_query_language::serialization::{FromBson, ToBson};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::Value;
+use tree_sitter::Node;
+
+use crate::tree_ext::predicate_from_driver_method::FILTERS_HELPERS;
+use crate::tree_ext::predicate_from_expression::predicate_from_expression;
+use crate::tree_ext::value_from_expression::{iterable_elements, value_from_expression};
+use crate::tree_ext::{
+    field_from_expression, invocation_arguments, invocation_name, optional_node_to_string,
+    resolved_expression, simple_type_name, string_literal,
+};
+
+const DOCUMENT_CLASSES: [&str; 2] = ["Document", "BasicDBObject"];
+
+fn is_document_construction(node: Node, code: &String) -> bool {
+    let class = match node.kind() {
+        "object_creation_expression" => {
+            return DOCUMENT_CLASSES.contains(
+                &simple_type_name(&optional_node_to_string(
+                    &node.child_by_field_name("type"),
+                    code,
+                ))
+                .as_str(),
+            );
+        }
+        _ => optional_node_to_string(&node.child_by_field_name("object"), code),
+    };
+
+    return match invocation_name(node, code).as_str() {
+        "parse" => DOCUMENT_CLASSES.contains(&class.as_str()),
+        "of" | "ofEntries" => class == "Map",
+        _ => false,
+    };
+}
+
+fn element_value(node: Node, code: &String, warnings: &mut Vec<ExtractionWarning>) -> Value {
+    if let Some(elements) = iterable_elements(node, code) {
+        return Value::Array(
+            elements
+                .into_iter()
+                .map(|element| element_value(element, code, warnings))
+                .collect(),
+            Span::from(node),
+        );
+    }
+
+    if let Some(fields) = document_from_expression(node, code, warnings) {
+        return Value::Object(fields, Span::from(node));
+    }
+
+    if node.kind() == "method_invocation"
+        && FILTERS_HELPERS.contains(&invocation_name(node, code).as_str())
+    {
+        if let Some(predicate) = predicate_from_expression(node, code, warnings) {
+            if let Ok(value) = Value::from_bson(&predicate.to_bson()) {
+                return value;
+            }
+        }
+    }
+
+    return value_from_expression(node, code);
+}
+
+fn pairs(
+    arguments: &[Node],
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<Vec<(String, Value)>> {
+    return arguments
+        .chunks(2)
+        .map(|pair| match pair {
+            [key, value] => Some((
+                field_from_expression(*key, code)?,
+                element_value(*value, code, warnings),
+            )),
+            _ => None,
+        })
+        .collect();
+}
+
+pub fn document_from_expression(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<Vec<(String, Value)>> {
+    let node = resolved_expression(node, code);
+    if !matches!(
+        node.kind(),
+        "object_creation_expression" | "method_invocation"
+    ) {
+        return None;
+    }
+
+    let arguments = invocation_arguments(node);
+    if node.kind() == "method_invocation" && invocation_name(node, code) == "append" {
+        let mut document =
+            document_from_expression(node.child_by_field_name("object")?, code, warnings)?;
+        document.append(&mut pairs(&arguments, code, warnings)?);
+        return Some(document);
+    }
+
+    let name = invocation_name(node, code);
+    let factory = matches!(name.as_str(), "parse" | "of" | "ofEntries");
+    if (node.kind() == "method_invocation" && !factory) || !is_document_construction(node, code) {
+        return None;
+    }
+
+    return match (name.as_str(), &arguments[..]) {
+        ("parse", [json]) => match parse::<Value>(&string_literal(*json, code)?) {
+            Ok(Value::Object(fields, _)) => Some(fields),
+            _ => None,
+        },
+        ("ofEntries", entries) => entries
+            .iter()
+            .map(|entry| {
+                let entry = resolved_expression(*entry, code);
+                pairs(&invocation_arguments(entry), code, warnings)?.pop()
+            })
+            .collect(),
+        (_, [map]) => document_from_expression(*map, code, warnings),
+        (_, pairs_arguments) => pairs(pairs_arguments, code, warnings),
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use dialect_interface::ExtractionWarning;
+    use mongodb_query_language::filter::FilterOperator::{And, Equals, GreaterThan, In, Or};
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::values::Value::{Integer, Reference, String};
+    use mongodb_query_language::values::{BsonType, ValueType};
+
+    use crate::tree_ext::test_fixtures::predicates_of;
+
+    #[test]
+    fn lowers_documents_built_with_constructors_and_append() {
+        let (predicates, _) = predicates_of(
+            r#"collection.find(new Document("age", new Document("$gt", 18)).append("status", status));
+            Bson filter = new BasicDBObject("$or", Arrays.asList(eq("a", 1), new BasicDBObject("b", 2)));
+            collection.find(filter);"#,
+        );
+        let status = Reference(
+            "status".to_string(),
+            ValueType::Of(BsonType::String),
+            Span::default(),
+        );
+
+        assert_eq!(
+            predicates,
+            vec![
+                And {
+                    predicates: vec![
+                        GreaterThan {
+                            field: "age".to_string(),
+                            value: Integer(18, Span::default()),
+                            span: Span::default()
+                        },
+                        Equals {
+                            field: "status".to_string(),
+                            value: status,
+                            span: Span::default()
+                        },
+                    ],
+                    span: Span::default()
+                },
+                Or {
+                    predicates: vec![
+                        Equals {
+                            field: "a".to_string(),
+                            value: Integer(1, Span::default()),
+                            span: Span::default()
+                        },
+                        Equals {
+                            field: "b".to_string(),
+                            value: Integer(2, Span::default()),
+                            span: Span::default()
+                        },
+                    ],
+                    span: Span::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lowers_parsed_json_and_maps() {
+        let (predicates, _) = predicates_of(
+            r#"collection.find(Document.parse("{ \"tags\": { \"$in\": [\"a\", 'b'] } }"));
+            collection.find(new Document(Map.of("status", "A")));"#,
+        );
+
+        assert_eq!(
+            predicates,
+            vec![
+                In {
+                    field: "tags".to_string(),
+                    values: vec![
+                        String("a".to_string(), Span::default()),
+                        String("b".to_string(), Span::default())
+                    ],
+                    span: Span::default()
+                },
+                Equals {
+                    field: "status".to_string(),
+                    value: String("A".to_string(), Span::default()),
+                    span: Span::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_warnings_of_nested_filter_helpers() {
+        let (_, warnings) = predicates_of(
+            r#"collection.find(new Document("$or", Arrays.asList(exists("deletedAt", flag), eq("a", 1))));"#,
+        );
+
+        assert_eq!(
+            warnings[0],
+            ExtractionWarning::UnresolvedFilter {
+                expression: "exists(deletedAt, flag)".to_string(),
+                span: Span::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use mongodb_query_language::schema::{DocumentSchema, SchemaField};
+use mongodb_query_language::values::{BsonType, ValueType};
+use tree_sitter::Node;
+
+use crate::tree_ext::infer_value_type::java_type_to_value_type;
+use crate::tree_ext::{
+    annotation_element, annotation_named, descendants_of_kind, field_from_expression,
+    optional_node_to_string, simple_type_name,
+};
+
+const SKIPPED_MODIFIERS: [&str; 2] = ["static", "transient"];
+
+struct Member<'a> {
+    declaration: Node<'a>,
+    name: String,
+    type_node: Node<'a>,
+}
+
+fn type_declaration<'a>(root: Node<'a>, name: &str, code: &String) -> Option<Node<'a>> {
+    let mut declarations = vec![];
+    descendants_of_kind(
+        root,
+        &[
+            "class_declaration",
+            "record_declaration",
+            "enum_declaration",
+        ],
+        &mut declarations,
+    );
+
+    return declarations.into_iter().find(|declaration| {
+        optional_node_to_string(&declaration.child_by_field_name("name"), code) == name
+    });
+}
+
+fn is_skipped(declaration: Node, code: &String) -> bool {
+    let mut cursor = declaration.walk();
+    let skipped = declaration
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() == "modifiers")
+        .any(|modifiers| {
+            optional_node_to_string(&Some(modifiers), code)
+                .split_whitespace()
+                .any(|modifier| SKIPPED_MODIFIERS.contains(&modifier))
+        });
+    return skipped;
+}
+
+fn members<'a>(
+    root: Node<'a>,
+    class: Node<'a>,
+    code: &String,
+    visited: &mut Vec<String>,
+) -> Vec<Member<'a>> {
+    let mut result = vec![];
+    if class.kind() == "record_declaration" {
+        let Some(parameters) = class.child_by_field_name("parameters") else {
+            return result;
+        };
+
+        let mut cursor = parameters.walk();
+        for parameter in parameters.named_children(&mut cursor) {
+            if let Some(type_node) = parameter.child_by_field_name("type") {
+                result.push(Member {
+                    declaration: parameter,
+                    name: optional_node_to_string(&parameter.child_by_field_name("name"), code),
+                    type_node,
+                });
+            }
+        }
+        return result;
+    }
+
+    let superclass = class
+        .child_by_field_name("superclass")
+        .and_then(|superclass| superclass.named_child(0))
+        .map(|superclass| simple_type_name(&optional_node_to_string(&Some(superclass), code)));
+    if let Some(superclass) = superclass.filter(|superclass| !visited.contains(superclass)) {
+        if let Some(declaration) = type_declaration(root, &superclass, code) {
+            visited.push(superclass);
+            result.extend(members(root, declaration, code, visited));
+            visited.pop();
+        }
+    }
+
+    let Some(body) = class.child_by_field_name("body") else {
+        return result;
+    };
+    let mut cursor = body.walk();
+    for field in body
+        .named_children(&mut cursor)
+        .filter(|member| member.kind() == "field_declaration")
+        .filter(|field| !is_skipped(*field, code))
+    {
+        let Some(type_node) = field.child_by_field_name("type") else {
+            continue;
+        };
+
+        let mut declarators_cursor = field.walk();
+        for declarator in field.children_by_field_name("declarator", &mut declarators_cursor) {
+            result.push(Member {
+                declaration: field,
+                name: optional_node_to_string(&declarator.child_by_field_name("name"), code),
+                type_node,
+            });
+        }
+    }
+
+    return result;
+}
+
+fn schema_field(
+    root: Node,
+    member: Member,
+    code: &String,
+    visited: &mut Vec<String>,
+) -> Option<SchemaField> {
+    let Member {
+        declaration,
+        name,
+        type_node,
+    } = member;
+    if annotation_named(declaration, "BsonIgnore", code).is_some() {
+        return None;
+    }
+
+    let property = annotation_named(declaration, "BsonProperty", code)
+        .and_then(|property| annotation_element(property, "value", code))
+        .and_then(|property| field_from_expression(property, code));
+    let name = match (annotation_named(declaration, "BsonId", code), property) {
+        (Some(_), _) => "_id".to_string(),
+        (None, Some(property)) => property,
+        (None, None) if name == "id" => "_id".to_string(),
+        (None, None) => name,
+    };
+
+    let value_type = java_type_to_value_type(type_node, code);
+    let element = match type_node.kind() {
+        "array_type" => type_node.child_by_field_name("element"),
+        "generic_type" if value_type == ValueType::Of(BsonType::Array) => type_node
+            .named_children(&mut type_node.walk())
+            .find(|child| child.kind() == "type_arguments")
+            .and_then(|arguments| arguments.named_child(0)),
+        _ => Some(type_node),
+    };
+    let declared = element
+        .map(|element| simple_type_name(&optional_node_to_string(&Some(element), code)))
+        .and_then(|element| type_declaration(root, &element, code));
+    let schema = declared
+        .filter(|declaration| declaration.kind() != "enum_declaration")
+        .and_then(|declaration| schema_of_declaration(root, declaration, code, visited));
+
+    let value_type = match value_type {
+        ValueType::Any
+            if declared.map(|declaration| declaration.kind()) == Some("enum_declaration") =>
+        {
+            ValueType::Of(BsonType::String)
+        }
+        ValueType::Any if declared.is_some() => ValueType::Of(BsonType::Object),
+        value_type => value_type,
+    };
+
+    return Some(SchemaField {
+        name,
+        value_type,
+        schema,
+    });
+}
+
+fn schema_of_declaration(
+    root: Node,
+    declaration: Node,
+    code: &String,
+    visited: &mut Vec<String>,
+) -> Option<DocumentSchema> {
+    let name = optional_node_to_string(&declaration.child_by_field_name("name"), code);
+    if visited.contains(&name) {
+        return None;
+    }
+
+    visited.push(name.clone());
+    let fields = members(root, declaration, code, visited)
+        .into_iter()
+        .filter_map(|member| schema_field(root, member, code, visited))
+        .collect();
+    visited.pop();
+
+    return Some(DocumentSchema { name, fields });
+}
+
+pub fn document_schema_of_class(
+    root: Node,
+    class_name: &str,
+    code: &String,
+) -> Option<DocumentSchema> {
+    let declaration = type_declaration(root, &simple_type_name(class_name), code)
+        .filter(|declaration| declaration.kind() != "enum_declaration")?;
+    return schema_of_declaration(root, declaration, code, &mut vec![]);
+}
+
+#[cfg(test)]
+mod test {
+    use mongodb_query_language::schema::{DocumentSchema, SchemaField};
+    use mongodb_query_language::values::{BsonType, ValueType};
+
+    use crate::tree_ext::document_schema_from_class::document_schema_of_class;
+    use crate::Java;
+
+    fn field(name: &str, bson_type: BsonType, schema: Option<DocumentSchema>) -> SchemaField {
+        return SchemaField {
+            name: name.to_string(),
+            value_type: ValueType::Of(bson_type),
+            schema,
+        };
+    }
+
+    #[test]
+    fn builds_document_schemas_from_pojos_and_records() {
+        let code = r#"
+        public abstract class Auditable {
+            private Instant createdAt;
+        }
+
+        public class User extends Auditable {
+            private static final String COLLECTION = "users";
+            public static final String FULL_NAME = "full_name";
+
+            @BsonId
+            private ObjectId key;
+            @BsonProperty(FULL_NAME)
+            private String name;
+            @BsonIgnore
+            private String password;
+            private transient int cachedHash;
+            private Status status;
+            private List<Address> addresses;
+            private User manager;
+        }
+
+        public record Address(@BsonProperty("zip_code") String zip, String city) {}
+
+        public enum Status { ACTIVE, DISABLED }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let root = tree.borrow();
+        let address = DocumentSchema {
+            name: "Address".to_string(),
+            fields: vec![
+                field("zip_code", BsonType::String, None),
+                field("city", BsonType::String, None),
+            ],
+        };
+
+        let schema = document_schema_of_class(root.root_node(), "User", &code).unwrap();
+        assert_eq!(
+            schema,
+            DocumentSchema {
+                name: "User".to_string(),
+                fields: vec![
+                    field("createdAt", BsonType::Date, None),
+                    field("_id", BsonType::ObjectId, None),
+                    field("full_name", BsonType::String, None),
+                    field("status", BsonType::String, None),
+                    field("addresses", BsonType::Array, Some(address)),
+                    field("manager", BsonType::Object, None),
+                ],
+            }
+        );
+        assert_eq!(
+            document_schema_of_class(root.root_node(), "Status", &code),
+            None
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use tree_sitter::Node;
+
+use crate::tree_ext::{
+    annotation_element, annotation_named, optional_node_to_string, string_value, uncapitalize,
+};
+
+const ID_ANNOTATIONS: [&str; 2] = ["Id", "MongoId"];
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct EntityMapping {
+    pub name: String,
+    pub document: bool,
+    pub collection: Option<String>,
+    pub fields: Vec<(String, String)>,
+}
+
+impl EntityMapping {
+    pub fn field_name(&self, property: &str) -> String {
+        if let Some((_, field)) = self.fields.iter().find(|(name, _)| name == property) {
+            return field.clone();
+        }
+
+        return match property {
+            "id" => "_id".to_string(),
+            _ => property.to_string(),
+        };
+    }
+}
+
+fn properties<'a>(class: Node<'a>, code: &String) -> Vec<(String, Node<'a>)> {
+    let mut properties = vec![];
+    match class.kind() {
+        "record_declaration" => {
+            let Some(parameters) = class.child_by_field_name("parameters") else {
+                return properties;
+            };
+            let mut cursor = parameters.walk();
+            for parameter in parameters.named_children(&mut cursor) {
+                let name = optional_node_to_string(&parameter.child_by_field_name("name"), code);
+                properties.push((name, parameter));
+            }
+        }
+        _ => {
+            let Some(body) = class.child_by_field_name("body") else {
+                return properties;
+            };
+            let mut cursor = body.walk();
+            for field in body
+                .named_children(&mut cursor)
+                .filter(|member| member.kind() == "field_declaration")
+            {
+                let mut declarators_cursor = field.walk();
+                for declarator in
+                    field.children_by_field_name("declarator", &mut declarators_cursor)
+                {
+                    let name =
+                        optional_node_to_string(&declarator.child_by_field_name("name"), code);
+                    properties.push((name, field));
+                }
+            }
+        }
+    }
+
+    return properties;
+}
+
+fn stored_name(declaration: Node, code: &String) -> Option<String> {
+    if ID_ANNOTATIONS
+        .iter()
+        .any(|id| annotation_named(declaration, id, code).is_some())
+    {
+        return Some("_id".to_string());
+    }
+
+    return annotation_named(declaration, "Field", code).and_then(|field| {
+        annotation_element(field, "name", code)
+            .or_else(|| annotation_element(field, "value", code))
+            .and_then(|name| string_value(name, code))
+    });
+}
+
+pub fn entity_mapping(class: Node, code: &String) -> EntityMapping {
+    let name = optional_node_to_string(&class.child_by_field_name("name"), code);
+    let document = annotation_named(class, "Document", code);
+
+    let collection = match document {
+        Some(document) => annotation_element(document, "collection", code)
+            .or_else(|| annotation_element(document, "value", code))
+            .map(|collection| string_value(collection, code))
+            .unwrap_or(Some(uncapitalize(&name))),
+        None => Some(uncapitalize(&name)),
+    };
+
+    return EntityMapping {
+        name: name.clone(),
+        document: document.is_some(),
+        collection: collection.filter(|collection| !collection.contains("#{")),
+        fields: properties(class, code)
+            .into_iter()
+            .filter_map(|(property, declaration)| Some((property, stored_name(declaration, code)?)))
+            .collect(),
+    };
+}
@@ -0,0 +1,34 @@
+use mongodb_query_language::aggregation::Expression;
+use mongodb_query_language::serialization::{FromBson, ToBson};
+use mongodb_query_language::values::Value;
+use tree_sitter::Node;
+
+use crate::tree_ext::value_from_expression::{iterable_elements, value_from_expression};
+
+pub fn expression_from_expression(node: Node, code: &String) -> Expression {
+    if let Some(elements) = iterable_elements(node, code) {
+        return Expression::Array(
+            elements
+                .into_iter()
+                .map(|element| expression_from_expression(element, code))
+                .collect(),
+        );
+    }
+
+    let value = value_from_expression(node, code);
+    if let Value::Object(..) = value {
+        if let Ok(expression) = Expression::from_bson(&value.to_bson()) {
+            return expression;
+        }
+    }
+
+    return match value {
+        Value::String(path, _) if path.starts_with("$$") => {
+            Expression::Variable(path[2..].to_string())
+        }
+        Value::String(path, _) if path.starts_with('$') => {
+            Expression::FieldPath(path[1..].to_string())
+        }
+        value => Expression::Literal(value),
+    };
+}
@@ -0,0 +1,107 @@
+use dialect_interface::ExtractionWarning;
+use mongodb_query_language::execution::{FindOptions, Hint};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::{BsonType, Value, ValueType};
+use tree_sitter::Node;
+
+use crate::tree_ext::index_keys_from_expression::index_keys_from_expression;
+use crate::tree_ext::projection_from_expression::projection_from_expression;
+use crate::tree_ext::sort_from_expression::sort_from_expression;
+use crate::tree_ext::value_from_expression::value_from_expression;
+use crate::tree_ext::{
+    invocation_arguments, invocation_name, local_usages, optional_node_to_string,
+};
+
+pub fn find_options_from_chain(
+    find_invocation: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> FindOptions {
+    let mut options = FindOptions::default();
+    options_from_chain(find_invocation, code, warnings, &mut options);
+
+    return options;
+}
+
+fn options_from_chain(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+    options: &mut FindOptions,
+) {
+    let mut current = node;
+
+    while let Some(parent) = current.parent() {
+        if parent.kind() != "method_invocation"
+            || parent.child_by_field_name("object") != Some(current)
+        {
+            break;
+        }
+
+        let arguments = invocation_arguments(parent);
+        match (invocation_name(parent, code).as_str(), &arguments[..]) {
+            ("limit", [limit]) => options.limit = Some(value_from_expression(*limit, code)),
+            ("skip", [skip]) => options.skip = Some(value_from_expression(*skip, code)),
+            ("sort", [sort]) => options.sort = sort_from_expression(*sort, code, warnings),
+            ("projection", [projection]) => {
+                options.projection = projection_from_expression(*projection, code, warnings)
+            }
+            ("hint", [hint]) => {
+                options.hint = Some(Hint::Keys(
+                    index_keys_from_expression(*hint, code)
+                        .unwrap_or_else(|| value_from_expression(*hint, code)),
+                ))
+            }
+            ("hintString", [hint]) => {
+                options.hint = Some(Hint::Name(optional_node_to_string(&Some(*hint), code)))
+            }
+            ("collation", [collation]) => {
+                options.collation = Some(value_from_expression(*collation, code))
+            }
+            ("maxTime", [amount, unit]) => {
+                options.max_time_ms = Some(max_time_in_millis(*amount, *unit, code))
+            }
+            _ => {}
+        }
+
+        current = parent;
+    }
+
+    for usage in local_usages(current, code) {
+        options_from_chain(usage, code, warnings, options);
+    }
+}
+
+fn max_time_in_millis(amount: Node, unit: Node, code: &String) -> Value {
+    let unit_name = optional_node_to_string(&Some(unit), code);
+    let factor = match unit_name.rsplit('.').next().unwrap_or("") {
+        "MILLISECONDS" => 1,
+        "SECONDS" => 1_000,
+        "MINUTES" => 60_000,
+        "HOURS" => 3_600_000,
+        _ => 0,
+    };
+
+    let reference = Value::Reference(
+        format!(
+            "{} {}",
+            optional_node_to_string(&Some(amount), code),
+            unit_name
+        ),
+        ValueType::Of(BsonType::Long),
+        Span::from(amount).merge(&Span::from(unit)),
+    );
+
+    return match value_from_expression(amount, code) {
+        Value::Integer(amount, span) if factor > 0 => match amount.checked_mul(factor) {
+            Some(millis) => Value::Integer(millis, span),
+            None => Value::Int64(i64::from(amount) * i64::from(factor), span),
+        },
+        Value::Int64(amount, span) if factor > 0 => amount
+            .checked_mul(i64::from(factor))
+            .map(|millis| Value::Int64(millis, span))
+            .unwrap_or(reference),
+        value if factor == 1 => value,
+        _ => reference,
+    };
+}
@@ -0,0 +1,51 @@
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::Value;
+use tree_sitter::Node;
+
+use crate::tree_ext::{
+    fields_from_arguments, invocation_arguments, invocation_name, operands_from_arguments,
+    resolved_expression,
+};
+
+fn index_keys(node: Node, code: &String) -> Option<Vec<(String, Value)>> {
+    let node = resolved_expression(node, code);
+    if node.kind() != "method_invocation" {
+        return None;
+    }
+
+    let span = Span::from(node);
+    let arguments = invocation_arguments(node);
+    let key = match invocation_name(node, code).as_str() {
+        "ascending" => Value::Integer(1, span),
+        "descending" => Value::Integer(-1, span),
+        "geo2dsphere" => Value::String("2dsphere".to_string(), span),
+        "geo2d" => Value::String("2d".to_string(), span),
+        "hashed" => Value::String("hashed".to_string(), span),
+        "text" if arguments.is_empty() => {
+            return Some(vec![(
+                "$**".to_string(),
+                Value::String("text".to_string(), span),
+            )])
+        }
+        "text" => Value::String("text".to_string(), span),
+        "compoundIndex" => {
+            return operands_from_arguments(&arguments, code)
+                .into_iter()
+                .map(|argument| index_keys(argument, code))
+                .collect::<Option<Vec<Vec<(String, Value)>>>>()
+                .map(|keys| keys.concat());
+        }
+        _ => return None,
+    };
+
+    return Some(
+        fields_from_arguments(&arguments, code)
+            .into_iter()
+            .map(|field| (field, key.clone()))
+            .collect(),
+    );
+}
+
+pub fn index_keys_from_expression(node: Node, code: &String) -> Option<Value> {
+    return index_keys(node, code).map(|keys| Value::Object(keys, Span::from(node)));
+}
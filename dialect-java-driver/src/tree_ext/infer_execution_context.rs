@@ -0,0 +1,262 @@
+use mongodb_query_language::execution::ExecutionContext;
+use mongodb_query_language::span::Span;
+use tree_sitter::Node;
+
+use crate::tree_ext::infer_value_type::declared_type_name;
+use crate::tree_ext::reaching_definitions::{EXECUTABLE_SCOPES, LOOPS};
+use crate::tree_ext::{
+    descendants_of_kind, invocation_arguments, invocation_name, optional_node_to_string,
+};
+
+const SESSION_TYPES: [&str; 2] = ["ClientSession", "ClientSessionImpl"];
+const TRANSACTION_ENDS: [&str; 2] = ["commitTransaction", "abortTransaction"];
+
+const SESSION_OVERLOADS: [(&str, usize, usize); 12] = [
+    ("find", 1, 3),
+    ("aggregate", 2, 3),
+    ("updateOne", 3, 4),
+    ("updateMany", 3, 4),
+    ("findOneAndUpdate", 3, 4),
+    ("createIndex", 2, 3),
+    ("createIndexes", 2, 3),
+    ("dropIndex", 2, 3),
+    ("dropIndexes", 1, 2),
+    ("createCollection", 2, 3),
+    ("createView", 4, 5),
+    ("runCommand", 2, 4),
+];
+
+fn has_session_overload(invocation: Node, arguments: usize, code: &String) -> bool {
+    let name = invocation_name(invocation, code);
+    return SESSION_OVERLOADS
+        .iter()
+        .any(|(method, min, max)| *method == name && (*min..=*max).contains(&arguments));
+}
+
+fn is_session(invocation: Node, argument: Node, arguments: usize, code: &String) -> bool {
+    return match declared_type_name(argument, code) {
+        Some(type_name) => SESSION_TYPES.contains(&type_name.as_str()),
+        None => {
+            argument.kind() == "identifier"
+                && optional_node_to_string(&Some(argument), code)
+                    .to_lowercase()
+                    .ends_with("session")
+                && has_session_overload(invocation, arguments, code)
+        }
+    };
+}
+
+pub fn session_argument<'a>(invocation: Node<'a>, code: &String) -> Option<Node<'a>> {
+    let arguments = invocation_arguments(invocation);
+    let count = arguments.len();
+    return arguments
+        .into_iter()
+        .next()
+        .filter(|argument| is_session(invocation, *argument, count, code));
+}
+
+pub fn operation_arguments<'a>(invocation: Node<'a>, code: &String) -> Vec<Node<'a>> {
+    let mut arguments = invocation_arguments(invocation);
+    if session_argument(invocation, code).is_some() {
+        arguments.remove(0);
+    }
+
+    return arguments;
+}
+
+fn transaction_callback<'a>(scope: Node<'a>, code: &String) -> Option<Node<'a>> {
+    if scope.kind() != "lambda_expression" {
+        return None;
+    }
+
+    let invocation = scope
+        .parent()
+        .filter(|arguments| arguments.kind() == "argument_list")?
+        .parent()?;
+    return Some(invocation)
+        .filter(|invocation| invocation_name(*invocation, code) == "withTransaction");
+}
+
+fn started_transaction<'a>(
+    body: Node<'a>,
+    session: &str,
+    usage: Node<'a>,
+    code: &String,
+) -> Option<Node<'a>> {
+    let mut invocations = vec![];
+    descendants_of_kind(body, &["method_invocation"], &mut invocations);
+
+    let mut started = None;
+    for invocation in invocations
+        .into_iter()
+        .filter(|invocation| invocation.end_byte() <= usage.start_byte())
+        .filter(|invocation| {
+            optional_node_to_string(&invocation.child_by_field_name("object"), code) == session
+        })
+    {
+        match invocation_name(invocation, code).as_str() {
+            "startTransaction" => started = Some(invocation),
+            name if TRANSACTION_ENDS.contains(&name) => started = None,
+            _ => {}
+        }
+    }
+
+    return started;
+}
+
+pub fn infer_execution_context(invocation: Node, code: &String) -> ExecutionContext {
+    let mut context = ExecutionContext {
+        session: session_argument(invocation, code)
+            .map(|session| optional_node_to_string(&Some(session), code)),
+        ..ExecutionContext::default()
+    };
+
+    let mut loops = vec![];
+    let mut scope = invocation.parent();
+    while let Some(current) = scope {
+        if let Some(transaction) = transaction_callback(current, code) {
+            context.transaction = Some(Span::from(transaction));
+            context.session = context.session.or_else(|| {
+                Some(optional_node_to_string(
+                    &transaction.child_by_field_name("object"),
+                    code,
+                ))
+            });
+            break;
+        }
+
+        if EXECUTABLE_SCOPES.contains(&current.kind()) {
+            let started = context
+                .session
+                .as_ref()
+                .and_then(|session| started_transaction(current, session, invocation, code));
+            if let Some(started) = started {
+                context.transaction = Some(Span::from(started));
+                loops.retain(|loop_node: &Node| loop_node.start_byte() > started.end_byte());
+            }
+            break;
+        }
+
+        if LOOPS.contains(&current.kind()) {
+            loops.push(current);
+        }
+        scope = current.parent();
+    }
+
+    context.in_loop = !loops.is_empty();
+    return context;
+}
+
+#[cfg(test)]
+mod test {
+    use dialect_interface::ExtractionWarning;
+    use mongodb_query_language::execution::Execution::{FindOne, UpdateMany};
+    use mongodb_query_language::execution::ExecutionContext;
+    use mongodb_query_language::filter::FilterOperator::Equals;
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::values::Value::String;
+
+    use crate::use_cases::aggregate::aggregate;
+    use crate::use_cases::find::find;
+    use crate::use_cases::update::update;
+    use crate::Java;
+
+    #[test]
+    fn annotates_executions_with_their_session_and_transaction() {
+        let code = r#"
+        public class Transfers {
+            private final MongoCollection<Document> accounts;
+
+            public void transfer(ClientSession session, List<String> owners) {
+                accounts.find(session, eq("owner", "ana")).first();
+                clientSession.withTransaction(() -> {
+                    for (String owner : owners) {
+                        accounts.updateMany(eq("owner", owner), inc("balance", 1));
+                    }
+                    return accounts.find(eq("owner", "ana")).first();
+                });
+
+                session.startTransaction();
+                accounts.updateMany(session, eq("owner", "bob"), inc("balance", -1));
+                session.commitTransaction();
+                accounts.find().first();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let finds = find(java.full_parse(&code), &code).unwrap().executions;
+        let updates = update(java.full_parse(&code), &code).unwrap().executions;
+        let contexts: Vec<ExecutionContext> = finds
+            .iter()
+            .chain(updates.iter())
+            .map(|execution| execution.context().clone())
+            .collect();
+
+        let span = Span::default();
+        let session = |name: &str, transaction: bool, in_loop: bool| ExecutionContext {
+            session: Some(name.to_string()),
+            transaction: transaction.then_some(span),
+            in_loop,
+        };
+        assert_eq!(
+            contexts,
+            vec![
+                session("session", false, false),
+                session("clientSession", true, false),
+                ExecutionContext::default(),
+                session("clientSession", true, true),
+                session("session", true, false),
+            ]
+        );
+
+        let FindOne { predicate, .. } = &finds[0] else {
+            panic!()
+        };
+        assert_eq!(
+            *predicate,
+            Equals {
+                field: "owner".to_string(),
+                value: String("ana".to_string(), span),
+                span,
+            }
+        );
+        assert!(
+            matches!(&updates[1], UpdateMany { predicate: Equals { field, .. }, .. } if field == "owner")
+        );
+    }
+
+    #[test]
+    fn guesses_sessions_by_name_only_for_session_overloads() {
+        let code = r#"
+        public class Transfers {
+            private final MongoCollection<Document> accounts;
+
+            public void transfer(String id) {
+                accounts.find(currentSession, eq("_id", id)).first();
+                accounts.updateOne(bySession, set("active", false));
+                accounts.aggregate(pipelineSession);
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let finds = find(java.full_parse(&code), &code).unwrap().executions;
+        let updates = update(java.full_parse(&code), &code).unwrap();
+        let aggregates = aggregate(java.full_parse(&code), &code).unwrap().executions;
+
+        assert_eq!(
+            finds[0].context().session,
+            Some("currentSession".to_string())
+        );
+        assert!(updates
+            .warnings
+            .contains(&ExtractionWarning::UnresolvedFilter {
+                expression: "bySession".to_string(),
+                span: Span::default(),
+            }));
+        assert_eq!(aggregates[0].context().session, None);
+    }
+}
@@ -1,7 +1,14 @@
-use crate::tree_ext::optional_node_to_string;
+use crate::symbol_index::{enclosing_class, resolve_collection, resolve_database};
+use crate::tree_ext::infer_value_type::{
+    declared_as_member, declared_type, initializer_of, member_initializer_of,
+};
+use crate::tree_ext::{
+    field_from_expression, invocation_arguments, invocation_name, optional_node_to_string,
+    simple_type_name,
+};
 use mongodb_query_language::execution::ExecutionNamespace;
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use tree_sitter::Node;
 
@@ -53,3 +60,338 @@ pub fn infer_mongodb_namespace(
 
     return Ok(result);
 }
+
+const DERIVED_HANDLES: [&str; 6] = [
+    "withCodecRegistry",
+    "withDocumentClass",
+    "withReadPreference",
+    "withReadConcern",
+    "withWriteConcern",
+    "withTimeout",
+];
+
+fn assignments_to<'a>(node: Node<'a>, name: &str, code: &String, result: &mut Vec<Node<'a>>) {
+    if node.kind() == "assignment_expression" {
+        let target = node
+            .child_by_field_name("left")
+            .map(|left| match left.kind() {
+                "field_access" => optional_node_to_string(&left.child_by_field_name("field"), code),
+                _ => optional_node_to_string(&Some(left), code),
+            });
+
+        if target.as_deref() == Some(name) {
+            if let Some(value) = node.child_by_field_name("right") {
+                result.push(value);
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        assignments_to(child, name, code, result);
+    }
+}
+
+fn constructor_assignment<'a>(usage: Node<'a>, name: &str, code: &String) -> Option<Node<'a>> {
+    let mut class_body = usage.parent();
+    while let Some(scope) = class_body {
+        if scope.kind() == "class_body" {
+            break;
+        }
+        class_body = scope.parent();
+    }
+
+    let class_body = class_body?;
+    let mut cursor = class_body.walk();
+    let mut assignments = vec![];
+    for constructor in class_body
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() == "constructor_declaration")
+    {
+        if let Some(body) = constructor.child_by_field_name("body") {
+            assignments_to(body, name, code, &mut assignments);
+        }
+    }
+
+    return assignments.into_iter().find(|value| {
+        value.start_byte() > usage.start_byte() || value.end_byte() < usage.end_byte()
+    });
+}
+
+type Visited = HashSet<(Option<String>, String)>;
+
+fn member_definition<'a>(
+    usage: Node<'a>,
+    name: &str,
+    initializer: Option<Node<'a>>,
+    code: &String,
+    visited: &mut Visited,
+) -> Option<Node<'a>> {
+    if !visited.insert((enclosing_class(usage, code), name.to_string())) {
+        return None;
+    }
+
+    return initializer.or_else(|| constructor_assignment(usage, name, code));
+}
+
+fn definition_of<'a>(node: Node<'a>, code: &String, visited: &mut Visited) -> Option<Node<'a>> {
+    return match node.kind() {
+        "identifier" => {
+            let name = optional_node_to_string(&Some(node), code);
+            match declared_as_member(node, &name, code) {
+                true => member_definition(node, &name, initializer_of(node, code), code, visited),
+                false => initializer_of(node, code),
+            }
+        }
+        "field_access"
+            if optional_node_to_string(&node.child_by_field_name("object"), code) == "this" =>
+        {
+            let name = optional_node_to_string(&node.child_by_field_name("field"), code);
+            let initializer = member_initializer_of(node, &name, code);
+            member_definition(node, &name, initializer, code, visited)
+        }
+        "parenthesized_expression" => node.named_child(0),
+        _ => None,
+    };
+}
+
+fn database_of(node: Node, code: &String, visited: &mut Visited) -> Option<String> {
+    if let Some(definition) = definition_of(node, code, visited) {
+        return database_of(definition, code, visited);
+    }
+
+    if node.kind() != "method_invocation" {
+        return resolve_database(node, code);
+    }
+
+    let name = invocation_name(node, code);
+    let arguments = invocation_arguments(node);
+    return match (name.as_str(), &arguments[..]) {
+        ("getDatabase", [database]) => field_from_expression(*database, code),
+        (derived, _) if DERIVED_HANDLES.contains(&derived) => {
+            database_of(node.child_by_field_name("object")?, code, visited)
+        }
+        _ => None,
+    };
+}
+
+fn namespace_from_expression(
+    node: Node,
+    code: &String,
+    visited: &mut Visited,
+) -> Option<(Option<String>, String)> {
+    if let Some(definition) = definition_of(node, code, visited) {
+        return namespace_from_expression(definition, code, visited);
+    }
+
+    if node.kind() != "method_invocation" {
+        return resolve_collection(node, code);
+    }
+
+    let name = invocation_name(node, code);
+    let arguments = invocation_arguments(node);
+    let receiver = node.child_by_field_name("object");
+    return match (name.as_str(), &arguments[..]) {
+        ("getCollection", [collection, ..]) => Some((
+            receiver.and_then(|database| database_of(database, code, visited)),
+            field_from_expression(*collection, code)?,
+        )),
+        (derived, _) if DERIVED_HANDLES.contains(&derived) => {
+            namespace_from_expression(receiver?, code, visited)
+        }
+        _ => None,
+    };
+}
+
+pub fn infer_database_from_usage(database: Node, code: &String) -> Option<String> {
+    return database_of(database, code, &mut HashSet::new());
+}
+
+pub fn infer_namespace_from_usage(collection: Node, code: &String) -> Option<ExecutionNamespace> {
+    let (database, collection_name) =
+        namespace_from_expression(collection, code, &mut HashSet::new())?;
+    return Some(ExecutionNamespace {
+        database,
+        collection: Some(collection_name),
+        reference_name: optional_node_to_string(&Some(collection), code),
+    });
+}
+
+const DOCUMENT_CLASSES: [&str; 7] = [
+    "Document",
+    "BsonDocument",
+    "RawBsonDocument",
+    "BasicDBObject",
+    "DBObject",
+    "Bson",
+    "Object",
+];
+
+fn type_argument(type_name: &str) -> Option<String> {
+    let (_, arguments) = type_name.split_once('<')?;
+    let mut depth = 0;
+    let argument: String = arguments
+        .chars()
+        .take_while(|c| match c {
+            '<' => {
+                depth += 1;
+                true
+            }
+            '>' | ',' if depth == 0 => false,
+            '>' => {
+                depth -= 1;
+                true
+            }
+            _ => true,
+        })
+        .collect();
+
+    return Some(argument.trim().to_string());
+}
+
+fn document_class_from_expression(
+    node: Node,
+    code: &String,
+    visited: &mut Visited,
+) -> Option<String> {
+    if matches!(node.kind(), "identifier" | "field_access") {
+        let declared = declared_type(node, code).and_then(|type_name| type_argument(&type_name));
+        if declared.is_some() {
+            return declared;
+        }
+    }
+
+    if let Some(definition) = definition_of(node, code, visited) {
+        return document_class_from_expression(definition, code, visited);
+    }
+
+    if node.kind() != "method_invocation" {
+        return None;
+    }
+
+    let name = invocation_name(node, code);
+    let arguments = invocation_arguments(node);
+    return match (name.as_str(), &arguments[..]) {
+        ("getCollection", [_, class]) | ("withDocumentClass", [class])
+            if class.kind() == "class_literal" =>
+        {
+            Some(optional_node_to_string(&class.named_child(0), code))
+        }
+        (derived, _) if DERIVED_HANDLES.contains(&derived) => {
+            document_class_from_expression(node.child_by_field_name("object")?, code, visited)
+        }
+        _ => None,
+    };
+}
+
+pub fn infer_document_class(collection: Node, code: &String) -> Option<String> {
+    let class = simple_type_name(&document_class_from_expression(
+        collection,
+        code,
+        &mut HashSet::new(),
+    )?);
+    return Some(class).filter(|class| !DOCUMENT_CLASSES.contains(&class.as_str()));
+}
+
+#[cfg(test)]
+mod test {
+    use dialect_interface::ExtractionWarning;
+    use mongodb_query_language::execution::Execution::FindMany;
+    use mongodb_query_language::execution::ExecutionNamespace;
+    use mongodb_query_language::span::Span;
+
+    use crate::use_cases::find::find;
+    use crate::Java;
+
+    #[test]
+    fn infers_namespaces_from_collection_handles() {
+        let code = r#"
+        public class UserRepository {
+            private static final String USERS = "users";
+            private final MongoCollection<User> users;
+
+            public UserRepository(MongoClient client, CodecRegistry registry) {
+                this.users = client.getDatabase("shop")
+                    .getCollection(USERS, User.class)
+                    .withCodecRegistry(registry);
+            }
+
+            public void findAll(MongoClient client, MongoDatabase database) {
+                this.users.find();
+                MongoDatabase shop = client.getDatabase("shop");
+                MongoCollection<Document> orders = shop.getCollection("orders");
+                orders.find();
+                database.getCollection("logs").find();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap();
+        let namespaces: Vec<ExecutionNamespace> = result
+            .executions
+            .iter()
+            .map(|execution| match execution {
+                FindMany { namespace, .. } => namespace.clone(),
+                _ => panic!(),
+            })
+            .collect();
+
+        assert_eq!(result.warnings, vec![]);
+        assert_eq!(
+            namespaces,
+            vec![
+                ExecutionNamespace {
+                    database: Some("shop".to_string()),
+                    collection: Some("users".to_string()),
+                    reference_name: "this.users".to_string()
+                },
+                ExecutionNamespace {
+                    database: Some("shop".to_string()),
+                    collection: Some("orders".to_string()),
+                    reference_name: "orders".to_string()
+                },
+                ExecutionNamespace {
+                    database: None,
+                    collection: Some("logs".to_string()),
+                    reference_name: "database.getCollection(logs)".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_at_cyclic_member_assignments() {
+        let code = r#"
+        public class UserRepository {
+            private MongoCollection<Document> users;
+            private MongoCollection<Document> people;
+
+            public UserRepository() {
+                this.users = people;
+                this.people = users;
+            }
+
+            public void findAll() {
+                users.find();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap();
+
+        assert_eq!(result.executions.len(), 1);
+        assert_eq!(
+            result.warnings,
+            vec![ExtractionWarning::UnresolvedCollection {
+                reference: "users".to_string(),
+                span: Span::default(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,405 @@
+use mongodb_query_language::values::{BsonType, ValueType};
+use tree_sitter::Node;
+
+use crate::tree_ext::{invocation_name, optional_node_to_string};
+
+const LOCAL_SCOPES: [&str; 4] = [
+    "block",
+    "constructor_body",
+    "switch_block_statement_group",
+    "for_statement",
+];
+const PARAMETER_SCOPES: [&str; 4] = [
+    "method_declaration",
+    "constructor_declaration",
+    "lambda_expression",
+    "record_declaration",
+];
+const MEMBER_SCOPES: [&str; 3] = ["class_body", "enum_body", "interface_body"];
+
+pub fn java_type_name_to_value_type(type_name: &str) -> ValueType {
+    let simple_name = type_name
+        .split('<')
+        .next()
+        .unwrap_or(type_name)
+        .rsplit('.')
+        .next()
+        .unwrap_or(type_name)
+        .trim();
+
+    let bson_type = match simple_name {
+        "String" | "CharSequence" | "char" | "Character" => BsonType::String,
+        "int" | "Integer" | "short" | "Short" | "byte" | "Byte" | "AtomicInteger" => BsonType::Int,
+        "long" | "Long" | "AtomicLong" | "BigInteger" => BsonType::Long,
+        "double" | "Double" | "float" | "Float" => BsonType::Double,
+        "BigDecimal" | "Decimal128" => BsonType::Decimal,
+        "Number" => BsonType::Number,
+        "boolean" | "Boolean" => BsonType::Bool,
+        "ObjectId" => BsonType::ObjectId,
+        "Date" | "Instant" | "LocalDate" | "LocalDateTime" | "ZonedDateTime" | "OffsetDateTime"
+        | "BsonDateTime" => BsonType::Date,
+        "UUID" | "Binary" | "BsonBinary" => BsonType::BinData,
+        "Pattern" | "BsonRegularExpression" => BsonType::Regex,
+        "BsonTimestamp" => BsonType::Timestamp,
+        "MinKey" => BsonType::MinKey,
+        "MaxKey" => BsonType::MaxKey,
+        "List" | "ArrayList" | "LinkedList" | "Set" | "HashSet" | "TreeSet" | "Collection"
+        | "Iterable" | "BsonArray" => BsonType::Array,
+        "Document" | "BasicDBObject" | "DBObject" | "Bson" | "BsonDocument" | "Map" | "HashMap"
+        | "LinkedHashMap" => BsonType::Object,
+        _ => return ValueType::Any,
+    };
+
+    return ValueType::Of(bson_type);
+}
+
+pub fn java_type_to_value_type(type_node: Node, code: &String) -> ValueType {
+    if type_node.kind() == "array_type" {
+        let element = optional_node_to_string(&type_node.child_by_field_name("element"), code);
+        if element == "byte" {
+            return ValueType::Of(BsonType::BinData);
+        }
+
+        return ValueType::Of(BsonType::Array);
+    }
+
+    return java_type_name_to_value_type(&optional_node_to_string(&Some(type_node), code));
+}
+
+struct Declaration<'a> {
+    type_node: Node<'a>,
+    initializer: Option<Node<'a>>,
+    variadic: bool,
+}
+
+fn declarators_of<'a>(declaration: Node<'a>, name: &str, code: &String) -> Option<Declaration<'a>> {
+    let type_node = declaration.child_by_field_name("type")?;
+    let mut cursor = declaration.walk();
+    let declarator = declaration
+        .children_by_field_name("declarator", &mut cursor)
+        .find(|declarator| {
+            optional_node_to_string(&declarator.child_by_field_name("name"), code) == name
+        })?;
+
+    return Some(Declaration {
+        type_node,
+        initializer: declarator.child_by_field_name("value"),
+        variadic: false,
+    });
+}
+
+fn parameter_of<'a>(parameter: Node<'a>, name: &str, code: &String) -> Option<Declaration<'a>> {
+    let declared_name = match parameter.kind() {
+        "spread_parameter" => {
+            let mut cursor = parameter.walk();
+            let declarator = parameter
+                .named_children(&mut cursor)
+                .find(|child| child.kind() == "variable_declarator")?;
+            declarator.child_by_field_name("name")
+        }
+        _ => parameter.child_by_field_name("name"),
+    };
+
+    if optional_node_to_string(&declared_name, code) != name {
+        return None;
+    }
+
+    let mut cursor = parameter.walk();
+    let type_node = parameter.child_by_field_name("type").or(parameter
+        .named_children(&mut cursor)
+        .find(|child| child.kind() != "modifiers" && child.kind() != "variable_declarator"))?;
+
+    return Some(Declaration {
+        type_node,
+        initializer: None,
+        variadic: parameter.kind() == "spread_parameter",
+    });
+}
+
+fn declaration_in_scope<'a>(
+    scope: Node<'a>,
+    usage: Node<'a>,
+    name: &str,
+    code: &String,
+) -> Option<Declaration<'a>> {
+    let kind = scope.kind();
+    let mut cursor = scope.walk();
+
+    if LOCAL_SCOPES.contains(&kind) {
+        return scope
+            .named_children(&mut cursor)
+            .filter(|child| {
+                child.end_byte() <= usage.start_byte()
+                    && child.kind() == "local_variable_declaration"
+            })
+            .filter_map(|declaration| declarators_of(declaration, name, code))
+            .last();
+    }
+
+    if PARAMETER_SCOPES.contains(&kind) {
+        let parameters = scope.child_by_field_name("parameters")?;
+        let mut parameters_cursor = parameters.walk();
+        let result = parameters
+            .named_children(&mut parameters_cursor)
+            .find_map(|parameter| parameter_of(parameter, name, code));
+        return result;
+    }
+
+    if kind == "catch_clause" {
+        let parameter = scope
+            .named_children(&mut cursor)
+            .find(|child| child.kind() == "catch_formal_parameter")?;
+        return parameter_of(parameter, name, code);
+    }
+
+    if kind == "enhanced_for_statement" {
+        return parameter_of(scope, name, code);
+    }
+
+    if MEMBER_SCOPES.contains(&kind) {
+        return scope
+            .named_children(&mut cursor)
+            .filter(|child| matches!(child.kind(), "field_declaration" | "constant_declaration"))
+            .find_map(|declaration| declarators_of(declaration, name, code));
+    }
+
+    return None;
+}
+
+fn declaration_of<'a>(
+    usage: Node<'a>,
+    name: &str,
+    fields_only: bool,
+    code: &String,
+) -> Option<Declaration<'a>> {
+    let mut current = usage;
+    while let Some(scope) = current.parent() {
+        if !fields_only || MEMBER_SCOPES.contains(&scope.kind()) {
+            if let Some(declaration) = declaration_in_scope(scope, usage, name, code) {
+                return Some(declaration);
+            }
+        }
+
+        current = scope;
+    }
+
+    return None;
+}
+
+fn declared_value_type(declaration: Declaration, code: &String) -> ValueType {
+    if declaration.variadic {
+        return ValueType::Of(BsonType::Array);
+    }
+
+    if optional_node_to_string(&Some(declaration.type_node), code) == "var" {
+        return declaration
+            .initializer
+            .map(|initializer| infer_value_type(initializer, code))
+            .unwrap_or(ValueType::Any);
+    }
+
+    return java_type_to_value_type(declaration.type_node, code);
+}
+
+fn invocation_value_type(invocation: Node, code: &String) -> ValueType {
+    let receiver = optional_node_to_string(&invocation.child_by_field_name("object"), code);
+    let method = invocation_name(invocation, code);
+
+    let bson_type = match (receiver.as_str(), method.as_str()) {
+        ("String", _) => BsonType::String,
+        ("Integer", "parseInt" | "valueOf") => BsonType::Int,
+        ("Long", "parseLong" | "valueOf") => BsonType::Long,
+        ("Double", "parseDouble" | "valueOf") => BsonType::Double,
+        ("Boolean", "parseBoolean" | "valueOf") => BsonType::Bool,
+        ("UUID", "randomUUID" | "fromString") => BsonType::BinData,
+        ("Instant" | "LocalDate" | "LocalDateTime" | "ZonedDateTime", "now" | "parse" | "of") => {
+            BsonType::Date
+        }
+        (_, "toString" | "toHexString" | "toLowerCase" | "toUpperCase" | "trim" | "name") => {
+            BsonType::String
+        }
+        _ => return ValueType::Any,
+    };
+
+    return ValueType::Of(bson_type);
+}
+
+pub fn initializer_of<'a>(identifier: Node<'a>, code: &String) -> Option<Node<'a>> {
+    let name = optional_node_to_string(&Some(identifier), code);
+    return declaration_of(identifier, &name, false, code)?.initializer;
+}
+
+pub fn member_initializer_of<'a>(usage: Node<'a>, name: &str, code: &String) -> Option<Node<'a>> {
+    return declaration_of(usage, name, true, code)?.initializer;
+}
+
+pub fn declared_as_member(usage: Node, name: &str, code: &String) -> bool {
+    return match (
+        declaration_of(usage, name, false, code),
+        declaration_of(usage, name, true, code),
+    ) {
+        (Some(nearest), Some(member)) => nearest.type_node == member.type_node,
+        _ => false,
+    };
+}
+
+pub fn declared_type(usage: Node, code: &String) -> Option<String> {
+    let declaration = match usage.kind() {
+        "identifier" => {
+            let name = optional_node_to_string(&Some(usage), code);
+            declaration_of(usage, &name, false, code)
+        }
+        "field_access" => {
+            let field = optional_node_to_string(&usage.child_by_field_name("field"), code);
+            declaration_of(usage, &field, true, code)
+        }
+        _ => None,
+    }?;
+
+    return Some(optional_node_to_string(&Some(declaration.type_node), code));
+}
+
+pub fn declared_type_name(usage: Node, code: &String) -> Option<String> {
+    let type_name = declared_type(usage, code)?;
+    return Some(
+        type_name
+            .split('<')
+            .next()
+            .unwrap_or(&type_name)
+            .to_string(),
+    );
+}
+
+pub fn infer_value_type(expression: Node, code: &String) -> ValueType {
+    let text = optional_node_to_string(&Some(expression), code);
+
+    return match expression.kind() {
+        "identifier" => declaration_of(expression, &text, false, code)
+            .map(|declaration| declared_value_type(declaration, code))
+            .unwrap_or(ValueType::Any),
+        "field_access" => {
+            let object = optional_node_to_string(&expression.child_by_field_name("object"), code);
+            let field = optional_node_to_string(&expression.child_by_field_name("field"), code);
+            match object.as_str() {
+                "this" => declaration_of(expression, &field, true, code)
+                    .map(|declaration| declared_value_type(declaration, code))
+                    .unwrap_or(ValueType::Any),
+                _ => ValueType::Any,
+            }
+        }
+        "cast_expression" | "object_creation_expression" | "array_creation_expression" => {
+            expression
+                .child_by_field_name("type")
+                .map(|type_node| match expression.kind() {
+                    "array_creation_expression" => ValueType::Of(BsonType::Array),
+                    _ => java_type_to_value_type(type_node, code),
+                })
+                .unwrap_or(ValueType::Any)
+        }
+        "parenthesized_expression" => expression
+            .named_child(0)
+            .map(|inner| infer_value_type(inner, code))
+            .unwrap_or(ValueType::Any),
+        "ternary_expression" => {
+            let branch = |field| {
+                expression
+                    .child_by_field_name(field)
+                    .map(|branch| infer_value_type(branch, code))
+                    .unwrap_or(ValueType::Any)
+            };
+            branch("consequence").join(&branch("alternative"))
+        }
+        "binary_expression" => {
+            let operator =
+                optional_node_to_string(&expression.child_by_field_name("operator"), code);
+            let operand = |field| {
+                expression
+                    .child_by_field_name(field)
+                    .map(|operand| infer_value_type(operand, code))
+                    .unwrap_or(ValueType::Any)
+            };
+            let string = ValueType::Of(BsonType::String);
+            match operator.as_str() {
+                "==" | "!=" | "<" | ">" | "<=" | ">=" | "&&" | "||" => {
+                    ValueType::Of(BsonType::Bool)
+                }
+                "+" if operand("left") == string || operand("right") == string => string,
+                _ => operand("left").join(&operand("right")),
+            }
+        }
+        "method_invocation" => invocation_value_type(expression, code),
+        "string_literal" | "text_block" | "character_literal" => ValueType::Of(BsonType::String),
+        "decimal_integer_literal"
+        | "hex_integer_literal"
+        | "octal_integer_literal"
+        | "binary_integer_literal" => match text.ends_with(['l', 'L']) {
+            true => ValueType::Of(BsonType::Long),
+            false => ValueType::Of(BsonType::Int),
+        },
+        "decimal_floating_point_literal" => ValueType::Of(BsonType::Double),
+        "true" | "false" => ValueType::Of(BsonType::Bool),
+        "null_literal" => ValueType::Of(BsonType::Null),
+        _ => ValueType::Any,
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use mongodb_query_language::values::{BsonType, ValueType};
+    use tree_sitter::Node;
+
+    use crate::tree_ext::infer_value_type::infer_value_type;
+    use crate::Java;
+
+    fn find_identifier<'a>(node: Node<'a>, code: &String, name: &str) -> Option<Node<'a>> {
+        if node.kind() == "identifier"
+            && &code[node.byte_range()] == name
+            && node.parent().map(|parent| parent.kind()) == Some("argument_list")
+        {
+            return Some(node);
+        }
+
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        return children
+            .into_iter()
+            .find_map(|child| find_identifier(child, code, name));
+    }
+
+    #[test]
+    fn infers_types_from_java_declarations() {
+        let code = r#"
+        public class MyRepository {
+            private final ObjectId ownerId;
+
+            public Document find(String id, int age, Instant since, long... counts) {
+                var active = age > 18;
+                List<String> tags = List.of();
+                return collection.find(and(eq("_id", id), eq("age", age), gte("since", since),
+                    eq("active", active), in("tags", tags), eq("owner", ownerId),
+                    in("counts", counts), eq("unknown", unknown))).first();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let root = tree.borrow();
+        let infer = |name| {
+            infer_value_type(
+                find_identifier(root.root_node(), &code, name).unwrap(),
+                &code,
+            )
+        };
+
+        assert_eq!(infer("id"), ValueType::Of(BsonType::String));
+        assert_eq!(infer("age"), ValueType::Of(BsonType::Int));
+        assert_eq!(infer("since"), ValueType::Of(BsonType::Date));
+        assert_eq!(infer("active"), ValueType::Of(BsonType::Bool));
+        assert_eq!(infer("tags"), ValueType::Of(BsonType::Array));
+        assert_eq!(infer("ownerId"), ValueType::Of(BsonType::ObjectId));
+        assert_eq!(infer("counts"), ValueType::Of(BsonType::Array));
+        assert_eq!(infer("unknown"), ValueType::Any);
+    }
+}
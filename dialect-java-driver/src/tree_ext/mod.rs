@@ -1,8 +1,31 @@
+use dialect_interface::ExtractionWarning;
+use mongodb_query_language::span::Span;
 use tree_sitter::Node;
 
+use crate::symbol_index::resolve_constant;
+use crate::tree_ext::infer_value_type::initializer_of;
+use crate::tree_ext::value_from_expression::iterable_elements;
+
+pub mod document_from_expression;
+pub mod document_schema_from_class;
+pub mod entity_mapping;
+pub mod expression_from_expression;
+pub mod find_options_from_chain;
 pub mod friendly_capture;
+pub mod index_keys_from_expression;
+pub mod infer_execution_context;
 pub mod infer_mongodb_namespace;
+pub mod infer_value_type;
+pub mod pipeline_from_expression;
 pub mod predicate_from_driver_method;
+pub mod predicate_from_expression;
+pub mod projection_from_expression;
+pub mod reaching_definitions;
+pub mod sort_from_expression;
+#[cfg(test)]
+pub mod test_fixtures;
+pub mod update_from_expression;
+pub mod value_from_expression;
 
 pub fn optional_node_to_string(node: &Option<Node>, code: &String) -> String {
     return node
@@ -14,3 +37,273 @@ pub fn optional_node_to_string(node: &Option<Node>, code: &String) -> String {
         })
         .unwrap_or("<unknown>".to_string());
 }
+
+fn unescape_java(text: &str) -> Option<String> {
+    let mut units: Vec<u16> = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(character) = chars.next() {
+        if character != '\\' {
+            let mut buffer = [0; 2];
+            units.extend_from_slice(character.encode_utf16(&mut buffer));
+            continue;
+        }
+
+        let escaped = match chars.next()? {
+            'b' => 0x08,
+            't' => 0x09,
+            'n' => 0x0a,
+            'f' => 0x0c,
+            'r' => 0x0d,
+            's' => 0x20,
+            'u' => {
+                while chars.peek() == Some(&'u') {
+                    chars.next();
+                }
+                let digits: String = chars.by_ref().take(4).collect();
+                u16::from_str_radix(&digits, 16).ok()?
+            }
+            digit @ '0'..='7' => {
+                let mut code = digit.to_digit(8)?;
+                let limit = if digit <= '3' { 2 } else { 1 };
+                for _ in 0..limit {
+                    match chars.peek().and_then(|next| next.to_digit(8)) {
+                        Some(next) => {
+                            code = code * 8 + next;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                code as u16
+            }
+            other => other as u16,
+        };
+        units.push(escaped);
+    }
+
+    return String::from_utf16(&units).ok();
+}
+
+pub fn string_literal(node: Node, code: &String) -> Option<String> {
+    let quote = match node.kind() {
+        "string_literal" => '"',
+        "character_literal" => '\'',
+        _ => return None,
+    };
+
+    let text = node.utf8_text(code.as_bytes()).ok()?;
+    return unescape_java(text.strip_prefix(quote)?.strip_suffix(quote)?);
+}
+
+pub fn uncapitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    return match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    };
+}
+
+pub fn string_value(node: Node, code: &String) -> Option<String> {
+    return match node.kind() {
+        "string_literal" => string_literal(node, code),
+        "binary_expression" => Some(format!(
+            "{}{}",
+            string_value(node.child_by_field_name("left")?, code)?,
+            string_value(node.child_by_field_name("right")?, code)?
+        )),
+        "parenthesized_expression" => string_value(node.named_child(0)?, code),
+        "identifier" => string_value(initializer_of(node, code)?, code),
+        "field_access" => string_value(
+            initializer_of(node.child_by_field_name("field")?, code)?,
+            code,
+        ),
+        _ => None,
+    };
+}
+
+pub fn invocation_name(invocation: Node, code: &String) -> String {
+    return optional_node_to_string(&invocation.child_by_field_name("name"), code);
+}
+
+pub fn invocation_arguments(invocation: Node) -> Vec<Node> {
+    let Some(arguments) = invocation.child_by_field_name("arguments") else {
+        return vec![];
+    };
+
+    let mut cursor = arguments.walk();
+    return arguments
+        .named_children(&mut cursor)
+        .filter(|argument| !argument.is_extra())
+        .collect();
+}
+
+pub fn field_from_expression(node: Node, code: &String) -> Option<String> {
+    return match node.kind() {
+        "string_literal" => string_literal(node, code),
+        "identifier" => match initializer_of(node, code) {
+            Some(initializer) => field_from_expression(initializer, code),
+            None => resolve_constant(node, code),
+        },
+        "field_access" => match initializer_of(node.child_by_field_name("field")?, code) {
+            Some(initializer) => field_from_expression(initializer, code),
+            None => resolve_constant(node, code),
+        },
+        _ => None,
+    };
+}
+
+pub fn fields_from_arguments(arguments: &[Node], code: &String) -> Vec<String> {
+    if let [single] = arguments {
+        if let Some(elements) = iterable_elements(*single, code) {
+            return fields_from_arguments(&elements, code);
+        }
+    }
+
+    return arguments
+        .iter()
+        .filter_map(|argument| field_from_expression(*argument, code))
+        .collect();
+}
+
+pub fn operands_from_arguments<'a>(arguments: &[Node<'a>], code: &String) -> Vec<Node<'a>> {
+    if let [single] = arguments {
+        if let Some(elements) = iterable_elements(*single, code) {
+            return elements;
+        }
+    }
+
+    return arguments.to_vec();
+}
+
+pub fn resolved_expression<'a>(node: Node<'a>, code: &String) -> Node<'a> {
+    return match node.kind() {
+        "identifier" => initializer_of(node, code)
+            .map(|initializer| resolved_expression(initializer, code))
+            .unwrap_or(node),
+        "parenthesized_expression" => node
+            .named_child(0)
+            .map(|inner| resolved_expression(inner, code))
+            .unwrap_or(node),
+        _ => node,
+    };
+}
+
+pub fn local_usages<'a>(value: Node<'a>, code: &String) -> Vec<Node<'a>> {
+    let Some(declarator) = value.parent().filter(|declarator| {
+        declarator.kind() == "variable_declarator"
+            && declarator.child_by_field_name("value") == Some(value)
+    }) else {
+        return vec![];
+    };
+    let Some(scope) = declarator
+        .parent()
+        .filter(|declaration| declaration.kind() == "local_variable_declaration")
+        .and_then(|declaration| declaration.parent())
+    else {
+        return vec![];
+    };
+
+    let name = optional_node_to_string(&declarator.child_by_field_name("name"), code);
+    let mut identifiers = vec![];
+    descendants_of_kind(scope, &["identifier"], &mut identifiers);
+    return identifiers
+        .into_iter()
+        .filter(|identifier| {
+            let member = identifier.parent().is_some_and(|parent| {
+                parent.child_by_field_name("field") == Some(*identifier)
+                    || parent.child_by_field_name("name") == Some(*identifier)
+            });
+            !member
+                && identifier.start_byte() >= declarator.end_byte()
+                && optional_node_to_string(&Some(*identifier), code) == name
+        })
+        .collect();
+}
+
+pub fn builder_invocations<'a>(node: Node<'a>, code: &String) -> Option<Vec<Node<'a>>> {
+    let node = resolved_expression(node, code);
+    return match node.kind() {
+        "object_creation_expression" => Some(vec![]),
+        "method_invocation" => {
+            let mut calls = builder_invocations(node.child_by_field_name("object")?, code)?;
+            calls.push(node);
+            Some(calls)
+        }
+        _ => None,
+    };
+}
+
+pub fn builder_calls<'a>(node: Node<'a>, code: &String) -> Option<Vec<(String, Vec<Node<'a>>)>> {
+    let calls = builder_invocations(node, code)?;
+    return Some(
+        calls
+            .into_iter()
+            .map(|call| (invocation_name(call, code), invocation_arguments(call)))
+            .collect(),
+    );
+}
+
+pub fn unsupported_operator(invocation: Node, code: &String) -> ExtractionWarning {
+    return ExtractionWarning::UnsupportedOperator {
+        operator: invocation_name(invocation, code),
+        span: invocation
+            .child_by_field_name("name")
+            .map(Span::from)
+            .unwrap_or(Span::from(invocation)),
+    };
+}
+
+pub fn simple_type_name(type_name: &str) -> String {
+    let raw = type_name.split('<').next().unwrap_or(type_name);
+    return raw.rsplit('.').next().unwrap_or(raw).trim().to_string();
+}
+
+pub fn annotation_named<'a>(declaration: Node<'a>, name: &str, code: &String) -> Option<Node<'a>> {
+    let mut cursor = declaration.walk();
+    let modifiers = declaration
+        .named_children(&mut cursor)
+        .find(|child| child.kind() == "modifiers")?;
+
+    let mut modifiers_cursor = modifiers.walk();
+    let annotation = modifiers
+        .named_children(&mut modifiers_cursor)
+        .filter(|modifier| matches!(modifier.kind(), "annotation" | "marker_annotation"))
+        .find(|annotation| {
+            simple_type_name(&optional_node_to_string(
+                &annotation.child_by_field_name("name"),
+                code,
+            )) == name
+        });
+    return annotation;
+}
+
+pub fn annotation_element<'a>(annotation: Node<'a>, key: &str, code: &String) -> Option<Node<'a>> {
+    let arguments = annotation.child_by_field_name("arguments")?;
+    let mut cursor = arguments.walk();
+    let element =
+        arguments
+            .named_children(&mut cursor)
+            .find_map(|argument| match argument.kind() {
+                "element_value_pair" => {
+                    let pair_key =
+                        optional_node_to_string(&argument.child_by_field_name("key"), code);
+                    (pair_key == key)
+                        .then(|| argument.child_by_field_name("value"))
+                        .flatten()
+                }
+                _ if key == "value" => Some(argument),
+                _ => None,
+            });
+    return element;
+}
+
+pub fn descendants_of_kind<'a>(node: Node<'a>, kinds: &[&str], result: &mut Vec<Node<'a>>) {
+    if kinds.contains(&node.kind()) {
+        result.push(node);
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        descendants_of_kind(child, kinds, result);
+    }
+}
@@ -0,0 +1,474 @@
+use dialect_interface::ExtractionWarning;
+use mongodb_query_language::aggregation::{
+    Expression, MergeWhenMatched, MergeWhenNotMatched, Stage,
+};
+use mongodb_query_language::filter::FilterOperator;
+use mongodb_query_language::serialization::{FromBson, ToBson};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::Value;
+use tree_sitter::Node;
+
+use crate::tree_ext::document_from_expression::document_from_expression;
+use crate::tree_ext::expression_from_expression::expression_from_expression;
+use crate::tree_ext::predicate_from_expression::predicate_from_expression;
+use crate::tree_ext::projection_from_expression::projection_from_expression;
+use crate::tree_ext::sort_from_expression::sort_from_expression;
+use crate::tree_ext::value_from_expression::{
+    iterable_elements, value_from_expression, value_from_iterable,
+};
+use crate::tree_ext::{
+    builder_calls, field_from_expression, fields_from_arguments, invocation_arguments,
+    invocation_name, operands_from_arguments, optional_node_to_string, resolved_expression,
+    unsupported_operator,
+};
+
+const AGGREGATES_HELPERS: [&str; 21] = [
+    "match",
+    "project",
+    "sort",
+    "limit",
+    "skip",
+    "sample",
+    "count",
+    "group",
+    "lookup",
+    "graphLookup",
+    "unwind",
+    "facet",
+    "addFields",
+    "set",
+    "unset",
+    "replaceRoot",
+    "replaceWith",
+    "bucket",
+    "merge",
+    "out",
+    "search",
+];
+
+const OTHER_STAGES: [&str; 9] = [
+    "bucketAuto",
+    "sortByCount",
+    "unionWith",
+    "densify",
+    "fill",
+    "setWindowFields",
+    "geoNear",
+    "vectorSearch",
+    "searchMeta",
+];
+
+fn named_expression(node: Node, code: &String) -> Option<(String, Expression)> {
+    let node = resolved_expression(node, code);
+    let arguments = match node.kind() {
+        "object_creation_expression" | "method_invocation" => invocation_arguments(node),
+        _ => return None,
+    };
+
+    let [name, operands @ ..] = &arguments[..] else {
+        return None;
+    };
+    let name = field_from_expression(*name, code)?;
+    let operands: Vec<Expression> = operands
+        .iter()
+        .map(|operand| expression_from_expression(*operand, code))
+        .collect();
+
+    if node.kind() == "method_invocation" {
+        let accumulator = Expression::Operator {
+            name: format!("${}", invocation_name(node, code)),
+            arguments: operands,
+        };
+        return Some((name, accumulator));
+    }
+
+    let [expression] = &operands[..] else {
+        return None;
+    };
+    return Some((name, expression.clone()));
+}
+
+fn named_expressions(arguments: &[Node], code: &String) -> Vec<(String, Expression)> {
+    return operands_from_arguments(arguments, code)
+        .into_iter()
+        .filter_map(|argument| named_expression(argument, code))
+        .collect();
+}
+
+fn stages_from_arguments(
+    arguments: &[Node],
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Vec<Stage> {
+    return operands_from_arguments(arguments, code)
+        .into_iter()
+        .filter_map(|argument| stage_from_expression(argument, code, warnings))
+        .collect();
+}
+
+fn facet_from_expression(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<(String, Vec<Stage>)> {
+    let node = resolved_expression(node, code);
+    if node.kind() != "object_creation_expression" {
+        return None;
+    }
+
+    let arguments = invocation_arguments(node);
+    let [name, stages @ ..] = &arguments[..] else {
+        return None;
+    };
+    return Some((
+        field_from_expression(*name, code)?,
+        stages_from_arguments(stages, code, warnings),
+    ));
+}
+
+fn namespace_from_expression(node: Node, code: &String) -> Option<(Option<String>, String)> {
+    let node = resolved_expression(node, code);
+    if node.kind() == "object_creation_expression" {
+        return match &invocation_arguments(node)[..] {
+            [database, collection] => Some((
+                Some(field_from_expression(*database, code)?),
+                field_from_expression(*collection, code)?,
+            )),
+            [full_name] => {
+                let full_name = field_from_expression(*full_name, code)?;
+                let (database, collection) = full_name.split_once('.')?;
+                Some((Some(database.to_string()), collection.to_string()))
+            }
+            _ => None,
+        };
+    }
+
+    return Some((None, field_from_expression(node, code)?));
+}
+
+fn unwind_stage(path: String, options: Option<&Node>, code: &String) -> Option<Stage> {
+    let mut include_array_index = None;
+    let mut preserve_null_and_empty_arrays = false;
+
+    if let Some(options) = options {
+        for (name, arguments) in builder_calls(*options, code)? {
+            let argument = *arguments.first()?;
+            match (name.as_str(), value_from_expression(argument, code)) {
+                ("includeArrayIndex", _) => {
+                    include_array_index = Some(field_from_expression(argument, code)?)
+                }
+                ("preserveNullAndEmptyArrays", Value::Boolean(preserve, _)) => {
+                    preserve_null_and_empty_arrays = preserve
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    return Some(Stage::Unwind {
+        path: path.trim_start_matches('$').to_string(),
+        include_array_index,
+        preserve_null_and_empty_arrays,
+    });
+}
+
+fn merge_stage(
+    into: Node,
+    options: Option<&Node>,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<Stage> {
+    let (database, collection) = namespace_from_expression(into, code)?;
+    let mut on = vec![];
+    let mut let_variables = vec![];
+    let mut when_matched = None;
+    let mut when_not_matched = None;
+
+    if let Some(options) = options {
+        for (name, arguments) in builder_calls(*options, code)? {
+            let constant = arguments
+                .first()
+                .map(|argument| optional_node_to_string(&Some(*argument), code))
+                .unwrap_or_default();
+            let constant = constant.rsplit('.').next().unwrap_or_default();
+
+            match (name.as_str(), constant) {
+                ("uniqueIdentifier", _) => on = fields_from_arguments(&arguments, code),
+                ("variables", _) => let_variables = named_expressions(&arguments, code),
+                ("whenMatched", "REPLACE") => when_matched = Some(MergeWhenMatched::Replace),
+                ("whenMatched", "KEEP_EXISTING") => {
+                    when_matched = Some(MergeWhenMatched::KeepExisting)
+                }
+                ("whenMatched", "MERGE") => when_matched = Some(MergeWhenMatched::Merge),
+                ("whenMatched", "FAIL") => when_matched = Some(MergeWhenMatched::Fail),
+                ("whenMatched", "PIPELINE") => {}
+                ("whenMatchedPipeline", _) => {
+                    when_matched = Some(MergeWhenMatched::Pipeline(stages_from_arguments(
+                        &arguments, code, warnings,
+                    )))
+                }
+                ("whenNotMatched", "INSERT") => {
+                    when_not_matched = Some(MergeWhenNotMatched::Insert)
+                }
+                ("whenNotMatched", "DISCARD") => {
+                    when_not_matched = Some(MergeWhenNotMatched::Discard)
+                }
+                ("whenNotMatched", "FAIL") => when_not_matched = Some(MergeWhenNotMatched::Fail),
+                _ => return None,
+            }
+        }
+    }
+
+    return Some(Stage::Merge {
+        database,
+        collection,
+        on,
+        let_variables,
+        when_matched,
+        when_not_matched,
+    });
+}
+
+fn graph_lookup_stage(
+    arguments: &[Node],
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<Stage> {
+    let [from, start_with, connect_from_field, connect_to_field, as_field, options @ ..] =
+        arguments
+    else {
+        return None;
+    };
+
+    let mut max_depth = None;
+    let mut depth_field = None;
+    let mut restrict_search_with_match = None;
+    if let Some(options) = options.first() {
+        for (name, arguments) in builder_calls(*options, code)? {
+            let argument = *arguments.first()?;
+            match name.as_str() {
+                "maxDepth" => max_depth = Some(value_from_expression(argument, code)),
+                "depthField" => depth_field = Some(field_from_expression(argument, code)?),
+                "restrictSearchWithMatch" => {
+                    restrict_search_with_match =
+                        predicate_from_expression(argument, code, warnings).map(Box::new)
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    return Some(Stage::GraphLookup {
+        from: field_from_expression(*from, code)?,
+        start_with: expression_from_expression(*start_with, code),
+        connect_from_field: field_from_expression(*connect_from_field, code)?,
+        connect_to_field: field_from_expression(*connect_to_field, code)?,
+        as_field: field_from_expression(*as_field, code)?,
+        max_depth,
+        depth_field,
+        restrict_search_with_match,
+    });
+}
+
+fn bucket_stage(
+    group_by: Node,
+    boundaries: Node,
+    options: Option<&Node>,
+    code: &String,
+) -> Option<Stage> {
+    let mut default = None;
+    let mut output = vec![];
+    if let Some(options) = options {
+        for (name, arguments) in builder_calls(*options, code)? {
+            match (name.as_str(), &arguments[..]) {
+                ("defaultBucket", [value]) => default = Some(value_from_expression(*value, code)),
+                ("output", _) => output = named_expressions(&arguments, code),
+                _ => return None,
+            }
+        }
+    }
+
+    let boundaries = match value_from_iterable(boundaries, code) {
+        Value::Array(boundaries, _) => boundaries,
+        boundary => vec![boundary],
+    };
+
+    return Some(Stage::Bucket {
+        group_by: expression_from_expression(group_by, code),
+        boundaries,
+        default,
+        output,
+    });
+}
+
+fn search_index(options: Option<&Node>, code: &String) -> Option<String> {
+    return builder_calls(*options?, code)?
+        .into_iter()
+        .find(|(name, _)| name == "index")
+        .and_then(|(_, arguments)| field_from_expression(*arguments.first()?, code));
+}
+
+pub fn stage_from_expression(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<Stage> {
+    let node = resolved_expression(node, code);
+    if let Some(fields) = document_from_expression(node, code, warnings) {
+        let stage = Stage::from_bson(&Value::Object(fields, Span::from(node)).to_bson()).ok();
+        if stage.is_none() {
+            warnings.push(ExtractionWarning::UnresolvedStage {
+                expression: optional_node_to_string(&Some(node), code),
+                span: Span::from(node),
+            });
+        }
+        return stage;
+    }
+
+    if node.kind() != "method_invocation" {
+        warnings.push(ExtractionWarning::UnresolvedStage {
+            expression: optional_node_to_string(&Some(node), code),
+            span: Span::from(node),
+        });
+        return None;
+    }
+
+    let name = invocation_name(node, code);
+    let arguments = invocation_arguments(node);
+    let field = |node: &Node| field_from_expression(*node, code);
+    let value = |node: &Node| value_from_expression(*node, code);
+
+    let stage = match (name.as_str(), &arguments[..]) {
+        ("match", [filter]) => Some(Stage::Match {
+            predicate: predicate_from_expression(*filter, code, warnings).unwrap_or(
+                FilterOperator::And {
+                    predicates: vec![],
+                    span: Span::from(*filter),
+                },
+            ),
+        }),
+        ("project", [projection]) => Some(Stage::Project {
+            fields: projection_from_expression(*projection, code, warnings),
+        }),
+        ("sort", [sort]) => Some(Stage::Sort {
+            fields: sort_from_expression(*sort, code, warnings),
+        }),
+        ("limit", [limit]) => Some(Stage::Limit {
+            limit: value(limit),
+        }),
+        ("skip", [skip]) => Some(Stage::Skip { skip: value(skip) }),
+        ("sample", [size]) => Some(Stage::Sample { size: value(size) }),
+        ("count", []) => Some(Stage::Count {
+            field: "count".to_string(),
+        }),
+        ("count", [count_field]) => field(count_field).map(|field| Stage::Count { field }),
+        ("group", [id, accumulators @ ..]) => Some(Stage::Group {
+            id: expression_from_expression(*id, code),
+            accumulators: named_expressions(accumulators, code),
+        }),
+        ("lookup", [from, local_field, foreign_field, as_field])
+            if field(local_field).is_some() && field(foreign_field).is_some() =>
+        {
+            Some(Stage::Lookup {
+                from: field(from)?,
+                local_field: field(local_field),
+                foreign_field: field(foreign_field),
+                let_variables: vec![],
+                pipeline: vec![],
+                as_field: field(as_field)?,
+            })
+        }
+        ("lookup", [from, pipeline, as_field]) => Some(Stage::Lookup {
+            from: field(from)?,
+            local_field: None,
+            foreign_field: None,
+            let_variables: vec![],
+            pipeline: pipeline_from_expression(*pipeline, code, warnings),
+            as_field: field(as_field)?,
+        }),
+        ("lookup", [from, let_variables, pipeline, as_field]) => Some(Stage::Lookup {
+            from: field(from)?,
+            local_field: None,
+            foreign_field: None,
+            let_variables: named_expressions(&[*let_variables], code),
+            pipeline: pipeline_from_expression(*pipeline, code, warnings),
+            as_field: field(as_field)?,
+        }),
+        ("graphLookup", _) => graph_lookup_stage(&arguments, code, warnings),
+        ("unwind", [path, options @ ..]) if options.len() <= 1 => {
+            unwind_stage(field(path)?, options.first(), code)
+        }
+        ("facet", facets) => operands_from_arguments(facets, code)
+            .into_iter()
+            .map(|facet| facet_from_expression(facet, code, warnings))
+            .collect::<Option<Vec<(String, Vec<Stage>)>>>()
+            .map(|facets| Stage::Facet { facets }),
+        ("addFields", fields) => Some(Stage::AddFields {
+            fields: named_expressions(fields, code),
+        }),
+        ("set", fields) => Some(Stage::Set {
+            fields: named_expressions(fields, code),
+        }),
+        ("unset", fields) => Some(Stage::Unset {
+            fields: fields_from_arguments(fields, code),
+        }),
+        ("replaceRoot", [new_root]) => Some(Stage::ReplaceRoot {
+            new_root: expression_from_expression(*new_root, code),
+        }),
+        ("replaceWith", [new_root]) => Some(Stage::ReplaceWith {
+            new_root: expression_from_expression(*new_root, code),
+        }),
+        ("bucket", [group_by, boundaries, options @ ..]) if options.len() <= 1 => {
+            bucket_stage(*group_by, *boundaries, options.first(), code)
+        }
+        ("merge", [into, options @ ..]) if options.len() <= 1 => {
+            merge_stage(*into, options.first(), code, warnings)
+        }
+        ("out", [collection]) => Some(Stage::Out {
+            database: None,
+            collection: field(collection)?,
+        }),
+        ("out", [database, collection]) => Some(Stage::Out {
+            database: Some(field(database)?),
+            collection: field(collection)?,
+        }),
+        ("search", [operator, options @ ..]) if options.len() <= 1 => Some(Stage::Search {
+            index: search_index(options.first(), code),
+            definition: value(operator),
+        }),
+        (other, arguments) if OTHER_STAGES.contains(&other) => Some(Stage::Other {
+            name: format!("${}", other),
+            definition: Value::Array(arguments.iter().map(value).collect(), Span::from(node)),
+        }),
+        _ => None,
+    };
+
+    if stage.is_none() {
+        warnings.push(match AGGREGATES_HELPERS.contains(&name.as_str()) {
+            true => ExtractionWarning::UnresolvedStage {
+                expression: optional_node_to_string(&Some(node), code),
+                span: Span::from(node),
+            },
+            false => unsupported_operator(node, code),
+        });
+    }
+
+    return stage;
+}
+
+pub fn pipeline_from_expression(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Vec<Stage> {
+    let node = resolved_expression(node, code);
+    let Some(stages) = iterable_elements(node, code) else {
+        warnings.push(ExtractionWarning::UnresolvedStage {
+            expression: optional_node_to_string(&Some(node), code),
+            span: Span::from(node),
+        });
+        return vec![];
+    };
+
+    return stages_from_arguments(&stages, code, warnings);
+}
@@ -1,15 +1,717 @@
-use mongodb_query_language::filter::FilterOperator;
-use mongodb_query_language::filter::FilterOperator::{Equals, GreaterThan};
-use mongodb_query_language::values::Value;
+use dialect_interface::ExtractionWarning;
+use mongodb_query_language::filter::FilterOperator::*;
+use mongodb_query_language::filter::{FilterOperator, GeoShape};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::{BsonType, Value};
+use tree_sitter::Node;
+
+use crate::tree_ext::expression_from_expression::expression_from_expression;
+use crate::tree_ext::infer_value_type::initializer_of;
+use crate::tree_ext::predicate_from_expression::predicate_from_expression;
+use crate::tree_ext::value_from_expression::{
+    value_from_expression, value_from_iterable, values_from_arguments,
+};
+use crate::tree_ext::{
+    builder_calls, field_from_expression, invocation_arguments, invocation_name,
+    optional_node_to_string, string_literal,
+};
+
+pub const FILTERS_HELPERS: [&str; 35] = [
+    "eq",
+    "ne",
+    "gt",
+    "lt",
+    "gte",
+    "lte",
+    "in",
+    "nin",
+    "and",
+    "or",
+    "nor",
+    "not",
+    "exists",
+    "type",
+    "mod",
+    "regex",
+    "text",
+    "where",
+    "expr",
+    "all",
+    "elemMatch",
+    "size",
+    "bitsAllClear",
+    "bitsAllSet",
+    "bitsAnyClear",
+    "bitsAnySet",
+    "geoWithin",
+    "geoWithinBox",
+    "geoWithinPolygon",
+    "geoWithinCenter",
+    "geoWithinCenterSphere",
+    "geoIntersects",
+    "near",
+    "nearSphere",
+    "jsonSchema",
+];
+
+fn bson_type_from_expression(node: Node, code: &String) -> Option<BsonType> {
+    if let Some(alias) = string_literal(node, code) {
+        return BsonType::from_alias(&alias);
+    }
+
+    let constant = match node.kind() {
+        "field_access" => optional_node_to_string(&node.child_by_field_name("field"), code),
+        _ => optional_node_to_string(&Some(node), code),
+    };
+
+    return match constant.as_str() {
+        "DOUBLE" => Some(BsonType::Double),
+        "STRING" => Some(BsonType::String),
+        "DOCUMENT" => Some(BsonType::Object),
+        "ARRAY" => Some(BsonType::Array),
+        "BINARY" => Some(BsonType::BinData),
+        "UNDEFINED" => Some(BsonType::Undefined),
+        "OBJECT_ID" => Some(BsonType::ObjectId),
+        "BOOLEAN" => Some(BsonType::Bool),
+        "DATE_TIME" => Some(BsonType::Date),
+        "NULL" => Some(BsonType::Null),
+        "REGULAR_EXPRESSION" => Some(BsonType::Regex),
+        "DB_POINTER" => Some(BsonType::DbPointer),
+        "JAVASCRIPT" => Some(BsonType::JavaScript),
+        "SYMBOL" => Some(BsonType::Symbol),
+        "JAVASCRIPT_WITH_SCOPE" => Some(BsonType::JavaScriptWithScope),
+        "INT32" => Some(BsonType::Int),
+        "TIMESTAMP" => Some(BsonType::Timestamp),
+        "INT64" => Some(BsonType::Long),
+        "DECIMAL128" => Some(BsonType::Decimal),
+        "MIN_KEY" => Some(BsonType::MinKey),
+        "MAX_KEY" => Some(BsonType::MaxKey),
+        _ => None,
+    };
+}
+
+fn regex_options_from_flags(node: Node, code: &String) -> String {
+    let flags = optional_node_to_string(&Some(node), code);
+    return [
+        ("CASE_INSENSITIVE", 'i'),
+        ("MULTILINE", 'm'),
+        ("DOTALL", 's'),
+        ("COMMENTS", 'x'),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| flags.contains(flag))
+    .map(|(_, option)| option)
+    .collect();
+}
+
+fn regex_from_pattern(node: Node, code: &String) -> (Value, Option<String>) {
+    if node.kind() == "identifier" {
+        if let Some(initializer) = initializer_of(node, code) {
+            return regex_from_pattern(initializer, code);
+        }
+    }
+
+    if node.kind() == "method_invocation" && invocation_name(node, code) == "compile" {
+        match &invocation_arguments(node)[..] {
+            [pattern] => return (value_from_expression(*pattern, code), None),
+            [pattern, flags] => {
+                return (
+                    value_from_expression(*pattern, code),
+                    Some(regex_options_from_flags(*flags, code)),
+                )
+            }
+            _ => {}
+        }
+    }
+
+    return (value_from_expression(node, code), None);
+}
+
+struct TextSearchOptions {
+    language: Option<String>,
+    case_sensitive: Option<bool>,
+    diacritic_sensitive: Option<bool>,
+}
+
+fn text_search_options(node: Node, code: &String) -> Option<TextSearchOptions> {
+    let mut options = TextSearchOptions {
+        language: None,
+        case_sensitive: None,
+        diacritic_sensitive: None,
+    };
+
+    for (name, arguments) in builder_calls(node, code)? {
+        let value = value_from_expression(*arguments.first()?, code);
+        match (name.as_str(), value) {
+            ("language", Value::String(language, _)) => options.language = Some(language),
+            ("caseSensitive", Value::Boolean(flag, _)) => options.case_sensitive = Some(flag),
+            ("diacriticSensitive", Value::Boolean(flag, _)) => {
+                options.diacritic_sensitive = Some(flag)
+            }
+            _ => return None,
+        }
+    }
+
+    return Some(options);
+}
+
+fn optional_distance(node: Option<&Node>, code: &String) -> Option<Value> {
+    return node
+        .map(|node| value_from_expression(*node, code))
+        .filter(|value| !matches!(value, Value::Null(_)));
+}
+
+fn legacy_coordinates(values: &[&Node], code: &String, span: Span) -> Value {
+    return Value::Array(
+        values
+            .iter()
+            .map(|value| value_from_expression(**value, code))
+            .collect(),
+        span,
+    );
+}
 
 pub fn predicate_from_driver_method(
     operator: &String,
-    field: String,
-    value: Value,
-) -> FilterOperator {
-    return match operator.as_str() {
-        "eq" => Equals { field, value },
-        "gt" => GreaterThan { field, value },
-        _ => Equals { field, value },
+    arguments: &[Node],
+    code: &String,
+    span: Span,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<FilterOperator> {
+    if let ("eq", [value]) = (operator.as_str(), arguments) {
+        return Some(Equals {
+            field: "_id".to_string(),
+            value: value_from_expression(*value, code),
+            span,
+        });
+    }
+
+    if let ("text" | "where" | "expr" | "jsonSchema", [argument, rest @ ..]) =
+        (operator.as_str(), arguments)
+    {
+        let value = value_from_expression(*argument, code);
+        return match (operator.as_str(), rest) {
+            ("text", []) => Some(Text {
+                search: value,
+                language: None,
+                case_sensitive: None,
+                diacritic_sensitive: None,
+                span,
+            }),
+            ("text", [options]) => {
+                let options = text_search_options(*options, code)?;
+                Some(Text {
+                    search: value,
+                    language: options.language,
+                    case_sensitive: options.case_sensitive,
+                    diacritic_sensitive: options.diacritic_sensitive,
+                    span,
+                })
+            }
+            ("where", []) => Some(Where { code: value, span }),
+            ("expr", []) => Some(Expr {
+                expression: expression_from_expression(*argument, code),
+                span,
+            }),
+            ("jsonSchema", []) => Some(JsonSchema {
+                schema: value,
+                span,
+            }),
+            _ => None,
+        };
+    }
+
+    let [field, arguments @ ..] = arguments else {
+        return None;
+    };
+    let field = field_from_expression(*field, code)?;
+    let value = |node: &Node| value_from_expression(*node, code);
+
+    return match (operator.as_str(), arguments) {
+        ("eq", [v]) => Some(Equals {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("ne", [v]) => Some(NotEquals {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("gt", [v]) => Some(GreaterThan {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("gte", [v]) => Some(GreaterThanOrEquals {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("lt", [v]) => Some(LessThan {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("lte", [v]) => Some(LessThanOrEquals {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("in", values) => Some(In {
+            field,
+            values: values_from_arguments(values, code),
+            span,
+        }),
+        ("nin", values) => Some(NotIn {
+            field,
+            values: values_from_arguments(values, code),
+            span,
+        }),
+        ("all", values) => Some(All {
+            field,
+            values: values_from_arguments(values, code),
+            span,
+        }),
+        ("exists", []) => Some(Exists {
+            field,
+            exists: true,
+            span,
+        }),
+        ("exists", [exists]) => match value(exists) {
+            Value::Boolean(exists, _) => Some(Exists {
+                field,
+                exists,
+                span,
+            }),
+            _ => None,
+        },
+        ("type", [bson_type]) => Some(Type {
+            field,
+            types: vec![bson_type_from_expression(*bson_type, code)?],
+            span,
+        }),
+        ("regex", [pattern]) => {
+            let (pattern, options) = regex_from_pattern(*pattern, code);
+            Some(Regex {
+                field,
+                pattern,
+                options,
+                span,
+            })
+        }
+        ("regex", [pattern, options]) => match value(options) {
+            Value::String(options, _) => Some(Regex {
+                field,
+                pattern: value(pattern),
+                options: Some(options),
+                span,
+            }),
+            Value::Null(_) => Some(Regex {
+                field,
+                pattern: value(pattern),
+                options: None,
+                span,
+            }),
+            _ => None,
+        },
+        ("elemMatch", [filter]) => {
+            predicate_from_expression(*filter, code, warnings).map(|predicate| ElemMatch {
+                field,
+                predicates: vec![predicate],
+                span,
+            })
+        }
+        ("size", [size]) => Some(Size {
+            field,
+            size: value(size),
+            span,
+        }),
+        ("mod", [divisor, remainder]) => Some(Mod {
+            field,
+            divisor: value(divisor),
+            remainder: value(remainder),
+            span,
+        }),
+        ("bitsAllSet", [mask]) => Some(BitsAllSet {
+            field,
+            mask: value(mask),
+            span,
+        }),
+        ("bitsAnySet", [mask]) => Some(BitsAnySet {
+            field,
+            mask: value(mask),
+            span,
+        }),
+        ("bitsAllClear", [mask]) => Some(BitsAllClear {
+            field,
+            mask: value(mask),
+            span,
+        }),
+        ("bitsAnyClear", [mask]) => Some(BitsAnyClear {
+            field,
+            mask: value(mask),
+            span,
+        }),
+        ("geoWithin", [geometry]) => Some(GeoWithin {
+            field,
+            shape: GeoShape::Geometry(value(geometry)),
+            span,
+        }),
+        ("geoWithinBox", [lower_left_x, lower_left_y, upper_right_x, upper_right_y]) => {
+            Some(GeoWithin {
+                field,
+                shape: GeoShape::Box(Value::Array(
+                    vec![
+                        legacy_coordinates(&[lower_left_x, lower_left_y], code, span),
+                        legacy_coordinates(&[upper_right_x, upper_right_y], code, span),
+                    ],
+                    span,
+                )),
+                span,
+            })
+        }
+        ("geoWithinPolygon", [points]) => Some(GeoWithin {
+            field,
+            shape: GeoShape::Polygon(value_from_iterable(*points, code)),
+            span,
+        }),
+        ("geoWithinCenter" | "geoWithinCenterSphere", [x, y, radius]) => {
+            let circle = Value::Array(
+                vec![legacy_coordinates(&[x, y], code, span), value(radius)],
+                span,
+            );
+            Some(GeoWithin {
+                field,
+                shape: match operator.as_str() {
+                    "geoWithinCenter" => GeoShape::Center(circle),
+                    _ => GeoShape::CenterSphere(circle),
+                },
+                span,
+            })
+        }
+        ("geoIntersects", [geometry]) => Some(GeoIntersects {
+            field,
+            geometry: value(geometry),
+            span,
+        }),
+        ("near" | "nearSphere", [geometry, rest @ ..]) => {
+            let (geometry, distances) = match rest {
+                [y, distances @ ..] if distances.len() == 2 => {
+                    (legacy_coordinates(&[geometry, y], code, span), distances)
+                }
+                distances => (value(geometry), distances),
+            };
+            let max_distance = optional_distance(distances.first(), code);
+            let min_distance = optional_distance(distances.get(1), code);
+            Some(match operator.as_str() {
+                "near" => Near {
+                    field,
+                    geometry,
+                    max_distance,
+                    min_distance,
+                    span,
+                },
+                _ => NearSphere {
+                    field,
+                    geometry,
+                    max_distance,
+                    min_distance,
+                    span,
+                },
+            })
+        }
+        _ => None,
     };
 }
+
+#[cfg(test)]
+mod test {
+    use dialect_interface::ExtractionWarning;
+    use mongodb_query_language::aggregation::Expression;
+    use mongodb_query_language::filter::FilterOperator::*;
+    use mongodb_query_language::filter::{FilterOperator, GeoShape};
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::values::Value::{Array, Floating, Integer, String};
+    use mongodb_query_language::values::{BsonType, Value};
+
+    use crate::tree_ext::test_fixtures;
+
+    fn predicates_of(filters: &str) -> (Vec<FilterOperator>, Vec<ExtractionWarning>) {
+        let body = format!(
+            "collection.find(and({})).forEach(System.out::println);",
+            filters
+        );
+        let (mut predicates, warnings) = test_fixtures::predicates_of(&body);
+        let Some(And { predicates, .. }) = predicates.pop() else {
+            panic!()
+        };
+
+        return (predicates, warnings);
+    }
+
+    fn s(value: &str) -> Value {
+        return String(value.to_string(), Span::default());
+    }
+
+    fn i(value: i32) -> Value {
+        return Integer(value, Span::default());
+    }
+
+    #[test]
+    fn maps_comparison_array_and_element_helpers() {
+        let (predicates, warnings) = predicates_of(
+            r#"ne(STATUS, "closed"), gte("age", 18), lte("age", 65), lt("score", 1.5),
+            in("tags", "a", "b"), nin("tags", tags), all("tags", List.of("a")),
+            exists("deletedAt", false), type("age", BsonType.INT32), type("name", "string"),
+            regex("name", Pattern.compile("^a", Pattern.CASE_INSENSITIVE | Pattern.MULTILINE)),
+            elemMatch("items", gt("qty", 2)), size("tags", 2), mod("age", 2, 0)"#,
+        );
+        let span = Span::default();
+
+        assert_eq!(warnings, vec![]);
+        assert_eq!(
+            predicates,
+            vec![
+                NotEquals {
+                    field: "status".to_string(),
+                    value: s("closed"),
+                    span
+                },
+                GreaterThanOrEquals {
+                    field: "age".to_string(),
+                    value: i(18),
+                    span
+                },
+                LessThanOrEquals {
+                    field: "age".to_string(),
+                    value: i(65),
+                    span
+                },
+                LessThan {
+                    field: "score".to_string(),
+                    value: Floating(1.5, span),
+                    span
+                },
+                In {
+                    field: "tags".to_string(),
+                    values: vec![s("a"), s("b")],
+                    span
+                },
+                NotIn {
+                    field: "tags".to_string(),
+                    values: vec![s("a"), s("b")],
+                    span
+                },
+                All {
+                    field: "tags".to_string(),
+                    values: vec![s("a")],
+                    span
+                },
+                Exists {
+                    field: "deletedAt".to_string(),
+                    exists: false,
+                    span
+                },
+                Type {
+                    field: "age".to_string(),
+                    types: vec![BsonType::Int],
+                    span
+                },
+                Type {
+                    field: "name".to_string(),
+                    types: vec![BsonType::String],
+                    span
+                },
+                Regex {
+                    field: "name".to_string(),
+                    pattern: s("^a"),
+                    options: Some("im".to_string()),
+                    span
+                },
+                ElemMatch {
+                    field: "items".to_string(),
+                    predicates: vec![GreaterThan {
+                        field: "qty".to_string(),
+                        value: i(2),
+                        span
+                    }],
+                    span
+                },
+                Size {
+                    field: "tags".to_string(),
+                    size: i(2),
+                    span
+                },
+                Mod {
+                    field: "age".to_string(),
+                    divisor: i(2),
+                    remainder: i(0),
+                    span
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn maps_evaluation_bitwise_and_geospatial_helpers() {
+        let (predicates, warnings) = predicates_of(
+            r#"text("coffee", new TextSearchOptions().language("en").caseSensitive(true)),
+            where("this.a > 1"), bitsAnySet("flags", 5), geoWithinBox("loc", 0, 0, 1, 1),
+            geoWithinCenter("loc", 1, 2, 3), near("loc", 1, 2, 10, null),
+            expr(new Document("$gt", Arrays.asList("$spent", "$budget")))"#,
+        );
+        let span = Span::default();
+        let point = |x, y| Array(vec![i(x), i(y)], span);
+
+        assert_eq!(warnings, vec![]);
+        assert_eq!(
+            predicates,
+            vec![
+                Text {
+                    search: s("coffee"),
+                    language: Some("en".to_string()),
+                    case_sensitive: Some(true),
+                    diacritic_sensitive: None,
+                    span
+                },
+                Where {
+                    code: s("this.a > 1"),
+                    span
+                },
+                BitsAnySet {
+                    field: "flags".to_string(),
+                    mask: i(5),
+                    span
+                },
+                GeoWithin {
+                    field: "loc".to_string(),
+                    shape: GeoShape::Box(Array(vec![point(0, 0), point(1, 1)], span)),
+                    span
+                },
+                GeoWithin {
+                    field: "loc".to_string(),
+                    shape: GeoShape::Center(Array(vec![point(1, 2), i(3)], span)),
+                    span
+                },
+                Near {
+                    field: "loc".to_string(),
+                    geometry: point(1, 2),
+                    max_distance: Some(i(10)),
+                    min_distance: None,
+                    span
+                },
+                Expr {
+                    expression: Expression::Operator {
+                        name: "$gt".to_string(),
+                        arguments: vec![
+                            Expression::FieldPath("spent".to_string()),
+                            Expression::FieldPath("budget".to_string()),
+                        ],
+                    },
+                    span
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn warns_about_unknown_helpers_and_unresolved_arguments() {
+        let (predicates, warnings) = test_fixtures::predicates_of(
+            r#"collection.find(and(eqIgnoringCase("name", "a"), eq("a", 1)));
+            collection.find(and(exists("deletedAt", flag), eq("a", 1)));
+            collection.find(and(external));
+            collection.find(nor(eq("a", 1), external));
+            collection.find(eq("a", 1));"#,
+        );
+        let unresolved = |expression: &str| ExtractionWarning::UnresolvedFilter {
+            expression: expression.to_string(),
+            span: Span::default(),
+        };
+
+        assert_eq!(
+            warnings,
+            vec![
+                ExtractionWarning::UnsupportedOperator {
+                    operator: "eqIgnoringCase".to_string(),
+                    span: Span::default()
+                },
+                unresolved("and(eqIgnoringCase(name, a), eq(a, 1))"),
+                unresolved("exists(deletedAt, flag)"),
+                unresolved("and(exists(deletedAt, flag), eq(a, 1))"),
+                unresolved("external"),
+                unresolved("and(external)"),
+                unresolved("external"),
+                unresolved("nor(eq(a, 1), external)"),
+            ]
+        );
+        assert_eq!(
+            predicates,
+            vec![Equals {
+                field: "a".to_string(),
+                value: i(1),
+                span: Span::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn drops_element_matches_and_negations_of_unresolved_filters() {
+        let (predicates, warnings) = test_fixtures::predicates_of(
+            r#"collection.find(elemMatch("items", itemFilter));
+            collection.find(and(not(statusFilter), eq("a", 1)));"#,
+        );
+        let unresolved = |expression: &str| ExtractionWarning::UnresolvedFilter {
+            expression: expression.to_string(),
+            span: Span::default(),
+        };
+
+        assert_eq!(predicates, vec![]);
+        assert_eq!(
+            warnings,
+            vec![
+                unresolved("itemFilter"),
+                unresolved("elemMatch(items, itemFilter)"),
+                unresolved("statusFilter"),
+                unresolved("not(statusFilter)"),
+                unresolved("and(not(statusFilter), eq(a, 1))"),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_java_literals() {
+        let (predicates, warnings) = predicates_of(
+            r#"eq("say \"hi\"", "caf\u00e9\tbar\\"), bitsAllSet("flags", 0x80000000),
+            eq("mode", 0755), eq("mask", 0xFFFFFFFFFFFFFFFFL)"#,
+        );
+        let span = Span::default();
+
+        assert_eq!(warnings, vec![]);
+        assert_eq!(
+            predicates,
+            vec![
+                Equals {
+                    field: "say \"hi\"".to_string(),
+                    value: s("café\tbar\\"),
+                    span
+                },
+                BitsAllSet {
+                    field: "flags".to_string(),
+                    mask: i(i32::MIN),
+                    span
+                },
+                Equals {
+                    field: "mode".to_string(),
+                    value: i(0o755),
+                    span
+                },
+                Equals {
+                    field: "mask".to_string(),
+                    value: Value::Int64(-1, span),
+                    span
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use dialect_interface::ExtractionWarning;
+use mongodb_query_language::filter::FilterOperator;
+use mongodb_query_language::serialization::{FromBson, ToBson};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::Value;
+use tree_sitter::Node;
+
+use crate::tree_ext::document_from_expression::document_from_expression;
+use crate::tree_ext::infer_value_type::initializer_of;
+use crate::tree_ext::predicate_from_driver_method::{
+    predicate_from_driver_method, FILTERS_HELPERS,
+};
+use crate::tree_ext::reaching_definitions::{reaching_definitions, Definition};
+use crate::tree_ext::{
+    invocation_arguments, invocation_name, operands_from_arguments, optional_node_to_string,
+    unsupported_operator,
+};
+
+pub fn predicate_from_expression(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<FilterOperator> {
+    let span = Span::from(node);
+
+    if let Some(fields) = document_from_expression(node, code, warnings) {
+        let predicate = FilterOperator::from_bson(&Value::Object(fields, span).to_bson()).ok();
+        if predicate.is_none() {
+            warnings.push(ExtractionWarning::UnresolvedFilter {
+                expression: optional_node_to_string(&Some(node), code),
+                span,
+            });
+        }
+        return predicate;
+    }
+
+    return match node.kind() {
+        "method_invocation" => predicate_from_invocation(node, code, warnings),
+        "parenthesized_expression" => {
+            predicate_from_expression(node.named_child(0)?, code, warnings)
+        }
+        "identifier" => match (reaching_definitions(node, code), initializer_of(node, code)) {
+            (Some(definitions), _) => alternatives(
+                definitions
+                    .into_iter()
+                    .filter_map(|definition| match definition {
+                        Definition::Value(value) => {
+                            predicate_from_expression(value, code, warnings)
+                        }
+                        Definition::Elements(_) => None,
+                    })
+                    .collect(),
+                span,
+            ),
+            (None, Some(initializer)) => predicate_from_expression(initializer, code, warnings),
+            (None, None) => {
+                warnings.push(ExtractionWarning::UnresolvedFilter {
+                    expression: optional_node_to_string(&Some(node), code),
+                    span,
+                });
+                None
+            }
+        },
+        _ => {
+            warnings.push(ExtractionWarning::UnresolvedFilter {
+                expression: optional_node_to_string(&Some(node), code),
+                span,
+            });
+            None
+        }
+    };
+}
+
+fn alternatives(predicates: Vec<FilterOperator>, span: Span) -> Option<FilterOperator> {
+    let mut distinct: Vec<FilterOperator> = vec![];
+    for predicate in predicates {
+        if !distinct.contains(&predicate) {
+            distinct.push(predicate);
+        }
+    }
+
+    if distinct.len() > 1 {
+        return Some(FilterOperator::Or {
+            predicates: distinct,
+            span,
+        });
+    }
+
+    return distinct.pop();
+}
+
+fn list_alternatives<'a>(arguments: &[Node<'a>], code: &String) -> Option<Vec<Vec<Node<'a>>>> {
+    let [list] = arguments else {
+        return None;
+    };
+    if list.kind() != "identifier" {
+        return None;
+    }
+
+    return reaching_definitions(*list, code)?
+        .into_iter()
+        .map(|definition| match definition {
+            Definition::Elements(elements) => Some(elements),
+            Definition::Value(_) => None,
+        })
+        .collect();
+}
+
+fn predicate_from_conditions(
+    name: &str,
+    conditions: Vec<Vec<Node>>,
+    code: &String,
+    span: Span,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<FilterOperator> {
+    let mut evaluated: Vec<(Node, Option<FilterOperator>)> = vec![];
+    let mut combined = vec![];
+    for elements in conditions {
+        let mut predicates = vec![];
+        for element in elements {
+            let cached = evaluated.iter().find(|(node, _)| *node == element);
+            let predicate = match cached {
+                Some((_, predicate)) => predicate.clone(),
+                None => {
+                    let predicate = predicate_from_expression(element, code, warnings);
+                    evaluated.push((element, predicate.clone()));
+                    predicate
+                }
+            };
+            predicates.push(predicate?);
+        }
+
+        combined.push(match name {
+            "and" => FilterOperator::And { predicates, span },
+            "or" => FilterOperator::Or { predicates, span },
+            _ => FilterOperator::Nor { predicates, span },
+        });
+    }
+
+    return alternatives(combined, span);
+}
+
+fn predicate_from_invocation(
+    invocation: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<FilterOperator> {
+    let span = Span::from(invocation);
+    let name = invocation_name(invocation, code);
+    let arguments = invocation_arguments(invocation);
+    let mut nested = || {
+        return operands_from_arguments(&arguments, code)
+            .iter()
+            .map(|operand| predicate_from_expression(*operand, code, warnings))
+            .collect::<Vec<Option<FilterOperator>>>()
+            .into_iter()
+            .collect::<Option<Vec<FilterOperator>>>();
+    };
+    let conditions = match name.as_str() {
+        "and" | "or" | "nor" => list_alternatives(&arguments, code),
+        _ => None,
+    };
+
+    let predicate = match (name.as_str(), &arguments[..], conditions) {
+        ("and" | "or" | "nor", _, Some(conditions)) => {
+            predicate_from_conditions(&name, conditions, code, span, warnings)
+        }
+        ("empty", [], _) => Some(FilterOperator::And {
+            predicates: vec![],
+            span,
+        }),
+        ("and", _, _) => nested().map(|predicates| FilterOperator::And { predicates, span }),
+        ("or", _, _) => nested().map(|predicates| FilterOperator::Or { predicates, span }),
+        ("nor", _, _) => nested().map(|predicates| FilterOperator::Nor { predicates, span }),
+        ("not", [_], _) => nested()
+            .filter(|predicates| !predicates.is_empty())
+            .map(|predicates| FilterOperator::Not { predicates, span }),
+        _ => predicate_from_driver_method(&name, &arguments, code, span, warnings),
+    };
+
+    if predicate.is_some() {
+        return predicate;
+    }
+
+    warnings.push(match FILTERS_HELPERS.contains(&name.as_str()) {
+        true => ExtractionWarning::UnresolvedFilter {
+            expression: optional_node_to_string(&Some(invocation), code),
+            span,
+        },
+        false => unsupported_operator(invocation, code),
+    });
+
+    return None;
+}
@@ -0,0 +1,108 @@
+use dialect_interface::ExtractionWarning;
+use mongodb_query_language::aggregation::ProjectionField;
+use mongodb_query_language::serialization::{FromBson, ToBson};
+use mongodb_query_language::span::Span;
+use tree_sitter::Node;
+
+use crate::tree_ext::document_from_expression::document_from_expression;
+use crate::tree_ext::expression_from_expression::expression_from_expression;
+use crate::tree_ext::predicate_from_expression::predicate_from_expression;
+use crate::tree_ext::value_from_expression::value_from_expression;
+use crate::tree_ext::{
+    field_from_expression, fields_from_arguments, invocation_arguments, invocation_name,
+    operands_from_arguments, optional_node_to_string, resolved_expression, unsupported_operator,
+};
+
+fn meta(field: String, name: &str) -> Vec<(String, ProjectionField)> {
+    return vec![(field, ProjectionField::Meta(name.to_string()))];
+}
+
+pub fn projection_from_expression(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Vec<(String, ProjectionField)> {
+    let node = resolved_expression(node, code);
+    if let Some(fields) = document_from_expression(node, code, warnings) {
+        let projection = fields
+            .into_iter()
+            .map(|(field, projected)| {
+                let projected = ProjectionField::from_bson(&projected.to_bson()).ok()?;
+                Some((field, projected))
+            })
+            .collect::<Option<Vec<(String, ProjectionField)>>>();
+        if projection.is_none() {
+            warnings.push(ExtractionWarning::UnresolvedStage {
+                expression: optional_node_to_string(&Some(node), code),
+                span: Span::from(node),
+            });
+        }
+        return projection.unwrap_or_default();
+    }
+
+    if node.kind() != "method_invocation" {
+        warnings.push(ExtractionWarning::UnresolvedStage {
+            expression: optional_node_to_string(&Some(node), code),
+            span: Span::from(node),
+        });
+        return vec![];
+    }
+
+    let name = invocation_name(node, code);
+    let arguments = invocation_arguments(node);
+    let field = arguments
+        .first()
+        .and_then(|field| field_from_expression(*field, code));
+
+    return match (name.as_str(), field, &arguments[..]) {
+        ("include", _, _) => fields_from_arguments(&arguments, code)
+            .into_iter()
+            .map(|field| (field, ProjectionField::Include))
+            .collect(),
+        ("exclude", _, _) => fields_from_arguments(&arguments, code)
+            .into_iter()
+            .map(|field| (field, ProjectionField::Exclude))
+            .collect(),
+        ("excludeId", _, []) => vec![("_id".to_string(), ProjectionField::Exclude)],
+        ("fields", _, _) => operands_from_arguments(&arguments, code)
+            .into_iter()
+            .flat_map(|argument| projection_from_expression(argument, code, warnings))
+            .collect(),
+        ("elemMatch", Some(field), [_]) => vec![(format!("{}.$", field), ProjectionField::Include)],
+        ("elemMatch", Some(field), [_, filter]) => {
+            predicate_from_expression(*filter, code, warnings)
+                .map(|predicate| vec![(field, ProjectionField::ElemMatch(predicate))])
+                .unwrap_or_default()
+        }
+        ("slice", Some(field), [_, limit]) => vec![(
+            field,
+            ProjectionField::Slice {
+                skip: None,
+                limit: value_from_expression(*limit, code),
+            },
+        )],
+        ("slice", Some(field), [_, skip, limit]) => vec![(
+            field,
+            ProjectionField::Slice {
+                skip: Some(value_from_expression(*skip, code)),
+                limit: value_from_expression(*limit, code),
+            },
+        )],
+        ("computed", Some(field), [_, expression]) => vec![(
+            field,
+            ProjectionField::Computed(expression_from_expression(*expression, code)),
+        )],
+        ("metaTextScore", Some(field), [_]) => meta(field, "textScore"),
+        ("metaSearchScore", Some(field), [_]) => meta(field, "searchScore"),
+        ("metaSearchHighlights", Some(field), [_]) => meta(field, "searchHighlights"),
+        ("metaVectorSearchScore", Some(field), [_]) => meta(field, "vectorSearchScore"),
+        ("meta", Some(field), [_, meta_field]) => match field_from_expression(*meta_field, code) {
+            Some(meta_field) => meta(field, &meta_field),
+            None => vec![],
+        },
+        _ => {
+            warnings.push(unsupported_operator(node, code));
+            vec![]
+        }
+    };
+}
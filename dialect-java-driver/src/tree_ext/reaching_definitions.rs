@@ -0,0 +1,378 @@
+use tree_sitter::Node;
+
+use crate::tree_ext::value_from_expression::iterable_elements;
+use crate::tree_ext::{
+    descendants_of_kind, invocation_arguments, invocation_name, optional_node_to_string,
+    simple_type_name,
+};
+
+const MAX_ALTERNATIVES: usize = 16;
+pub const EXECUTABLE_SCOPES: [&str; 3] = [
+    "method_declaration",
+    "constructor_declaration",
+    "static_initializer",
+];
+const SEQUENCES: [&str; 10] = [
+    "block",
+    "constructor_body",
+    "try_statement",
+    "try_with_resources_statement",
+    "catch_clause",
+    "finally_clause",
+    "synchronized_statement",
+    "labeled_statement",
+    "switch_block_statement_group",
+    "switch_rule",
+];
+pub const LOOPS: [&str; 4] = [
+    "for_statement",
+    "enhanced_for_statement",
+    "while_statement",
+    "do_statement",
+];
+const MUTABLE_LISTS: [&str; 3] = ["ArrayList", "LinkedList", "Vector"];
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Definition<'a> {
+    Value(Node<'a>),
+    Elements(Vec<Node<'a>>),
+}
+
+enum Flow<'a> {
+    Reached(Vec<Definition<'a>>),
+    Continue(Vec<Definition<'a>>),
+}
+
+fn contains(node: Node, usage: Node) -> bool {
+    return node.start_byte() <= usage.start_byte() && usage.end_byte() <= node.end_byte();
+}
+
+fn merge<'a>(mut left: Vec<Definition<'a>>, right: Vec<Definition<'a>>) -> Vec<Definition<'a>> {
+    for definition in right {
+        if !left.contains(&definition) && left.len() < MAX_ALTERNATIVES {
+            left.push(definition);
+        }
+    }
+
+    return left;
+}
+
+fn definition_of<'a>(value: Node<'a>, code: &String) -> Definition<'a> {
+    if value.kind() == "object_creation_expression" && invocation_arguments(value).is_empty() {
+        let type_name = optional_node_to_string(&value.child_by_field_name("type"), code);
+        if MUTABLE_LISTS.contains(&simple_type_name(&type_name).as_str()) {
+            return Definition::Elements(vec![]);
+        }
+    }
+
+    return match iterable_elements(value, code) {
+        Some(elements) => Definition::Elements(elements),
+        None => Definition::Value(value),
+    };
+}
+
+fn declares(declaration: Node, name: &str, code: &String) -> bool {
+    let mut cursor = declaration.walk();
+    let declared = declaration
+        .children_by_field_name("declarator", &mut cursor)
+        .any(|declarator| {
+            optional_node_to_string(&declarator.child_by_field_name("name"), code) == name
+        });
+    return declared;
+}
+
+fn declared_value<'a>(declaration: Node<'a>, name: &str, code: &String) -> Option<Node<'a>> {
+    let mut cursor = declaration.walk();
+    let value = declaration
+        .children_by_field_name("declarator", &mut cursor)
+        .find(|declarator| {
+            optional_node_to_string(&declarator.child_by_field_name("name"), code) == name
+        })
+        .and_then(|declarator| declarator.child_by_field_name("value"));
+    return value;
+}
+
+fn effect_of<'a>(
+    statement: Node<'a>,
+    name: &str,
+    code: &String,
+    definitions: Vec<Definition<'a>>,
+) -> Vec<Definition<'a>> {
+    let Some(expression) = statement.named_child(0) else {
+        return definitions;
+    };
+
+    let target = match expression.kind() {
+        "assignment_expression" => expression.child_by_field_name("left"),
+        "method_invocation" => expression.child_by_field_name("object"),
+        _ => None,
+    };
+    if optional_node_to_string(&target, code) != name {
+        return definitions;
+    }
+
+    if expression.kind() == "assignment_expression" {
+        let operator = optional_node_to_string(&expression.child_by_field_name("operator"), code);
+        return match (operator.as_str(), expression.child_by_field_name("right")) {
+            ("=", Some(value)) => vec![definition_of(value, code)],
+            _ => definitions,
+        };
+    }
+
+    let added = match (
+        invocation_name(expression, code).as_str(),
+        &invocation_arguments(expression)[..],
+    ) {
+        ("add", [element]) => vec![*element],
+        ("addAll", [elements]) => iterable_elements(*elements, code).unwrap_or_default(),
+        ("clear", []) => return vec![Definition::Elements(vec![])],
+        _ => return definitions,
+    };
+
+    return definitions
+        .into_iter()
+        .map(|definition| match definition {
+            Definition::Elements(mut elements) => {
+                elements.extend(added.iter().copied());
+                Definition::Elements(elements)
+            }
+            value => value,
+        })
+        .collect();
+}
+
+fn after<'a>(
+    node: Node<'a>,
+    name: &str,
+    usage: Node<'a>,
+    code: &String,
+    definitions: Vec<Definition<'a>>,
+) -> Vec<Definition<'a>> {
+    return match flow(node, name, usage, code, definitions) {
+        Flow::Reached(definitions) | Flow::Continue(definitions) => definitions,
+    };
+}
+
+fn flow<'a>(
+    node: Node<'a>,
+    name: &str,
+    usage: Node<'a>,
+    code: &String,
+    definitions: Vec<Definition<'a>>,
+) -> Flow<'a> {
+    let inside = contains(node, usage);
+    let kind = node.kind();
+    let mut cursor = node.walk();
+
+    if SEQUENCES.contains(&kind) {
+        let mut definitions = definitions;
+        for child in node.named_children(&mut cursor) {
+            match flow(child, name, usage, code, definitions) {
+                Flow::Reached(reached) => return Flow::Reached(reached),
+                Flow::Continue(next) => definitions = next,
+            }
+        }
+
+        return match inside {
+            true => Flow::Reached(definitions),
+            false => Flow::Continue(definitions),
+        };
+    }
+
+    if inside {
+        let branch = match kind {
+            "if_statement" => [
+                node.child_by_field_name("consequence"),
+                node.child_by_field_name("alternative"),
+            ]
+            .into_iter()
+            .flatten()
+            .find(|branch| contains(*branch, usage)),
+            "switch_expression" => node.child_by_field_name("body").and_then(|body| {
+                body.named_children(&mut cursor)
+                    .find(|group| contains(*group, usage))
+            }),
+            _ if LOOPS.contains(&kind) => node
+                .child_by_field_name("body")
+                .filter(|body| contains(*body, usage)),
+            _ => None,
+        };
+
+        return Flow::Reached(match branch {
+            Some(branch) => after(branch, name, usage, code, definitions),
+            None => definitions,
+        });
+    }
+
+    return Flow::Continue(match kind {
+        "local_variable_declaration" if declares(node, name, code) => {
+            declared_value(node, name, code)
+                .map(|value| definition_of(value, code))
+                .into_iter()
+                .collect()
+        }
+        "expression_statement" => effect_of(node, name, code, definitions),
+        "if_statement" => {
+            let consequence = node
+                .child_by_field_name("consequence")
+                .map(|branch| after(branch, name, usage, code, definitions.clone()))
+                .unwrap_or(definitions.clone());
+            let alternative = node
+                .child_by_field_name("alternative")
+                .map(|branch| after(branch, name, usage, code, definitions.clone()))
+                .unwrap_or(definitions);
+            merge(consequence, alternative)
+        }
+        "switch_expression" => {
+            let groups = node
+                .child_by_field_name("body")
+                .map(|body| body.named_children(&mut cursor).collect::<Vec<Node>>())
+                .unwrap_or_default();
+            groups
+                .into_iter()
+                .fold(definitions.clone(), |merged, group| {
+                    merge(merged, after(group, name, usage, code, definitions.clone()))
+                })
+        }
+        "return_statement" | "throw_statement" => vec![],
+        _ if LOOPS.contains(&kind) => match node.child_by_field_name("body") {
+            Some(body) => merge(
+                definitions.clone(),
+                after(body, name, usage, code, definitions),
+            ),
+            None => definitions,
+        },
+        _ => definitions,
+    });
+}
+
+pub fn reaching_definitions<'a>(usage: Node<'a>, code: &String) -> Option<Vec<Definition<'a>>> {
+    let name = optional_node_to_string(&Some(usage), code);
+    let mut scope = usage.parent()?;
+    while !EXECUTABLE_SCOPES.contains(&scope.kind()) {
+        scope = scope.parent()?;
+    }
+
+    let body = match scope.kind() {
+        "static_initializer" => scope.named_child(0),
+        _ => scope.child_by_field_name("body"),
+    }?;
+
+    let mut declarations = vec![];
+    descendants_of_kind(body, &["local_variable_declaration"], &mut declarations);
+    if !declarations.into_iter().any(|declaration| {
+        declaration.end_byte() <= usage.start_byte() && declares(declaration, &name, code)
+    }) {
+        return None;
+    }
+
+    let definitions = after(body, &name, usage, code, vec![]);
+    return Some(definitions).filter(|definitions| !definitions.is_empty());
+}
+
+#[cfg(test)]
+mod test {
+    use mongodb_query_language::execution::Execution::FindMany;
+    use mongodb_query_language::filter::FilterOperator::{And, Equals, GreaterThan, Or};
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::values::Value::{Boolean, Integer, String};
+
+    use crate::use_cases::find::find;
+    use crate::Java;
+
+    #[test]
+    fn follows_reassigned_filters_and_conditionally_built_conditions() {
+        let code = r#"
+        public class Search {
+            private final MongoCollection<Document> collection;
+
+            public List<Document> byStatus(boolean urgent) {
+                Bson filter = eq("status", "A");
+                if (urgent) {
+                    filter = and(filter, gt("priority", 3));
+                }
+                return collection.find(filter).into(new ArrayList<>());
+            }
+
+            public List<Document> byConditions(boolean active, String city) {
+                List<Bson> conditions = new ArrayList<>();
+                conditions.add(eq("kind", "shop"));
+                if (active) {
+                    conditions.add(eq("active", true));
+                }
+                if (city == null) {
+                    return List.of();
+                }
+                return collection.find(and(conditions)).into(new ArrayList<>());
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap();
+        let predicates: Vec<_> = result
+            .executions
+            .into_iter()
+            .map(|execution| match execution {
+                FindMany { predicate, .. } => predicate,
+                _ => panic!(),
+            })
+            .collect();
+
+        let span = Span::default();
+        let status = Equals {
+            field: "status".to_string(),
+            value: String("A".to_string(), span),
+            span,
+        };
+        let kind = Equals {
+            field: "kind".to_string(),
+            value: String("shop".to_string(), span),
+            span,
+        };
+        assert_eq!(
+            predicates,
+            vec![
+                Or {
+                    predicates: vec![
+                        And {
+                            predicates: vec![
+                                status.clone(),
+                                GreaterThan {
+                                    field: "priority".to_string(),
+                                    value: Integer(3, span),
+                                    span,
+                                },
+                            ],
+                            span,
+                        },
+                        status,
+                    ],
+                    span,
+                },
+                Or {
+                    predicates: vec![
+                        And {
+                            predicates: vec![
+                                kind.clone(),
+                                Equals {
+                                    field: "active".to_string(),
+                                    value: Boolean(true, span),
+                                    span,
+                                },
+                            ],
+                            span,
+                        },
+                        And {
+                            predicates: vec![kind],
+                            span,
+                        },
+                    ],
+                    span,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use dialect_interface::ExtractionWarning;
+use mongodb_query_language::aggregation::SortDirection;
+use mongodb_query_language::serialization::{FromBson, ToBson};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::Value;
+use tree_sitter::Node;
+
+use crate::tree_ext::document_from_expression::document_from_expression;
+use crate::tree_ext::{
+    fields_from_arguments, invocation_arguments, invocation_name, operands_from_arguments,
+    optional_node_to_string, resolved_expression, unsupported_operator,
+};
+
+pub fn sort_from_expression(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Vec<(String, SortDirection)> {
+    let node = resolved_expression(node, code);
+    if let Some(fields) = document_from_expression(node, code, warnings) {
+        let sort = fields
+            .into_iter()
+            .map(|(field, direction)| {
+                let direction = SortDirection::from_bson(&direction.to_bson()).ok()?;
+                Some((field, direction))
+            })
+            .collect::<Option<Vec<(String, SortDirection)>>>();
+        if sort.is_none() {
+            warnings.push(ExtractionWarning::UnresolvedStage {
+                expression: optional_node_to_string(&Some(node), code),
+                span: Span::from(node),
+            });
+        }
+        return sort.unwrap_or_default();
+    }
+
+    if node.kind() != "method_invocation" {
+        warnings.push(ExtractionWarning::UnresolvedStage {
+            expression: optional_node_to_string(&Some(node), code),
+            span: Span::from(node),
+        });
+        return vec![];
+    }
+
+    let arguments = invocation_arguments(node);
+    let direction = match invocation_name(node, code).as_str() {
+        "ascending" => SortDirection::Ascending,
+        "descending" => SortDirection::Descending,
+        "metaTextScore" => SortDirection::TextScore,
+        "orderBy" => {
+            return operands_from_arguments(&arguments, code)
+                .into_iter()
+                .flat_map(|argument| sort_from_expression(argument, code, warnings))
+                .collect();
+        }
+        _ => {
+            warnings.push(unsupported_operator(node, code));
+            return vec![];
+        }
+    };
+
+    return fields_from_arguments(&arguments, code)
+        .into_iter()
+        .map(|field| (field, direction))
+        .collect();
+}
+
+pub fn sort_to_value(sort: Vec<(String, SortDirection)>, span: Span) -> Value {
+    return Value::Object(
+        sort.into_iter()
+            .map(|(field, direction)| {
+                let direction = match direction {
+                    SortDirection::Ascending => Value::Integer(1, span),
+                    SortDirection::Descending => Value::Integer(-1, span),
+                    SortDirection::TextScore => Value::Object(
+                        vec![(
+                            "$meta".to_string(),
+                            Value::String("textScore".to_string(), span),
+                        )],
+                        span,
+                    ),
+                };
+                (field, direction)
+            })
+            .collect(),
+        span,
+    );
+}
@@ -0,0 +1,42 @@
+use dialect_interface::ExtractionWarning;
+use mongodb_query_language::execution::Execution::{FindMany, FindOne};
+use mongodb_query_language::filter::FilterOperator;
+
+use crate::use_cases::find::find;
+use crate::Java;
+
+pub fn predicates_of(method_body: &str) -> (Vec<FilterOperator>, Vec<ExtractionWarning>) {
+    let code = format!(
+        r#"
+        public class MyRepository {{
+            private static final String STATUS = "status";
+            private final MongoCollection<Document> collection;
+
+            public void findAll(String status) {{
+                List<String> tags = Arrays.asList("a", "b");
+                {}
+            }}
+        }}
+        "#,
+        method_body
+    );
+
+    let java = Java::new();
+    let tree = java.full_parse(&code);
+    let result = find(tree, &code).unwrap();
+    let predicates = result
+        .executions
+        .into_iter()
+        .map(|execution| match execution {
+            FindOne { predicate, .. } | FindMany { predicate, .. } => predicate,
+            _ => panic!(),
+        })
+        .collect();
+
+    let warnings = result
+        .warnings
+        .into_iter()
+        .filter(|warning| !matches!(warning, ExtractionWarning::UnresolvedCollection { .. }))
+        .collect();
+    return (predicates, warnings);
+}
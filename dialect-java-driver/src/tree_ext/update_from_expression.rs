@@ -0,0 +1,325 @@
+use dialect_interface::ExtractionWarning;
+use mongodb_query_language::filter::FilterOperator;
+use mongodb_query_language::serialization::{FromBson, ToBson};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::update::{
+    ArrayUpdateValue, BitOperation, PullCondition, Update, UpdateOperator,
+};
+use mongodb_query_language::values::Value;
+use tree_sitter::Node;
+
+use crate::tree_ext::document_from_expression::document_from_expression;
+use crate::tree_ext::pipeline_from_expression::stage_from_expression;
+use crate::tree_ext::predicate_from_expression::predicate_from_expression;
+use crate::tree_ext::sort_from_expression::{sort_from_expression, sort_to_value};
+use crate::tree_ext::value_from_expression::{
+    iterable_elements, value_from_expression, values_from_arguments,
+};
+use crate::tree_ext::{
+    builder_calls, field_from_expression, invocation_arguments, invocation_name,
+    operands_from_arguments, optional_node_to_string, resolved_expression, unsupported_operator,
+};
+
+const UPDATES_HELPERS: [&str; 22] = [
+    "set",
+    "setOnInsert",
+    "unset",
+    "rename",
+    "inc",
+    "mul",
+    "min",
+    "max",
+    "currentDate",
+    "currentTimestamp",
+    "addToSet",
+    "addEachToSet",
+    "push",
+    "pushEach",
+    "pull",
+    "pullByFilter",
+    "pullAll",
+    "popFirst",
+    "popLast",
+    "bitwiseAnd",
+    "bitwiseOr",
+    "bitwiseXor",
+];
+
+struct PushOptions {
+    position: Option<Box<Value>>,
+    slice: Option<Box<Value>>,
+    sort: Option<Box<Value>>,
+}
+
+fn push_options(
+    node: Option<&Node>,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> PushOptions {
+    let mut options = PushOptions {
+        position: None,
+        slice: None,
+        sort: None,
+    };
+
+    let calls = node
+        .and_then(|node| builder_calls(*node, code))
+        .unwrap_or_default();
+    for (name, arguments) in calls {
+        let Some(argument) = arguments.first() else {
+            continue;
+        };
+
+        match name.as_str() {
+            "position" => options.position = Some(Box::new(value_from_expression(*argument, code))),
+            "slice" => options.slice = Some(Box::new(value_from_expression(*argument, code))),
+            "sort" => options.sort = Some(Box::new(value_from_expression(*argument, code))),
+            "sortDocument" => {
+                options.sort = Some(Box::new(sort_to_value(
+                    sort_from_expression(*argument, code, warnings),
+                    Span::from(*argument),
+                )))
+            }
+            _ => {}
+        }
+    }
+
+    return options;
+}
+
+fn element_condition(mut predicate: FilterOperator) -> Option<(String, FilterOperator)> {
+    if let FilterOperator::Not { predicates, span } = predicate {
+        let mut field = None;
+        let mut conditions = vec![];
+        for predicate in predicates {
+            let (inner_field, condition) = element_condition(predicate)?;
+            if field.get_or_insert(inner_field.clone()) != &inner_field {
+                return None;
+            }
+            conditions.push(condition);
+        }
+
+        return Some((
+            field?,
+            FilterOperator::Not {
+                predicates: conditions,
+                span,
+            },
+        ));
+    }
+
+    let field = std::mem::take(predicate.field_mut()?);
+    return Some((field, predicate));
+}
+
+fn pull_from_predicate(predicate: FilterOperator) -> Option<UpdateOperator> {
+    if let FilterOperator::Equals { field, value, .. } = predicate {
+        return Some(UpdateOperator::Pull {
+            field,
+            condition: PullCondition::Value(value),
+        });
+    }
+
+    let (field, condition) = element_condition(predicate)?;
+    return Some(UpdateOperator::Pull {
+        field,
+        condition: PullCondition::Predicate(Box::new(condition)),
+    });
+}
+
+fn pulls_from_filter(
+    filter: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Vec<UpdateOperator> {
+    let predicates = match predicate_from_expression(filter, code, warnings) {
+        Some(FilterOperator::And { predicates, .. }) => predicates,
+        Some(predicate) => vec![predicate],
+        None => return vec![],
+    };
+
+    return predicates
+        .into_iter()
+        .filter_map(|predicate| {
+            let span = predicate.span();
+            let pull = pull_from_predicate(predicate);
+            if pull.is_none() {
+                warnings.push(ExtractionWarning::UnresolvedUpdate {
+                    expression: optional_node_to_string(&Some(filter), code),
+                    span,
+                });
+            }
+            pull
+        })
+        .collect();
+}
+
+fn update_operators_from_expression(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Vec<UpdateOperator> {
+    let node = resolved_expression(node, code);
+    if node.kind() != "method_invocation" {
+        warnings.push(ExtractionWarning::UnresolvedUpdate {
+            expression: optional_node_to_string(&Some(node), code),
+            span: Span::from(node),
+        });
+        return vec![];
+    }
+
+    let name = invocation_name(node, code);
+    let arguments = invocation_arguments(node);
+    match name.as_str() {
+        "combine" => {
+            return operands_from_arguments(&arguments, code)
+                .into_iter()
+                .flat_map(|argument| update_operators_from_expression(argument, code, warnings))
+                .collect()
+        }
+        "pullByFilter" if arguments.len() == 1 => {
+            return pulls_from_filter(arguments[0], code, warnings)
+        }
+        _ => {}
+    }
+
+    let field = arguments
+        .first()
+        .and_then(|field| field_from_expression(*field, code));
+    let value = |node: &Node| value_from_expression(*node, code);
+
+    let operator = match (name.as_str(), field, &arguments[..]) {
+        ("set", Some(field), [_, v]) => Some(UpdateOperator::Set {
+            field,
+            value: value(v),
+        }),
+        ("setOnInsert", Some(field), [_, v]) => Some(UpdateOperator::SetOnInsert {
+            field,
+            value: value(v),
+        }),
+        ("unset", Some(field), [_]) => Some(UpdateOperator::Unset { field }),
+        ("rename", Some(field), [_, new_name]) => field_from_expression(*new_name, code)
+            .map(|new_name| UpdateOperator::Rename { field, new_name }),
+        ("inc", Some(field), [_, v]) => Some(UpdateOperator::Inc {
+            field,
+            amount: value(v),
+        }),
+        ("mul", Some(field), [_, v]) => Some(UpdateOperator::Mul {
+            field,
+            factor: value(v),
+        }),
+        ("min", Some(field), [_, v]) => Some(UpdateOperator::Min {
+            field,
+            value: value(v),
+        }),
+        ("max", Some(field), [_, v]) => Some(UpdateOperator::Max {
+            field,
+            value: value(v),
+        }),
+        ("currentDate", Some(field), [_]) => Some(UpdateOperator::CurrentDate {
+            field,
+            as_timestamp: false,
+        }),
+        ("currentTimestamp", Some(field), [_]) => Some(UpdateOperator::CurrentDate {
+            field,
+            as_timestamp: true,
+        }),
+        ("addToSet", Some(field), [_, v]) => Some(UpdateOperator::AddToSet {
+            field,
+            values: ArrayUpdateValue::Single(value(v)),
+        }),
+        ("addEachToSet", Some(field), [_, values]) => Some(UpdateOperator::AddToSet {
+            field,
+            values: ArrayUpdateValue::Each(values_from_arguments(&[*values], code)),
+        }),
+        ("push", Some(field), [_, v]) => Some(UpdateOperator::Push {
+            field,
+            values: ArrayUpdateValue::Single(value(v)),
+            position: None,
+            slice: None,
+            sort: None,
+        }),
+        ("pushEach", Some(field), [_, values, options @ ..]) if options.len() <= 1 => {
+            let options = push_options(options.first(), code, warnings);
+            Some(UpdateOperator::Push {
+                field,
+                values: ArrayUpdateValue::Each(values_from_arguments(&[*values], code)),
+                position: options.position,
+                slice: options.slice,
+                sort: options.sort,
+            })
+        }
+        ("pull", Some(field), [_, v]) => Some(UpdateOperator::Pull {
+            field,
+            condition: PullCondition::Value(value(v)),
+        }),
+        ("pullAll", Some(field), [_, values]) => Some(UpdateOperator::PullAll {
+            field,
+            values: values_from_arguments(&[*values], code),
+        }),
+        ("popFirst", Some(field), [_]) => Some(UpdateOperator::Pop { field, first: true }),
+        ("popLast", Some(field), [_]) => Some(UpdateOperator::Pop {
+            field,
+            first: false,
+        }),
+        ("bitwiseAnd", Some(field), [_, v]) => Some(UpdateOperator::Bit {
+            field,
+            operation: BitOperation::And,
+            value: value(v),
+        }),
+        ("bitwiseOr", Some(field), [_, v]) => Some(UpdateOperator::Bit {
+            field,
+            operation: BitOperation::Or,
+            value: value(v),
+        }),
+        ("bitwiseXor", Some(field), [_, v]) => Some(UpdateOperator::Bit {
+            field,
+            operation: BitOperation::Xor,
+            value: value(v),
+        }),
+        _ => None,
+    };
+
+    if operator.is_none() {
+        warnings.push(match UPDATES_HELPERS.contains(&name.as_str()) {
+            true => ExtractionWarning::UnresolvedUpdate {
+                expression: optional_node_to_string(&Some(node), code),
+                span: Span::from(node),
+            },
+            false => unsupported_operator(node, code),
+        });
+    }
+
+    return operator.into_iter().collect();
+}
+
+pub fn update_from_expression(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Update {
+    let node = resolved_expression(node, code);
+    if let Some(fields) = document_from_expression(node, code, warnings) {
+        return match Update::from_bson(&Value::Object(fields, Span::from(node)).to_bson()) {
+            Ok(update) => update,
+            Err(_) => {
+                warnings.push(ExtractionWarning::UnresolvedUpdate {
+                    expression: optional_node_to_string(&Some(node), code),
+                    span: Span::from(node),
+                });
+                Update::Operators(vec![])
+            }
+        };
+    }
+
+    if let Some(stages) = iterable_elements(node, code) {
+        return Update::Pipeline(
+            stages
+                .into_iter()
+                .filter_map(|stage| stage_from_expression(stage, code, warnings))
+                .collect(),
+        );
+    }
+
+    return Update::Operators(update_operators_from_expression(node, code, warnings));
+}
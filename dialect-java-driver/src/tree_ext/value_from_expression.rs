@@ -0,0 +1,189 @@
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::Value;
+use tree_sitter::Node;
+
+use crate::tree_ext::document_from_expression::document_from_expression;
+use crate::tree_ext::infer_value_type::{infer_value_type, initializer_of};
+use crate::tree_ext::{
+    invocation_arguments, invocation_name, optional_node_to_string, string_literal,
+};
+
+const COLLECTION_FACTORIES: [(&str, &str); 6] = [
+    ("Arrays", "asList"),
+    ("List", "of"),
+    ("Set", "of"),
+    ("Collections", "singletonList"),
+    ("Collections", "singleton"),
+    ("Stream", "of"),
+];
+
+fn integer_from_literal(text: &str, span: Span) -> Option<Value> {
+    let is_long = text.ends_with(['l', 'L']);
+    let digits = text.trim_end_matches(['l', 'L']).replace('_', "");
+    let (digits, radix) = match digits.get(..2) {
+        Some("0x" | "0X") => (&digits[2..], 16),
+        Some("0b" | "0B") => (&digits[2..], 2),
+        _ if digits.len() > 1 && digits.starts_with('0') => (&digits[1..], 8),
+        _ => (&digits[..], 10),
+    };
+
+    if radix == 10 {
+        let value = i64::from_str_radix(digits, radix).ok()?;
+        return match is_long {
+            true => Some(Value::Int64(value, span)),
+            false => i32::try_from(value)
+                .map(|value| Value::Integer(value, span))
+                .ok(),
+        };
+    }
+
+    let bits = u64::from_str_radix(digits, radix).ok()?;
+    return match is_long {
+        true => Some(Value::Int64(bits as i64, span)),
+        false => u32::try_from(bits)
+            .map(|bits| Value::Integer(bits as i32, span))
+            .ok(),
+    };
+}
+
+fn negated(value: Value, span: Span) -> Option<Value> {
+    return match value {
+        Value::Integer(value, _) => Some(Value::Integer(value.wrapping_neg(), span)),
+        Value::Int64(value, _) => Some(Value::Int64(value.wrapping_neg(), span)),
+        Value::Floating(value, _) => Some(Value::Floating(-value, span)),
+        _ => None,
+    };
+}
+
+pub fn value_from_expression(node: Node, code: &String) -> Value {
+    let text = node.utf8_text(code.as_bytes()).unwrap().to_string();
+    let span = Span::from(node);
+    let reference = || Value::Reference(text.clone(), infer_value_type(node, code), span);
+
+    if let Some(fields) = document_from_expression(node, code, &mut vec![]) {
+        return Value::Object(fields, span);
+    }
+
+    return match node.kind() {
+        "string_literal" | "character_literal" => string_literal(node, code)
+            .map(|value| Value::String(value, span))
+            .unwrap_or_else(reference),
+        "decimal_integer_literal"
+        | "hex_integer_literal"
+        | "octal_integer_literal"
+        | "binary_integer_literal" => integer_from_literal(&text, span).unwrap_or_else(reference),
+        "decimal_floating_point_literal" => text
+            .trim_end_matches(['f', 'F', 'd', 'D'])
+            .replace('_', "")
+            .parse::<f64>()
+            .map(|value| Value::Floating(value, span))
+            .unwrap_or_else(|_| reference()),
+        "true" => Value::Boolean(true, span),
+        "false" => Value::Boolean(false, span),
+        "null_literal" => Value::Null(span),
+        "unary_expression"
+            if optional_node_to_string(&node.child_by_field_name("operator"), code) == "-" =>
+        {
+            node.child_by_field_name("operand")
+                .and_then(|operand| negated(value_from_expression(operand, code), span))
+                .unwrap_or_else(reference)
+        }
+        "object_creation_expression"
+            if optional_node_to_string(&node.child_by_field_name("type"), code) == "ObjectId" =>
+        {
+            match &invocation_arguments(node)[..] {
+                [hex] => string_literal(*hex, code)
+                    .map(|hex| Value::ObjectId(hex, span))
+                    .unwrap_or_else(reference),
+                _ => reference(),
+            }
+        }
+        _ => reference(),
+    };
+}
+
+pub fn iterable_elements<'a>(node: Node<'a>, code: &String) -> Option<Vec<Node<'a>>> {
+    return match node.kind() {
+        "method_invocation" => {
+            let receiver = optional_node_to_string(&node.child_by_field_name("object"), code);
+            let factory = (receiver.as_str(), invocation_name(node, code));
+            COLLECTION_FACTORIES
+                .iter()
+                .any(|(class, method)| *class == factory.0 && *method == factory.1)
+                .then(|| invocation_arguments(node))
+        }
+        "array_creation_expression" => {
+            let initializer = node.child_by_field_name("value")?;
+            let mut cursor = initializer.walk();
+            let elements = initializer.named_children(&mut cursor).collect();
+            Some(elements)
+        }
+        "array_initializer" => {
+            let mut cursor = node.walk();
+            let elements = node.named_children(&mut cursor).collect();
+            Some(elements)
+        }
+        "identifier" => iterable_elements(initializer_of(node, code)?, code),
+        _ => None,
+    };
+}
+
+pub fn values_from_arguments(arguments: &[Node], code: &String) -> Vec<Value> {
+    if let [single] = arguments {
+        if let Some(elements) = iterable_elements(*single, code) {
+            return values_from_arguments(&elements, code);
+        }
+    }
+
+    return arguments
+        .iter()
+        .map(|argument| value_from_expression(*argument, code))
+        .collect();
+}
+
+pub fn value_from_iterable(node: Node, code: &String) -> Value {
+    return match iterable_elements(node, code) {
+        Some(elements) => Value::Array(
+            elements
+                .into_iter()
+                .map(|element| value_from_iterable(element, code))
+                .collect(),
+            Span::from(node),
+        ),
+        None => value_from_expression(node, code),
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use mongodb_query_language::filter::FilterOperator::Equals;
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::values::Value::{Int64, Integer};
+
+    use crate::tree_ext::test_fixtures::predicates_of;
+
+    #[test]
+    fn wraps_negated_minimum_integer_literals() {
+        let (predicates, _) = predicates_of(
+            r#"collection.find(eq("a", -0x80000000));
+            collection.find(eq("a", -0x8000000000000000L));"#,
+        );
+
+        let span = Span::default();
+        assert_eq!(
+            predicates,
+            vec![
+                Equals {
+                    field: "a".to_string(),
+                    value: Integer(i32::MIN, span),
+                    span,
+                },
+                Equals {
+                    field: "a".to_string(),
+                    value: Int64(i64::MIN, span),
+                    span,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use std::cell::RefCell;
+use std::error::Error;
+
+use tree_sitter::Tree;
+
+use dialect_interface::{Extraction, ExtractionWarning};
+use mongodb_query_language::execution::Execution::Aggregate;
+use mongodb_query_language::span::Span;
+
+use crate::tree_ext::friendly_capture::FriendlyCapture;
+use crate::tree_ext::infer_execution_context::{infer_execution_context, operation_arguments};
+use crate::tree_ext::infer_mongodb_namespace::infer_mongodb_namespace;
+use crate::tree_ext::pipeline_from_expression::pipeline_from_expression;
+use crate::use_cases::{is_driver_receiver, namespace_of, push_execution, DATABASE_RECEIVERS};
+
+const ALL_AGGREGATE_METHOD_CALLS: &str = include_str!("queries/aggregate.all_aggregates.scm");
+
+pub fn aggregate(
+    tree: RefCell<Tree>,
+    code: &String,
+) -> Result<Extraction, Box<dyn Error + Sync + Send>> {
+    let all_queries_query =
+        tree_sitter::Query::new(tree_sitter_java::language(), ALL_AGGREGATE_METHOD_CALLS)?;
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let root = tree.borrow();
+    let namespaces = infer_mongodb_namespace(root.root_node(), code)?;
+    let all_matches = cursor.matches(&all_queries_query, root.root_node(), code.as_bytes());
+
+    let collection_idx = all_queries_query
+        .capture_index_for_name("collection")
+        .unwrap();
+    let arglist_idx = all_queries_query
+        .capture_index_for_name("argumentlist")
+        .unwrap();
+
+    let mut result = Extraction::default();
+
+    for each_match in all_matches {
+        let [Some(coll_node), Some(arg_list_node)] =
+            each_match.capture(vec![collection_idx, arglist_idx])[..]
+        else {
+            continue;
+        };
+
+        if !is_driver_receiver(coll_node, &DATABASE_RECEIVERS, &namespaces, code) {
+            continue;
+        }
+
+        let aggregate_node = arg_list_node.parent().unwrap();
+        let span = Span::from(aggregate_node);
+        let pipeline = match operation_arguments(aggregate_node, code)
+            .into_iter()
+            .find(|argument| argument.kind() != "class_literal")
+        {
+            Some(pipeline) => pipeline_from_expression(pipeline, code, &mut result.warnings),
+            None => {
+                result.warnings.push(ExtractionWarning::UnresolvedStage {
+                    expression: "<unknown>".to_string(),
+                    span,
+                });
+                vec![]
+            }
+        };
+        let namespace = namespace_of(coll_node, &namespaces, code, &mut result.warnings);
+
+        let execution = Aggregate {
+            namespace,
+            pipeline,
+            context: infer_execution_context(aggregate_node, code),
+            span,
+        };
+        push_execution(coll_node, execution, code, &mut result);
+    }
+
+    return Ok(result);
+}
+
+#[cfg(test)]
+mod test {
+    use mongodb_query_language::aggregation::Expression::{FieldPath, Literal, Operator};
+    use mongodb_query_language::aggregation::SortDirection::Descending;
+    use mongodb_query_language::aggregation::Stage;
+    use mongodb_query_language::execution::Execution::Aggregate;
+    use mongodb_query_language::filter::FilterOperator::Equals;
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::values::Value::{Array, Integer, String};
+
+    use dialect_interface::ExtractionWarning;
+
+    use crate::use_cases::aggregate::aggregate;
+    use crate::Java;
+
+    #[test]
+    fn parse_aggregates_and_accumulators_builders() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public List<Document> totalsByCustomer() {
+                List<Bson> pipeline = Arrays.asList(
+                    Aggregates.match(eq("status", "paid")),
+                    unwind("$items", new UnwindOptions().preserveNullAndEmptyArrays(true)),
+                    group("$customerId", Accumulators.sum("total", "$items.amount"), sum("orders", 1)),
+                    lookup("customers", "_id", "_id", "customer"),
+                    sort(Sorts.descending("total")),
+                    limit(10),
+                    sortByCount("$total"),
+                    redact("$$PRUNE"));
+                return collection.aggregate(pipeline).into(new ArrayList<>());
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = aggregate(tree, &code).unwrap();
+
+        assert_eq!(result.executions.len(), 1);
+        let Aggregate { pipeline, .. } = &result.executions[0] else {
+            panic!()
+        };
+
+        assert_eq!(
+            *pipeline,
+            vec![
+                Stage::Match {
+                    predicate: Equals {
+                        field: "status".to_string(),
+                        value: String("paid".to_string(), Span::default()),
+                        span: Span::default()
+                    }
+                },
+                Stage::Unwind {
+                    path: "items".to_string(),
+                    include_array_index: None,
+                    preserve_null_and_empty_arrays: true
+                },
+                Stage::Group {
+                    id: FieldPath("customerId".to_string()),
+                    accumulators: vec![
+                        (
+                            "total".to_string(),
+                            Operator {
+                                name: "$sum".to_string(),
+                                arguments: vec![FieldPath("items.amount".to_string())]
+                            }
+                        ),
+                        (
+                            "orders".to_string(),
+                            Operator {
+                                name: "$sum".to_string(),
+                                arguments: vec![Literal(Integer(1, Span::default()))]
+                            }
+                        ),
+                    ]
+                },
+                Stage::Lookup {
+                    from: "customers".to_string(),
+                    local_field: Some("_id".to_string()),
+                    foreign_field: Some("_id".to_string()),
+                    let_variables: vec![],
+                    pipeline: vec![],
+                    as_field: "customer".to_string()
+                },
+                Stage::Sort {
+                    fields: vec![("total".to_string(), Descending)]
+                },
+                Stage::Limit {
+                    limit: Integer(10, Span::default())
+                },
+                Stage::Other {
+                    name: "$sortByCount".to_string(),
+                    definition: Array(
+                        vec![String("$total".to_string(), Span::default())],
+                        Span::default()
+                    )
+                },
+            ]
+        );
+        assert_eq!(
+            result.warnings[0],
+            ExtractionWarning::UnsupportedOperator {
+                operator: "redact".to_string(),
+                span: Span::default()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_aggregates_of_reactive_publishers() {
+        let code = r#"
+        import com.mongodb.reactivestreams.client.MongoCollection;
+
+        public class ReactiveRepository {
+            private final MongoCollection<Document> collection;
+
+            public Flux<Document> totals() {
+                AggregatePublisher<Document> paid = collection
+                    .aggregate(List.of(Aggregates.match(eq("status", "paid"))))
+                    .allowDiskUse(true);
+                Mono.from(paid.first()).subscribe();
+                return Flux.from(collection.aggregate(List.of(limit(10))));
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = aggregate(tree, &code).unwrap();
+
+        let pipelines: Vec<Vec<Stage>> = result
+            .executions
+            .into_iter()
+            .map(|execution| match execution {
+                Aggregate { pipeline, .. } => pipeline,
+                _ => panic!(),
+            })
+            .collect();
+        assert_eq!(
+            pipelines,
+            vec![
+                vec![Stage::Match {
+                    predicate: Equals {
+                        field: "status".to_string(),
+                        value: String("paid".to_string(), Span::default()),
+                        span: Span::default()
+                    }
+                }],
+                vec![Stage::Limit {
+                    limit: Integer(10, Span::default())
+                }],
+            ]
+        );
+    }
+}
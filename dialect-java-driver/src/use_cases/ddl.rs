@@ -0,0 +1,613 @@
+use std::cell::RefCell;
+use std::error::Error;
+
+use tree_sitter::{Node, Tree};
+
+use dialect_interface::{Extraction, ExtractionWarning};
+use mongodb_query_language::ddl::{
+    CollectionOptions, IndexDefinition, IndexOptions, TimeSeriesOptions,
+};
+use mongodb_query_language::execution::Execution::{
+    CreateCollection, CreateIndexes, CreateView, DropIndex, RunCommand,
+};
+use mongodb_query_language::execution::{ExecutionNamespace, Hint};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::{BsonType, Value, ValueType};
+
+use crate::tree_ext::friendly_capture::FriendlyCapture;
+use crate::tree_ext::index_keys_from_expression::index_keys_from_expression;
+use crate::tree_ext::infer_execution_context::{infer_execution_context, operation_arguments};
+use crate::tree_ext::infer_mongodb_namespace::{
+    infer_database_from_usage, infer_mongodb_namespace,
+};
+use crate::tree_ext::pipeline_from_expression::pipeline_from_expression;
+use crate::tree_ext::predicate_from_expression::predicate_from_expression;
+use crate::tree_ext::value_from_expression::value_from_expression;
+use crate::tree_ext::{
+    builder_calls, builder_invocations, field_from_expression, invocation_arguments,
+    invocation_name, operands_from_arguments, optional_node_to_string, resolved_expression,
+    unsupported_operator,
+};
+use crate::use_cases::{is_driver_receiver, namespace_of, push_execution, DATABASE_RECEIVERS};
+
+const ALL_DDL_METHOD_CALLS: &str = include_str!("queries/ddl.all_ddl.scm");
+const COLLECTION_COMMANDS: [&str; 20] = [
+    "aggregate",
+    "cloneCollectionAsCapped",
+    "collMod",
+    "collStats",
+    "compact",
+    "convertToCapped",
+    "count",
+    "create",
+    "createIndexes",
+    "delete",
+    "distinct",
+    "drop",
+    "dropIndexes",
+    "find",
+    "findAndModify",
+    "insert",
+    "listIndexes",
+    "reIndex",
+    "update",
+    "validate",
+];
+
+fn constructor_arguments<'a>(node: Node<'a>, code: &String) -> Vec<Node<'a>> {
+    let node = resolved_expression(node, code);
+    return match node.kind() {
+        "object_creation_expression" => invocation_arguments(node),
+        "method_invocation" => node
+            .child_by_field_name("object")
+            .map(|object| constructor_arguments(object, code))
+            .unwrap_or_default(),
+        _ => vec![],
+    };
+}
+
+fn enum_constant(node: Node, code: &String) -> String {
+    let text = optional_node_to_string(&Some(node), code);
+    return text.rsplit('.').next().unwrap_or(&text).to_lowercase();
+}
+
+fn is_true(node: Node, code: &String) -> bool {
+    return matches!(value_from_expression(node, code), Value::Boolean(true, _));
+}
+
+fn expire_after_seconds(amount: Node, unit: Node, code: &String) -> Value {
+    let unit_name = optional_node_to_string(&Some(unit), code);
+    let factor = match unit_name.rsplit('.').next().unwrap_or("") {
+        "SECONDS" => Some(1),
+        "MINUTES" => Some(60),
+        "HOURS" => Some(3_600),
+        "DAYS" => Some(86_400),
+        _ => None,
+    };
+    let reference = Value::Reference(
+        format!(
+            "{} {}",
+            optional_node_to_string(&Some(amount), code),
+            unit_name
+        ),
+        ValueType::Of(BsonType::Long),
+        Span::from(amount).merge(&Span::from(unit)),
+    );
+
+    return match (value_from_expression(amount, code), factor) {
+        (Value::Integer(amount, span), Some(factor)) => amount
+            .checked_mul(factor)
+            .map(|seconds| Value::Integer(seconds, span))
+            .unwrap_or(reference),
+        (Value::Int64(amount, span), Some(factor)) => amount
+            .checked_mul(i64::from(factor))
+            .map(|seconds| Value::Int64(seconds, span))
+            .unwrap_or(reference),
+        (value, Some(1)) => value,
+        _ => reference,
+    };
+}
+
+fn index_keys(keys: Node, code: &String) -> Value {
+    return index_keys_from_expression(keys, code)
+        .unwrap_or_else(|| value_from_expression(keys, code));
+}
+
+fn index_options(
+    options: Option<Node>,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> IndexOptions {
+    let mut result = IndexOptions::default();
+    let calls = options
+        .and_then(|options| builder_invocations(options, code))
+        .unwrap_or_default();
+
+    for call in calls {
+        match (
+            invocation_name(call, code).as_str(),
+            &invocation_arguments(call)[..],
+        ) {
+            ("name", [name]) => result.name = field_from_expression(*name, code),
+            ("unique", [unique]) => result.unique = is_true(*unique, code),
+            ("sparse", [sparse]) => result.sparse = is_true(*sparse, code),
+            ("hidden", [hidden]) => result.hidden = is_true(*hidden, code),
+            ("expireAfter", [amount, unit]) => {
+                result.expire_after_seconds = Some(expire_after_seconds(*amount, *unit, code))
+            }
+            ("partialFilterExpression", [filter]) => {
+                result.partial_filter = predicate_from_expression(*filter, code, warnings)
+            }
+            ("collation", [collation]) => {
+                result.collation = Some(value_from_expression(*collation, code))
+            }
+            _ => warnings.push(unsupported_operator(call, code)),
+        }
+    }
+
+    return result;
+}
+
+fn index_definition(
+    keys: Node,
+    options: Option<Node>,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> IndexDefinition {
+    return IndexDefinition {
+        keys: index_keys(keys, code),
+        options: index_options(options, code, warnings),
+    };
+}
+
+fn index_models(
+    models: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Vec<IndexDefinition> {
+    return operands_from_arguments(&[models], code)
+        .into_iter()
+        .map(|model| resolved_expression(model, code))
+        .filter(|model| model.kind() == "object_creation_expression")
+        .filter_map(|model| match invocation_arguments(model)[..] {
+            [keys] => Some(index_definition(keys, None, code, warnings)),
+            [keys, options] => Some(index_definition(keys, Some(options), code, warnings)),
+            _ => None,
+        })
+        .collect();
+}
+
+fn validation_options(
+    result: &mut CollectionOptions,
+    validation: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) {
+    for call in builder_invocations(validation, code).unwrap_or_default() {
+        match (
+            invocation_name(call, code).as_str(),
+            &invocation_arguments(call)[..],
+        ) {
+            ("validator", [validator]) => {
+                result.validator = predicate_from_expression(*validator, code, warnings)
+            }
+            ("validationLevel", [level]) => {
+                result.validation_level = Some(enum_constant(*level, code))
+            }
+            ("validationAction", [action]) => {
+                result.validation_action = Some(enum_constant(*action, code))
+            }
+            _ => warnings.push(unsupported_operator(call, code)),
+        }
+    }
+}
+
+fn time_series_options(time_series: Node, code: &String) -> Option<TimeSeriesOptions> {
+    let [time_field] = constructor_arguments(time_series, code)[..] else {
+        return None;
+    };
+
+    let mut result = TimeSeriesOptions {
+        time_field: field_from_expression(time_field, code)?,
+        meta_field: None,
+        granularity: None,
+    };
+    for (name, arguments) in builder_calls(time_series, code).unwrap_or_default() {
+        match (name.as_str(), &arguments[..]) {
+            ("metaField", [meta_field]) => {
+                result.meta_field = field_from_expression(*meta_field, code)
+            }
+            ("granularity", [granularity]) => {
+                result.granularity = Some(enum_constant(*granularity, code))
+            }
+            _ => {}
+        }
+    }
+
+    return Some(result);
+}
+
+fn clustered_index(
+    clustered: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<IndexDefinition> {
+    let [keys, unique] = constructor_arguments(clustered, code)[..] else {
+        return None;
+    };
+
+    let mut index = index_definition(keys, Some(clustered), code, warnings);
+    index.options.unique = is_true(unique, code);
+    return Some(index);
+}
+
+fn collection_options(
+    options: Option<Node>,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> CollectionOptions {
+    let mut result = CollectionOptions::default();
+    let calls = options
+        .and_then(|options| builder_invocations(options, code))
+        .unwrap_or_default();
+
+    for call in calls {
+        match (
+            invocation_name(call, code).as_str(),
+            &invocation_arguments(call)[..],
+        ) {
+            ("capped", [capped]) => result.capped = is_true(*capped, code),
+            ("sizeInBytes", [size]) => {
+                result.size_in_bytes = Some(value_from_expression(*size, code))
+            }
+            ("maxDocuments", [max]) => {
+                result.max_documents = Some(value_from_expression(*max, code))
+            }
+            ("validationOptions", [validation]) => {
+                validation_options(&mut result, *validation, code, warnings)
+            }
+            ("timeSeriesOptions", [time_series]) => {
+                result.time_series = time_series_options(*time_series, code)
+            }
+            ("expireAfter", [amount, unit]) => {
+                result.expire_after_seconds = Some(expire_after_seconds(*amount, *unit, code))
+            }
+            ("clusteredIndexOptions", [clustered]) => {
+                result.clustered_index = clustered_index(*clustered, code, warnings)
+            }
+            ("collation", [collation]) => {
+                result.collation = Some(value_from_expression(*collation, code))
+            }
+            _ => warnings.push(unsupported_operator(call, code)),
+        }
+    }
+
+    return result;
+}
+
+fn database_namespace(
+    database: Node,
+    collection: Option<String>,
+    code: &String,
+) -> ExecutionNamespace {
+    return ExecutionNamespace {
+        database: infer_database_from_usage(database, code),
+        collection,
+        reference_name: optional_node_to_string(&Some(database), code),
+    };
+}
+
+fn created_namespace(
+    database: Node,
+    name: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> ExecutionNamespace {
+    let collection = field_from_expression(name, code);
+    if collection.is_none() {
+        warnings.push(ExtractionWarning::UnresolvedCollection {
+            reference: optional_node_to_string(&Some(name), code),
+            span: Span::from(name),
+        });
+    }
+
+    return database_namespace(database, collection, code);
+}
+
+fn command_collection(command: &Value) -> Option<String> {
+    return match command {
+        Value::Object(fields, _) => match fields.first() {
+            Some((name, Value::String(collection, _)))
+                if COLLECTION_COMMANDS.contains(&name.as_str()) =>
+            {
+                Some(collection.clone())
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+}
+
+pub fn ddl(tree: RefCell<Tree>, code: &String) -> Result<Extraction, Box<dyn Error + Sync + Send>> {
+    let all_queries_query =
+        tree_sitter::Query::new(tree_sitter_java::language(), ALL_DDL_METHOD_CALLS)?;
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let root = tree.borrow();
+    let namespaces = infer_mongodb_namespace(root.root_node(), code)?;
+    let all_matches = cursor.matches(&all_queries_query, root.root_node(), code.as_bytes());
+
+    let receiver_idx = all_queries_query
+        .capture_index_for_name("receiver")
+        .unwrap();
+    let arglist_idx = all_queries_query
+        .capture_index_for_name("argumentlist")
+        .unwrap();
+
+    let mut result = Extraction::default();
+
+    for each_match in all_matches {
+        let [Some(receiver), Some(arg_list_node)] =
+            each_match.capture(vec![receiver_idx, arglist_idx])[..]
+        else {
+            continue;
+        };
+
+        if !is_driver_receiver(receiver, &DATABASE_RECEIVERS, &namespaces, code) {
+            continue;
+        }
+
+        let invocation = arg_list_node.parent().unwrap();
+        let span = Span::from(invocation);
+        let context = infer_execution_context(invocation, code);
+        let arguments = operation_arguments(invocation, code);
+        let warnings = &mut result.warnings;
+
+        let execution = match (invocation_name(invocation, code).as_str(), &arguments[..]) {
+            ("createIndex", [keys, options @ ..]) => CreateIndexes {
+                namespace: namespace_of(receiver, &namespaces, code, warnings),
+                indexes: vec![index_definition(
+                    *keys,
+                    options.first().copied(),
+                    code,
+                    warnings,
+                )],
+                context,
+                span,
+            },
+            ("createIndexes", [models, ..]) => CreateIndexes {
+                namespace: namespace_of(receiver, &namespaces, code, warnings),
+                indexes: index_models(*models, code, warnings),
+                context,
+                span,
+            },
+            ("dropIndex", [index]) => DropIndex {
+                namespace: namespace_of(receiver, &namespaces, code, warnings),
+                index: match field_from_expression(*index, code) {
+                    Some(name) => Hint::Name(name),
+                    None => Hint::Keys(index_keys(*index, code)),
+                },
+                context,
+                span,
+            },
+            ("dropIndexes", []) => DropIndex {
+                namespace: namespace_of(receiver, &namespaces, code, warnings),
+                index: Hint::Name("*".to_string()),
+                context,
+                span,
+            },
+            ("createCollection", [name, options @ ..]) => CreateCollection {
+                namespace: created_namespace(receiver, *name, code, warnings),
+                options: Box::new(collection_options(options.first().copied(), code, warnings)),
+                context,
+                span,
+            },
+            ("createView", [name, view_on, pipeline, ..]) => CreateView {
+                namespace: created_namespace(receiver, *name, code, warnings),
+                view_on: field_from_expression(*view_on, code)
+                    .unwrap_or_else(|| optional_node_to_string(&Some(*view_on), code)),
+                pipeline: pipeline_from_expression(*pipeline, code, warnings),
+                context,
+                span,
+            },
+            ("runCommand", [command, ..]) => {
+                let command = value_from_expression(*command, code);
+                RunCommand {
+                    namespace: database_namespace(receiver, command_collection(&command), code),
+                    command,
+                    context,
+                    span,
+                }
+            }
+            _ => continue,
+        };
+
+        match execution {
+            CreateIndexes { .. } | DropIndex { .. } => {
+                push_execution(receiver, execution, code, &mut result)
+            }
+            execution => result.executions.push(execution),
+        }
+    }
+
+    return Ok(result);
+}
+
+#[cfg(test)]
+mod test {
+    use dialect_interface::ExtractionWarning;
+    use mongodb_query_language::ddl::{IndexDefinition, IndexOptions, TimeSeriesOptions};
+    use mongodb_query_language::execution::Execution::{
+        CreateCollection, CreateIndexes, CreateView, DropIndex, RunCommand,
+    };
+    use mongodb_query_language::execution::Hint;
+    use mongodb_query_language::filter::FilterOperator::{Equals, Exists};
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::values::Value::{Boolean, Int64, Integer, Object, String};
+
+    use crate::use_cases::ddl::ddl;
+    use crate::Java;
+
+    #[test]
+    fn extracts_indexes_collections_views_and_commands() {
+        let code = r#"
+        public class Migrations {
+            private final MongoDatabase database = client.getDatabase("shop");
+            private final MongoCollection<Document> orders = database.getCollection("orders");
+
+            public void migrate() {
+                orders.createIndex(
+                    Indexes.compoundIndex(Indexes.ascending("owner"), Indexes.descending("createdAt")),
+                    new IndexOptions().unique(true).partialFilterExpression(Filters.eq("active", true)));
+                orders.createIndexes(List.of(
+                    new IndexModel(Indexes.text("notes")),
+                    new IndexModel(new Document("expiresAt", 1), new IndexOptions().expireAfter(1L, TimeUnit.DAYS))));
+                orders.dropIndex("legacy_1");
+                database.createCollection("events", new CreateCollectionOptions()
+                    .timeSeriesOptions(new TimeSeriesOptions("at").metaField("source").granularity(TimeSeriesGranularity.HOURS))
+                    .validationOptions(new ValidationOptions().validator(Filters.exists("source")).validationLevel(ValidationLevel.STRICT)));
+                database.createView("activeOrders", "orders", List.of(Aggregates.match(Filters.eq("active", true))));
+                database.runCommand(new Document("collMod", "orders").append("validationLevel", "off"));
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let result = ddl(java.full_parse(&code), &code).unwrap();
+        assert_eq!(result.warnings, vec![]);
+        assert_eq!(result.executions.len(), 6);
+
+        let span = Span::default();
+        let active = Equals {
+            field: "active".to_string(),
+            value: Boolean(true, span),
+            span,
+        };
+        let CreateIndexes { indexes, .. } = &result.executions[0] else {
+            panic!()
+        };
+        assert_eq!(
+            indexes,
+            &vec![IndexDefinition {
+                keys: Object(
+                    vec![
+                        ("owner".to_string(), Integer(1, span)),
+                        ("createdAt".to_string(), Integer(-1, span)),
+                    ],
+                    span
+                ),
+                options: IndexOptions {
+                    unique: true,
+                    partial_filter: Some(active.clone()),
+                    ..IndexOptions::default()
+                },
+            }]
+        );
+        assert_eq!(indexes[0].name(), "owner_1_createdAt_-1");
+
+        let CreateIndexes { indexes, .. } = &result.executions[1] else {
+            panic!()
+        };
+        let names: Vec<_> = indexes.iter().map(IndexDefinition::name).collect();
+        assert_eq!(names, vec!["notes_text", "expiresAt_1"]);
+        assert_eq!(
+            indexes[1].options.expire_after_seconds,
+            Some(Int64(86_400, span))
+        );
+
+        assert!(matches!(
+            &result.executions[2],
+            DropIndex { index: Hint::Name(name), .. } if name == "legacy_1"
+        ));
+
+        let CreateCollection {
+            namespace, options, ..
+        } = &result.executions[3]
+        else {
+            panic!()
+        };
+        assert_eq!(namespace.database, Some("shop".to_string()));
+        assert_eq!(namespace.collection, Some("events".to_string()));
+        assert_eq!(
+            options.time_series,
+            Some(TimeSeriesOptions {
+                time_field: "at".to_string(),
+                meta_field: Some("source".to_string()),
+                granularity: Some("hours".to_string()),
+            })
+        );
+        assert_eq!(
+            options.validator,
+            Some(Exists {
+                field: "source".to_string(),
+                exists: true,
+                span,
+            })
+        );
+        assert_eq!(options.validation_level, Some("strict".to_string()));
+
+        assert!(matches!(
+            &result.executions[4],
+            CreateView { view_on, pipeline, .. } if view_on == "orders" && pipeline.len() == 1
+        ));
+        let RunCommand {
+            namespace, command, ..
+        } = &result.executions[5]
+        else {
+            panic!()
+        };
+        assert_eq!(namespace.collection, Some("orders".to_string()));
+        assert_eq!(
+            command,
+            &Object(
+                vec![
+                    ("collMod".to_string(), String("orders".to_string(), span)),
+                    (
+                        "validationLevel".to_string(),
+                        String("off".to_string(), span)
+                    ),
+                ],
+                span
+            )
+        );
+    }
+
+    #[test]
+    fn warns_about_unknown_options_and_ignores_commands_without_collections() {
+        let code = r#"
+        public class Migrations {
+            private final MongoDatabase database = client.getDatabase("shop");
+            private final MongoCollection<Document> orders = database.getCollection("orders");
+
+            public void migrate() {
+                orders.createIndex(Indexes.ascending("owner"), new IndexOptions().background(true).unique(true));
+                database.createCollection("events", new CreateCollectionOptions().storageEngineOptions(engine));
+                database.runCommand(new Document("createUser", "bob").append("pwd", "secret"));
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let result = ddl(java.full_parse(&code), &code).unwrap();
+        let operators: Vec<_> = result
+            .warnings
+            .iter()
+            .filter_map(|warning| match warning {
+                ExtractionWarning::UnsupportedOperator { operator, .. } => Some(operator.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(operators, vec!["background", "storageEngineOptions"]);
+
+        let CreateIndexes { indexes, .. } = &result.executions[0] else {
+            panic!()
+        };
+        assert!(indexes[0].options.unique);
+        assert!(matches!(
+            &result.executions[2],
+            RunCommand { namespace, .. } if namespace.collection.is_none()
+        ));
+    }
+}
@@ -0,0 +1,891 @@
+use std::cell::RefCell;
+use std::error::Error;
+
+use tree_sitter::{Node, Tree};
+
+use dialect_interface::Extraction;
+use mongodb_query_language::execution::Execution::{FindMany, FindOne};
+use mongodb_query_language::span::Span;
+
+use crate::tree_ext::find_options_from_chain::find_options_from_chain;
+use crate::tree_ext::friendly_capture::FriendlyCapture;
+use crate::tree_ext::infer_execution_context::{infer_execution_context, operation_arguments};
+use crate::tree_ext::infer_mongodb_namespace::infer_mongodb_namespace;
+use crate::tree_ext::{
+    invocation_arguments, invocation_name, local_usages, optional_node_to_string, simple_type_name,
+};
+use crate::use_cases::{
+    is_driver_receiver, namespace_of, predicate_of, push_execution, COLLECTION_RECEIVERS,
+};
+
+const ALL_FIND_METHOD_CALLS: &str = include_str!("queries/find.all_finds.scm");
+
+const FIRST_RESULT_METHODS: [&str; 4] = ["first", "firstOrNull", "single", "singleOrEmpty"];
+const CURSOR_METHODS: [&str; 2] = ["iterator", "cursor"];
+const PUBLISHER_ADAPTERS: [&str; 2] = ["Mono", "Flux"];
+
+fn publisher_adapter<'a>(publisher: Node<'a>, code: &String) -> Option<(String, Node<'a>)> {
+    let adapter = publisher
+        .parent()
+        .filter(|arguments| arguments.kind() == "argument_list")?
+        .parent()?;
+    let adapter_type = simple_type_name(&optional_node_to_string(
+        &adapter.child_by_field_name("object"),
+        code,
+    ));
+
+    return match (
+        invocation_name(adapter, code).as_str(),
+        &invocation_arguments(adapter)[..],
+    ) {
+        ("from" | "fromDirect", [_]) if PUBLISHER_ADAPTERS.contains(&adapter_type.as_str()) => {
+            Some((adapter_type, adapter))
+        }
+        _ => None,
+    };
+}
+
+fn returns_first(find_invocation: Node, adapted: bool, code: &String) -> bool {
+    let mut current = find_invocation;
+    let mut adapted = adapted;
+    while let Some(parent) = current.parent() {
+        if let Some((adapter_type, adapter)) = publisher_adapter(current, code) {
+            if adapter_type == "Mono" {
+                return true;
+            }
+
+            adapted = true;
+            current = adapter;
+            continue;
+        }
+
+        if parent.kind() != "method_invocation"
+            || parent.child_by_field_name("object") != Some(current)
+        {
+            break;
+        }
+
+        let method = invocation_name(parent, code);
+        if CURSOR_METHODS.contains(&method.as_str()) {
+            return false;
+        }
+
+        if FIRST_RESULT_METHODS.contains(&method.as_str()) || (adapted && method == "next") {
+            return true;
+        }
+
+        current = parent;
+    }
+
+    return local_usages(current, code)
+        .into_iter()
+        .any(|usage| returns_first(usage, adapted, code));
+}
+
+pub fn find(
+    tree: RefCell<Tree>,
+    code: &String,
+) -> Result<Extraction, Box<dyn Error + Sync + Send>> {
+    let all_queries_query =
+        tree_sitter::Query::new(tree_sitter_java::language(), ALL_FIND_METHOD_CALLS)?;
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let root = tree.borrow();
+    let namespaces = infer_mongodb_namespace(root.root_node(), code)?;
+    let all_matches = cursor.matches(&all_queries_query, root.root_node(), code.as_bytes());
+
+    let collection_idx = all_queries_query
+        .capture_index_for_name("collection")
+        .unwrap();
+    let arglist_idx = all_queries_query
+        .capture_index_for_name("argumentlist")
+        .unwrap();
+
+    let mut result = Extraction::default();
+
+    for each_match in all_matches {
+        let [Some(coll_node), Some(arg_list_node)] =
+            each_match.capture(vec![collection_idx, arglist_idx])[..]
+        else {
+            continue;
+        };
+
+        if !is_driver_receiver(coll_node, &COLLECTION_RECEIVERS, &namespaces, code) {
+            continue;
+        }
+
+        let find_node = arg_list_node.parent().unwrap();
+        let filter_node = operation_arguments(find_node, code)
+            .into_iter()
+            .find(|argument| argument.kind() != "class_literal");
+        let Some(predicate) = predicate_of(filter_node, code, &mut result.warnings) else {
+            continue;
+        };
+        let namespace = namespace_of(coll_node, &namespaces, code, &mut result.warnings);
+
+        let options = find_options_from_chain(find_node, code, &mut result.warnings);
+        let context = infer_execution_context(find_node, code);
+        let span = Span::from(find_node);
+        let execution = match returns_first(find_node, false, code) {
+            true => FindOne {
+                namespace,
+                predicate,
+                options,
+                context,
+                span,
+            },
+            false => FindMany {
+                namespace,
+                predicate,
+                options,
+                context,
+                span,
+            },
+        };
+        push_execution(coll_node, execution, code, &mut result);
+    }
+
+    return Ok(result);
+}
+
+#[cfg(test)]
+mod test {
+    use mongodb_query_language::aggregation::Expression;
+    use mongodb_query_language::aggregation::ProjectionField::{
+        Computed, ElemMatch, Exclude, Include, Meta, Slice,
+    };
+    use mongodb_query_language::aggregation::SortDirection::{Ascending, Descending};
+    use mongodb_query_language::execution::Execution::{FindMany, FindOne};
+    use mongodb_query_language::execution::{
+        ExecutionContext, ExecutionNamespace, FindOptions, Hint,
+    };
+    use mongodb_query_language::filter::FilterOperator::{And, Equals, GreaterThan, Not, Or};
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::values::Value::{Int64, Integer, Object, Reference, String};
+    use mongodb_query_language::values::{BsonType, ValueType};
+
+    use dialect_interface::ExtractionWarning;
+
+    use crate::use_cases::find::find;
+    use crate::Java;
+
+    #[test]
+    fn parse_common_find_where_collection_is_a_class_property() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public Document findOne(String id) {
+                return collection.find(eq("_id", id)).first();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        assert_eq!(result.len(), 1);
+        let first = &result[0];
+
+        assert_eq!(
+            *first,
+            FindOne {
+                namespace: ExecutionNamespace {
+                    collection: None,
+                    database: None,
+                    reference_name: "collection".to_string()
+                },
+                predicate: Equals {
+                    field: "_id".to_string(),
+                    value: Reference(
+                        "id".to_string(),
+                        ValueType::Of(BsonType::String),
+                        Span::default()
+                    ),
+                    span: Span::default()
+                },
+                options: FindOptions::default(),
+                context: ExecutionContext::default(),
+                span: Span::default()
+            }
+        )
+    }
+
+    #[test]
+    fn parse_namespace_from_javadoc_in_field() {
+        let code = r#"
+        public class MyRepository {
+            /**
+              * @mongodb.namespace mydb.mycoll
+            **/
+            private final MongoCollection<Document> myMongoCollection;
+
+            public Document findOne(String id) {
+                return myMongoCollection.find(eq("_id", id)).first();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        assert_eq!(result.len(), 1);
+        let first = &result[0];
+
+        assert_eq!(
+            *first,
+            FindOne {
+                namespace: ExecutionNamespace {
+                    database: Some("mydb".to_string()),
+                    collection: Some("mycoll".to_string()),
+                    reference_name: "myMongoCollection".to_string()
+                },
+                predicate: Equals {
+                    field: "_id".to_string(),
+                    value: Reference(
+                        "id".to_string(),
+                        ValueType::Of(BsonType::String),
+                        Span::default()
+                    ),
+                    span: Span::default()
+                },
+                options: FindOptions::default(),
+                context: ExecutionContext::default(),
+                span: Span::default()
+            }
+        )
+    }
+
+    #[test]
+    fn parse_gt_query() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> myMongoCollection;
+
+            public Document findOne(int age) {
+                return myMongoCollection.find(gt("age", age)).first();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        assert_eq!(result.len(), 1);
+        let FindOne {
+            namespace: _,
+            predicate,
+            options: _,
+            context: _,
+            span: _,
+        } = &result[0]
+        else {
+            panic!()
+        };
+
+        assert_eq!(
+            *predicate,
+            GreaterThan {
+                field: "age".to_string(),
+                value: Reference(
+                    "age".to_string(),
+                    ValueType::Of(BsonType::Int),
+                    Span::default()
+                ),
+                span: Span::default()
+            }
+        )
+    }
+
+    #[test]
+    fn parse_find_options_from_chained_calls() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> myMongoCollection;
+
+            public Document findOne(int age, int page) {
+                return myMongoCollection.find(gt("age", age))
+                    .projection(Projections.fields(include("name", "age"), excludeId()))
+                    .sort(orderBy(ascending("age"), Sorts.descending("name")))
+                    .skip(page)
+                    .limit(10)
+                    .maxTime(5, TimeUnit.SECONDS)
+                    .first();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        assert_eq!(result.len(), 1);
+        let FindOne {
+            namespace: _,
+            predicate: _,
+            options,
+            context: _,
+            span: _,
+        } = &result[0]
+        else {
+            panic!()
+        };
+
+        assert_eq!(
+            *options,
+            FindOptions {
+                projection: vec![
+                    ("name".to_string(), Include),
+                    ("age".to_string(), Include),
+                    ("_id".to_string(), Exclude),
+                ],
+                sort: vec![
+                    ("age".to_string(), Ascending),
+                    ("name".to_string(), Descending)
+                ],
+                skip: Some(Reference(
+                    "page".to_string(),
+                    ValueType::Of(BsonType::Int),
+                    Span::default()
+                )),
+                limit: Some(Integer(10, Span::default())),
+                hint: None,
+                collation: None,
+                max_time_ms: Some(Integer(5000, Span::default())),
+            }
+        )
+    }
+
+    #[test]
+    fn widens_max_time_that_overflows_an_int() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public List<Document> slow() {
+                return collection.find().maxTime(1000, TimeUnit.HOURS).into(new ArrayList<>());
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        let FindMany { options, .. } = &result[0] else {
+            panic!()
+        };
+        assert_eq!(
+            options.max_time_ms,
+            Some(Int64(3_600_000_000, Span::default()))
+        );
+    }
+
+    #[test]
+    fn records_the_source_span_of_executions_predicates_and_values() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public Document findOne(String id) {
+                return collection.find(eq("_id", id)).first();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        let FindOne {
+            predicate: Equals { value, span, .. },
+            ..
+        } = &result[0]
+        else {
+            panic!()
+        };
+
+        let text = |span: Span| &code[span.start_byte..span.end_byte];
+        assert_eq!(text(result[0].span()), r#"collection.find(eq("_id", id))"#);
+        assert_eq!(text(*span), r#"eq("_id", id)"#);
+        assert_eq!(text(value.span()), "id");
+        assert_eq!(span.start_point.row, 5);
+    }
+
+    #[test]
+    fn warns_about_unresolved_collections_and_unsupported_operators() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public Document findOne(String id, int age) {
+                collection.find(byAge("age", age)).first();
+                return collection.find(eq("_id", id)).first();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap();
+
+        assert_eq!(result.executions.len(), 1);
+        assert_eq!(
+            result.warnings,
+            vec![
+                ExtractionWarning::UnsupportedOperator {
+                    operator: "byAge".to_string(),
+                    span: Span::default()
+                },
+                ExtractionWarning::UnresolvedCollection {
+                    reference: "collection".to_string(),
+                    span: Span::default()
+                },
+            ]
+        );
+        assert_eq!(
+            result.warnings[0].to_string(),
+            "unsupported operator `byAge`".to_string()
+        );
+    }
+
+    #[test]
+    fn ignores_find_methods_of_other_receivers() {
+        let code = r#"
+        public class MyRepository {
+            private final EntityManager em;
+
+            public User findUser(Matcher matcher, Object id) {
+                if (matcher.find()) {
+                    return em.find(User.class, id);
+                }
+                return scanner.find("users");
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap();
+
+        assert!(result.executions.is_empty());
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn ignores_find_methods_of_database_receivers() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoDatabase database;
+
+            public void queries(MongoClient client) {
+                database.find(eq("a", 1));
+                var shop = client.getDatabase("shop");
+                shop.find(eq("a", 2));
+                client.getDatabase("shop").find(eq("a", 3));
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap();
+
+        assert!(result.executions.is_empty());
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn distinguishes_single_document_finds_from_cursors() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public List<Document> findAll() {
+                collection.find().first();
+                return collection.find(Document.class).into(new ArrayList<>());
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        let match_all = And {
+            predicates: vec![],
+            span: Span::default(),
+        };
+        assert_eq!(result.len(), 2);
+        assert!(matches!(&result[0], FindOne { predicate, .. } if *predicate == match_all));
+        assert!(matches!(&result[1], FindMany { predicate, .. } if *predicate == match_all));
+    }
+
+    #[test]
+    fn distinguishes_single_document_finds_through_reactive_publishers() {
+        let code = r#"
+        import com.mongodb.reactivestreams.client.MongoCollection;
+
+        public class ReactiveRepository {
+            private final MongoCollection<Document> collection;
+
+            public void queries() {
+                Mono.from(collection.find(eq("status", "A")));
+                Mono.from(collection.find().sort(ascending("name")).first());
+                Flux.from(collection.find().limit(10)).collectList();
+                Flux.from(collection.find()).next();
+                collection.find().firstOrNull();
+                collection.find().first((document, error) -> {});
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        let single: Vec<bool> = result
+            .iter()
+            .map(|execution| matches!(execution, FindOne { .. }))
+            .collect();
+        assert_eq!(single, vec![true, true, false, true, true, true]);
+        assert!(matches!(&result[2], FindMany { options, .. } if options.limit.is_some()));
+    }
+
+    #[test]
+    fn follows_find_publishers_held_in_local_variables() {
+        let code = r#"
+        import com.mongodb.reactivestreams.client.MongoCollection;
+
+        public class ReactiveRepository {
+            private final MongoCollection<Document> collection;
+
+            public void queries() {
+                FindPublisher<Document> newest = collection.find(eq("status", "A"))
+                    .sort(descending("createdAt"));
+                Mono.from(newest).subscribe();
+
+                var all = collection.find(eq("status", "B"));
+                all.limit(10);
+                Flux.from(all).subscribe();
+
+                var first = collection.find(eq("status", "C"));
+                first.first().subscribe();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        let single: Vec<bool> = result
+            .iter()
+            .map(|execution| matches!(execution, FindOne { .. }))
+            .collect();
+        assert_eq!(single, vec![true, false, true]);
+        assert!(
+            matches!(&result[0], FindOne { options, .. } if options.sort == vec![("createdAt".to_string(), Descending)])
+        );
+        assert!(
+            matches!(&result[1], FindMany { options, .. } if options.limit == Some(Integer(10, Span::default())))
+        );
+    }
+
+    #[test]
+    fn treats_next_as_a_first_result_only_on_flux_adapters() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public void queries() {
+                MongoCursor<Document> it = collection.find(eq("a", 1)).iterator();
+                while (it.hasNext()) {
+                    it.next();
+                }
+
+                var published = Flux.from(collection.find(eq("a", 2)));
+                published.next().subscribe();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        let single: Vec<bool> = result
+            .iter()
+            .map(|execution| matches!(execution, FindOne { .. }))
+            .collect();
+        assert_eq!(single, vec![false, true]);
+    }
+
+    #[test]
+    fn parse_nested_logical_filters_and_filter_variables() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public void findAdults(String name, int age) {
+                Bson filter = and(eq("name", name), or(gt("age", age), not(eq("age", 18))));
+                for (Document document : collection.find(filter)) {
+                    System.out.println(document);
+                }
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        let name = Reference(
+            "name".to_string(),
+            ValueType::Of(BsonType::String),
+            Span::default(),
+        );
+        let age = Reference(
+            "age".to_string(),
+            ValueType::Of(BsonType::Int),
+            Span::default(),
+        );
+
+        assert_eq!(result.len(), 1);
+        let FindMany { predicate, .. } = &result[0] else {
+            panic!()
+        };
+        assert_eq!(
+            *predicate,
+            And {
+                predicates: vec![
+                    Equals {
+                        field: "name".to_string(),
+                        value: name,
+                        span: Span::default()
+                    },
+                    Or {
+                        predicates: vec![
+                            GreaterThan {
+                                field: "age".to_string(),
+                                value: age,
+                                span: Span::default()
+                            },
+                            Not {
+                                predicates: vec![Equals {
+                                    field: "age".to_string(),
+                                    value: Integer(18, Span::default()),
+                                    span: Span::default()
+                                }],
+                                span: Span::default()
+                            },
+                        ],
+                        span: Span::default()
+                    },
+                ],
+                span: Span::default()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_projection_builders_and_index_hints() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public List<Document> findAll() {
+                Bson projection = fields(elemMatch("items", gt("qty", 1)), slice("tags", 1, 5),
+                    metaTextScore("score"), computed("total", "$amount"));
+                return collection.find()
+                    .projection(projection)
+                    .hint(compoundIndex(Indexes.ascending("a"), Indexes.text("b")))
+                    .into(new ArrayList<>());
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap().executions;
+
+        let FindMany { options, .. } = &result[0] else {
+            panic!()
+        };
+        assert_eq!(
+            options.projection,
+            vec![
+                (
+                    "items".to_string(),
+                    ElemMatch(GreaterThan {
+                        field: "qty".to_string(),
+                        value: Integer(1, Span::default()),
+                        span: Span::default()
+                    })
+                ),
+                (
+                    "tags".to_string(),
+                    Slice {
+                        skip: Some(Integer(1, Span::default())),
+                        limit: Integer(5, Span::default())
+                    }
+                ),
+                ("score".to_string(), Meta("textScore".to_string())),
+                (
+                    "total".to_string(),
+                    Computed(Expression::FieldPath("amount".to_string()))
+                ),
+            ]
+        );
+        assert_eq!(
+            options.hint,
+            Some(Hint::Keys(Object(
+                vec![
+                    ("a".to_string(), Integer(1, Span::default())),
+                    ("b".to_string(), String("text".to_string(), Span::default())),
+                ],
+                Span::default()
+            )))
+        );
+    }
+
+    #[test]
+    fn parse_sort_and_projection_documents() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public List<Document> findAll(Request request) {
+                collection.find()
+                    .projection(new Document("name", 1).append("_id", 0))
+                    .sort(new Document("age", -1).append("name", 1))
+                    .into(new ArrayList<>());
+                return collection.find()
+                    .projection(request.fields)
+                    .sort(new Document("age", "up"))
+                    .into(new ArrayList<>());
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap();
+
+        let FindMany { options, .. } = &result.executions[0] else {
+            panic!()
+        };
+        assert_eq!(
+            options.projection,
+            vec![("name".to_string(), Include), ("_id".to_string(), Exclude)]
+        );
+        assert_eq!(
+            options.sort,
+            vec![
+                ("age".to_string(), Descending),
+                ("name".to_string(), Ascending)
+            ]
+        );
+        let warnings: Vec<&ExtractionWarning> = result
+            .warnings
+            .iter()
+            .filter(|warning| !matches!(warning, ExtractionWarning::UnresolvedCollection { .. }))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                &ExtractionWarning::UnresolvedStage {
+                    expression: "request.fields".to_string(),
+                    span: Span::default()
+                },
+                &ExtractionWarning::UnresolvedStage {
+                    expression: "new Document(age, up)".to_string(),
+                    span: Span::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_filters_against_the_collection_document_class() {
+        let code = r#"
+        public class UserRepository {
+            private final MongoCollection<User> users;
+
+            public User byAge(String age) {
+                return users.find(and(eq("age", age), eq("nickname", "x"), eq("_id", 1))).first();
+            }
+
+            public List<User> byCity(MongoDatabase database, String city) {
+                var people = database.getCollection("users", User.class);
+                return people.find(eq("address.city", city)).into(new ArrayList<>());
+            }
+        }
+
+        public class User {
+            private ObjectId id;
+            private int age;
+            private Address address;
+        }
+
+        public class Address {
+            private String city;
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = find(tree, &code).unwrap();
+
+        assert_eq!(
+            result.warnings,
+            vec![
+                ExtractionWarning::UnresolvedCollection {
+                    reference: "users".to_string(),
+                    span: Span::default(),
+                },
+                ExtractionWarning::MismatchedFieldType {
+                    field: "age".to_string(),
+                    expected: ValueType::Of(BsonType::Int),
+                    actual: ValueType::Of(BsonType::String),
+                    span: Span::default(),
+                },
+                ExtractionWarning::UnknownField {
+                    field: "nickname".to_string(),
+                    document: "User".to_string(),
+                    span: Span::default(),
+                },
+                ExtractionWarning::MismatchedFieldType {
+                    field: "_id".to_string(),
+                    expected: ValueType::Of(BsonType::ObjectId),
+                    actual: ValueType::Of(BsonType::Int),
+                    span: Span::default(),
+                },
+            ]
+        );
+        assert_eq!(
+            result.schemas["people"].paths(),
+            vec!["_id", "age", "address", "address.city"]
+        );
+    }
+}
@@ -1 +1,109 @@
-pub mod find_one;
+use std::collections::HashMap;
+
+use dialect_interface::{Extraction, ExtractionWarning};
+use mongodb_query_language::execution::{Execution, ExecutionNamespace};
+use mongodb_query_language::filter::FilterOperator;
+use mongodb_query_language::span::Span;
+use tree_sitter::Node;
+
+use crate::symbol_index::resolve_schema;
+use crate::tree_ext::document_schema_from_class::document_schema_of_class;
+use crate::tree_ext::infer_mongodb_namespace::{
+    infer_database_from_usage, infer_document_class, infer_namespace_from_usage,
+};
+use crate::tree_ext::infer_value_type::declared_type;
+use crate::tree_ext::predicate_from_expression::predicate_from_expression;
+use crate::tree_ext::{optional_node_to_string, simple_type_name};
+
+pub mod aggregate;
+pub mod ddl;
+pub mod find;
+pub mod update;
+
+const COLLECTION_RECEIVERS: [&str; 1] = ["MongoCollection"];
+const DATABASE_RECEIVERS: [&str; 2] = ["MongoCollection", "MongoDatabase"];
+
+fn is_driver_receiver(
+    receiver: Node,
+    receivers: &[&str],
+    namespaces: &HashMap<String, ExecutionNamespace>,
+    code: &String,
+) -> bool {
+    if let Some(type_name) = declared_type(receiver, code).filter(|name| name != "var") {
+        return receivers.contains(&simple_type_name(&type_name).as_str());
+    }
+
+    if infer_database_from_usage(receiver, code).is_some() {
+        return receivers.contains(&"MongoDatabase");
+    }
+
+    let reference = optional_node_to_string(&Some(receiver), code);
+    return receiver.kind() == "method_invocation"
+        || namespaces.contains_key(&reference)
+        || infer_namespace_from_usage(receiver, code).is_some();
+}
+
+fn namespace_of(
+    collection: Node,
+    namespaces: &HashMap<String, ExecutionNamespace>,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> ExecutionNamespace {
+    let reference = optional_node_to_string(&Some(collection), code);
+    let field_name = match collection.kind() {
+        "field_access" => optional_node_to_string(&collection.child_by_field_name("field"), code),
+        _ => reference.clone(),
+    };
+    let namespace = namespaces
+        .get(&field_name)
+        .cloned()
+        .or_else(|| infer_namespace_from_usage(collection, code))
+        .unwrap_or(ExecutionNamespace::empty(reference.clone()));
+
+    if namespace.collection.is_none() {
+        warnings.push(ExtractionWarning::UnresolvedCollection {
+            reference,
+            span: Span::from(collection),
+        });
+    }
+
+    return namespace;
+}
+
+fn predicate_of(
+    filter: Option<Node>,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<FilterOperator> {
+    return match filter {
+        Some(filter) => predicate_from_expression(filter, code, warnings),
+        None => Some(FilterOperator::And {
+            predicates: vec![],
+            span: Span::default(),
+        }),
+    };
+}
+
+fn push_execution(collection: Node, execution: Execution, code: &String, result: &mut Extraction) {
+    let mut root = collection;
+    while let Some(parent) = root.parent() {
+        root = parent;
+    }
+
+    let schema = infer_document_class(collection, code).and_then(|class| {
+        document_schema_of_class(root, &class, code).or_else(|| resolve_schema(&class))
+    });
+    if let Some(schema) = schema {
+        result.warnings.extend(
+            schema
+                .violations(&execution)
+                .into_iter()
+                .map(|violation| ExtractionWarning::from_violation(violation, &schema.name)),
+        );
+        result
+            .schemas
+            .insert(execution.namespace().reference_name.clone(), schema);
+    }
+
+    result.executions.push(execution);
+}
@@ -0,0 +1,13 @@
+((method_invocation
+   object: [(identifier) (field_access)] @receiver
+   name: (identifier) @method
+   arguments: (argument_list) @argumentlist)
+  (#match? @method "^(createIndex|createIndexes|dropIndex|dropIndexes|createCollection|createView|runCommand)$"))
+
+((method_invocation
+   object: (method_invocation
+     name: (identifier) @handle) @receiver
+   name: (identifier) @method
+   arguments: (argument_list) @argumentlist)
+  (#match? @method "^(createIndex|createIndexes|dropIndex|dropIndexes|createCollection|createView|runCommand)$")
+  (#match? @handle "^(getDatabase|getCollection|withCodecRegistry|withDocumentClass|withReadPreference|withReadConcern|withWriteConcern|withTimeout)$"))
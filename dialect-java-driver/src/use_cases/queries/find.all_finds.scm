@@ -0,0 +1,13 @@
+((method_invocation
+   object: [(identifier) (field_access)] @collection
+   name: (identifier) @method
+   arguments: (argument_list) @argumentlist)
+  (#eq? @method "find"))
+
+((method_invocation
+   object: (method_invocation
+     name: (identifier) @handle) @collection
+   name: (identifier) @method
+   arguments: (argument_list) @argumentlist)
+  (#eq? @method "find")
+  (#match? @handle "^(getCollection|withCodecRegistry|withDocumentClass|withReadPreference|withReadConcern|withWriteConcern|withTimeout)$"))
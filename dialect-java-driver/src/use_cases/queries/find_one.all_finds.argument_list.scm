@@ -1,3 +0,0 @@
-(method_invocation
-  name: (identifier) @method
-  arguments: (argument_list ((string_literal) @field (expression) @value)))
\ No newline at end of file
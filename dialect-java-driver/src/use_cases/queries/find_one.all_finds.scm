@@ -1,5 +0,0 @@
-((method_invocation
-   object: (identifier) @collection
-   name: (identifier) @method
-   arguments: (argument_list) @argumentlist)
-  (#match? @method "find"))
\ No newline at end of file
@@ -0,0 +1,13 @@
+((method_invocation
+   object: [(identifier) (field_access)] @collection
+   name: (identifier) @method
+   arguments: (argument_list) @argumentlist)
+  (#match? @method "^(updateOne|updateMany|findOneAndUpdate)$"))
+
+((method_invocation
+   object: (method_invocation
+     name: (identifier) @handle) @collection
+   name: (identifier) @method
+   arguments: (argument_list) @argumentlist)
+  (#match? @method "^(updateOne|updateMany|findOneAndUpdate)$")
+  (#match? @handle "^(getCollection|withCodecRegistry|withDocumentClass|withReadPreference|withReadConcern|withWriteConcern|withTimeout)$"))
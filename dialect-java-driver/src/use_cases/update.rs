@@ -0,0 +1,330 @@
+use std::cell::RefCell;
+use std::error::Error;
+
+use tree_sitter::{Node, Tree};
+
+use dialect_interface::{Extraction, ExtractionWarning};
+use mongodb_query_language::execution::Execution::{FindOneAndUpdate, UpdateMany, UpdateOne};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::update::UpdateOptions;
+use mongodb_query_language::values::Value;
+
+use crate::tree_ext::friendly_capture::FriendlyCapture;
+use crate::tree_ext::infer_execution_context::{infer_execution_context, operation_arguments};
+use crate::tree_ext::infer_mongodb_namespace::infer_mongodb_namespace;
+use crate::tree_ext::predicate_from_expression::predicate_from_expression;
+use crate::tree_ext::update_from_expression::update_from_expression;
+use crate::tree_ext::value_from_expression::value_from_expression;
+use crate::tree_ext::{builder_calls, invocation_name, operands_from_arguments};
+use crate::use_cases::{
+    is_driver_receiver, namespace_of, predicate_of, push_execution, COLLECTION_RECEIVERS,
+};
+
+const ALL_UPDATE_METHOD_CALLS: &str = include_str!("queries/update.all_updates.scm");
+
+fn update_options(
+    options: Option<&Node>,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> UpdateOptions {
+    let mut result = UpdateOptions::default();
+    let calls = options
+        .and_then(|options| builder_calls(*options, code))
+        .unwrap_or_default();
+
+    for (name, arguments) in calls {
+        match (name.as_str(), &arguments[..]) {
+            ("upsert", [upsert]) => {
+                result.upsert = matches!(
+                    value_from_expression(*upsert, code),
+                    Value::Boolean(true, _)
+                )
+            }
+            ("arrayFilters", [_]) => {
+                result.array_filters = operands_from_arguments(&arguments, code)
+                    .into_iter()
+                    .filter_map(|filter| predicate_from_expression(filter, code, warnings))
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+
+    return result;
+}
+
+pub fn update(
+    tree: RefCell<Tree>,
+    code: &String,
+) -> Result<Extraction, Box<dyn Error + Sync + Send>> {
+    let all_queries_query =
+        tree_sitter::Query::new(tree_sitter_java::language(), ALL_UPDATE_METHOD_CALLS)?;
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let root = tree.borrow();
+    let namespaces = infer_mongodb_namespace(root.root_node(), code)?;
+    let all_matches = cursor.matches(&all_queries_query, root.root_node(), code.as_bytes());
+
+    let collection_idx = all_queries_query
+        .capture_index_for_name("collection")
+        .unwrap();
+    let arglist_idx = all_queries_query
+        .capture_index_for_name("argumentlist")
+        .unwrap();
+
+    let mut result = Extraction::default();
+
+    for each_match in all_matches {
+        let [Some(coll_node), Some(arg_list_node)] =
+            each_match.capture(vec![collection_idx, arglist_idx])[..]
+        else {
+            continue;
+        };
+
+        if !is_driver_receiver(coll_node, &COLLECTION_RECEIVERS, &namespaces, code) {
+            continue;
+        }
+
+        let update_node = arg_list_node.parent().unwrap();
+        let span = Span::from(update_node);
+        let arguments = operation_arguments(update_node, code);
+        let [filter, update, options @ ..] = &arguments[..] else {
+            continue;
+        };
+
+        let Some(predicate) = predicate_of(Some(*filter), code, &mut result.warnings) else {
+            continue;
+        };
+        let update = update_from_expression(*update, code, &mut result.warnings);
+        let options = update_options(options.first(), code, &mut result.warnings);
+        let namespace = namespace_of(coll_node, &namespaces, code, &mut result.warnings);
+        let context = infer_execution_context(update_node, code);
+
+        let execution = match invocation_name(update_node, code).as_str() {
+            "updateOne" => UpdateOne {
+                namespace,
+                predicate,
+                update,
+                options,
+                context,
+                span,
+            },
+            "updateMany" => UpdateMany {
+                namespace,
+                predicate,
+                update,
+                options,
+                context,
+                span,
+            },
+            _ => FindOneAndUpdate {
+                namespace,
+                predicate,
+                update,
+                options,
+                context,
+                span,
+            },
+        };
+        push_execution(coll_node, execution, code, &mut result);
+    }
+
+    return Ok(result);
+}
+
+#[cfg(test)]
+mod test {
+    use mongodb_query_language::aggregation::Expression::Literal;
+    use mongodb_query_language::aggregation::Stage;
+    use mongodb_query_language::execution::Execution::{UpdateMany, UpdateOne};
+    use mongodb_query_language::filter::FilterOperator::{Equals, GreaterThan};
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::update::{
+        ArrayUpdateValue, PullCondition, Update, UpdateOperator, UpdateOptions,
+    };
+    use mongodb_query_language::values::Value::{Boolean, Integer, String};
+
+    use mongodb_query_language::serialization::{FromBson, ToBson};
+
+    use crate::use_cases::update::update;
+    use crate::Java;
+
+    #[test]
+    fn parse_updates_builders() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public void checkout(String id) {
+                collection.updateOne(eq("_id", id),
+                    Updates.combine(set("status", "paid"), inc("version", 1),
+                        pushEach("events", List.of("paid"), new PushOptions().slice(-10)),
+                        pullByFilter(gt("attempts", 3)), currentDate("updatedAt")),
+                    new UpdateOptions().upsert(true));
+                collection.updateMany(eq("status", "paid"),
+                    List.of(Aggregates.set(new Field<>("archived", true))));
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = update(tree, &code).unwrap().executions;
+
+        assert_eq!(result.len(), 2);
+        let UpdateOne {
+            update, options, ..
+        } = &result[0]
+        else {
+            panic!()
+        };
+        assert_eq!(
+            *update,
+            Update::Operators(vec![
+                UpdateOperator::Set {
+                    field: "status".to_string(),
+                    value: String("paid".to_string(), Span::default())
+                },
+                UpdateOperator::Inc {
+                    field: "version".to_string(),
+                    amount: Integer(1, Span::default())
+                },
+                UpdateOperator::Push {
+                    field: "events".to_string(),
+                    values: ArrayUpdateValue::Each(vec![String(
+                        "paid".to_string(),
+                        Span::default()
+                    )]),
+                    position: None,
+                    slice: Some(Box::new(Integer(-10, Span::default()))),
+                    sort: None
+                },
+                UpdateOperator::Pull {
+                    field: "attempts".to_string(),
+                    condition: PullCondition::Predicate(Box::new(GreaterThan {
+                        field: "".to_string(),
+                        value: Integer(3, Span::default()),
+                        span: Span::default()
+                    }))
+                },
+                UpdateOperator::CurrentDate {
+                    field: "updatedAt".to_string(),
+                    as_timestamp: false
+                },
+            ])
+        );
+        assert_eq!(
+            *options,
+            UpdateOptions {
+                upsert: true,
+                array_filters: vec![]
+            }
+        );
+
+        let UpdateMany {
+            predicate, update, ..
+        } = &result[1]
+        else {
+            panic!()
+        };
+        assert_eq!(
+            *predicate,
+            Equals {
+                field: "status".to_string(),
+                value: String("paid".to_string(), Span::default()),
+                span: Span::default()
+            }
+        );
+        assert_eq!(
+            *update,
+            Update::Pipeline(vec![Stage::Set {
+                fields: vec![(
+                    "archived".to_string(),
+                    Literal(Boolean(true, Span::default()))
+                )]
+            }])
+        );
+    }
+
+    #[test]
+    fn round_trips_pull_by_filter_updates() {
+        let code = r#"
+        public class MyRepository {
+            private final MongoCollection<Document> collection;
+
+            public void prune(String id) {
+                collection.updateOne(eq("_id", id), Updates.pullByFilter(and(
+                    eq("fruits", "apple"), gte("votes", 6), in("tags", "a", "b"),
+                    regex("names", "^x"), not(gt("scores", 3)), exists("flags"),
+                    elemMatch("results", eq("score", 8)), eq("items.qty", 0))));
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = update(tree, &code).unwrap().executions;
+        let UpdateOne { update, .. } = &result[0] else {
+            panic!()
+        };
+
+        assert_eq!(
+            update.to_bson().to_string(),
+            concat!(
+                r#"{ "$pull": { "fruits": "apple", "votes": { "$gte": 6 }, "#,
+                r#""tags": { "$in": ["a", "b"] }, "names": { "$regex": "^x" }, "#,
+                r#""scores": { "$not": { "$gt": 3 } }, "flags": { "$exists": true }, "#,
+                r#""results": { "$elemMatch": { "score": 8 } }, "items.qty": 0 } }"#
+            )
+        );
+        assert_eq!(Update::from_bson(&update.to_bson()).unwrap(), *update);
+    }
+
+    #[test]
+    fn parse_updates_of_reactive_publishers() {
+        let code = r#"
+        import com.mongodb.reactivestreams.client.MongoCollection;
+
+        public class ReactiveRepository {
+            private final MongoCollection<Document> collection;
+
+            public Mono<UpdateResult> pay(ClientSession session, String id) {
+                Flux.from(collection.updateMany(eq("status", "new"), set("status", "open")))
+                    .subscribe();
+                return Mono.from(collection.updateOne(session, eq("_id", id), set("status", "paid"),
+                    new UpdateOptions().upsert(true)));
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = update(tree, &code).unwrap().executions;
+
+        assert_eq!(result.len(), 2);
+        assert!(matches!(&result[0], UpdateMany { .. }));
+        let UpdateOne {
+            predicate,
+            update,
+            options,
+            context,
+            ..
+        } = &result[1]
+        else {
+            panic!()
+        };
+        assert_eq!(predicate.field(), Some(&"_id".to_string()));
+        assert_eq!(
+            *update,
+            Update::Operators(vec![UpdateOperator::Set {
+                field: "status".to_string(),
+                value: String("paid".to_string(), Span::default())
+            }])
+        );
+        assert!(options.upsert);
+        assert!(context.session.is_some());
+    }
+}
@@ -0,0 +1,38 @@
+use crate::use_cases::repository::repository;
+use crate::use_cases::template::template;
+use dialect_interface::{DialectExtractor, Extraction, FileResource};
+use dialect_java_driver::symbol_index::{with_symbols, SymbolIndex};
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
+
+mod tree_ext;
+mod use_cases;
+
+pub struct SpringData {}
+
+impl SpringData {
+    pub fn extractor() -> Rc<dyn DialectExtractor> {
+        return SpringData::extractor_with_symbols(SymbolIndex::shared());
+    }
+
+    pub fn extractor_with_symbols(symbols: Rc<RefCell<SymbolIndex>>) -> Rc<dyn DialectExtractor> {
+        return Rc::new(SpringDataExtractor { symbols });
+    }
+}
+
+pub struct SpringDataExtractor {
+    symbols: Rc<RefCell<SymbolIndex>>,
+}
+
+impl DialectExtractor for SpringDataExtractor {
+    fn extract(&self, file: &FileResource) -> Result<Extraction, Box<dyn Error + Sync + Send>> {
+        return with_symbols(&self.symbols, || {
+            let mut result = Extraction::default();
+            result.append(&mut repository(file.tree(), file.source())?);
+            result.append(&mut template(file.tree(), file.source())?);
+
+            Ok(result)
+        });
+    }
+}
@@ -0,0 +1,159 @@
+use dialect_interface::ExtractionWarning;
+use dialect_java_driver::tree_ext::value_from_expression::value_from_expression;
+use dialect_java_driver::tree_ext::{
+    field_from_expression, fields_from_arguments, invocation_arguments, invocation_name,
+    optional_node_to_string, resolved_expression,
+};
+use mongodb_query_language::aggregation::{Expression, ProjectionField, Stage};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::Value;
+use tree_sitter::Node;
+
+use crate::tree_ext::chain_calls;
+use crate::tree_ext::criteria_from_expression::predicate_from_criteria;
+use crate::tree_ext::query_from_expression::sort_from_spring_expression;
+
+const ACCUMULATORS: [&str; 8] = [
+    "sum", "avg", "min", "max", "first", "last", "push", "addToSet",
+];
+
+fn group_id(fields: Vec<String>, span: Span) -> Expression {
+    return match &fields[..] {
+        [] => Expression::Literal(Value::Null(span)),
+        [field] => Expression::FieldPath(field.clone()),
+        _ => Expression::Object(
+            fields
+                .iter()
+                .map(|field| (field.clone(), Expression::FieldPath(field.clone())))
+                .collect(),
+        ),
+    };
+}
+
+fn group_stage(calls: &[(String, Vec<Node>)], code: &String, span: Span) -> Option<Stage> {
+    let ((_, fields), operations) = calls.split_first()?;
+    let mut accumulators = vec![];
+    let mut pending = None;
+
+    for (name, arguments) in operations {
+        match (name.as_str(), &arguments[..]) {
+            ("count", []) => {
+                pending = Some(Expression::Operator {
+                    name: "$sum".to_string(),
+                    arguments: vec![Expression::Literal(Value::Integer(1, span))],
+                })
+            }
+            (accumulator, [field]) if ACCUMULATORS.contains(&accumulator) => {
+                pending = Some(Expression::Operator {
+                    name: format!("${}", accumulator),
+                    arguments: vec![Expression::FieldPath(field_from_expression(*field, code)?)],
+                })
+            }
+            ("as", [alias]) => {
+                accumulators.push((field_from_expression(*alias, code)?, pending.take()?))
+            }
+            _ => return None,
+        }
+    }
+
+    return Some(Stage::Group {
+        id: group_id(fields_from_arguments(fields, code), span),
+        accumulators,
+    });
+}
+
+fn stage_from_operation(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<Stage> {
+    let span = Span::from(node);
+    let calls = chain_calls(node, code);
+    let (name, arguments) = calls.first()?;
+    let field = arguments
+        .first()
+        .and_then(|field| field_from_expression(*field, code));
+
+    return match (name.as_str(), field, &arguments[..], &calls[1..]) {
+        ("match", _, [criteria], []) => Some(Stage::Match {
+            predicate: predicate_from_criteria(*criteria, code, warnings)?,
+        }),
+        ("sort", _, [sort], []) => Some(Stage::Sort {
+            fields: sort_from_spring_expression(*sort, code),
+        }),
+        ("limit", _, [limit], []) => Some(Stage::Limit {
+            limit: value_from_expression(*limit, code),
+        }),
+        ("skip", _, [skip], []) => Some(Stage::Skip {
+            skip: value_from_expression(*skip, code),
+        }),
+        ("sample", _, [size], []) => Some(Stage::Sample {
+            size: value_from_expression(*size, code),
+        }),
+        ("unwind", Some(path), [_], []) => Some(Stage::Unwind {
+            path: path.trim_start_matches('$').to_string(),
+            include_array_index: None,
+            preserve_null_and_empty_arrays: false,
+        }),
+        ("unwind", Some(path), [_, preserve], []) => Some(Stage::Unwind {
+            path: path.trim_start_matches('$').to_string(),
+            include_array_index: None,
+            preserve_null_and_empty_arrays: preserve.kind() == "true",
+        }),
+        ("project", _, fields, []) => Some(Stage::Project {
+            fields: fields_from_arguments(fields, code)
+                .into_iter()
+                .map(|field| (field, ProjectionField::Include))
+                .collect(),
+        }),
+        ("count", _, [], [(alias, as_arguments)]) if alias == "as" => Some(Stage::Count {
+            field: field_from_expression(*as_arguments.first()?, code)?,
+        }),
+        ("group", _, _, _) => group_stage(&calls, code, span),
+        ("lookup", Some(from), [_, local_field, foreign_field, as_field], []) => {
+            Some(Stage::Lookup {
+                from,
+                local_field: field_from_expression(*local_field, code),
+                foreign_field: field_from_expression(*foreign_field, code),
+                let_variables: vec![],
+                pipeline: vec![],
+                as_field: field_from_expression(*as_field, code)?,
+            })
+        }
+        ("out", Some(collection), [_], []) => Some(Stage::Out {
+            database: None,
+            collection,
+        }),
+        _ => None,
+    };
+}
+
+pub fn pipeline_from_aggregation(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Vec<Stage> {
+    let node = resolved_expression(node, code);
+    if node.kind() != "method_invocation" || invocation_name(node, code) != "newAggregation" {
+        warnings.push(ExtractionWarning::UnresolvedStage {
+            expression: optional_node_to_string(&Some(node), code),
+            span: Span::from(node),
+        });
+        return vec![];
+    }
+
+    return invocation_arguments(node)
+        .into_iter()
+        .filter(|operation| operation.kind() != "class_literal")
+        .filter_map(|operation| {
+            let stage = stage_from_operation(operation, code, warnings);
+            if stage.is_none() {
+                warnings.push(ExtractionWarning::UnresolvedStage {
+                    expression: optional_node_to_string(&Some(operation), code),
+                    span: Span::from(operation),
+                });
+            }
+            stage
+        })
+        .collect();
+}
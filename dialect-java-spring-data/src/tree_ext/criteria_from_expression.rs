@@ -0,0 +1,221 @@
+use dialect_interface::ExtractionWarning;
+use dialect_java_driver::tree_ext::value_from_expression::{
+    value_from_expression, values_from_arguments,
+};
+use dialect_java_driver::tree_ext::{
+    field_from_expression, operands_from_arguments, optional_node_to_string,
+};
+use mongodb_query_language::filter::FilterOperator;
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::Value;
+use tree_sitter::Node;
+
+use crate::tree_ext::chain_calls;
+
+fn nested(
+    arguments: &[Node],
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<Vec<FilterOperator>> {
+    return operands_from_arguments(arguments, code)
+        .into_iter()
+        .map(|argument| predicate_from_criteria(argument, code, warnings))
+        .collect::<Vec<Option<FilterOperator>>>()
+        .into_iter()
+        .collect();
+}
+
+fn field_predicate(
+    name: &str,
+    field: String,
+    arguments: &[Node],
+    code: &String,
+    span: Span,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<FilterOperator> {
+    let value = |node: &Node| value_from_expression(*node, code);
+
+    return match (name, arguments) {
+        ("is", [v]) => Some(FilterOperator::Equals {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("isNull", []) => Some(FilterOperator::Equals {
+            field,
+            value: Value::Null(span),
+            span,
+        }),
+        ("ne", [v]) => Some(FilterOperator::NotEquals {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("gt", [v]) => Some(FilterOperator::GreaterThan {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("gte", [v]) => Some(FilterOperator::GreaterThanOrEquals {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("lt", [v]) => Some(FilterOperator::LessThan {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("lte", [v]) => Some(FilterOperator::LessThanOrEquals {
+            field,
+            value: value(v),
+            span,
+        }),
+        ("in", values) => Some(FilterOperator::In {
+            field,
+            values: values_from_arguments(values, code),
+            span,
+        }),
+        ("nin", values) => Some(FilterOperator::NotIn {
+            field,
+            values: values_from_arguments(values, code),
+            span,
+        }),
+        ("all", values) => Some(FilterOperator::All {
+            field,
+            values: values_from_arguments(values, code),
+            span,
+        }),
+        ("exists", [exists]) => Some(FilterOperator::Exists {
+            field,
+            exists: exists.kind() != "false",
+            span,
+        }),
+        ("regex", [pattern]) => Some(FilterOperator::Regex {
+            field,
+            pattern: value(pattern),
+            options: None,
+            span,
+        }),
+        ("regex", [pattern, options]) => Some(FilterOperator::Regex {
+            field,
+            pattern: value(pattern),
+            options: Some(optional_node_to_string(&Some(*options), code)),
+            span,
+        }),
+        ("size", [size]) => Some(FilterOperator::Size {
+            field,
+            size: value(size),
+            span,
+        }),
+        ("mod", [divisor, remainder]) => Some(FilterOperator::Mod {
+            field,
+            divisor: value(divisor),
+            remainder: value(remainder),
+            span,
+        }),
+        ("elemMatch", [criteria]) => Some(FilterOperator::ElemMatch {
+            field,
+            predicates: match predicate_from_criteria(*criteria, code, warnings)? {
+                FilterOperator::And { predicates, .. } => predicates,
+                predicate => vec![predicate],
+            },
+            span,
+        }),
+        _ => None,
+    };
+}
+
+pub fn predicate_from_criteria(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<FilterOperator> {
+    let span = Span::from(node);
+    let calls = chain_calls(node, code);
+    if calls.is_empty() {
+        warnings.push(ExtractionWarning::UnresolvedFilter {
+            expression: optional_node_to_string(&Some(node), code),
+            span,
+        });
+        return None;
+    }
+
+    let unresolved = |warnings: &mut Vec<ExtractionWarning>| {
+        warnings.push(ExtractionWarning::UnresolvedFilter {
+            expression: optional_node_to_string(&Some(node), code),
+            span,
+        });
+    };
+
+    let mut field = None;
+    let mut awaiting_operator = false;
+    let mut negated = false;
+    let mut predicates = vec![];
+    for (name, arguments) in calls {
+        let predicate = match (name.as_str(), &arguments[..]) {
+            ("where" | "and" | "new Criteria", [key]) => {
+                if awaiting_operator {
+                    unresolved(warnings);
+                    return None;
+                }
+                field = field_from_expression(*key, code);
+                awaiting_operator = true;
+                continue;
+            }
+            ("new Criteria", []) => continue,
+            ("not", []) => {
+                negated = true;
+                continue;
+            }
+            ("orOperator", _) => FilterOperator::Or {
+                predicates: nested(&arguments, code, warnings)?,
+                span,
+            },
+            ("andOperator", _) => FilterOperator::And {
+                predicates: nested(&arguments, code, warnings)?,
+                span,
+            },
+            ("norOperator", _) => FilterOperator::Nor {
+                predicates: nested(&arguments, code, warnings)?,
+                span,
+            },
+            _ => {
+                let predicate = field.clone().and_then(|field| {
+                    field_predicate(&name, field, &arguments, code, span, warnings)
+                });
+                match predicate {
+                    Some(predicate) => predicate,
+                    None => {
+                        warnings.push(ExtractionWarning::UnsupportedOperator {
+                            operator: name,
+                            span,
+                        });
+                        return None;
+                    }
+                }
+            }
+        };
+
+        predicates.push(match negated {
+            true => FilterOperator::Not {
+                predicates: vec![predicate],
+                span,
+            },
+            false => predicate,
+        });
+        negated = false;
+        awaiting_operator = false;
+    }
+
+    if awaiting_operator {
+        unresolved(warnings);
+        return None;
+    }
+
+    if predicates.len() == 1 {
+        return predicates.pop();
+    }
+
+    return Some(FilterOperator::And { predicates, span });
+}
@@ -0,0 +1,802 @@
+use mongodb_query_language::aggregation::SortDirection;
+use mongodb_query_language::filter::{FilterOperator, GeoShape};
+use mongodb_query_language::serialization::reference_placeholder;
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::Value;
+use regex::Regex;
+
+use crate::tree_ext::uncapitalize;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Subject {
+    Find,
+    Count,
+    Exists,
+    Delete,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Keyword {
+    Equals,
+    Not,
+    GreaterThan,
+    GreaterThanEqual,
+    LessThan,
+    LessThanEqual,
+    Between,
+    Null,
+    NotNull,
+    In,
+    NotIn,
+    Like,
+    NotLike,
+    StartingWith,
+    EndingWith,
+    Containing,
+    NotContaining,
+    Regex,
+    Exists,
+    True,
+    False,
+    Near,
+    Within,
+}
+
+const KEYWORDS: [(&str, Keyword); 52] = [
+    ("IsNotNull", Keyword::NotNull),
+    ("NotNull", Keyword::NotNull),
+    ("IsNull", Keyword::Null),
+    ("Null", Keyword::Null),
+    ("IsNotIn", Keyword::NotIn),
+    ("NotIn", Keyword::NotIn),
+    ("IsIn", Keyword::In),
+    ("In", Keyword::In),
+    ("IsNotLike", Keyword::NotLike),
+    ("NotLike", Keyword::NotLike),
+    ("IsLike", Keyword::Like),
+    ("Like", Keyword::Like),
+    ("IsStartingWith", Keyword::StartingWith),
+    ("StartingWith", Keyword::StartingWith),
+    ("StartsWith", Keyword::StartingWith),
+    ("IsEndingWith", Keyword::EndingWith),
+    ("EndingWith", Keyword::EndingWith),
+    ("EndsWith", Keyword::EndingWith),
+    ("IsNotContaining", Keyword::NotContaining),
+    ("NotContaining", Keyword::NotContaining),
+    ("NotContains", Keyword::NotContaining),
+    ("IsContaining", Keyword::Containing),
+    ("Containing", Keyword::Containing),
+    ("Contains", Keyword::Containing),
+    ("IsBetween", Keyword::Between),
+    ("Between", Keyword::Between),
+    ("IsGreaterThanEqual", Keyword::GreaterThanEqual),
+    ("GreaterThanEqual", Keyword::GreaterThanEqual),
+    ("IsGreaterThan", Keyword::GreaterThan),
+    ("GreaterThan", Keyword::GreaterThan),
+    ("IsLessThanEqual", Keyword::LessThanEqual),
+    ("LessThanEqual", Keyword::LessThanEqual),
+    ("IsLessThan", Keyword::LessThan),
+    ("LessThan", Keyword::LessThan),
+    ("IsAfter", Keyword::GreaterThan),
+    ("After", Keyword::GreaterThan),
+    ("IsBefore", Keyword::LessThan),
+    ("Before", Keyword::LessThan),
+    ("IsNear", Keyword::Near),
+    ("Near", Keyword::Near),
+    ("IsWithin", Keyword::Within),
+    ("Within", Keyword::Within),
+    ("MatchesRegex", Keyword::Regex),
+    ("Matches", Keyword::Regex),
+    ("Regex", Keyword::Regex),
+    ("Exists", Keyword::Exists),
+    ("IsTrue", Keyword::True),
+    ("True", Keyword::True),
+    ("IsFalse", Keyword::False),
+    ("False", Keyword::False),
+    ("IsNot", Keyword::Not),
+    ("Not", Keyword::Not),
+];
+
+impl Keyword {
+    fn arity(&self) -> usize {
+        return match self {
+            Keyword::Between => 2,
+            Keyword::Null | Keyword::NotNull | Keyword::True | Keyword::False => 0,
+            _ => 1,
+        };
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Part {
+    pub property: String,
+    pub keyword: Keyword,
+    pub ignore_case: bool,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct DerivedQuery {
+    pub subject: Subject,
+    pub limit: Option<i32>,
+    pub alternatives: Vec<Vec<Part>>,
+    pub sort: Vec<(String, SortDirection)>,
+}
+
+fn split_on_keyword<'a>(text: &'a str, keyword: &str) -> Vec<&'a str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    for (index, _) in text.match_indices(keyword) {
+        let next = text[index + keyword.len()..].chars().next();
+        if index > start && next.map(char::is_uppercase).unwrap_or(false) {
+            parts.push(&text[start..index]);
+            start = index + keyword.len();
+        }
+    }
+
+    parts.push(&text[start..]);
+    return parts;
+}
+
+fn property_path(property: &str) -> String {
+    return property
+        .split('_')
+        .map(uncapitalize)
+        .collect::<Vec<String>>()
+        .join(".");
+}
+
+fn part(text: &str, ignore_all_case: bool) -> Option<Part> {
+    let (text, ignore_case) = match text
+        .strip_suffix("IgnoreCase")
+        .or_else(|| text.strip_suffix("IgnoringCase"))
+    {
+        Some(stripped) => (stripped, true),
+        None => (text, ignore_all_case),
+    };
+
+    let (property, keyword) = KEYWORDS
+        .iter()
+        .filter(|(suffix, _)| text.len() > suffix.len() && text.ends_with(suffix))
+        .max_by_key(|(suffix, _)| suffix.len())
+        .map(|(suffix, keyword)| (&text[..text.len() - suffix.len()], *keyword))
+        .unwrap_or((
+            text.strip_suffix("Is")
+                .or_else(|| text.strip_suffix("Equals"))
+                .unwrap_or(text),
+            Keyword::Equals,
+        ));
+
+    if property.is_empty() {
+        return None;
+    }
+
+    return Some(Part {
+        property: property_path(property),
+        keyword,
+        ignore_case,
+    });
+}
+
+fn sort(order_by: &str) -> Vec<(String, SortDirection)> {
+    let mut result = vec![];
+    let mut remaining = order_by;
+    while !remaining.is_empty() {
+        let boundary = ["Asc", "Desc"]
+            .iter()
+            .flat_map(|direction| {
+                remaining.match_indices(direction).filter(|(index, _)| {
+                    let next = remaining[index + direction.len()..].chars().next();
+                    *index > 0 && next.map(char::is_uppercase).unwrap_or(true)
+                })
+            })
+            .min_by_key(|(index, _)| *index);
+
+        let Some((index, direction)) = boundary else {
+            result.push((property_path(remaining), SortDirection::Ascending));
+            break;
+        };
+
+        let sort_direction = match direction {
+            "Desc" => SortDirection::Descending,
+            _ => SortDirection::Ascending,
+        };
+        result.push((property_path(&remaining[..index]), sort_direction));
+        remaining = &remaining[index + direction.len()..];
+    }
+
+    return result;
+}
+
+pub fn parse_derived_query(method_name: &str) -> Option<DerivedQuery> {
+    let prefix = Regex::new(
+        "^(find|read|get|query|search|stream|count|exists|delete|remove)(.*?)By([A-Z].*)$",
+    )
+    .ok()?;
+    let captures = prefix.captures(method_name)?;
+
+    let subject = match &captures[1] {
+        "count" => Subject::Count,
+        "exists" => Subject::Exists,
+        "delete" | "remove" => Subject::Delete,
+        _ => Subject::Find,
+    };
+
+    let limit = Regex::new(r"(First|Top)(\d*)")
+        .ok()?
+        .captures(&captures[2])
+        .map(|limit| limit[2].parse::<i32>().unwrap_or(1));
+
+    let (criteria, order_by) = match split_on_keyword(&captures[3], "OrderBy")[..] {
+        _ if captures[3].starts_with("OrderBy") => ("", Some(&captures[3]["OrderBy".len()..])),
+        [criteria, order_by] => (criteria, Some(order_by)),
+        [criteria] => (criteria, None),
+        _ => return None,
+    };
+    let (criteria, ignore_all_case) = match criteria
+        .strip_suffix("AllIgnoreCase")
+        .or_else(|| criteria.strip_suffix("AllIgnoringCase"))
+    {
+        Some(stripped) => (stripped, true),
+        None => (criteria, false),
+    };
+
+    let alternatives = split_on_keyword(criteria, "Or")
+        .into_iter()
+        .filter(|alternative| !alternative.is_empty())
+        .map(|alternative| {
+            split_on_keyword(alternative, "And")
+                .into_iter()
+                .map(|text| part(text, ignore_all_case))
+                .collect::<Option<Vec<Part>>>()
+        })
+        .collect::<Option<Vec<Vec<Part>>>>()?;
+
+    return Some(DerivedQuery {
+        subject,
+        limit,
+        alternatives,
+        sort: order_by.map(sort).unwrap_or_default(),
+    });
+}
+
+fn pattern_of(value: Value, prefix: &str, suffix: &str) -> Value {
+    let span = value.span();
+    let pattern = match value {
+        Value::String(text, _) => regex::escape(&text),
+        Value::Reference(name, value_type, _) if !prefix.is_empty() || !suffix.is_empty() => {
+            reference_placeholder(&name, &value_type)
+        }
+        value => return value,
+    };
+
+    return Value::String(format!("{}{}{}", prefix, pattern, suffix), span);
+}
+
+fn elements_of(value: Value) -> Vec<Value> {
+    return match value {
+        Value::Array(elements, _) => elements,
+        value => vec![value],
+    };
+}
+
+fn predicates_of_part(
+    part: &Part,
+    field: String,
+    values: Vec<Value>,
+    span: Span,
+) -> Option<Vec<FilterOperator>> {
+    let case_options = part.ignore_case.then(|| "i".to_string());
+    let regex = |field: String, pattern: Value| FilterOperator::Regex {
+        field,
+        pattern,
+        options: case_options.clone(),
+        span,
+    };
+
+    let mut values = values.into_iter();
+    let mut value = || values.next().unwrap_or(Value::Null(span));
+
+    let predicate = match part.keyword {
+        Keyword::Equals if part.ignore_case => regex(field, value()),
+        Keyword::Equals => FilterOperator::Equals {
+            field,
+            value: value(),
+            span,
+        },
+        Keyword::Not => FilterOperator::NotEquals {
+            field,
+            value: value(),
+            span,
+        },
+        Keyword::GreaterThan => FilterOperator::GreaterThan {
+            field,
+            value: value(),
+            span,
+        },
+        Keyword::GreaterThanEqual => FilterOperator::GreaterThanOrEquals {
+            field,
+            value: value(),
+            span,
+        },
+        Keyword::LessThan => FilterOperator::LessThan {
+            field,
+            value: value(),
+            span,
+        },
+        Keyword::LessThanEqual => FilterOperator::LessThanOrEquals {
+            field,
+            value: value(),
+            span,
+        },
+        Keyword::Between => {
+            return Some(vec![
+                FilterOperator::GreaterThan {
+                    field: field.clone(),
+                    value: value(),
+                    span,
+                },
+                FilterOperator::LessThan {
+                    field,
+                    value: value(),
+                    span,
+                },
+            ])
+        }
+        Keyword::Null => FilterOperator::Equals {
+            field,
+            value: Value::Null(span),
+            span,
+        },
+        Keyword::NotNull => FilterOperator::NotEquals {
+            field,
+            value: Value::Null(span),
+            span,
+        },
+        Keyword::In => FilterOperator::In {
+            field,
+            values: elements_of(value()),
+            span,
+        },
+        Keyword::NotIn => FilterOperator::NotIn {
+            field,
+            values: elements_of(value()),
+            span,
+        },
+        Keyword::Like | Keyword::Regex => regex(field, value()),
+        Keyword::NotLike => FilterOperator::Not {
+            predicates: vec![regex(field, value())],
+            span,
+        },
+        Keyword::StartingWith => regex(field, pattern_of(value(), "^", "")),
+        Keyword::EndingWith => regex(field, pattern_of(value(), "", "$")),
+        Keyword::Containing => regex(field, pattern_of(value(), "", "")),
+        Keyword::NotContaining => FilterOperator::Not {
+            predicates: vec![regex(field, pattern_of(value(), "", ""))],
+            span,
+        },
+        Keyword::Exists => match value() {
+            Value::Boolean(exists, _) => FilterOperator::Exists {
+                field,
+                exists,
+                span,
+            },
+            _ => return None,
+        },
+        Keyword::True | Keyword::False => FilterOperator::Equals {
+            field,
+            value: Value::Boolean(part.keyword == Keyword::True, span),
+            span,
+        },
+        Keyword::Near => FilterOperator::Near {
+            field,
+            geometry: value(),
+            max_distance: None,
+            min_distance: None,
+            span,
+        },
+        Keyword::Within => FilterOperator::GeoWithin {
+            field,
+            shape: GeoShape::Geometry(value()),
+            span,
+        },
+    };
+
+    return Some(vec![predicate]);
+}
+
+fn unwrap_single(mut predicates: Vec<FilterOperator>, span: Span) -> FilterOperator {
+    if predicates.len() == 1 {
+        return predicates.remove(0);
+    }
+
+    return FilterOperator::And { predicates, span };
+}
+
+pub fn predicate_from_derived_query(
+    query: &DerivedQuery,
+    parameters: &[Value],
+    field_of: &dyn Fn(&str) -> String,
+    span: Span,
+) -> Option<FilterOperator> {
+    let mut parameters = parameters.iter();
+    let mut alternatives = vec![];
+
+    for alternative in &query.alternatives {
+        let mut predicates = vec![];
+        for part in alternative {
+            let values = (0..part.keyword.arity())
+                .map(|_| parameters.next().cloned())
+                .collect::<Option<Vec<Value>>>()?;
+            predicates.append(&mut predicates_of_part(
+                part,
+                field_of(&part.property),
+                values,
+                span,
+            )?);
+        }
+
+        alternatives.push(unwrap_single(predicates, span));
+    }
+
+    if alternatives.len() <= 1 {
+        return Some(alternatives.pop().unwrap_or(FilterOperator::And {
+            predicates: vec![],
+            span,
+        }));
+    }
+
+    return Some(FilterOperator::Or {
+        predicates: alternatives,
+        span,
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use mongodb_query_language::aggregation::SortDirection::{Ascending, Descending};
+    use mongodb_query_language::filter::FilterOperator::{self, *};
+    use mongodb_query_language::filter::GeoShape;
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::values::{BsonType, Value, ValueType};
+
+    use crate::tree_ext::derived_query::{
+        parse_derived_query, predicate_from_derived_query, DerivedQuery, Keyword, Part, Subject,
+    };
+
+    fn reference(name: &str, bson_type: BsonType) -> Value {
+        return Value::Reference(name.to_string(), ValueType::Of(bson_type), Span::default());
+    }
+
+    fn string(value: &str) -> Value {
+        return Value::String(value.to_string(), Span::default());
+    }
+
+    fn predicate_of(method_name: &str, parameters: &[Value]) -> Option<FilterOperator> {
+        let query = parse_derived_query(method_name)?;
+        return predicate_from_derived_query(
+            &query,
+            parameters,
+            &|property| property.to_string(),
+            Span::default(),
+        );
+    }
+
+    fn regex(pattern: Value) -> FilterOperator {
+        return Regex {
+            field: "name".to_string(),
+            pattern,
+            options: None,
+            span: Span::default(),
+        };
+    }
+
+    fn part(property: &str, keyword: Keyword, ignore_case: bool) -> Part {
+        return Part {
+            property: property.to_string(),
+            keyword,
+            ignore_case,
+        };
+    }
+
+    #[test]
+    fn parses_subjects_criteria_and_ordering_from_method_names() {
+        assert_eq!(
+            parse_derived_query(
+                "findTop10ByAgeGreaterThanAndStatusOrAddress_CityInOrderByNameAscAgeDesc"
+            ),
+            Some(DerivedQuery {
+                subject: Subject::Find,
+                limit: Some(10),
+                alternatives: vec![
+                    vec![
+                        part("age", Keyword::GreaterThan, false),
+                        part("status", Keyword::Equals, false)
+                    ],
+                    vec![part("address.city", Keyword::In, false)],
+                ],
+                sort: vec![
+                    ("name".to_string(), Ascending),
+                    ("age".to_string(), Descending)
+                ],
+            })
+        );
+
+        assert_eq!(
+            parse_derived_query("countByOrderNumberAndLastnameStartingWithAllIgnoreCase"),
+            Some(DerivedQuery {
+                subject: Subject::Count,
+                limit: None,
+                alternatives: vec![vec![
+                    part("orderNumber", Keyword::Equals, true),
+                    part("lastname", Keyword::StartingWith, true)
+                ]],
+                sort: vec![],
+            })
+        );
+
+        assert_eq!(parse_derived_query("findAll"), None);
+        assert_eq!(parse_derived_query("saveAll"), None);
+    }
+
+    #[test]
+    fn maps_comparison_keywords_to_filter_operators() {
+        let age = || reference("age", BsonType::Int);
+        let field = || "age".to_string();
+        let span = Span::default();
+
+        let cases = [
+            (
+                "findByAge",
+                Equals {
+                    field: field(),
+                    value: age(),
+                    span,
+                },
+            ),
+            (
+                "findByAgeIsNot",
+                NotEquals {
+                    field: field(),
+                    value: age(),
+                    span,
+                },
+            ),
+            (
+                "findByAgeGreaterThan",
+                GreaterThan {
+                    field: field(),
+                    value: age(),
+                    span,
+                },
+            ),
+            (
+                "findByAgeGreaterThanEqual",
+                GreaterThanOrEquals {
+                    field: field(),
+                    value: age(),
+                    span,
+                },
+            ),
+            (
+                "findByAgeLessThan",
+                LessThan {
+                    field: field(),
+                    value: age(),
+                    span,
+                },
+            ),
+            (
+                "findByAgeLessThanEqual",
+                LessThanOrEquals {
+                    field: field(),
+                    value: age(),
+                    span,
+                },
+            ),
+            (
+                "findByAgeAfter",
+                GreaterThan {
+                    field: field(),
+                    value: age(),
+                    span,
+                },
+            ),
+            (
+                "findByAgeBefore",
+                LessThan {
+                    field: field(),
+                    value: age(),
+                    span,
+                },
+            ),
+            (
+                "findByAgeIsNull",
+                Equals {
+                    field: field(),
+                    value: Value::Null(span),
+                    span,
+                },
+            ),
+            (
+                "findByAgeIsNotNull",
+                NotEquals {
+                    field: field(),
+                    value: Value::Null(span),
+                    span,
+                },
+            ),
+            (
+                "findByAgeTrue",
+                Equals {
+                    field: field(),
+                    value: Value::Boolean(true, span),
+                    span,
+                },
+            ),
+            (
+                "findByAgeFalse",
+                Equals {
+                    field: field(),
+                    value: Value::Boolean(false, span),
+                    span,
+                },
+            ),
+        ];
+
+        for (method_name, predicate) in cases {
+            assert_eq!(
+                predicate_of(method_name, &[age()]),
+                Some(predicate),
+                "{}",
+                method_name
+            );
+        }
+
+        assert_eq!(
+            predicate_of(
+                "findByAgeBetween",
+                &[age(), reference("max", BsonType::Int)]
+            ),
+            Some(And {
+                predicates: vec![
+                    GreaterThan {
+                        field: field(),
+                        value: age(),
+                        span
+                    },
+                    LessThan {
+                        field: field(),
+                        value: reference("max", BsonType::Int),
+                        span
+                    },
+                ],
+                span
+            })
+        );
+        assert_eq!(predicate_of("findByAgeBetween", &[age()]), None);
+    }
+
+    #[test]
+    fn passes_collection_parameters_as_the_whole_membership_operand() {
+        let statuses = || reference("statuses", BsonType::Array);
+        let span = Span::default();
+
+        assert_eq!(
+            predicate_of("findByStatusIn", &[statuses()]),
+            Some(In {
+                field: "status".to_string(),
+                values: vec![statuses()],
+                span
+            })
+        );
+        assert_eq!(
+            predicate_of("findByStatusNotIn", &[statuses()]),
+            Some(NotIn {
+                field: "status".to_string(),
+                values: vec![statuses()],
+                span
+            })
+        );
+        assert_eq!(
+            predicate_of(
+                "findByStatusIn",
+                &[Value::Array(vec![string("A"), string("B")], span)]
+            ),
+            Some(In {
+                field: "status".to_string(),
+                values: vec![string("A"), string("B")],
+                span
+            })
+        );
+    }
+
+    #[test]
+    fn anchors_string_matching_keywords() {
+        let name = || reference("name", BsonType::String);
+        let span = Span::default();
+
+        assert_eq!(
+            predicate_of("findByNameStartingWith", &[name()]),
+            Some(regex(string("^{{name:string}}")))
+        );
+        assert_eq!(
+            predicate_of("findByNameEndsWith", &[string("a.b")]),
+            Some(regex(string("a\\.b$")))
+        );
+        assert_eq!(
+            predicate_of("findByNameContaining", &[name()]),
+            Some(regex(name()))
+        );
+        assert_eq!(
+            predicate_of("findByNameNotContaining", &[string("a+")]),
+            Some(Not {
+                predicates: vec![regex(string("a\\+"))],
+                span
+            })
+        );
+        assert_eq!(
+            predicate_of("findByNameLike", &[name()]),
+            Some(regex(name()))
+        );
+        assert_eq!(
+            predicate_of("findByNameNotLike", &[name()]),
+            Some(Not {
+                predicates: vec![regex(name())],
+                span
+            })
+        );
+        assert_eq!(
+            predicate_of("findByNameRegex", &[name()]),
+            Some(regex(name()))
+        );
+        assert_eq!(
+            predicate_of("findByNameIgnoreCase", &[name()]),
+            Some(Regex {
+                field: "name".to_string(),
+                pattern: name(),
+                options: Some("i".to_string()),
+                span
+            })
+        );
+    }
+
+    #[test]
+    fn maps_existence_and_geospatial_keywords() {
+        let point = || reference("point", BsonType::Object);
+        let span = Span::default();
+
+        assert_eq!(
+            predicate_of("findByLocationExists", &[Value::Boolean(false, span)]),
+            Some(Exists {
+                field: "location".to_string(),
+                exists: false,
+                span
+            })
+        );
+        assert_eq!(
+            predicate_of(
+                "findByLocationExists",
+                &[reference("exists", BsonType::Bool)]
+            ),
+            None
+        );
+        assert_eq!(
+            predicate_of("findByLocationNear", &[point()]),
+            Some(Near {
+                field: "location".to_string(),
+                geometry: point(),
+                max_distance: None,
+                min_distance: None,
+                span
+            })
+        );
+        assert_eq!(
+            predicate_of("findByLocationWithin", &[point()]),
+            Some(GeoWithin {
+                field: "location".to_string(),
+                shape: GeoShape::Geometry(point()),
+                span
+            })
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use dialect_java_driver::symbol_index::resolve_entity;
+use dialect_java_driver::tree_ext::entity_mapping::{entity_mapping, EntityMapping};
+use dialect_java_driver::tree_ext::optional_node_to_string;
+use mongodb_query_language::execution::ExecutionNamespace;
+use tree_sitter::Node;
+
+use crate::tree_ext::{descendants_of_kind, simple_type_name};
+
+pub fn entity_of(root: Node, type_name: &str, code: &String) -> Option<EntityMapping> {
+    let simple_name = simple_type_name(type_name);
+    let mut classes = vec![];
+    descendants_of_kind(
+        root,
+        &["class_declaration", "record_declaration"],
+        &mut classes,
+    );
+
+    return classes
+        .into_iter()
+        .find(|class| {
+            optional_node_to_string(&class.child_by_field_name("name"), code) == simple_name
+        })
+        .map(|class| entity_mapping(class, code))
+        .or_else(|| resolve_entity(&simple_name));
+}
+
+pub fn entity_namespace(root: Node, type_name: &str, code: &String) -> ExecutionNamespace {
+    return ExecutionNamespace {
+        database: None,
+        collection: entity_of(root, type_name, code).and_then(|entity| entity.collection),
+        reference_name: simple_type_name(type_name),
+    };
+}
+
+pub fn entity_field_name(root: Node, type_name: &str, property: &str, code: &String) -> String {
+    return match entity_of(root, type_name, code) {
+        Some(entity) => entity.field_name(property),
+        None if property == "id" => "_id".to_string(),
+        None => property.to_string(),
+    };
+}
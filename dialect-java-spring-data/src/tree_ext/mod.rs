@@ -0,0 +1,115 @@
+use dialect_java_driver::tree_ext::infer_value_type::initializer_of;
+pub use dialect_java_driver::tree_ext::{
+    annotation_element, annotation_named, descendants_of_kind, simple_type_name, string_value,
+    uncapitalize,
+};
+use dialect_java_driver::tree_ext::{
+    invocation_arguments, invocation_name, optional_node_to_string,
+};
+use tree_sitter::Node;
+
+pub mod aggregation_from_expression;
+pub mod criteria_from_expression;
+pub mod derived_query;
+pub mod entity_namespace;
+pub mod query_from_expression;
+pub mod string_query;
+pub mod update_definition_from_expression;
+
+pub type ChainCalls<'a> = Vec<(String, Vec<Node<'a>>)>;
+
+pub fn string_values(node: Node, code: &String) -> Option<Vec<String>> {
+    if node.kind() != "element_value_array_initializer" {
+        return Some(vec![string_value(node, code)?]);
+    }
+
+    let mut cursor = node.walk();
+    let values = node
+        .named_children(&mut cursor)
+        .map(|element| string_value(element, code))
+        .collect();
+    return values;
+}
+
+pub fn boolean_element(annotation: Node, key: &str, code: &String) -> bool {
+    return annotation_element(annotation, key, code)
+        .map(|element| element.kind() == "true")
+        .unwrap_or(false);
+}
+
+fn receiver_chain<'a>(node: Node<'a>, code: &String) -> Option<(Node<'a>, ChainCalls<'a>)> {
+    if node.kind() != "method_invocation" {
+        return Some((node, vec![]));
+    }
+
+    let (receiver, mut calls) = receiver_chain(node.child_by_field_name("object")?, code)?;
+    calls.push((invocation_name(node, code), invocation_arguments(node)));
+    return Some((receiver, calls));
+}
+
+fn mutations_of<'a>(usage: Node<'a>, code: &String) -> ChainCalls<'a> {
+    let name = optional_node_to_string(&Some(usage), code);
+    let mut statements = vec![];
+    let mut current = usage;
+    while let Some(parent) = current.parent() {
+        if matches!(parent.kind(), "block" | "constructor_body") {
+            let mut cursor = parent.walk();
+            statements.extend(parent.named_children(&mut cursor).filter(|statement| {
+                statement.kind() == "expression_statement"
+                    && statement.end_byte() <= usage.start_byte()
+            }));
+        }
+
+        if matches!(
+            parent.kind(),
+            "method_declaration" | "constructor_declaration"
+        ) {
+            break;
+        }
+        current = parent;
+    }
+
+    statements.sort_by_key(|statement| statement.start_byte());
+    return statements
+        .into_iter()
+        .filter_map(|statement| receiver_chain(statement.named_child(0)?, code))
+        .filter(|(receiver, _)| {
+            receiver.kind() == "identifier"
+                && optional_node_to_string(&Some(*receiver), code) == name
+        })
+        .flat_map(|(_, calls)| calls)
+        .collect();
+}
+
+pub fn chain_calls<'a>(node: Node<'a>, code: &String) -> ChainCalls<'a> {
+    return match node.kind() {
+        "method_invocation" => {
+            let mut calls = node
+                .child_by_field_name("object")
+                .map(|object| chain_calls(object, code))
+                .unwrap_or_default();
+            calls.push((invocation_name(node, code), invocation_arguments(node)));
+            calls
+        }
+        "object_creation_expression" => {
+            let type_name = optional_node_to_string(&node.child_by_field_name("type"), code);
+            vec![(
+                format!("new {}", simple_type_name(&type_name)),
+                invocation_arguments(node),
+            )]
+        }
+        "parenthesized_expression" => node
+            .named_child(0)
+            .map(|inner| chain_calls(inner, code))
+            .unwrap_or_default(),
+        "identifier" => match initializer_of(node, code) {
+            Some(initializer) => {
+                let mut calls = chain_calls(initializer, code);
+                calls.append(&mut mutations_of(node, code));
+                calls
+            }
+            None => vec![],
+        },
+        _ => vec![],
+    };
+}
@@ -0,0 +1,181 @@
+use dialect_interface::ExtractionWarning;
+use dialect_java_driver::tree_ext::value_from_expression::value_from_expression;
+use dialect_java_driver::tree_ext::{
+    field_from_expression, fields_from_arguments, optional_node_to_string,
+};
+use mongodb_query_language::aggregation::{ProjectionField, SortDirection, Stage};
+use mongodb_query_language::execution::{FindOptions, Hint};
+use mongodb_query_language::filter::FilterOperator;
+use mongodb_query_language::parser::{parse, parse_filter};
+use mongodb_query_language::span::Span;
+use tree_sitter::Node;
+
+use crate::tree_ext::criteria_from_expression::predicate_from_criteria;
+use crate::tree_ext::{chain_calls, string_value};
+
+fn direction_of(node: Node, code: &String) -> Option<SortDirection> {
+    let text = optional_node_to_string(&Some(node), code);
+    return match text.rsplit('.').next() {
+        Some("ASC") => Some(SortDirection::Ascending),
+        Some("DESC") => Some(SortDirection::Descending),
+        _ => None,
+    };
+}
+
+pub fn sort_from_spring_expression(node: Node, code: &String) -> Vec<(String, SortDirection)> {
+    let mut sort = vec![];
+    for (name, arguments) in chain_calls(node, code) {
+        let direction = arguments
+            .first()
+            .and_then(|direction| direction_of(*direction, code));
+        let is_constructor = matches!(name.as_str(), "by" | "new Sort");
+        match (name.as_str(), &arguments[..]) {
+            (_, [_, fields @ ..]) if is_constructor && direction.is_some() => {
+                let direction = direction.unwrap_or(SortDirection::Ascending);
+                sort.extend(
+                    fields_from_arguments(fields, code)
+                        .into_iter()
+                        .map(|field| (field, direction)),
+                );
+            }
+            (_, orders)
+                if is_constructor
+                    && orders
+                        .iter()
+                        .all(|order| order.kind() == "method_invocation") =>
+            {
+                sort.extend(
+                    orders
+                        .iter()
+                        .flat_map(|order| sort_from_spring_expression(*order, code)),
+                );
+            }
+            (_, fields) if is_constructor => sort.extend(
+                fields_from_arguments(fields, code)
+                    .into_iter()
+                    .map(|field| (field, SortDirection::Ascending)),
+            ),
+            ("asc" | "desc", [field]) => {
+                if let Some(field) = field_from_expression(*field, code) {
+                    sort.push(match name.as_str() {
+                        "desc" => (field, SortDirection::Descending),
+                        _ => (field, SortDirection::Ascending),
+                    });
+                }
+            }
+            ("and", [other]) => sort.extend(sort_from_spring_expression(*other, code)),
+            ("ascending" | "descending", []) => {
+                let direction = match name.as_str() {
+                    "descending" => SortDirection::Descending,
+                    _ => SortDirection::Ascending,
+                };
+                sort.iter_mut().for_each(|(_, each)| *each = direction);
+            }
+            ("of", [_, _, sort_argument]) => {
+                sort = sort_from_spring_expression(*sort_argument, code)
+            }
+            _ => {}
+        }
+    }
+
+    return sort;
+}
+
+pub fn projection_from_json(json: &str) -> Option<Vec<(String, ProjectionField)>> {
+    return match parse::<Stage>(&format!("{{ $project: {} }}", json)) {
+        Ok(Stage::Project { fields }) => Some(fields),
+        _ => None,
+    };
+}
+
+pub fn sort_from_json(json: &str) -> Option<Vec<(String, SortDirection)>> {
+    return match parse::<Stage>(&format!("{{ $sort: {} }}", json)) {
+        Ok(Stage::Sort { fields }) => Some(fields),
+        _ => None,
+    };
+}
+
+fn unresolved(node: Node, code: &String, warnings: &mut Vec<ExtractionWarning>) {
+    warnings.push(ExtractionWarning::UnresolvedFilter {
+        expression: optional_node_to_string(&Some(node), code),
+        span: Span::from(node),
+    });
+}
+
+pub fn query_from_expression(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<(FilterOperator, FindOptions)> {
+    let span = Span::from(node);
+    let mut predicates = vec![];
+    let mut options = FindOptions::default();
+    let mut projecting = false;
+
+    let calls = chain_calls(node, code);
+    if calls.is_empty() {
+        unresolved(node, code, warnings);
+        return None;
+    }
+
+    for (name, arguments) in calls {
+        match (name.as_str(), &arguments[..]) {
+            ("new Query" | "query", []) => {}
+            ("new Query" | "query" | "addCriteria", [criteria]) => {
+                predicates.push(predicate_from_criteria(*criteria, code, warnings)?)
+            }
+            ("new BasicQuery", [filter, fields @ ..]) => {
+                match filter_from_json_expression(*filter, code) {
+                    Some(predicate) => predicates.push(predicate),
+                    None => {
+                        unresolved(*filter, code, warnings);
+                        return None;
+                    }
+                }
+                if let Some(fields) = fields.first() {
+                    let json = string_value(*fields, code);
+                    options.projection.extend(
+                        json.and_then(|json| projection_from_json(&json))
+                            .unwrap_or_default(),
+                    );
+                }
+            }
+            ("with", [sort]) => options
+                .sort
+                .extend(sort_from_spring_expression(*sort, code)),
+            ("limit", [limit]) => options.limit = Some(value_from_expression(*limit, code)),
+            ("skip", [skip]) => options.skip = Some(value_from_expression(*skip, code)),
+            ("withHint", [hint]) => {
+                options.hint = field_from_expression(*hint, code).map(Hint::Name)
+            }
+            ("maxTimeMsec", [max_time]) => {
+                options.max_time_ms = Some(value_from_expression(*max_time, code))
+            }
+            ("fields", []) => projecting = true,
+            ("include", fields) if projecting => options.projection.extend(
+                fields_from_arguments(fields, code)
+                    .into_iter()
+                    .map(|field| (field, ProjectionField::Include)),
+            ),
+            ("exclude", fields) if projecting => options.projection.extend(
+                fields_from_arguments(fields, code)
+                    .into_iter()
+                    .map(|field| (field, ProjectionField::Exclude)),
+            ),
+            _ => warnings.push(ExtractionWarning::UnsupportedOperator {
+                operator: name,
+                span,
+            }),
+        }
+    }
+
+    let predicate = match predicates.len() {
+        1 => predicates.remove(0),
+        _ => FilterOperator::And { predicates, span },
+    };
+    return Some((predicate, options));
+}
+
+fn filter_from_json_expression(node: Node, code: &String) -> Option<FilterOperator> {
+    return parse_filter(&string_value(node, code)?).ok();
+}
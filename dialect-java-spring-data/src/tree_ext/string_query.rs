@@ -0,0 +1,25 @@
+use mongodb_query_language::serialization::reference_placeholder;
+use mongodb_query_language::values::Value;
+use regex::{Captures, Regex};
+
+pub fn bind_parameters(json: &str, parameters: &[Value]) -> String {
+    let Ok(placeholder) = Regex::new(r#"['"]?\?(\d+)['"]?"#) else {
+        return json.to_string();
+    };
+
+    return placeholder
+        .replace_all(json, |captures: &Captures| {
+            let parameter = captures[1]
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| parameters.get(index));
+
+            return match parameter {
+                Some(Value::Reference(name, value_type, _)) => {
+                    format!("\"{}\"", reference_placeholder(name, value_type))
+                }
+                _ => captures[0].to_string(),
+            };
+        })
+        .to_string();
+}
@@ -0,0 +1,124 @@
+use dialect_interface::ExtractionWarning;
+use dialect_java_driver::tree_ext::value_from_expression::{
+    value_from_expression, values_from_arguments,
+};
+use dialect_java_driver::tree_ext::{field_from_expression, optional_node_to_string};
+use mongodb_query_language::parser::parse_update;
+use mongodb_query_language::span::Span;
+use mongodb_query_language::update::{ArrayUpdateValue, PullCondition, Update, UpdateOperator};
+use mongodb_query_language::values::Value;
+use tree_sitter::Node;
+
+use crate::tree_ext::{chain_calls, string_value};
+
+fn update_operator(name: &str, arguments: &[Node], code: &String) -> Option<UpdateOperator> {
+    let field = field_from_expression(*arguments.first()?, code)?;
+    let value = |node: &Node| value_from_expression(*node, code);
+
+    return match (name, arguments) {
+        ("set" | "update", [_, v]) => Some(UpdateOperator::Set {
+            field,
+            value: value(v),
+        }),
+        ("setOnInsert", [_, v]) => Some(UpdateOperator::SetOnInsert {
+            field,
+            value: value(v),
+        }),
+        ("unset", [_]) => Some(UpdateOperator::Unset { field }),
+        ("inc", [_]) => Some(UpdateOperator::Inc {
+            field,
+            amount: Value::Integer(1, Span::from(arguments[0])),
+        }),
+        ("inc", [_, v]) => Some(UpdateOperator::Inc {
+            field,
+            amount: value(v),
+        }),
+        ("multiply", [_, v]) => Some(UpdateOperator::Mul {
+            field,
+            factor: value(v),
+        }),
+        ("min", [_, v]) => Some(UpdateOperator::Min {
+            field,
+            value: value(v),
+        }),
+        ("max", [_, v]) => Some(UpdateOperator::Max {
+            field,
+            value: value(v),
+        }),
+        ("rename", [_, new_name]) => Some(UpdateOperator::Rename {
+            field,
+            new_name: field_from_expression(*new_name, code)?,
+        }),
+        ("currentDate", [_]) => Some(UpdateOperator::CurrentDate {
+            field,
+            as_timestamp: false,
+        }),
+        ("currentTimestamp", [_]) => Some(UpdateOperator::CurrentDate {
+            field,
+            as_timestamp: true,
+        }),
+        ("push", [_, v]) => Some(UpdateOperator::Push {
+            field,
+            values: ArrayUpdateValue::Single(value(v)),
+            position: None,
+            slice: None,
+            sort: None,
+        }),
+        ("addToSet", [_, v]) => Some(UpdateOperator::AddToSet {
+            field,
+            values: ArrayUpdateValue::Single(value(v)),
+        }),
+        ("pull", [_, v]) => Some(UpdateOperator::Pull {
+            field,
+            condition: PullCondition::Value(value(v)),
+        }),
+        ("pullAll", [_, values]) => Some(UpdateOperator::PullAll {
+            field,
+            values: values_from_arguments(&[*values], code),
+        }),
+        ("pop", [_, position]) => Some(UpdateOperator::Pop {
+            field,
+            first: optional_node_to_string(&Some(*position), code).ends_with("FIRST"),
+        }),
+        _ => None,
+    };
+}
+
+pub fn update_from_definition(
+    node: Node,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Update {
+    let calls = chain_calls(node, code);
+    if calls.is_empty() {
+        warnings.push(ExtractionWarning::UnresolvedUpdate {
+            expression: optional_node_to_string(&Some(node), code),
+            span: Span::from(node),
+        });
+    }
+
+    let mut operators = vec![];
+    for (name, arguments) in calls {
+        match (name.as_str(), &arguments[..]) {
+            ("new Update", []) => {}
+            ("new BasicUpdate", [json]) => {
+                match string_value(*json, code).and_then(|json| parse_update(&json).ok()) {
+                    Some(update) => return update,
+                    None => warnings.push(ExtractionWarning::UnresolvedUpdate {
+                        expression: optional_node_to_string(&Some(*json), code),
+                        span: Span::from(*json),
+                    }),
+                }
+            }
+            _ => match update_operator(&name, &arguments, code) {
+                Some(operator) => operators.push(operator),
+                None => warnings.push(ExtractionWarning::UnsupportedOperator {
+                    operator: name,
+                    span: Span::from(node),
+                }),
+            },
+        }
+    }
+
+    return Update::Operators(operators);
+}
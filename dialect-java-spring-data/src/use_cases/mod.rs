@@ -0,0 +1,54 @@
+use mongodb_query_language::aggregation::Stage;
+use mongodb_query_language::execution::{
+    Execution, ExecutionContext, ExecutionNamespace, FindOptions,
+};
+use mongodb_query_language::filter::FilterOperator;
+use mongodb_query_language::span::Span;
+
+use crate::tree_ext::derived_query::Subject;
+
+pub mod repository;
+pub mod template;
+
+fn execution_of(
+    subject: Subject,
+    many: bool,
+    namespace: ExecutionNamespace,
+    predicate: FilterOperator,
+    options: FindOptions,
+    span: Span,
+) -> Execution {
+    return match (subject, many) {
+        (Subject::Find, true) => Execution::FindMany {
+            namespace,
+            predicate,
+            options,
+            context: ExecutionContext::default(),
+            span,
+        },
+        (Subject::Find | Subject::Exists, _) => Execution::FindOne {
+            namespace,
+            predicate,
+            options,
+            context: ExecutionContext::default(),
+            span,
+        },
+        (Subject::Count, _) => Execution::Aggregate {
+            namespace,
+            pipeline: vec![
+                Stage::Match { predicate },
+                Stage::Count {
+                    field: "count".to_string(),
+                },
+            ],
+            context: ExecutionContext::default(),
+            span,
+        },
+        (Subject::Delete, _) => Execution::DeleteMany {
+            namespace,
+            predicate,
+            context: ExecutionContext::default(),
+            span,
+        },
+    };
+}
@@ -0,0 +1,8 @@
+((interface_declaration
+   (extends_interfaces
+     (type_list
+       (generic_type
+         (type_identifier) @base
+         (type_arguments . (_) @entity))))
+   body: (interface_body) @body)
+  (#match? @base "^(Reactive)?(Mongo|Crud|ListCrud|PagingAndSorting|ListPagingAndSorting|Sorting)?Repository$"))
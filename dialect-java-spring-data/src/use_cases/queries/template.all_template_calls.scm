@@ -0,0 +1,5 @@
+((method_invocation
+   object: [(identifier) (field_access)] @template
+   name: (identifier) @method
+   arguments: (argument_list) @argumentlist)
+  (#match? @method "^(find|findOne|findAll|findById|count|exists|remove|findAndRemove|findAllAndRemove|updateFirst|updateMulti|upsert|findAndModify|aggregate)$"))
@@ -0,0 +1,564 @@
+use std::cell::RefCell;
+use std::error::Error;
+
+use tree_sitter::{Node, Tree};
+
+use dialect_interface::{Extraction, ExtractionWarning};
+use dialect_java_driver::tree_ext::friendly_capture::FriendlyCapture;
+use dialect_java_driver::tree_ext::infer_value_type::java_type_name_to_value_type;
+use dialect_java_driver::tree_ext::optional_node_to_string;
+use mongodb_query_language::aggregation::Stage;
+use mongodb_query_language::execution::{
+    Execution, ExecutionContext, ExecutionNamespace, FindOptions,
+};
+use mongodb_query_language::parser::{parse, parse_filter};
+use mongodb_query_language::span::Span;
+use mongodb_query_language::values::{BsonType, Value, ValueType};
+
+use crate::tree_ext::derived_query::{parse_derived_query, predicate_from_derived_query, Subject};
+use crate::tree_ext::entity_namespace::{entity_field_name, entity_namespace, entity_of};
+use crate::tree_ext::query_from_expression::{projection_from_json, sort_from_json};
+use crate::tree_ext::string_query::bind_parameters;
+use crate::tree_ext::{
+    annotation_element, annotation_named, boolean_element, simple_type_name, string_value,
+    string_values,
+};
+use crate::use_cases::execution_of;
+
+const ALL_REPOSITORIES: &str = include_str!("queries/repository.all_repositories.scm");
+
+const MONGO_REPOSITORIES: [&str; 2] = ["MongoRepository", "ReactiveMongoRepository"];
+
+const SPECIAL_PARAMETERS: [&str; 5] = ["Pageable", "Sort", "Limit", "ScrollPosition", "Collation"];
+
+const MULTIPLE_RESULTS: [&str; 11] = [
+    "List",
+    "Collection",
+    "Set",
+    "Iterable",
+    "Stream",
+    "Page",
+    "Slice",
+    "Window",
+    "Flux",
+    "GeoResults",
+    "SearchHits",
+];
+
+fn bindable_parameters(method: Node, code: &String) -> Vec<Value> {
+    let Some(parameters) = method.child_by_field_name("parameters") else {
+        return vec![];
+    };
+
+    let mut cursor = parameters.walk();
+    let bindable = parameters
+        .named_children(&mut cursor)
+        .filter_map(|parameter| {
+            let mut parameter_cursor = parameter.walk();
+            let type_node = parameter.child_by_field_name("type").or(parameter
+                .named_children(&mut parameter_cursor)
+                .find(|child| {
+                    child.kind() != "modifiers" && child.kind() != "variable_declarator"
+                }))?;
+            let type_name = optional_node_to_string(&Some(type_node), code);
+            if SPECIAL_PARAMETERS.contains(&simple_type_name(&type_name).as_str()) {
+                return None;
+            }
+
+            let value_type = match parameter.kind() {
+                "spread_parameter" => ValueType::Of(BsonType::Array),
+                _ => java_type_name_to_value_type(&type_name),
+            };
+            let name = parameter.child_by_field_name("name").or_else(|| {
+                let mut declarator_cursor = parameter.walk();
+                let declarator = parameter
+                    .named_children(&mut declarator_cursor)
+                    .find(|child| child.kind() == "variable_declarator")
+                    .and_then(|declarator| declarator.child_by_field_name("name"));
+                declarator
+            });
+            Some(Value::Reference(
+                optional_node_to_string(&name, code),
+                value_type,
+                Span::from(parameter),
+            ))
+        })
+        .collect();
+    return bindable;
+}
+
+fn returns_many(method: Node, code: &String) -> bool {
+    let Some(return_type) = method.child_by_field_name("type") else {
+        return false;
+    };
+
+    return return_type.kind() == "array_type"
+        || MULTIPLE_RESULTS.contains(
+            &simple_type_name(&optional_node_to_string(&Some(return_type), code)).as_str(),
+        );
+}
+
+fn string_query_execution(
+    query: Node,
+    parameters: &[Value],
+    many: bool,
+    namespace: ExecutionNamespace,
+    span: Span,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<Execution> {
+    let json = |key: &str| {
+        annotation_element(query, key, code)
+            .and_then(|element| string_value(element, code))
+            .map(|json| bind_parameters(&json, parameters))
+    };
+
+    let filter = json("value")?;
+    let predicate = match parse_filter(&filter) {
+        Ok(predicate) => predicate,
+        Err(_) => {
+            warnings.push(ExtractionWarning::UnresolvedFilter {
+                expression: filter,
+                span,
+            });
+            return None;
+        }
+    };
+
+    let options = FindOptions {
+        projection: json("fields")
+            .and_then(|fields| projection_from_json(&fields))
+            .unwrap_or_default(),
+        sort: json("sort")
+            .and_then(|sort| sort_from_json(&sort))
+            .unwrap_or_default(),
+        ..FindOptions::default()
+    };
+
+    let subject = match (
+        boolean_element(query, "count", code),
+        boolean_element(query, "exists", code),
+        boolean_element(query, "delete", code),
+    ) {
+        (true, _, _) => Subject::Count,
+        (_, true, _) => Subject::Exists,
+        (_, _, true) => Subject::Delete,
+        _ => Subject::Find,
+    };
+
+    return Some(execution_of(
+        subject, many, namespace, predicate, options, span,
+    ));
+}
+
+fn aggregation_execution(
+    aggregation: Node,
+    parameters: &[Value],
+    namespace: ExecutionNamespace,
+    span: Span,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Execution {
+    let stages = annotation_element(aggregation, "pipeline", code)
+        .or_else(|| annotation_element(aggregation, "value", code))
+        .and_then(|pipeline| string_values(pipeline, code))
+        .unwrap_or_default();
+
+    let pipeline = stages
+        .into_iter()
+        .filter_map(|stage| {
+            let stage = bind_parameters(&stage, parameters);
+            let parsed = parse::<Stage>(&stage).ok();
+            if parsed.is_none() {
+                warnings.push(ExtractionWarning::UnresolvedStage {
+                    expression: stage,
+                    span,
+                });
+            }
+            parsed
+        })
+        .collect();
+
+    return Execution::Aggregate {
+        namespace,
+        pipeline,
+        context: ExecutionContext::default(),
+        span,
+    };
+}
+
+fn derived_query_execution(
+    root: Node,
+    method: Node,
+    entity: &str,
+    parameters: &[Value],
+    namespace: ExecutionNamespace,
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> Option<Execution> {
+    let span = Span::from(method);
+    let name = optional_node_to_string(&method.child_by_field_name("name"), code);
+    let query = parse_derived_query(&name)?;
+
+    let field_of = |property: &str| match property.split_once('.') {
+        Some((head, tail)) => format!("{}.{}", entity_field_name(root, entity, head, code), tail),
+        None => entity_field_name(root, entity, property, code),
+    };
+
+    let Some(predicate) = predicate_from_derived_query(&query, parameters, &field_of, span) else {
+        warnings.push(ExtractionWarning::UnresolvedFilter {
+            expression: name,
+            span,
+        });
+        return None;
+    };
+
+    let options = FindOptions {
+        sort: query
+            .sort
+            .into_iter()
+            .map(|(property, direction)| (field_of(&property), direction))
+            .collect(),
+        limit: query.limit.map(|limit| Value::Integer(limit, span)),
+        ..FindOptions::default()
+    };
+
+    let many = returns_many(method, code) && query.limit != Some(1);
+    return Some(execution_of(
+        query.subject,
+        many,
+        namespace,
+        predicate,
+        options,
+        span,
+    ));
+}
+
+pub fn repository(
+    tree: RefCell<Tree>,
+    code: &String,
+) -> Result<Extraction, Box<dyn Error + Sync + Send>> {
+    let all_repositories_query =
+        tree_sitter::Query::new(tree_sitter_java::language(), ALL_REPOSITORIES)?;
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let root = tree.borrow();
+    let all_matches = cursor.matches(&all_repositories_query, root.root_node(), code.as_bytes());
+
+    let base_idx = all_repositories_query
+        .capture_index_for_name("base")
+        .unwrap();
+    let entity_idx = all_repositories_query
+        .capture_index_for_name("entity")
+        .unwrap();
+    let body_idx = all_repositories_query
+        .capture_index_for_name("body")
+        .unwrap();
+
+    let mut result = Extraction::default();
+
+    for each_match in all_matches {
+        let [Some(base_node), Some(entity_node), Some(body_node)] =
+            each_match.capture(vec![base_idx, entity_idx, body_idx])[..]
+        else {
+            continue;
+        };
+
+        let base = optional_node_to_string(&Some(base_node), code);
+        let entity = optional_node_to_string(&Some(entity_node), code);
+        let mapped_document = || {
+            entity_of(root.root_node(), &entity, code)
+                .map(|entity| entity.document)
+                .unwrap_or(false)
+        };
+        if !MONGO_REPOSITORIES.contains(&base.as_str()) && !mapped_document() {
+            continue;
+        }
+
+        let namespace = entity_namespace(root.root_node(), &entity, code);
+        if namespace.collection.is_none() {
+            result
+                .warnings
+                .push(ExtractionWarning::UnresolvedCollection {
+                    reference: entity.clone(),
+                    span: Span::from(entity_node),
+                });
+        }
+
+        let mut body_cursor = body_node.walk();
+        let methods = body_node.named_children(&mut body_cursor).filter(|member| {
+            member.kind() == "method_declaration" && member.child_by_field_name("body").is_none()
+        });
+
+        for method in methods {
+            let span = Span::from(method);
+            let parameters = bindable_parameters(method, code);
+            let many = returns_many(method, code);
+
+            let query = annotation_named(method, "Query", code)
+                .filter(|query| annotation_element(*query, "value", code).is_some());
+            let execution = match (query, annotation_named(method, "Aggregation", code)) {
+                (Some(query), _) => string_query_execution(
+                    query,
+                    &parameters,
+                    many,
+                    namespace.clone(),
+                    span,
+                    code,
+                    &mut result.warnings,
+                ),
+                (None, Some(aggregation)) => Some(aggregation_execution(
+                    aggregation,
+                    &parameters,
+                    namespace.clone(),
+                    span,
+                    code,
+                    &mut result.warnings,
+                )),
+                (None, None) => derived_query_execution(
+                    root.root_node(),
+                    method,
+                    &entity,
+                    &parameters,
+                    namespace.clone(),
+                    code,
+                    &mut result.warnings,
+                ),
+            };
+
+            result.executions.extend(execution);
+        }
+    }
+
+    return Ok(result);
+}
+
+#[cfg(test)]
+mod test {
+    use mongodb_query_language::aggregation::ProjectionField::Include;
+    use mongodb_query_language::aggregation::SortDirection::Descending;
+    use mongodb_query_language::aggregation::Stage;
+    use mongodb_query_language::execution::Execution::{Aggregate, FindMany, FindOne};
+    use mongodb_query_language::execution::{ExecutionContext, ExecutionNamespace, FindOptions};
+    use mongodb_query_language::filter::FilterOperator::{
+        And, Equals, GreaterThan, GreaterThanOrEquals,
+    };
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::values::Value::{Integer, Reference};
+    use mongodb_query_language::values::{BsonType, ValueType};
+
+    use dialect_interface::ExtractionWarning;
+    use dialect_java_driver::symbol_index::{with_symbols, SymbolIndex};
+    use dialect_java_driver::Java;
+
+    use crate::use_cases::repository::repository;
+
+    #[test]
+    fn extracts_derived_and_annotated_repository_queries() {
+        let code = r#"
+        @Document(collection = "people")
+        public class Person {
+            @Id
+            private String id;
+            @Field("last_name")
+            private String lastname;
+            private int age;
+            private String status;
+        }
+
+        public interface PersonRepository extends MongoRepository<Person, String> {
+            List<Person> findByAgeGreaterThanAndLastname(int age, String lastname, Pageable page);
+
+            Optional<Person> findFirstByStatusOrderByAgeDesc(String status);
+
+            @Query(value = "{ 'age': { $gte: ?0 } }", fields = "{ 'lastname': 1 }")
+            List<Person> findAdults(int age);
+
+            @Aggregation(pipeline = { "{ $match: { status: ?0 } }", "{ $count: 'total' }" })
+            long countWithStatus(String status);
+
+            default List<Person> findNothing() {
+                return List.of();
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = repository(tree, &code).unwrap();
+
+        let namespace = ExecutionNamespace {
+            database: None,
+            collection: Some("people".to_string()),
+            reference_name: "Person".to_string(),
+        };
+        let reference = |name: &str, bson_type: BsonType| {
+            Reference(name.to_string(), ValueType::Of(bson_type), Span::default())
+        };
+
+        assert_eq!(result.warnings, vec![]);
+        assert_eq!(
+            result.executions,
+            vec![
+                FindMany {
+                    namespace: namespace.clone(),
+                    predicate: And {
+                        predicates: vec![
+                            GreaterThan {
+                                field: "age".to_string(),
+                                value: reference("age", BsonType::Int),
+                                span: Span::default(),
+                            },
+                            Equals {
+                                field: "last_name".to_string(),
+                                value: reference("lastname", BsonType::String),
+                                span: Span::default(),
+                            },
+                        ],
+                        span: Span::default(),
+                    },
+                    options: FindOptions::default(),
+                    context: ExecutionContext::default(),
+                    span: Span::default(),
+                },
+                FindOne {
+                    namespace: namespace.clone(),
+                    predicate: Equals {
+                        field: "status".to_string(),
+                        value: reference("status", BsonType::String),
+                        span: Span::default(),
+                    },
+                    options: FindOptions {
+                        sort: vec![("age".to_string(), Descending)],
+                        limit: Some(Integer(1, Span::default())),
+                        ..FindOptions::default()
+                    },
+                    context: ExecutionContext::default(),
+                    span: Span::default(),
+                },
+                FindMany {
+                    namespace: namespace.clone(),
+                    predicate: GreaterThanOrEquals {
+                        field: "age".to_string(),
+                        value: reference("age", BsonType::Int),
+                        span: Span::default(),
+                    },
+                    options: FindOptions {
+                        projection: vec![("lastname".to_string(), Include)],
+                        ..FindOptions::default()
+                    },
+                    context: ExecutionContext::default(),
+                    span: Span::default(),
+                },
+                Aggregate {
+                    namespace,
+                    pipeline: vec![
+                        Stage::Match {
+                            predicate: Equals {
+                                field: "status".to_string(),
+                                value: reference("status", BsonType::String),
+                                span: Span::default(),
+                            },
+                        },
+                        Stage::Count {
+                            field: "total".to_string(),
+                        },
+                    ],
+                    context: ExecutionContext::default(),
+                    span: Span::default(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_entities_declared_in_other_files() {
+        let entity = r#"
+        @Document(collection = "people")
+        public class Person {
+            @Field("last_name")
+            private String lastname;
+        }
+        "#
+        .to_string();
+        let code = r#"
+        public interface PersonRepository extends MongoRepository<Person, String> {
+            List<Person> findByLastname(String lastname);
+        }
+
+        public interface OrderRepository extends MongoRepository<Order, String> {
+            List<Order> findByStatus(String status);
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let symbols = SymbolIndex::shared();
+        symbols.borrow_mut().index(
+            &"Person.java".to_string(),
+            &java.full_parse(&entity).borrow(),
+            &entity,
+        );
+        let tree = java.full_parse(&code);
+        let result = with_symbols(&symbols, || repository(tree, &code)).unwrap();
+
+        let FindMany {
+            namespace,
+            predicate,
+            ..
+        } = &result.executions[0]
+        else {
+            panic!()
+        };
+        assert_eq!(namespace.collection, Some("people".to_string()));
+        assert_eq!(predicate.field(), Some(&"last_name".to_string()));
+        assert_eq!(result.executions[1].namespace().collection, None);
+        assert_eq!(
+            result.warnings,
+            vec![ExtractionWarning::UnresolvedCollection {
+                reference: "Order".to_string(),
+                span: Span::default(),
+            }]
+        );
+    }
+
+    #[test]
+    fn ignores_generic_repositories_of_entities_without_document_mappings() {
+        let code = r#"
+        @Entity
+        public class Customer {
+            private String name;
+        }
+
+        @Document(collection = "people")
+        public class Person {
+            private String status;
+        }
+
+        public interface CustomerRepository extends CrudRepository<Customer, Long> {
+            List<Customer> findByName(String name);
+        }
+
+        public interface InvoiceRepository extends JpaRepository<Invoice, Long> {
+            List<Invoice> findByNumber(String number);
+        }
+
+        public interface PersonRepository extends PagingAndSortingRepository<Person, String> {
+            List<Person> findByStatus(String status);
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = repository(tree, &code).unwrap();
+
+        assert_eq!(result.warnings, vec![]);
+        assert_eq!(result.executions.len(), 1);
+        assert_eq!(
+            result.executions[0].namespace().collection,
+            Some("people".to_string())
+        );
+    }
+}
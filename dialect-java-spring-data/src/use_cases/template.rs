@@ -0,0 +1,479 @@
+use std::cell::RefCell;
+use std::error::Error;
+
+use tree_sitter::{Node, Tree};
+
+use dialect_interface::{Extraction, ExtractionWarning};
+use dialect_java_driver::tree_ext::friendly_capture::FriendlyCapture;
+use dialect_java_driver::tree_ext::infer_value_type::declared_type_name;
+use dialect_java_driver::tree_ext::value_from_expression::value_from_expression;
+use dialect_java_driver::tree_ext::{
+    invocation_arguments, invocation_name, optional_node_to_string, resolved_expression,
+};
+use mongodb_query_language::execution::Execution::{
+    Aggregate, DeleteMany, FindOneAndDelete, FindOneAndUpdate, UpdateMany, UpdateOne,
+};
+use mongodb_query_language::execution::{ExecutionContext, ExecutionNamespace, FindOptions};
+use mongodb_query_language::filter::FilterOperator;
+use mongodb_query_language::span::Span;
+use mongodb_query_language::update::UpdateOptions;
+
+use crate::tree_ext::aggregation_from_expression::pipeline_from_aggregation;
+use crate::tree_ext::derived_query::Subject;
+use crate::tree_ext::entity_namespace::entity_namespace;
+use crate::tree_ext::query_from_expression::query_from_expression;
+use crate::tree_ext::string_value;
+use crate::tree_ext::update_definition_from_expression::update_from_definition;
+use crate::use_cases::execution_of;
+
+const ALL_TEMPLATE_CALLS: &str = include_str!("queries/template.all_template_calls.scm");
+
+const TEMPLATE_TYPES: [&str; 4] = [
+    "MongoTemplate",
+    "MongoOperations",
+    "ReactiveMongoTemplate",
+    "ReactiveMongoOperations",
+];
+
+fn is_template(receiver: Node, code: &String) -> bool {
+    return match declared_type_name(receiver, code) {
+        Some(type_name) => TEMPLATE_TYPES.contains(&type_name.as_str()),
+        None => {
+            let name = optional_node_to_string(&Some(receiver), code).to_lowercase();
+            name.ends_with("template") || name.ends_with("operations")
+        }
+    };
+}
+
+fn template_namespace(
+    invocation: Node,
+    arguments: &[Node],
+    code: &String,
+    warnings: &mut Vec<ExtractionWarning>,
+) -> ExecutionNamespace {
+    let root = invocation
+        .parent()
+        .map(|mut current| {
+            while let Some(parent) = current.parent() {
+                current = parent;
+            }
+            current
+        })
+        .unwrap_or(invocation);
+
+    if let Some(collection) = arguments
+        .iter()
+        .skip(1)
+        .filter(|argument| argument.kind() != "class_literal")
+        .find_map(|argument| string_value(*argument, code))
+    {
+        return ExecutionNamespace {
+            database: None,
+            collection: Some(collection.clone()),
+            reference_name: collection,
+        };
+    }
+
+    let entity = match invocation_name(invocation, code).as_str() {
+        "aggregate" => arguments
+            .first()
+            .map(|aggregation| resolved_expression(*aggregation, code))
+            .and_then(|aggregation| invocation_arguments(aggregation).first().copied())
+            .or(arguments.get(1).copied())
+            .filter(|entity| entity.kind() == "class_literal"),
+        _ => arguments
+            .iter()
+            .find(|argument| argument.kind() == "class_literal")
+            .copied(),
+    };
+    if let Some(entity) = entity.and_then(|entity| entity.named_child(0)) {
+        let namespace = entity_namespace(root, &optional_node_to_string(&Some(entity), code), code);
+        if namespace.collection.is_none() {
+            warnings.push(ExtractionWarning::UnresolvedCollection {
+                reference: namespace.reference_name.clone(),
+                span: Span::from(entity),
+            });
+        }
+        return namespace;
+    }
+
+    let reference = optional_node_to_string(&Some(invocation), code);
+    warnings.push(ExtractionWarning::UnresolvedCollection {
+        reference: reference.clone(),
+        span: Span::from(invocation),
+    });
+    return ExecutionNamespace::empty(reference);
+}
+
+pub fn template(
+    tree: RefCell<Tree>,
+    code: &String,
+) -> Result<Extraction, Box<dyn Error + Sync + Send>> {
+    let all_template_calls =
+        tree_sitter::Query::new(tree_sitter_java::language(), ALL_TEMPLATE_CALLS)?;
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let root = tree.borrow();
+    let all_matches = cursor.matches(&all_template_calls, root.root_node(), code.as_bytes());
+
+    let template_idx = all_template_calls
+        .capture_index_for_name("template")
+        .unwrap();
+    let arglist_idx = all_template_calls
+        .capture_index_for_name("argumentlist")
+        .unwrap();
+
+    let mut result = Extraction::default();
+
+    for each_match in all_matches {
+        let [Some(template_node), Some(arg_list_node)] =
+            each_match.capture(vec![template_idx, arglist_idx])[..]
+        else {
+            continue;
+        };
+
+        if !is_template(template_node, code) {
+            continue;
+        }
+
+        let invocation = arg_list_node.parent().unwrap();
+        let arguments = invocation_arguments(invocation);
+        let method = invocation_name(invocation, code);
+        let span = Span::from(invocation);
+        if method == "remove" && arguments.len() == 1 {
+            continue;
+        }
+
+        let warnings = &mut result.warnings;
+        let namespace = template_namespace(invocation, &arguments, code, warnings);
+        let query = |warnings: &mut Vec<ExtractionWarning>| match arguments.first() {
+            Some(query) => query_from_expression(*query, code, warnings),
+            None => Some((
+                FilterOperator::And {
+                    predicates: vec![],
+                    span,
+                },
+                FindOptions::default(),
+            )),
+        };
+        let update = |warnings: &mut Vec<ExtractionWarning>| {
+            arguments
+                .get(1)
+                .map(|update| update_from_definition(*update, code, warnings))
+        };
+
+        let execution = match method.as_str() {
+            "find" | "findOne" | "exists" | "count" => {
+                let Some((predicate, options)) = query(warnings) else {
+                    continue;
+                };
+                let subject = match method.as_str() {
+                    "count" => Subject::Count,
+                    "exists" => Subject::Exists,
+                    _ => Subject::Find,
+                };
+                execution_of(
+                    subject,
+                    method == "find",
+                    namespace,
+                    predicate,
+                    options,
+                    span,
+                )
+            }
+            "findAll" => execution_of(
+                Subject::Find,
+                true,
+                namespace,
+                FilterOperator::And {
+                    predicates: vec![],
+                    span,
+                },
+                FindOptions::default(),
+                span,
+            ),
+            "findById" => {
+                let Some(id) = arguments.first() else {
+                    continue;
+                };
+                execution_of(
+                    Subject::Find,
+                    false,
+                    namespace,
+                    FilterOperator::Equals {
+                        field: "_id".to_string(),
+                        value: value_from_expression(*id, code),
+                        span,
+                    },
+                    FindOptions::default(),
+                    span,
+                )
+            }
+            "remove" | "findAllAndRemove" | "findAndRemove" => {
+                let Some((predicate, _)) = query(warnings) else {
+                    continue;
+                };
+                match method.as_str() {
+                    "findAndRemove" => FindOneAndDelete {
+                        namespace,
+                        predicate,
+                        context: ExecutionContext::default(),
+                        span,
+                    },
+                    _ => DeleteMany {
+                        namespace,
+                        predicate,
+                        context: ExecutionContext::default(),
+                        span,
+                    },
+                }
+            }
+            "updateFirst" | "updateMulti" | "upsert" | "findAndModify" => {
+                let Some((predicate, _)) = query(warnings) else {
+                    continue;
+                };
+                let Some(update) = update(warnings) else {
+                    continue;
+                };
+                let options = UpdateOptions {
+                    upsert: method == "upsert",
+                    array_filters: vec![],
+                };
+                match method.as_str() {
+                    "updateMulti" => UpdateMany {
+                        namespace,
+                        predicate,
+                        update,
+                        options,
+                        context: ExecutionContext::default(),
+                        span,
+                    },
+                    "findAndModify" => FindOneAndUpdate {
+                        namespace,
+                        predicate,
+                        update,
+                        options,
+                        context: ExecutionContext::default(),
+                        span,
+                    },
+                    _ => UpdateOne {
+                        namespace,
+                        predicate,
+                        update,
+                        options,
+                        context: ExecutionContext::default(),
+                        span,
+                    },
+                }
+            }
+            "aggregate" => Aggregate {
+                namespace,
+                pipeline: arguments
+                    .first()
+                    .map(|aggregation| pipeline_from_aggregation(*aggregation, code, warnings))
+                    .unwrap_or_default(),
+                context: ExecutionContext::default(),
+                span,
+            },
+            _ => continue,
+        };
+
+        result.executions.push(execution);
+    }
+
+    return Ok(result);
+}
+
+#[cfg(test)]
+mod test {
+    use dialect_interface::ExtractionWarning;
+    use mongodb_query_language::aggregation::SortDirection::Descending;
+    use mongodb_query_language::aggregation::Stage;
+    use mongodb_query_language::execution::Execution::{Aggregate, FindMany, UpdateOne};
+    use mongodb_query_language::execution::{ExecutionContext, ExecutionNamespace, FindOptions};
+    use mongodb_query_language::filter::FilterOperator::{And, Equals, GreaterThanOrEquals, Or};
+    use mongodb_query_language::span::Span;
+    use mongodb_query_language::update::{Update, UpdateOperator, UpdateOptions};
+    use mongodb_query_language::values::Value::{Integer, Reference, String};
+    use mongodb_query_language::values::{BsonType, ValueType};
+
+    use dialect_java_driver::Java;
+
+    use crate::use_cases::template::template;
+
+    #[test]
+    fn extracts_mongo_template_operations_with_criteria() {
+        let code = r#"
+        public class PersonService {
+            private final MongoTemplate mongoTemplate;
+
+            public List<Person> adults(int age) {
+                Query query = new Query(Criteria.where("age").gte(age).and("status").is("A"));
+                query.with(Sort.by(Sort.Direction.DESC, "age")).limit(10);
+                return mongoTemplate.find(query, Person.class);
+            }
+
+            public void visit(String id) {
+                mongoTemplate.updateFirst(
+                    query(where("_id").is(id)),
+                    new Update().set("status", "B").inc("visits", 1),
+                    "people");
+            }
+
+            public long countEither() {
+                return mongoTemplate.count(
+                    new Query(new Criteria().orOperator(where("a").is(1), where("b").is(2))),
+                    Person.class);
+            }
+        }
+
+        public class Person {
+            private int age;
+            private String status;
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = template(tree, &code).unwrap();
+
+        let person = ExecutionNamespace {
+            database: None,
+            collection: Some("person".to_string()),
+            reference_name: "Person".to_string(),
+        };
+        let equals = |field: &str, value| Equals {
+            field: field.to_string(),
+            value,
+            span: Span::default(),
+        };
+
+        assert_eq!(result.warnings, vec![]);
+        assert_eq!(
+            result.executions,
+            vec![
+                FindMany {
+                    namespace: person.clone(),
+                    predicate: And {
+                        predicates: vec![
+                            GreaterThanOrEquals {
+                                field: "age".to_string(),
+                                value: Reference(
+                                    "age".to_string(),
+                                    ValueType::Of(BsonType::Int),
+                                    Span::default()
+                                ),
+                                span: Span::default(),
+                            },
+                            equals("status", String("A".to_string(), Span::default())),
+                        ],
+                        span: Span::default(),
+                    },
+                    options: FindOptions {
+                        sort: vec![("age".to_string(), Descending)],
+                        limit: Some(Integer(10, Span::default())),
+                        ..FindOptions::default()
+                    },
+                    context: ExecutionContext::default(),
+                    span: Span::default(),
+                },
+                UpdateOne {
+                    namespace: ExecutionNamespace {
+                        database: None,
+                        collection: Some("people".to_string()),
+                        reference_name: "people".to_string(),
+                    },
+                    predicate: equals(
+                        "_id",
+                        Reference(
+                            "id".to_string(),
+                            ValueType::Of(BsonType::String),
+                            Span::default()
+                        )
+                    ),
+                    update: Update::Operators(vec![
+                        UpdateOperator::Set {
+                            field: "status".to_string(),
+                            value: String("B".to_string(), Span::default()),
+                        },
+                        UpdateOperator::Inc {
+                            field: "visits".to_string(),
+                            amount: Integer(1, Span::default()),
+                        },
+                    ]),
+                    options: UpdateOptions::default(),
+                    context: ExecutionContext::default(),
+                    span: Span::default(),
+                },
+                Aggregate {
+                    namespace: person,
+                    pipeline: vec![
+                        Stage::Match {
+                            predicate: Or {
+                                predicates: vec![
+                                    equals("a", Integer(1, Span::default())),
+                                    equals("b", Integer(2, Span::default())),
+                                ],
+                                span: Span::default(),
+                            },
+                        },
+                        Stage::Count {
+                            field: "count".to_string(),
+                        },
+                    ],
+                    context: ExecutionContext::default(),
+                    span: Span::default(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn drops_queries_with_unresolved_criteria() {
+        let code = r#"
+        public class PersonService {
+            private final MongoTemplate mongoTemplate;
+
+            public void search(Criteria external, Point point) {
+                mongoTemplate.find(new Query(Criteria.where("status").is("A").and("loc").near(point)), Person.class);
+                mongoTemplate.find(new Query(new Criteria().orOperator(Criteria.where("a").is(1), external)), Person.class);
+                mongoTemplate.find(new Query(Criteria.where("a")), Person.class);
+                mongoTemplate.find(new Query(new Criteria()), Person.class);
+            }
+        }
+        "#
+        .to_string();
+
+        let java = Java::new();
+        let tree = java.full_parse(&code);
+        let result = template(tree, &code).unwrap();
+        let warnings: Vec<_> = result
+            .warnings
+            .into_iter()
+            .filter(|warning| !matches!(warning, ExtractionWarning::UnresolvedCollection { .. }))
+            .collect();
+
+        assert_eq!(
+            warnings,
+            vec![
+                ExtractionWarning::UnsupportedOperator {
+                    operator: "near".to_string(),
+                    span: Span::default(),
+                },
+                ExtractionWarning::UnresolvedFilter {
+                    expression: "external".to_string(),
+                    span: Span::default(),
+                },
+                ExtractionWarning::UnresolvedFilter {
+                    expression: "Criteria.where(a)".to_string(),
+                    span: Span::default(),
+                },
+            ]
+        );
+        assert!(matches!(
+            &result.executions[..],
+            [FindMany { predicate: And { predicates, .. }, .. }] if predicates.is_empty()
+        ));
+    }
+}
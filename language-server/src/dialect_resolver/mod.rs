@@ -1,10 +1,13 @@
-use dialect_interface::DialectParser;
+use dialect_interface::{CombinedExtractor, DialectExtractor, DialectParser};
+use dialect_java_driver::symbol_index::SymbolIndex;
 use dialect_java_driver::Java;
+use dialect_java_spring_data::SpringData;
 use std::collections::HashMap;
 use std::rc::Rc;
 
 pub struct LanguageBasedDialectResolver {
     resolvers: HashMap<&'static str, Rc<dyn DialectParser>>,
+    extractors: HashMap<&'static str, Rc<dyn DialectExtractor>>,
 }
 
 pub trait DialectResolver {
@@ -13,6 +16,8 @@ pub trait DialectResolver {
         language_id: &String,
         contents: &String,
     ) -> Option<Rc<dyn DialectParser>>;
+
+    fn resolve_extractor(&self, language_id: &String) -> Option<Rc<dyn DialectExtractor>>;
 }
 
 impl LanguageBasedDialectResolver {
@@ -20,7 +25,20 @@ impl LanguageBasedDialectResolver {
         let mut resolvers = HashMap::new();
         resolvers.insert("java", Java::new());
 
-        return Rc::new(LanguageBasedDialectResolver { resolvers });
+        let mut extractors = HashMap::new();
+        let java_symbols = SymbolIndex::shared();
+        extractors.insert(
+            "java",
+            CombinedExtractor::of(vec![
+                Java::extractor_with_symbols(Rc::clone(&java_symbols)),
+                SpringData::extractor_with_symbols(java_symbols),
+            ]),
+        );
+
+        return Rc::new(LanguageBasedDialectResolver {
+            resolvers,
+            extractors,
+        });
     }
 }
 
@@ -35,4 +53,8 @@ impl DialectResolver for LanguageBasedDialectResolver {
             None => None,
         };
     }
+
+    fn resolve_extractor(&self, language_id: &String) -> Option<Rc<dyn DialectExtractor>> {
+        return self.extractors.get(language_id.as_str()).map(Rc::clone);
+    }
 }
@@ -5,9 +5,11 @@ use std::rc::Rc;
 use lsp_server::{Connection, ExtractError, IoThreads, Message, Notification, Request, RequestId};
 use lsp_types::notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument};
 use lsp_types::{
-    DiagnosticOptions, DiagnosticServerCapabilities, OneOf, ServerCapabilities,
+    DiagnosticOptions, DiagnosticServerCapabilities, InitializeParams, OneOf, ServerCapabilities,
     TextDocumentSyncCapability, TextDocumentSyncKind, WorkDoneProgressOptions,
 };
+use tracing::info;
+use url::Url;
 
 use crate::dialect_resolver::{DialectResolver, LanguageBasedDialectResolver};
 use crate::workspace::Workspace;
@@ -59,9 +61,12 @@ pub fn start_lsp_server() -> Result<IoThreads, Box<dyn Error + Sync + Send>> {
     let workspace = Workspace::new();
     let resolver = LanguageBasedDialectResolver::new();
 
-    connection
+    let initialization = connection
         .initialize(server_capabilities)
         .expect("Initialization failed due to wrong capabilities.");
+    if let Ok(params) = serde_json::from_value::<InitializeParams>(initialization) {
+        index_workspace_folders(&params, &workspace, Rc::clone(&resolver));
+    }
     main_connection_loop(connection, workspace, resolver)?;
     return Ok(io_threads);
 }
@@ -87,6 +92,7 @@ fn main_connection_loop(
                         else {
                             continue;
                         };
+                        log_extraction(&workspace, &params.text_document.uri);
                     }
                     _ => {}
                 }
@@ -96,6 +102,7 @@ fn main_connection_loop(
                         let Some(_file) = workspace.borrow_mut().update(&params) else {
                             continue;
                         };
+                        log_extraction(&workspace, &params.text_document.uri);
                     }
                     _ => {}
                 }
@@ -115,6 +122,41 @@ fn main_connection_loop(
     return Ok(());
 }
 
+fn index_workspace_folders(
+    params: &InitializeParams,
+    workspace: &RefCell<Workspace>,
+    resolver: Rc<dyn DialectResolver>,
+) {
+    for folder in params.workspace_folders.iter().flatten() {
+        let Ok(path) = folder.uri.to_file_path() else {
+            continue;
+        };
+
+        let indexed = workspace
+            .borrow_mut()
+            .index_folder(&path, Rc::clone(&resolver));
+        info!(
+            folder = folder.uri.as_str(),
+            files = indexed,
+            "Indexed workspace folder."
+        );
+    }
+}
+
+fn log_extraction(workspace: &RefCell<Workspace>, url: &Url) {
+    let Some(extraction) = workspace.borrow_mut().extract(url) else {
+        return;
+    };
+
+    info!(
+        url = url.as_str(),
+        executions = extraction.executions.len(),
+        warnings = extraction.warnings.len(),
+        schemas = extraction.schemas.len(),
+        "Extracted executions."
+    );
+}
+
 fn cast_request<R>(req: &Request) -> Result<(RequestId, R::Params), ExtractError<Request>>
 where
     R: lsp_types::request::Request,
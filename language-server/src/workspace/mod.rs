@@ -1,26 +1,38 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 use std::rc::Rc;
 
 use lsp_types::{
     DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
 };
-use tracing::info;
+use tracing::{info, warn};
 use tree_sitter::Tree;
 use url::Url;
 
-use dialect_interface::{DialectParser, FileResource, FileResourceChange, FileResourceChangeRange};
+use dialect_interface::{
+    DialectExtractor, Extraction, FileResource, FileResourceChange, FileResourceChangeRange,
+};
 
 use crate::dialect_resolver::DialectResolver;
 
+const IGNORED_FOLDERS: [&str; 4] = ["target", "build", "out", "node_modules"];
+
 pub struct Workspace {
     open_files: HashMap<Url, RefCell<FileResource>>,
+    extractors: HashMap<Url, Rc<dyn DialectExtractor>>,
+    extractions: HashMap<Url, (i32, u64, Rc<Extraction>)>,
+    index_generation: u64,
 }
 
 impl Workspace {
     pub fn new() -> RefCell<Workspace> {
         return RefCell::new(Workspace {
             open_files: HashMap::new(),
+            extractors: HashMap::new(),
+            extractions: HashMap::new(),
+            index_generation: 0,
         });
     }
 
@@ -35,8 +47,19 @@ impl Workspace {
             &params.text_document.text,
         ) {
             Some(dialect) => {
-                let resource = FileResource::new(&params.text_document.text, Rc::clone(&dialect));
+                let resource = FileResource::new(
+                    &params.text_document.text,
+                    params.text_document.version,
+                    Rc::clone(&dialect),
+                );
                 self.open_files.insert(url.clone(), resource.clone());
+                if let Some(extractor) =
+                    resolver.resolve_extractor(&params.text_document.language_id)
+                {
+                    extractor.index(&url.to_string(), &resource.borrow());
+                    self.extractors.insert(url.clone(), extractor);
+                    self.index_generation += 1;
+                }
                 return Some(resource.borrow().tree());
             }
             None => {
@@ -76,30 +99,141 @@ impl Workspace {
             })
             .collect::<Vec<FileResourceChange>>();
 
-        file_resource.borrow_mut().update(&changes);
+        file_resource
+            .borrow_mut()
+            .update(&changes, params.text_document.version);
+        if let Some(extractor) = self.extractors.get(&params.text_document.uri) {
+            extractor.index(
+                &params.text_document.uri.to_string(),
+                &file_resource.borrow(),
+            );
+            self.index_generation += 1;
+        }
         return Some(file_resource.borrow().tree());
     }
 
+    pub fn extract(&mut self, url: &Url) -> Option<Rc<Extraction>> {
+        let file_resource = self.open_files.get(url)?;
+        let extractor = self.extractors.get(url)?;
+        let version = file_resource.borrow().version();
+
+        if let Some((cached_version, generation, extraction)) = self.extractions.get(url) {
+            if *cached_version == version && *generation == self.index_generation {
+                return Some(Rc::clone(extraction));
+            }
+        }
+
+        return match extractor.extract(&file_resource.borrow()) {
+            Ok(extraction) => {
+                let extraction = Rc::new(extraction);
+                self.extractions.insert(
+                    url.clone(),
+                    (version, self.index_generation, Rc::clone(&extraction)),
+                );
+                Some(extraction)
+            }
+            Err(error) => {
+                warn!(
+                    url = url.as_str(),
+                    error = error.to_string(),
+                    "Extraction failed."
+                );
+                None
+            }
+        };
+    }
+
+    pub fn index_folder(&mut self, folder: &Path, resolver: Rc<dyn DialectResolver>) -> usize {
+        let Ok(entries) = fs::read_dir(folder) else {
+            return 0;
+        };
+
+        let mut indexed = 0;
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if file_name.starts_with('.') || IGNORED_FOLDERS.contains(&file_name.as_str()) {
+                continue;
+            }
+
+            if path.is_dir() {
+                indexed += self.index_folder(&path, Rc::clone(&resolver));
+                continue;
+            }
+
+            let language_id = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("java") => "java".to_string(),
+                _ => continue,
+            };
+            let (Ok(url), Ok(contents)) = (Url::from_file_path(&path), fs::read_to_string(&path))
+            else {
+                continue;
+            };
+            if self.open_files.contains_key(&url) {
+                continue;
+            }
+
+            let (Some(dialect), Some(extractor)) = (
+                resolver.resolve_dialect(&language_id, &contents),
+                resolver.resolve_extractor(&language_id),
+            ) else {
+                continue;
+            };
+            let resource = FileResource::new(&contents, 0, dialect);
+            extractor.index(&url.to_string(), &resource.borrow());
+            self.index_generation += 1;
+            indexed += 1;
+        }
+
+        return indexed;
+    }
+
     pub fn close(&mut self, params: &DidCloseTextDocumentParams) {
-        self.open_files.remove(&params.text_document.uri);
+        let url = &params.text_document.uri;
+        let resource = self.open_files.remove(url);
+        self.extractions.remove(url);
+        let (Some(resource), Some(extractor)) = (resource, self.extractors.remove(url)) else {
+            return;
+        };
+
+        extractor.forget(&url.to_string());
+        let saved = url
+            .to_file_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok());
+        if let Some(contents) = saved {
+            let saved = FileResource::new(&contents, 0, resource.borrow().parser());
+            extractor.index(&url.to_string(), &saved.borrow());
+        }
+        self.index_generation += 1;
     }
 }
 
 #[cfg(test)]
 mod tests {
     use lsp_types::{
-        TextDocumentContentChangeEvent, TextDocumentItem, VersionedTextDocumentIdentifier,
+        TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+        VersionedTextDocumentIdentifier,
     };
     use tree_sitter::Parser;
 
+    use dialect_interface::DialectParser;
+
     use super::*;
 
     struct Java {
         parser: RefCell<Parser>,
+        indexed: Rc<RefCell<Vec<String>>>,
     }
 
     impl Java {
         fn new() -> Self {
+            return Java::sharing(Rc::new(RefCell::new(vec![])));
+        }
+
+        fn sharing(indexed: Rc<RefCell<Vec<String>>>) -> Self {
             let mut parser = Parser::new();
             parser
                 .set_language(tree_sitter_java::language())
@@ -107,6 +241,7 @@ mod tests {
 
             return Java {
                 parser: RefCell::new(parser),
+                indexed,
             };
         }
     }
@@ -119,6 +254,27 @@ mod tests {
         ) -> Option<Rc<dyn DialectParser>> {
             return Some(Rc::new(Java::new()));
         }
+
+        fn resolve_extractor(&self, _language_id: &String) -> Option<Rc<dyn DialectExtractor>> {
+            return Some(Rc::new(Java::sharing(Rc::clone(&self.indexed))));
+        }
+    }
+
+    impl DialectExtractor for Java {
+        fn extract(
+            &self,
+            _file: &FileResource,
+        ) -> Result<Extraction, Box<dyn std::error::Error + Sync + Send>> {
+            return Ok(Extraction::default());
+        }
+
+        fn index(&self, path: &String, _file: &FileResource) {
+            self.indexed.borrow_mut().push(path.clone());
+        }
+
+        fn forget(&self, path: &String) {
+            self.indexed.borrow_mut().retain(|indexed| indexed != path);
+        }
     }
 
     impl DialectParser for Java {
@@ -192,4 +348,90 @@ mod tests {
 
         assert_eq!(tree.is_some(), true);
     }
+
+    #[test]
+    fn reuses_extractions_until_the_document_changes() {
+        let java = Rc::new(Java::new());
+        let ws = Workspace::new();
+        let url = Url::parse("file://my-ws/test.java").unwrap();
+        ws.borrow_mut().open(
+            &DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: url.clone(),
+                    language_id: "java".to_string(),
+                    version: 0,
+                    text: "class X {}".to_string(),
+                },
+            },
+            java,
+        );
+
+        let first = ws.borrow_mut().extract(&url).unwrap();
+        let cached = ws.borrow_mut().extract(&url).unwrap();
+        ws.borrow_mut().update(&DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: url.clone(),
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                text: "class Y {}".to_string(),
+                range: None,
+                range_length: None,
+            }],
+        });
+        let updated = ws.borrow_mut().extract(&url).unwrap();
+
+        assert!(Rc::ptr_eq(&first, &cached));
+        assert!(!Rc::ptr_eq(&first, &updated));
+    }
+
+    #[test]
+    fn invalidates_extractions_when_another_file_is_indexed() {
+        let java = Rc::new(Java::new());
+        let ws = Workspace::new();
+        let url = Url::parse("file://my-ws/test.java").unwrap();
+        let open = |uri: &Url| DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "java".to_string(),
+                version: 0,
+                text: "class X {}".to_string(),
+            },
+        };
+
+        ws.borrow_mut()
+            .open(&open(&url), Rc::clone(&java) as Rc<dyn DialectResolver>);
+        let first = ws.borrow_mut().extract(&url).unwrap();
+        ws.borrow_mut()
+            .open(&open(&Url::parse("file://my-ws/other.java").unwrap()), java);
+        let after_indexing = ws.borrow_mut().extract(&url).unwrap();
+
+        assert!(!Rc::ptr_eq(&first, &after_indexing));
+    }
+
+    #[test]
+    fn forgets_closed_files_without_a_saved_copy() {
+        let java = Rc::new(Java::new());
+        let ws = Workspace::new();
+        let url = Url::parse("file://my-ws/test.java").unwrap();
+        ws.borrow_mut().open(
+            &DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: url.clone(),
+                    language_id: "java".to_string(),
+                    version: 0,
+                    text: "class X {}".to_string(),
+                },
+            },
+            Rc::clone(&java) as Rc<dyn DialectResolver>,
+        );
+        let indexed = java.indexed.borrow().clone();
+
+        ws.borrow_mut().close(&DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri: url.clone() },
+        });
+
+        assert_eq!(indexed, vec![url.to_string()]);
+        assert_eq!(*java.indexed.borrow(), Vec::<String>::new());
+    }
 }
@@ -0,0 +1,173 @@
+use crate::filter::FilterOperator;
+use crate::values::Value;
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Expression {
+    Literal(Value),
+    FieldPath(String),
+    Variable(String),
+    Object(Vec<(String, Expression)>),
+    Array(Vec<Expression>),
+    Operator {
+        name: String,
+        arguments: Vec<Expression>,
+    },
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum ProjectionField {
+    Include,
+    Exclude,
+    Computed(Expression),
+    Slice { skip: Option<Value>, limit: Value },
+    ElemMatch(FilterOperator),
+    Meta(String),
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+    TextScore,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum MergeWhenMatched {
+    Replace,
+    KeepExisting,
+    Merge,
+    Fail,
+    Pipeline(Vec<Stage>),
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum MergeWhenNotMatched {
+    Insert,
+    Discard,
+    Fail,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Stage {
+    Match {
+        predicate: FilterOperator,
+    },
+    Project {
+        fields: Vec<(String, ProjectionField)>,
+    },
+    Group {
+        id: Expression,
+        accumulators: Vec<(String, Expression)>,
+    },
+    Sort {
+        fields: Vec<(String, SortDirection)>,
+    },
+    Limit {
+        limit: Value,
+    },
+    Skip {
+        skip: Value,
+    },
+    Sample {
+        size: Value,
+    },
+    Lookup {
+        from: String,
+        local_field: Option<String>,
+        foreign_field: Option<String>,
+        let_variables: Vec<(String, Expression)>,
+        pipeline: Vec<Stage>,
+        as_field: String,
+    },
+    GraphLookup {
+        from: String,
+        start_with: Expression,
+        connect_from_field: String,
+        connect_to_field: String,
+        as_field: String,
+        max_depth: Option<Value>,
+        depth_field: Option<String>,
+        restrict_search_with_match: Option<Box<FilterOperator>>,
+    },
+    Unwind {
+        path: String,
+        include_array_index: Option<String>,
+        preserve_null_and_empty_arrays: bool,
+    },
+    Facet {
+        facets: Vec<(String, Vec<Stage>)>,
+    },
+    AddFields {
+        fields: Vec<(String, Expression)>,
+    },
+    Set {
+        fields: Vec<(String, Expression)>,
+    },
+    Unset {
+        fields: Vec<String>,
+    },
+    ReplaceRoot {
+        new_root: Expression,
+    },
+    ReplaceWith {
+        new_root: Expression,
+    },
+    Count {
+        field: String,
+    },
+    Bucket {
+        group_by: Expression,
+        boundaries: Vec<Value>,
+        default: Option<Value>,
+        output: Vec<(String, Expression)>,
+    },
+    Merge {
+        database: Option<String>,
+        collection: String,
+        on: Vec<String>,
+        let_variables: Vec<(String, Expression)>,
+        when_matched: Option<MergeWhenMatched>,
+        when_not_matched: Option<MergeWhenNotMatched>,
+    },
+    Out {
+        database: Option<String>,
+        collection: String,
+    },
+    Search {
+        index: Option<String>,
+        definition: Value,
+    },
+    Other {
+        name: String,
+        definition: Value,
+    },
+}
+
+impl Stage {
+    pub fn name(&self) -> &str {
+        return match self {
+            Stage::Match { .. } => "$match",
+            Stage::Project { .. } => "$project",
+            Stage::Group { .. } => "$group",
+            Stage::Sort { .. } => "$sort",
+            Stage::Limit { .. } => "$limit",
+            Stage::Skip { .. } => "$skip",
+            Stage::Sample { .. } => "$sample",
+            Stage::Lookup { .. } => "$lookup",
+            Stage::GraphLookup { .. } => "$graphLookup",
+            Stage::Unwind { .. } => "$unwind",
+            Stage::Facet { .. } => "$facet",
+            Stage::AddFields { .. } => "$addFields",
+            Stage::Set { .. } => "$set",
+            Stage::Unset { .. } => "$unset",
+            Stage::ReplaceRoot { .. } => "$replaceRoot",
+            Stage::ReplaceWith { .. } => "$replaceWith",
+            Stage::Count { .. } => "$count",
+            Stage::Bucket { .. } => "$bucket",
+            Stage::Merge { .. } => "$merge",
+            Stage::Out { .. } => "$out",
+            Stage::Search { .. } => "$search",
+            Stage::Other { name, .. } => name.as_str(),
+        };
+    }
+}
@@ -0,0 +1,75 @@
+use crate::filter::FilterOperator;
+use crate::values::Value;
+
+#[derive(PartialEq, Debug, Default)]
+pub struct IndexOptions {
+    pub name: Option<String>,
+    pub unique: bool,
+    pub sparse: bool,
+    pub hidden: bool,
+    pub expire_after_seconds: Option<Value>,
+    pub partial_filter: Option<FilterOperator>,
+    pub collation: Option<Value>,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct IndexDefinition {
+    pub keys: Value,
+    pub options: IndexOptions,
+}
+
+impl IndexDefinition {
+    pub fn key_pattern(&self) -> Vec<(String, String)> {
+        let Value::Object(keys, _) = &self.keys else {
+            return vec![];
+        };
+
+        return keys
+            .iter()
+            .map(|(field, key)| {
+                let kind = match key {
+                    Value::Integer(direction, _) => direction.to_string(),
+                    Value::Int64(direction, _) => direction.to_string(),
+                    Value::String(kind, _) => kind.clone(),
+                    Value::Reference(reference, _, _) => reference.clone(),
+                    _ => "<unknown>".to_string(),
+                };
+                (field.clone(), kind)
+            })
+            .collect();
+    }
+
+    pub fn name(&self) -> String {
+        if let Some(name) = &self.options.name {
+            return name.clone();
+        }
+
+        return self
+            .key_pattern()
+            .into_iter()
+            .map(|(field, kind)| format!("{}_{}", field, kind))
+            .collect::<Vec<String>>()
+            .join("_");
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct TimeSeriesOptions {
+    pub time_field: String,
+    pub meta_field: Option<String>,
+    pub granularity: Option<String>,
+}
+
+#[derive(PartialEq, Debug, Default)]
+pub struct CollectionOptions {
+    pub capped: bool,
+    pub size_in_bytes: Option<Value>,
+    pub max_documents: Option<Value>,
+    pub validator: Option<FilterOperator>,
+    pub validation_level: Option<String>,
+    pub validation_action: Option<String>,
+    pub time_series: Option<TimeSeriesOptions>,
+    pub expire_after_seconds: Option<Value>,
+    pub clustered_index: Option<IndexDefinition>,
+    pub collation: Option<Value>,
+}
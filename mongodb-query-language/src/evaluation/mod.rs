@@ -0,0 +1,757 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use bson::{Bson, Document};
+use regex::RegexBuilder;
+
+use crate::filter::FilterOperator;
+use crate::serialization::ToBson;
+use crate::values::{BsonType, Value};
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct EvaluationError {
+    pub message: String,
+}
+
+impl EvaluationError {
+    pub fn new(message: String) -> EvaluationError {
+        return EvaluationError { message };
+    }
+}
+
+impl Display for EvaluationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}", self.message);
+    }
+}
+
+impl Error for EvaluationError {}
+
+#[derive(Clone, Copy)]
+enum Scope<'a> {
+    Document(&'a Document),
+    Element(&'a Bson),
+}
+
+pub fn matches(predicate: &FilterOperator, document: &Document) -> Result<bool, EvaluationError> {
+    return evaluate(predicate, Scope::Document(document));
+}
+
+pub fn matching<'a>(
+    predicate: &FilterOperator,
+    documents: &'a [Document],
+) -> Result<Vec<&'a Document>, EvaluationError> {
+    let mut result = vec![];
+    for document in documents {
+        if matches(predicate, document)? {
+            result.push(document);
+        }
+    }
+
+    return Ok(result);
+}
+
+fn evaluate(predicate: &FilterOperator, scope: Scope) -> Result<bool, EvaluationError> {
+    return match predicate {
+        FilterOperator::And { predicates, .. } => all(predicates, scope),
+        FilterOperator::Or { predicates, .. } => {
+            for predicate in predicates {
+                if evaluate(predicate, scope)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+        FilterOperator::Nor { predicates, .. } => {
+            for predicate in predicates {
+                if evaluate(predicate, scope)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        FilterOperator::Not { predicates, .. } => Ok(!all(predicates, scope)?),
+        FilterOperator::Equals { field, value, .. } => {
+            Ok(equals_any(&resolve(scope, field), &literal(value)?))
+        }
+        FilterOperator::NotEquals { field, value, .. } => {
+            Ok(!equals_any(&resolve(scope, field), &literal(value)?))
+        }
+        FilterOperator::GreaterThan { field, value, .. } => {
+            compare_any(scope, field, value, |o| o == Ordering::Greater)
+        }
+        FilterOperator::GreaterThanOrEquals { field, value, .. } => {
+            compare_any(scope, field, value, |o| o != Ordering::Less)
+        }
+        FilterOperator::LessThan { field, value, .. } => {
+            compare_any(scope, field, value, |o| o == Ordering::Less)
+        }
+        FilterOperator::LessThanOrEquals { field, value, .. } => {
+            compare_any(scope, field, value, |o| o != Ordering::Greater)
+        }
+        FilterOperator::In { field, values, .. } => in_any(&resolve(scope, field), values),
+        FilterOperator::NotIn { field, values, .. } => Ok(!in_any(&resolve(scope, field), values)?),
+        FilterOperator::Exists { field, exists, .. } => {
+            Ok(resolve(scope, field).iter().any(Option::is_some) == *exists)
+        }
+        FilterOperator::Type { field, types, .. } => {
+            let candidates = resolve(scope, field);
+            let is_array = candidates
+                .iter()
+                .any(|candidate| matches!(candidate, Some(Bson::Array(_))));
+
+            Ok(types.iter().any(|expected| {
+                (*expected == BsonType::Array && is_array)
+                    || expand(&candidates)
+                        .iter()
+                        .any(|candidate| expected.overlaps(&bson_type(candidate)))
+            }))
+        }
+        FilterOperator::Regex {
+            field,
+            pattern,
+            options,
+            ..
+        } => {
+            let regex = match literal(pattern)? {
+                Bson::String(pattern) => compile(&pattern, options.as_deref().unwrap_or(""))?,
+                Bson::RegularExpression(regex) => {
+                    compile(&regex.pattern, options.as_deref().unwrap_or(&regex.options))?
+                }
+                _ => return Err(unsupported("$regex with a non-string pattern")),
+            };
+
+            Ok(expand(&resolve(scope, field))
+                .iter()
+                .any(|candidate| regex_matches(&regex, candidate)))
+        }
+        FilterOperator::ElemMatch {
+            field, predicates, ..
+        } => {
+            for candidate in resolve(scope, field).into_iter().flatten() {
+                let Bson::Array(elements) = candidate else {
+                    continue;
+                };
+
+                for element in elements {
+                    if element_matches(predicates, element)? {
+                        return Ok(true);
+                    }
+                }
+            }
+            Ok(false)
+        }
+        FilterOperator::Size { field, size, .. } => {
+            let size = integer(&literal(size)?, "$size")?;
+            Ok(resolve(scope, field)
+                .iter()
+                .any(|candidate| match candidate {
+                    Some(Bson::Array(elements)) => elements.len() as i64 == size,
+                    _ => false,
+                }))
+        }
+        FilterOperator::All { field, values, .. } => {
+            if values.is_empty() {
+                return Ok(false);
+            }
+
+            let candidates = resolve(scope, field);
+            for value in values {
+                let value = literal(value)?;
+                let matched = match &value {
+                    Bson::RegularExpression(regex) => {
+                        let regex = compile(&regex.pattern, &regex.options)?;
+                        expand(&candidates)
+                            .iter()
+                            .any(|candidate| regex_matches(&regex, candidate))
+                    }
+                    _ => equals_any(&candidates, &value),
+                };
+
+                if !matched {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        FilterOperator::Mod {
+            field,
+            divisor,
+            remainder,
+            ..
+        } => {
+            let divisor = integer(&literal(divisor)?, "$mod")?;
+            let remainder = integer(&literal(remainder)?, "$mod")?;
+            if divisor == 0 {
+                return Err(EvaluationError::new("$mod divisor cannot be 0".to_string()));
+            }
+
+            for candidate in expand(&resolve(scope, field)) {
+                let Some(dividend) = number(candidate).filter(|n| n.is_finite()) else {
+                    continue;
+                };
+                let rest = (dividend.trunc() as i64)
+                    .checked_rem(divisor)
+                    .ok_or(EvaluationError::new("$mod overflowed".to_string()))?;
+                if rest == remainder {
+                    return Ok(true);
+                }
+            }
+
+            Ok(false)
+        }
+        FilterOperator::BitsAllSet { field, mask, .. } => {
+            bits_any(scope, field, mask, |bits, positions| {
+                positions.iter().all(|p| bit(bits, *p))
+            })
+        }
+        FilterOperator::BitsAnySet { field, mask, .. } => {
+            bits_any(scope, field, mask, |bits, positions| {
+                positions.iter().any(|p| bit(bits, *p))
+            })
+        }
+        FilterOperator::BitsAllClear { field, mask, .. } => {
+            bits_any(scope, field, mask, |bits, positions| {
+                positions.iter().all(|p| !bit(bits, *p))
+            })
+        }
+        FilterOperator::BitsAnyClear { field, mask, .. } => {
+            bits_any(scope, field, mask, |bits, positions| {
+                positions.iter().any(|p| !bit(bits, *p))
+            })
+        }
+        FilterOperator::Comment { .. } => Ok(true),
+        FilterOperator::GeoWithin { .. } => Err(unsupported("$geoWithin")),
+        FilterOperator::GeoIntersects { .. } => Err(unsupported("$geoIntersects")),
+        FilterOperator::Near { .. } => Err(unsupported("$near")),
+        FilterOperator::NearSphere { .. } => Err(unsupported("$nearSphere")),
+        FilterOperator::Expr { .. } => Err(unsupported("$expr")),
+        FilterOperator::JsonSchema { .. } => Err(unsupported("$jsonSchema")),
+        FilterOperator::Text { .. } => Err(unsupported("$text")),
+        FilterOperator::Where { .. } => Err(unsupported("$where")),
+        FilterOperator::SampleRate { .. } => Err(unsupported("$sampleRate")),
+    };
+}
+
+fn all(predicates: &[FilterOperator], scope: Scope) -> Result<bool, EvaluationError> {
+    for predicate in predicates {
+        if !evaluate(predicate, scope)? {
+            return Ok(false);
+        }
+    }
+
+    return Ok(true);
+}
+
+fn element_matches(predicates: &[FilterOperator], element: &Bson) -> Result<bool, EvaluationError> {
+    // Only operator-only $elemMatch predicates apply to scalars, field predicates
+    // need the element to be a sub-document.
+    let on_element = predicates
+        .iter()
+        .all(|predicate| predicate.field().map(|f| f.is_empty()).unwrap_or(false));
+    if !on_element && !matches!(element, Bson::Document(_)) {
+        return Ok(false);
+    }
+
+    return all(predicates, Scope::Element(element));
+}
+
+fn unsupported(operator: &str) -> EvaluationError {
+    return EvaluationError::new(format!("{} can not be evaluated statically", operator));
+}
+
+fn literal(value: &Value) -> Result<Bson, EvaluationError> {
+    if let Some(name) = unresolved_reference(value) {
+        return Err(EvaluationError::new(format!(
+            "{} is not known until runtime",
+            name
+        )));
+    }
+
+    return Ok(value.to_bson());
+}
+
+fn unresolved_reference(value: &Value) -> Option<&String> {
+    return match value {
+        Value::Reference(name, _, _) => Some(name),
+        Value::Object(fields, _) => fields
+            .iter()
+            .find_map(|(_, value)| unresolved_reference(value)),
+        Value::Array(values, _) => values.iter().find_map(unresolved_reference),
+        _ => None,
+    };
+}
+
+// A path resolves to every value it reaches, descending into arrays of
+// sub-documents. `None` marks a branch where the path is missing.
+fn resolve<'a>(scope: Scope<'a>, field: &str) -> Vec<Option<&'a Bson>> {
+    return match scope {
+        Scope::Element(element) if field.is_empty() => vec![Some(element)],
+        Scope::Element(Bson::Document(document)) | Scope::Document(document) => {
+            resolve_in_document(document, &field.split('.').collect::<Vec<&str>>())
+        }
+        Scope::Element(_) => vec![None],
+    };
+}
+
+fn resolve_in_document<'a>(document: &'a Document, path: &[&str]) -> Vec<Option<&'a Bson>> {
+    return match document.get(path[0]) {
+        Some(value) => resolve_in_value(value, &path[1..]),
+        None => vec![None],
+    };
+}
+
+fn resolve_in_value<'a>(value: &'a Bson, path: &[&str]) -> Vec<Option<&'a Bson>> {
+    if path.is_empty() {
+        return vec![Some(value)];
+    }
+
+    return match value {
+        Bson::Document(document) => resolve_in_document(document, path),
+        Bson::Array(elements) => {
+            if let Ok(index) = path[0].parse::<usize>() {
+                return match elements.get(index) {
+                    Some(element) => resolve_in_value(element, &path[1..]),
+                    None => vec![None],
+                };
+            }
+
+            let resolved: Vec<Option<&Bson>> = elements
+                .iter()
+                .filter_map(|element| match element {
+                    Bson::Document(document) => Some(resolve_in_document(document, path)),
+                    _ => None,
+                })
+                .flatten()
+                .collect();
+
+            if resolved.is_empty() {
+                vec![None]
+            } else {
+                resolved
+            }
+        }
+        _ => vec![None],
+    };
+}
+
+// Comparisons see an array both as a whole and through each of its elements.
+fn expand<'a>(candidates: &[Option<&'a Bson>]) -> Vec<&'a Bson> {
+    let mut expanded = vec![];
+    for candidate in candidates.iter().flatten() {
+        expanded.push(*candidate);
+        if let Bson::Array(elements) = candidate {
+            expanded.extend(elements.iter());
+        }
+    }
+
+    return expanded;
+}
+
+fn equals_any(candidates: &[Option<&Bson>], value: &Bson) -> bool {
+    if is_null(value) && candidates.iter().any(Option::is_none) {
+        return true;
+    }
+
+    return expand(candidates)
+        .iter()
+        .any(|candidate| compare(candidate, value) == Some(Ordering::Equal));
+}
+
+fn in_any(candidates: &[Option<&Bson>], values: &[Value]) -> Result<bool, EvaluationError> {
+    for value in values {
+        let matched = match literal(value)? {
+            Bson::RegularExpression(regex) => {
+                let regex = compile(&regex.pattern, &regex.options)?;
+                expand(candidates)
+                    .iter()
+                    .any(|candidate| regex_matches(&regex, candidate))
+            }
+            value => equals_any(candidates, &value),
+        };
+
+        if matched {
+            return Ok(true);
+        }
+    }
+
+    return Ok(false);
+}
+
+fn compare_any(
+    scope: Scope,
+    field: &str,
+    value: &Value,
+    accept: fn(Ordering) -> bool,
+) -> Result<bool, EvaluationError> {
+    let value = literal(value)?;
+    let candidates = resolve(scope, field);
+    if is_null(&value) && accept(Ordering::Equal) && equals_any(&candidates, &value) {
+        return Ok(true);
+    }
+
+    // Range operators only match values of the same type bracket, except for
+    // $minKey and $maxKey which bound every other value.
+    let bounded = matches!(value, Bson::MinKey | Bson::MaxKey);
+    return Ok(expand(&candidates).iter().any(|candidate| {
+        (bounded || type_rank(candidate) == type_rank(&value))
+            && compare(candidate, &value).map(accept).unwrap_or(false)
+    }));
+}
+
+fn is_null(value: &Bson) -> bool {
+    return matches!(value, Bson::Null | Bson::Undefined);
+}
+
+fn type_rank(value: &Bson) -> u8 {
+    return match value {
+        Bson::MinKey => 1,
+        Bson::Null | Bson::Undefined => 2,
+        Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_) | Bson::Decimal128(_) => 3,
+        Bson::String(_) | Bson::Symbol(_) => 4,
+        Bson::Document(_) => 5,
+        Bson::Array(_) => 6,
+        Bson::Binary(_) => 7,
+        Bson::ObjectId(_) => 8,
+        Bson::Boolean(_) => 9,
+        Bson::DateTime(_) => 10,
+        Bson::Timestamp(_) => 11,
+        Bson::RegularExpression(_) => 12,
+        Bson::DbPointer(_) => 13,
+        Bson::JavaScriptCode(_) => 14,
+        Bson::JavaScriptCodeWithScope(_) => 15,
+        Bson::MaxKey => 16,
+    };
+}
+
+fn number(value: &Bson) -> Option<f64> {
+    return match value {
+        Bson::Int32(value) => Some(*value as f64),
+        Bson::Int64(value) => Some(*value as f64),
+        Bson::Double(value) => Some(*value),
+        Bson::Decimal128(value) => value.to_string().parse().ok(),
+        _ => None,
+    };
+}
+
+fn integer(value: &Bson, operator: &str) -> Result<i64, EvaluationError> {
+    return number(value)
+        .filter(|n| n.fract() == 0.0)
+        .map(|n| n as i64)
+        .ok_or_else(|| EvaluationError::new(format!("{} expects an integer", operator)));
+}
+
+fn string(value: &Bson) -> Option<&str> {
+    return match value {
+        Bson::String(value) | Bson::Symbol(value) => Some(value),
+        _ => None,
+    };
+}
+
+// Total order of BSON values as the server sorts them: first by type bracket,
+// then by value. Returns `None` for values without a meaningful order (NaN,
+// JavaScript code).
+fn compare(left: &Bson, right: &Bson) -> Option<Ordering> {
+    let by_type = type_rank(left).cmp(&type_rank(right));
+    if by_type != Ordering::Equal {
+        return Some(by_type);
+    }
+
+    return match (left, right) {
+        (Bson::Int64(left), Bson::Int64(right)) => Some(left.cmp(right)),
+        (Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_) | Bson::Decimal128(_), _) => {
+            number(left)?.partial_cmp(&number(right)?)
+        }
+        (Bson::String(_) | Bson::Symbol(_), _) => Some(string(left)?.cmp(string(right)?)),
+        (Bson::Document(left), Bson::Document(right)) => {
+            for ((left_key, left), (right_key, right)) in left.iter().zip(right.iter()) {
+                let ordering = type_rank(left)
+                    .cmp(&type_rank(right))
+                    .then(left_key.cmp(right_key))
+                    .then(compare(left, right)?);
+                if ordering != Ordering::Equal {
+                    return Some(ordering);
+                }
+            }
+            Some(left.len().cmp(&right.len()))
+        }
+        (Bson::Array(left), Bson::Array(right)) => {
+            for (left, right) in left.iter().zip(right.iter()) {
+                let ordering = compare(left, right)?;
+                if ordering != Ordering::Equal {
+                    return Some(ordering);
+                }
+            }
+            Some(left.len().cmp(&right.len()))
+        }
+        (Bson::Binary(left), Bson::Binary(right)) => Some(
+            left.bytes
+                .len()
+                .cmp(&right.bytes.len())
+                .then(u8::from(left.subtype).cmp(&u8::from(right.subtype)))
+                .then(left.bytes.cmp(&right.bytes)),
+        ),
+        (Bson::ObjectId(left), Bson::ObjectId(right)) => Some(left.bytes().cmp(&right.bytes())),
+        (Bson::Boolean(left), Bson::Boolean(right)) => Some(left.cmp(right)),
+        (Bson::DateTime(left), Bson::DateTime(right)) => Some(left.cmp(right)),
+        (Bson::Timestamp(left), Bson::Timestamp(right)) => Some(
+            left.time
+                .cmp(&right.time)
+                .then(left.increment.cmp(&right.increment)),
+        ),
+        (Bson::RegularExpression(left), Bson::RegularExpression(right)) => Some(
+            left.pattern
+                .cmp(&right.pattern)
+                .then(left.options.cmp(&right.options)),
+        ),
+        (Bson::MinKey, _) | (Bson::MaxKey, _) | (Bson::Null | Bson::Undefined, _) => {
+            Some(Ordering::Equal)
+        }
+        _ => None,
+    };
+}
+
+fn bson_type(value: &Bson) -> BsonType {
+    return match value {
+        Bson::Double(_) => BsonType::Double,
+        Bson::String(_) => BsonType::String,
+        Bson::Document(_) => BsonType::Object,
+        Bson::Array(_) => BsonType::Array,
+        Bson::Binary(_) => BsonType::BinData,
+        Bson::Undefined => BsonType::Undefined,
+        Bson::ObjectId(_) => BsonType::ObjectId,
+        Bson::Boolean(_) => BsonType::Bool,
+        Bson::DateTime(_) => BsonType::Date,
+        Bson::Null => BsonType::Null,
+        Bson::RegularExpression(_) => BsonType::Regex,
+        Bson::DbPointer(_) => BsonType::DbPointer,
+        Bson::JavaScriptCode(_) => BsonType::JavaScript,
+        Bson::Symbol(_) => BsonType::Symbol,
+        Bson::JavaScriptCodeWithScope(_) => BsonType::JavaScriptWithScope,
+        Bson::Int32(_) => BsonType::Int,
+        Bson::Timestamp(_) => BsonType::Timestamp,
+        Bson::Int64(_) => BsonType::Long,
+        Bson::Decimal128(_) => BsonType::Decimal,
+        Bson::MinKey => BsonType::MinKey,
+        Bson::MaxKey => BsonType::MaxKey,
+    };
+}
+
+fn compile(pattern: &str, options: &str) -> Result<regex::Regex, EvaluationError> {
+    return RegexBuilder::new(pattern)
+        .case_insensitive(options.contains('i'))
+        .multi_line(options.contains('m'))
+        .dot_matches_new_line(options.contains('s'))
+        .ignore_whitespace(options.contains('x'))
+        .build()
+        .map_err(|e| EvaluationError::new(format!("invalid regular expression: {}", e)));
+}
+
+fn regex_matches(regex: &regex::Regex, candidate: &Bson) -> bool {
+    return string(candidate)
+        .map(|value| regex.is_match(value))
+        .unwrap_or(false);
+}
+
+// Bit positions are counted from the least significant bit. Integers are
+// sign-extended and binary data is read as little-endian bytes.
+enum Bits<'a> {
+    Integer(i64),
+    Binary(&'a [u8]),
+}
+
+fn bit(bits: &Bits, position: u32) -> bool {
+    return match bits {
+        Bits::Integer(value) => {
+            if position >= 64 {
+                *value < 0
+            } else {
+                (value >> position) & 1 == 1
+            }
+        }
+        Bits::Binary(bytes) => bytes
+            .get((position / 8) as usize)
+            .map(|byte| (byte >> (position % 8)) & 1 == 1)
+            .unwrap_or(false),
+    };
+}
+
+fn bits_of(value: &Bson) -> Option<Bits<'_>> {
+    return match value {
+        Bson::Binary(binary) => Some(Bits::Binary(&binary.bytes)),
+        _ => number(value)
+            .filter(|n| n.fract() == 0.0 && n.abs() < i64::MAX as f64)
+            .map(|n| Bits::Integer(n as i64)),
+    };
+}
+
+fn mask_positions(mask: &Bson) -> Result<Vec<u32>, EvaluationError> {
+    let invalid =
+        || EvaluationError::new("bitwise operators expect a non-negative mask".to_string());
+
+    return match mask {
+        Bson::Array(positions) => positions
+            .iter()
+            .map(|position| match integer(position, "bit position") {
+                Ok(position) if position >= 0 => Ok(position as u32),
+                _ => Err(invalid()),
+            })
+            .collect(),
+        Bson::Binary(binary) => Ok((0..binary.bytes.len() as u32 * 8)
+            .filter(|position| bit(&Bits::Binary(&binary.bytes), *position))
+            .collect()),
+        _ => match integer(mask, "bitmask") {
+            Ok(mask) if mask >= 0 => Ok((0..63)
+                .filter(|position| bit(&Bits::Integer(mask), *position))
+                .collect()),
+            _ => Err(invalid()),
+        },
+    };
+}
+
+fn bits_any(
+    scope: Scope,
+    field: &str,
+    mask: &Value,
+    accept: fn(&Bits, &[u32]) -> bool,
+) -> Result<bool, EvaluationError> {
+    let positions = mask_positions(&literal(mask)?)?;
+
+    return Ok(resolve(scope, field)
+        .into_iter()
+        .flatten()
+        .filter_map(bits_of)
+        .any(|bits| accept(&bits, &positions)));
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{doc, Document};
+
+    use crate::evaluation::{matches, matching};
+    use crate::filter::FilterOperator;
+    use crate::parser::parse_filter;
+    use crate::span::Span;
+    use crate::values::{BsonType, Value, ValueType};
+
+    fn check(filter: &str, document: Document) -> bool {
+        return matches(&parse_filter(filter).unwrap(), &document).unwrap();
+    }
+
+    #[test]
+    fn compares_values_with_type_bracketing() {
+        let document = doc! { "age": 42_i64, "name": "mongold", "score": 9.5 };
+
+        assert!(check(r#"{ age: 42 }"#, document.clone()));
+        assert!(check(
+            r#"{ age: { $gt: 18.5, $lte: 42 } }"#,
+            document.clone()
+        ));
+        assert!(check(r#"{ name: { $gte: "m" } }"#, document.clone()));
+        assert!(!check(r#"{ name: { $gt: 1 } }"#, document.clone()));
+        assert!(!check(r#"{ score: { $lt: "a" } }"#, document.clone()));
+        assert!(check(r#"{ score: { $gt: MinKey() } }"#, document.clone()));
+        assert!(check(
+            r#"{ $or: [ { age: 1 }, { name: /^MON/i } ] }"#,
+            document
+        ));
+    }
+
+    #[test]
+    fn applies_array_semantics() {
+        let document = doc! {
+            "tags": ["a", "b"],
+            "results": [ { "product": "x", "score": 8 }, { "product": "y", "score": 3 } ],
+        };
+
+        assert!(check(r#"{ tags: "a" }"#, document.clone()));
+        assert!(check(r#"{ tags: ["a", "b"] }"#, document.clone()));
+        assert!(check(
+            r#"{ tags: { $all: ["b", "a"] }, "tags.1": "b" }"#,
+            document.clone()
+        ));
+        assert!(check(
+            r#"{ tags: { $size: 2, $type: "array" } }"#,
+            document.clone()
+        ));
+        assert!(check(r#"{ "results.product": "y" }"#, document.clone()));
+        assert!(check(
+            r#"{ results: { $elemMatch: { product: "x", score: { $gte: 8 } } } }"#,
+            document.clone()
+        ));
+        assert!(!check(
+            r#"{ results: { $elemMatch: { product: "y", score: { $gte: 8 } } } }"#,
+            document
+        ));
+    }
+
+    #[test]
+    fn treats_missing_fields_like_null() {
+        let document = doc! { "name": "mongold", "deletedAt": null };
+
+        assert!(check(r#"{ missing: null }"#, document.clone()));
+        assert!(check(
+            r#"{ deletedAt: { $exists: true, $in: [null] } }"#,
+            document.clone()
+        ));
+        assert!(check(
+            r#"{ missing: { $exists: false, $ne: 1, $nin: ["a"] } }"#,
+            document.clone()
+        ));
+        assert!(!check(
+            r#"{ name: { $not: { $type: "string" } } }"#,
+            document.clone()
+        ));
+        assert!(check(
+            r#"{ $nor: [ { name: { $exists: false } } ] }"#,
+            document
+        ));
+    }
+
+    #[test]
+    fn evaluates_arithmetic_and_bitwise_operators() {
+        let document = doc! { "count": 10, "flags": 5 };
+
+        assert!(check(r#"{ count: { $mod: [4, 2] } }"#, document.clone()));
+        assert!(check(
+            r#"{ flags: { $bitsAllSet: [0, 2], $bitsAnyClear: 2 } }"#,
+            document.clone()
+        ));
+        assert!(!check(r#"{ flags: { $bitsAnySet: 2 } }"#, document));
+    }
+
+    #[test]
+    fn reports_invalid_modulo_as_evaluation_errors() {
+        let by_zero = parse_filter(r#"{ count: { $mod: [0, 0] } }"#).unwrap();
+        let overflow = parse_filter(r#"{ count: { $mod: [-1, 0] } }"#).unwrap();
+
+        assert!(matches(&by_zero, &doc! { "count": 10 }).is_err());
+        assert!(matches(&overflow, &doc! { "count": i64::MIN }).is_err());
+    }
+
+    #[test]
+    fn refuses_to_evaluate_unresolved_references() {
+        let filter = FilterOperator::Equals {
+            field: "_id".to_string(),
+            value: Value::Reference(
+                "id".to_string(),
+                ValueType::Of(BsonType::ObjectId),
+                Span::default(),
+            ),
+            span: Span::default(),
+        };
+
+        assert!(matches(&filter, &doc! { "_id": 1 }).is_err());
+        assert!(matches(&parse_filter(r#"{ $where: "true" }"#).unwrap(), &doc! {}).is_err());
+    }
+
+    #[test]
+    fn selects_the_matching_samples() {
+        let samples = vec![doc! { "n": 1 }, doc! { "n": 2 }, doc! { "n": 3 }];
+
+        let matched = matching(&parse_filter(r#"{ n: { $gte: 2 } }"#).unwrap(), &samples).unwrap();
+
+        assert_eq!(matched, vec![&samples[1], &samples[2]]);
+    }
+}
@@ -1,7 +1,9 @@
+use crate::aggregation::{ProjectionField, SortDirection, Stage};
+use crate::ddl::{CollectionOptions, IndexDefinition};
 use crate::filter::FilterOperator;
-use std::cell::RefCell;
-use std::error::Error;
-use tree_sitter::Tree;
+use crate::span::Span;
+use crate::update::{Update, UpdateOptions, WriteModel};
+use crate::values::Value;
 
 #[derive(Eq, PartialEq, Debug, Clone)]
 pub struct ExecutionNamespace {
@@ -19,21 +21,249 @@ impl ExecutionNamespace {
         };
     }
 }
+
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct ExecutionContext {
+    pub session: Option<String>,
+    pub transaction: Option<Span>,
+    pub in_loop: bool,
+}
+
+impl ExecutionContext {
+    pub fn in_transaction(&self) -> bool {
+        return self.transaction.is_some();
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Hint {
+    Name(String),
+    Keys(Value),
+}
+
+#[derive(PartialEq, Debug, Default)]
+pub struct FindOptions {
+    pub projection: Vec<(String, ProjectionField)>,
+    pub sort: Vec<(String, SortDirection)>,
+    pub skip: Option<Value>,
+    pub limit: Option<Value>,
+    pub hint: Option<Hint>,
+    pub collation: Option<Value>,
+    pub max_time_ms: Option<Value>,
+}
+
 #[derive(PartialEq, Debug)]
 pub enum Execution {
     FindOne {
         namespace: ExecutionNamespace,
         predicate: FilterOperator,
+        options: FindOptions,
+        context: ExecutionContext,
+        span: Span,
     },
     FindMany {
         namespace: ExecutionNamespace,
         predicate: FilterOperator,
+        options: FindOptions,
+        context: ExecutionContext,
+        span: Span,
+    },
+    Aggregate {
+        namespace: ExecutionNamespace,
+        pipeline: Vec<Stage>,
+        context: ExecutionContext,
+        span: Span,
+    },
+    InsertOne {
+        namespace: ExecutionNamespace,
+        document: Value,
+        context: ExecutionContext,
+        span: Span,
+    },
+    InsertMany {
+        namespace: ExecutionNamespace,
+        documents: Value,
+        context: ExecutionContext,
+        span: Span,
+    },
+    UpdateOne {
+        namespace: ExecutionNamespace,
+        predicate: FilterOperator,
+        update: Update,
+        options: UpdateOptions,
+        context: ExecutionContext,
+        span: Span,
+    },
+    UpdateMany {
+        namespace: ExecutionNamespace,
+        predicate: FilterOperator,
+        update: Update,
+        options: UpdateOptions,
+        context: ExecutionContext,
+        span: Span,
+    },
+    ReplaceOne {
+        namespace: ExecutionNamespace,
+        predicate: FilterOperator,
+        replacement: Value,
+        upsert: bool,
+        context: ExecutionContext,
+        span: Span,
+    },
+    DeleteOne {
+        namespace: ExecutionNamespace,
+        predicate: FilterOperator,
+        context: ExecutionContext,
+        span: Span,
+    },
+    DeleteMany {
+        namespace: ExecutionNamespace,
+        predicate: FilterOperator,
+        context: ExecutionContext,
+        span: Span,
+    },
+    FindOneAndUpdate {
+        namespace: ExecutionNamespace,
+        predicate: FilterOperator,
+        update: Update,
+        options: UpdateOptions,
+        context: ExecutionContext,
+        span: Span,
+    },
+    FindOneAndReplace {
+        namespace: ExecutionNamespace,
+        predicate: FilterOperator,
+        replacement: Value,
+        upsert: bool,
+        context: ExecutionContext,
+        span: Span,
+    },
+    FindOneAndDelete {
+        namespace: ExecutionNamespace,
+        predicate: FilterOperator,
+        context: ExecutionContext,
+        span: Span,
+    },
+    BulkWrite {
+        namespace: ExecutionNamespace,
+        operations: Vec<WriteModel>,
+        ordered: bool,
+        context: ExecutionContext,
+        span: Span,
+    },
+    CreateIndexes {
+        namespace: ExecutionNamespace,
+        indexes: Vec<IndexDefinition>,
+        context: ExecutionContext,
+        span: Span,
+    },
+    DropIndex {
+        namespace: ExecutionNamespace,
+        index: Hint,
+        context: ExecutionContext,
+        span: Span,
+    },
+    CreateCollection {
+        namespace: ExecutionNamespace,
+        options: Box<CollectionOptions>,
+        context: ExecutionContext,
+        span: Span,
+    },
+    CreateView {
+        namespace: ExecutionNamespace,
+        view_on: String,
+        pipeline: Vec<Stage>,
+        context: ExecutionContext,
+        span: Span,
+    },
+    RunCommand {
+        namespace: ExecutionNamespace,
+        command: Value,
+        context: ExecutionContext,
+        span: Span,
     },
 }
 
-pub trait ExecutionProcessor {
-    fn process(
-        tree: RefCell<Tree>,
-        code: &String,
-    ) -> Result<Vec<Execution>, Box<dyn Error + Sync + Send>>;
+impl Execution {
+    pub fn span(&self) -> Span {
+        return match self {
+            Execution::FindOne { span, .. }
+            | Execution::FindMany { span, .. }
+            | Execution::Aggregate { span, .. }
+            | Execution::InsertOne { span, .. }
+            | Execution::InsertMany { span, .. }
+            | Execution::UpdateOne { span, .. }
+            | Execution::UpdateMany { span, .. }
+            | Execution::ReplaceOne { span, .. }
+            | Execution::DeleteOne { span, .. }
+            | Execution::DeleteMany { span, .. }
+            | Execution::FindOneAndUpdate { span, .. }
+            | Execution::FindOneAndReplace { span, .. }
+            | Execution::FindOneAndDelete { span, .. }
+            | Execution::BulkWrite { span, .. }
+            | Execution::CreateIndexes { span, .. }
+            | Execution::DropIndex { span, .. }
+            | Execution::CreateCollection { span, .. }
+            | Execution::CreateView { span, .. }
+            | Execution::RunCommand { span, .. } => *span,
+        };
+    }
+
+    pub fn namespace(&self) -> &ExecutionNamespace {
+        return match self {
+            Execution::FindOne { namespace, .. }
+            | Execution::FindMany { namespace, .. }
+            | Execution::Aggregate { namespace, .. }
+            | Execution::InsertOne { namespace, .. }
+            | Execution::InsertMany { namespace, .. }
+            | Execution::UpdateOne { namespace, .. }
+            | Execution::UpdateMany { namespace, .. }
+            | Execution::ReplaceOne { namespace, .. }
+            | Execution::DeleteOne { namespace, .. }
+            | Execution::DeleteMany { namespace, .. }
+            | Execution::FindOneAndUpdate { namespace, .. }
+            | Execution::FindOneAndReplace { namespace, .. }
+            | Execution::FindOneAndDelete { namespace, .. }
+            | Execution::BulkWrite { namespace, .. }
+            | Execution::CreateIndexes { namespace, .. }
+            | Execution::DropIndex { namespace, .. }
+            | Execution::CreateCollection { namespace, .. }
+            | Execution::CreateView { namespace, .. }
+            | Execution::RunCommand { namespace, .. } => namespace,
+        };
+    }
+
+    pub fn context(&self) -> &ExecutionContext {
+        return match self {
+            Execution::FindOne { context, .. }
+            | Execution::FindMany { context, .. }
+            | Execution::Aggregate { context, .. }
+            | Execution::InsertOne { context, .. }
+            | Execution::InsertMany { context, .. }
+            | Execution::UpdateOne { context, .. }
+            | Execution::UpdateMany { context, .. }
+            | Execution::ReplaceOne { context, .. }
+            | Execution::DeleteOne { context, .. }
+            | Execution::DeleteMany { context, .. }
+            | Execution::FindOneAndUpdate { context, .. }
+            | Execution::FindOneAndReplace { context, .. }
+            | Execution::FindOneAndDelete { context, .. }
+            | Execution::BulkWrite { context, .. }
+            | Execution::CreateIndexes { context, .. }
+            | Execution::DropIndex { context, .. }
+            | Execution::CreateCollection { context, .. }
+            | Execution::CreateView { context, .. }
+            | Execution::RunCommand { context, .. } => context,
+        };
+    }
+
+    pub fn is_ddl(&self) -> bool {
+        return matches!(
+            self,
+            Execution::CreateIndexes { .. }
+                | Execution::DropIndex { .. }
+                | Execution::CreateCollection { .. }
+                | Execution::CreateView { .. }
+        );
+    }
 }
@@ -1,13 +1,278 @@
-use crate::values::Value;
+use crate::aggregation::Expression;
+use crate::span::Span;
+use crate::values::{BsonType, Value};
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
+pub enum GeoShape {
+    Geometry(Value),
+    Box(Value),
+    Polygon(Value),
+    Center(Value),
+    CenterSphere(Value),
+}
+
+#[derive(PartialEq, Debug, Clone)]
 pub enum FilterOperator {
-    And { predicates: Vec<FilterOperator> },
-    Or { predicates: Vec<FilterOperator> },
-    Not { predicates: Vec<FilterOperator> },
-    Equals { field: String, value: Value },
-    GreaterThan { field: String, value: Value },
-    LessThan { field: String, value: Value },
-    GreaterThanOrEquals { field: String, value: Value },
-    LessThanOrEquals { field: String, value: Value },
+    And {
+        predicates: Vec<FilterOperator>,
+        span: Span,
+    },
+    Or {
+        predicates: Vec<FilterOperator>,
+        span: Span,
+    },
+    Nor {
+        predicates: Vec<FilterOperator>,
+        span: Span,
+    },
+    Not {
+        predicates: Vec<FilterOperator>,
+        span: Span,
+    },
+    Equals {
+        field: String,
+        value: Value,
+        span: Span,
+    },
+    NotEquals {
+        field: String,
+        value: Value,
+        span: Span,
+    },
+    GreaterThan {
+        field: String,
+        value: Value,
+        span: Span,
+    },
+    LessThan {
+        field: String,
+        value: Value,
+        span: Span,
+    },
+    GreaterThanOrEquals {
+        field: String,
+        value: Value,
+        span: Span,
+    },
+    LessThanOrEquals {
+        field: String,
+        value: Value,
+        span: Span,
+    },
+    In {
+        field: String,
+        values: Vec<Value>,
+        span: Span,
+    },
+    NotIn {
+        field: String,
+        values: Vec<Value>,
+        span: Span,
+    },
+    Exists {
+        field: String,
+        exists: bool,
+        span: Span,
+    },
+    Type {
+        field: String,
+        types: Vec<BsonType>,
+        span: Span,
+    },
+    Regex {
+        field: String,
+        pattern: Value,
+        options: Option<String>,
+        span: Span,
+    },
+    ElemMatch {
+        field: String,
+        predicates: Vec<FilterOperator>,
+        span: Span,
+    },
+    Size {
+        field: String,
+        size: Value,
+        span: Span,
+    },
+    All {
+        field: String,
+        values: Vec<Value>,
+        span: Span,
+    },
+    Mod {
+        field: String,
+        divisor: Value,
+        remainder: Value,
+        span: Span,
+    },
+    BitsAllSet {
+        field: String,
+        mask: Value,
+        span: Span,
+    },
+    BitsAnySet {
+        field: String,
+        mask: Value,
+        span: Span,
+    },
+    BitsAllClear {
+        field: String,
+        mask: Value,
+        span: Span,
+    },
+    BitsAnyClear {
+        field: String,
+        mask: Value,
+        span: Span,
+    },
+    GeoWithin {
+        field: String,
+        shape: GeoShape,
+        span: Span,
+    },
+    GeoIntersects {
+        field: String,
+        geometry: Value,
+        span: Span,
+    },
+    Near {
+        field: String,
+        geometry: Value,
+        max_distance: Option<Value>,
+        min_distance: Option<Value>,
+        span: Span,
+    },
+    NearSphere {
+        field: String,
+        geometry: Value,
+        max_distance: Option<Value>,
+        min_distance: Option<Value>,
+        span: Span,
+    },
+    Expr {
+        expression: Expression,
+        span: Span,
+    },
+    JsonSchema {
+        schema: Value,
+        span: Span,
+    },
+    Text {
+        search: Value,
+        language: Option<String>,
+        case_sensitive: Option<bool>,
+        diacritic_sensitive: Option<bool>,
+        span: Span,
+    },
+    Where {
+        code: Value,
+        span: Span,
+    },
+    Comment {
+        comment: String,
+        span: Span,
+    },
+    SampleRate {
+        rate: Value,
+        span: Span,
+    },
+}
+
+impl FilterOperator {
+    pub fn span(&self) -> Span {
+        return match self {
+            FilterOperator::And { span, .. }
+            | FilterOperator::Or { span, .. }
+            | FilterOperator::Nor { span, .. }
+            | FilterOperator::Not { span, .. }
+            | FilterOperator::Equals { span, .. }
+            | FilterOperator::NotEquals { span, .. }
+            | FilterOperator::GreaterThan { span, .. }
+            | FilterOperator::LessThan { span, .. }
+            | FilterOperator::GreaterThanOrEquals { span, .. }
+            | FilterOperator::LessThanOrEquals { span, .. }
+            | FilterOperator::In { span, .. }
+            | FilterOperator::NotIn { span, .. }
+            | FilterOperator::Exists { span, .. }
+            | FilterOperator::Type { span, .. }
+            | FilterOperator::Regex { span, .. }
+            | FilterOperator::ElemMatch { span, .. }
+            | FilterOperator::Size { span, .. }
+            | FilterOperator::All { span, .. }
+            | FilterOperator::Mod { span, .. }
+            | FilterOperator::BitsAllSet { span, .. }
+            | FilterOperator::BitsAnySet { span, .. }
+            | FilterOperator::BitsAllClear { span, .. }
+            | FilterOperator::BitsAnyClear { span, .. }
+            | FilterOperator::GeoWithin { span, .. }
+            | FilterOperator::GeoIntersects { span, .. }
+            | FilterOperator::Near { span, .. }
+            | FilterOperator::NearSphere { span, .. }
+            | FilterOperator::Expr { span, .. }
+            | FilterOperator::JsonSchema { span, .. }
+            | FilterOperator::Text { span, .. }
+            | FilterOperator::Where { span, .. }
+            | FilterOperator::Comment { span, .. }
+            | FilterOperator::SampleRate { span, .. } => *span,
+        };
+    }
+
+    pub fn field(&self) -> Option<&String> {
+        return match self {
+            FilterOperator::Equals { field, .. }
+            | FilterOperator::NotEquals { field, .. }
+            | FilterOperator::GreaterThan { field, .. }
+            | FilterOperator::LessThan { field, .. }
+            | FilterOperator::GreaterThanOrEquals { field, .. }
+            | FilterOperator::LessThanOrEquals { field, .. }
+            | FilterOperator::In { field, .. }
+            | FilterOperator::NotIn { field, .. }
+            | FilterOperator::Exists { field, .. }
+            | FilterOperator::Type { field, .. }
+            | FilterOperator::Regex { field, .. }
+            | FilterOperator::ElemMatch { field, .. }
+            | FilterOperator::Size { field, .. }
+            | FilterOperator::All { field, .. }
+            | FilterOperator::Mod { field, .. }
+            | FilterOperator::BitsAllSet { field, .. }
+            | FilterOperator::BitsAnySet { field, .. }
+            | FilterOperator::BitsAllClear { field, .. }
+            | FilterOperator::BitsAnyClear { field, .. }
+            | FilterOperator::GeoWithin { field, .. }
+            | FilterOperator::GeoIntersects { field, .. }
+            | FilterOperator::Near { field, .. }
+            | FilterOperator::NearSphere { field, .. } => Some(field),
+            _ => None,
+        };
+    }
+
+    pub fn field_mut(&mut self) -> Option<&mut String> {
+        return match self {
+            FilterOperator::Equals { field, .. }
+            | FilterOperator::NotEquals { field, .. }
+            | FilterOperator::GreaterThan { field, .. }
+            | FilterOperator::LessThan { field, .. }
+            | FilterOperator::GreaterThanOrEquals { field, .. }
+            | FilterOperator::LessThanOrEquals { field, .. }
+            | FilterOperator::In { field, .. }
+            | FilterOperator::NotIn { field, .. }
+            | FilterOperator::Exists { field, .. }
+            | FilterOperator::Type { field, .. }
+            | FilterOperator::Regex { field, .. }
+            | FilterOperator::ElemMatch { field, .. }
+            | FilterOperator::Size { field, .. }
+            | FilterOperator::All { field, .. }
+            | FilterOperator::Mod { field, .. }
+            | FilterOperator::BitsAllSet { field, .. }
+            | FilterOperator::BitsAnySet { field, .. }
+            | FilterOperator::BitsAllClear { field, .. }
+            | FilterOperator::BitsAnyClear { field, .. }
+            | FilterOperator::GeoWithin { field, .. }
+            | FilterOperator::GeoIntersects { field, .. }
+            | FilterOperator::Near { field, .. }
+            | FilterOperator::NearSphere { field, .. } => Some(field),
+            _ => None,
+        };
+    }
 }
@@ -0,0 +1,989 @@
+use crate::aggregation::{Expression, MergeWhenMatched, ProjectionField, Stage};
+use crate::ddl::{CollectionOptions, IndexDefinition, IndexOptions};
+use crate::execution::{Execution, FindOptions, Hint};
+use crate::filter::{FilterOperator, GeoShape};
+use crate::update::{
+    ArrayUpdateValue, PullCondition, Update, UpdateOperator, UpdateOptions, WriteModel,
+};
+use crate::values::Value;
+
+pub trait Fold {
+    fn fold_execution(&mut self, execution: Execution) -> Execution {
+        return fold_execution(self, execution);
+    }
+
+    fn fold_find_options(&mut self, options: FindOptions) -> FindOptions {
+        return fold_find_options(self, options);
+    }
+
+    fn fold_filter(&mut self, predicate: FilterOperator) -> FilterOperator {
+        return fold_filter(self, predicate);
+    }
+
+    fn fold_geo_shape(&mut self, shape: GeoShape) -> GeoShape {
+        return fold_geo_shape(self, shape);
+    }
+
+    fn fold_value(&mut self, value: Value) -> Value {
+        return fold_value(self, value);
+    }
+
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        return fold_expression(self, expression);
+    }
+
+    fn fold_stage(&mut self, stage: Stage) -> Stage {
+        return fold_stage(self, stage);
+    }
+
+    fn fold_projection(&mut self, projection: ProjectionField) -> ProjectionField {
+        return fold_projection(self, projection);
+    }
+
+    fn fold_update(&mut self, update: Update) -> Update {
+        return fold_update(self, update);
+    }
+
+    fn fold_update_operator(&mut self, operator: UpdateOperator) -> UpdateOperator {
+        return fold_update_operator(self, operator);
+    }
+
+    fn fold_write_model(&mut self, model: WriteModel) -> WriteModel {
+        return fold_write_model(self, model);
+    }
+}
+
+fn fold_filters<F: Fold + ?Sized>(
+    folder: &mut F,
+    predicates: Vec<FilterOperator>,
+) -> Vec<FilterOperator> {
+    return predicates
+        .into_iter()
+        .map(|predicate| folder.fold_filter(predicate))
+        .collect();
+}
+
+fn fold_values<F: Fold + ?Sized>(folder: &mut F, values: Vec<Value>) -> Vec<Value> {
+    return values
+        .into_iter()
+        .map(|value| folder.fold_value(value))
+        .collect();
+}
+
+fn fold_optional_value<F: Fold + ?Sized>(folder: &mut F, value: Option<Value>) -> Option<Value> {
+    return value.map(|value| folder.fold_value(value));
+}
+
+fn fold_boxed_value<F: Fold + ?Sized>(
+    folder: &mut F,
+    value: Option<Box<Value>>,
+) -> Option<Box<Value>> {
+    return value.map(|value| Box::new(folder.fold_value(*value)));
+}
+
+fn fold_expressions<F: Fold + ?Sized>(
+    folder: &mut F,
+    fields: Vec<(String, Expression)>,
+) -> Vec<(String, Expression)> {
+    return fields
+        .into_iter()
+        .map(|(name, expression)| (name, folder.fold_expression(expression)))
+        .collect();
+}
+
+fn fold_pipeline<F: Fold + ?Sized>(folder: &mut F, pipeline: Vec<Stage>) -> Vec<Stage> {
+    return pipeline
+        .into_iter()
+        .map(|stage| folder.fold_stage(stage))
+        .collect();
+}
+
+fn fold_update_options<F: Fold + ?Sized>(folder: &mut F, options: UpdateOptions) -> UpdateOptions {
+    return UpdateOptions {
+        upsert: options.upsert,
+        array_filters: fold_filters(folder, options.array_filters),
+    };
+}
+
+pub fn fold_execution<F: Fold + ?Sized>(folder: &mut F, execution: Execution) -> Execution {
+    return match execution {
+        Execution::FindOne {
+            namespace,
+            predicate,
+            options,
+            context,
+            span,
+        } => Execution::FindOne {
+            namespace,
+            predicate: folder.fold_filter(predicate),
+            options: folder.fold_find_options(options),
+            context,
+            span,
+        },
+        Execution::FindMany {
+            namespace,
+            predicate,
+            options,
+            context,
+            span,
+        } => Execution::FindMany {
+            namespace,
+            predicate: folder.fold_filter(predicate),
+            options: folder.fold_find_options(options),
+            context,
+            span,
+        },
+        Execution::Aggregate {
+            namespace,
+            pipeline,
+            context,
+            span,
+        } => Execution::Aggregate {
+            namespace,
+            pipeline: fold_pipeline(folder, pipeline),
+            context,
+            span,
+        },
+        Execution::InsertOne {
+            namespace,
+            document,
+            context,
+            span,
+        } => Execution::InsertOne {
+            namespace,
+            document: folder.fold_value(document),
+            context,
+            span,
+        },
+        Execution::InsertMany {
+            namespace,
+            documents,
+            context,
+            span,
+        } => Execution::InsertMany {
+            namespace,
+            documents: folder.fold_value(documents),
+            context,
+            span,
+        },
+        Execution::UpdateOne {
+            namespace,
+            predicate,
+            update,
+            options,
+            context,
+            span,
+        } => Execution::UpdateOne {
+            namespace,
+            predicate: folder.fold_filter(predicate),
+            update: folder.fold_update(update),
+            options: fold_update_options(folder, options),
+            context,
+            span,
+        },
+        Execution::UpdateMany {
+            namespace,
+            predicate,
+            update,
+            options,
+            context,
+            span,
+        } => Execution::UpdateMany {
+            namespace,
+            predicate: folder.fold_filter(predicate),
+            update: folder.fold_update(update),
+            options: fold_update_options(folder, options),
+            context,
+            span,
+        },
+        Execution::ReplaceOne {
+            namespace,
+            predicate,
+            replacement,
+            upsert,
+            context,
+            span,
+        } => Execution::ReplaceOne {
+            namespace,
+            predicate: folder.fold_filter(predicate),
+            replacement: folder.fold_value(replacement),
+            upsert,
+            context,
+            span,
+        },
+        Execution::DeleteOne {
+            namespace,
+            predicate,
+            context,
+            span,
+        } => Execution::DeleteOne {
+            namespace,
+            predicate: folder.fold_filter(predicate),
+            context,
+            span,
+        },
+        Execution::DeleteMany {
+            namespace,
+            predicate,
+            context,
+            span,
+        } => Execution::DeleteMany {
+            namespace,
+            predicate: folder.fold_filter(predicate),
+            context,
+            span,
+        },
+        Execution::FindOneAndUpdate {
+            namespace,
+            predicate,
+            update,
+            options,
+            context,
+            span,
+        } => Execution::FindOneAndUpdate {
+            namespace,
+            predicate: folder.fold_filter(predicate),
+            update: folder.fold_update(update),
+            options: fold_update_options(folder, options),
+            context,
+            span,
+        },
+        Execution::FindOneAndReplace {
+            namespace,
+            predicate,
+            replacement,
+            upsert,
+            context,
+            span,
+        } => Execution::FindOneAndReplace {
+            namespace,
+            predicate: folder.fold_filter(predicate),
+            replacement: folder.fold_value(replacement),
+            upsert,
+            context,
+            span,
+        },
+        Execution::FindOneAndDelete {
+            namespace,
+            predicate,
+            context,
+            span,
+        } => Execution::FindOneAndDelete {
+            namespace,
+            predicate: folder.fold_filter(predicate),
+            context,
+            span,
+        },
+        Execution::BulkWrite {
+            namespace,
+            operations,
+            ordered,
+            context,
+            span,
+        } => Execution::BulkWrite {
+            namespace,
+            operations: operations
+                .into_iter()
+                .map(|model| folder.fold_write_model(model))
+                .collect(),
+            ordered,
+            context,
+            span,
+        },
+        Execution::CreateIndexes {
+            namespace,
+            indexes,
+            context,
+            span,
+        } => Execution::CreateIndexes {
+            namespace,
+            indexes: indexes
+                .into_iter()
+                .map(|index| fold_index_definition(folder, index))
+                .collect(),
+            context,
+            span,
+        },
+        Execution::DropIndex {
+            namespace,
+            index,
+            context,
+            span,
+        } => Execution::DropIndex {
+            namespace,
+            index: match index {
+                Hint::Keys(keys) => Hint::Keys(folder.fold_value(keys)),
+                index => index,
+            },
+            context,
+            span,
+        },
+        Execution::CreateCollection {
+            namespace,
+            options,
+            context,
+            span,
+        } => Execution::CreateCollection {
+            namespace,
+            options: Box::new(fold_collection_options(folder, *options)),
+            context,
+            span,
+        },
+        Execution::CreateView {
+            namespace,
+            view_on,
+            pipeline,
+            context,
+            span,
+        } => Execution::CreateView {
+            namespace,
+            view_on,
+            pipeline: fold_pipeline(folder, pipeline),
+            context,
+            span,
+        },
+        Execution::RunCommand {
+            namespace,
+            command,
+            context,
+            span,
+        } => Execution::RunCommand {
+            namespace,
+            command: folder.fold_value(command),
+            context,
+            span,
+        },
+    };
+}
+
+fn fold_collection_options<F: Fold + ?Sized>(
+    folder: &mut F,
+    options: CollectionOptions,
+) -> CollectionOptions {
+    return CollectionOptions {
+        size_in_bytes: fold_optional_value(folder, options.size_in_bytes),
+        max_documents: fold_optional_value(folder, options.max_documents),
+        validator: options
+            .validator
+            .map(|validator| folder.fold_filter(validator)),
+        expire_after_seconds: fold_optional_value(folder, options.expire_after_seconds),
+        clustered_index: options
+            .clustered_index
+            .map(|index| fold_index_definition(folder, index)),
+        collation: fold_optional_value(folder, options.collation),
+        ..options
+    };
+}
+
+fn fold_index_definition<F: Fold + ?Sized>(
+    folder: &mut F,
+    index: IndexDefinition,
+) -> IndexDefinition {
+    return IndexDefinition {
+        keys: folder.fold_value(index.keys),
+        options: IndexOptions {
+            expire_after_seconds: fold_optional_value(folder, index.options.expire_after_seconds),
+            partial_filter: index
+                .options
+                .partial_filter
+                .map(|filter| folder.fold_filter(filter)),
+            collation: fold_optional_value(folder, index.options.collation),
+            ..index.options
+        },
+    };
+}
+
+pub fn fold_find_options<F: Fold + ?Sized>(folder: &mut F, options: FindOptions) -> FindOptions {
+    return FindOptions {
+        projection: options
+            .projection
+            .into_iter()
+            .map(|(field, projection)| (field, folder.fold_projection(projection)))
+            .collect(),
+        sort: options.sort,
+        skip: fold_optional_value(folder, options.skip),
+        limit: fold_optional_value(folder, options.limit),
+        hint: options.hint.map(|hint| match hint {
+            Hint::Keys(keys) => Hint::Keys(folder.fold_value(keys)),
+            hint => hint,
+        }),
+        collation: fold_optional_value(folder, options.collation),
+        max_time_ms: fold_optional_value(folder, options.max_time_ms),
+    };
+}
+
+pub fn fold_filter<F: Fold + ?Sized>(folder: &mut F, predicate: FilterOperator) -> FilterOperator {
+    return match predicate {
+        FilterOperator::And { predicates, span } => FilterOperator::And {
+            predicates: fold_filters(folder, predicates),
+            span,
+        },
+        FilterOperator::Or { predicates, span } => FilterOperator::Or {
+            predicates: fold_filters(folder, predicates),
+            span,
+        },
+        FilterOperator::Nor { predicates, span } => FilterOperator::Nor {
+            predicates: fold_filters(folder, predicates),
+            span,
+        },
+        FilterOperator::Not { predicates, span } => FilterOperator::Not {
+            predicates: fold_filters(folder, predicates),
+            span,
+        },
+        FilterOperator::Equals { field, value, span } => FilterOperator::Equals {
+            field,
+            value: folder.fold_value(value),
+            span,
+        },
+        FilterOperator::NotEquals { field, value, span } => FilterOperator::NotEquals {
+            field,
+            value: folder.fold_value(value),
+            span,
+        },
+        FilterOperator::GreaterThan { field, value, span } => FilterOperator::GreaterThan {
+            field,
+            value: folder.fold_value(value),
+            span,
+        },
+        FilterOperator::LessThan { field, value, span } => FilterOperator::LessThan {
+            field,
+            value: folder.fold_value(value),
+            span,
+        },
+        FilterOperator::GreaterThanOrEquals { field, value, span } => {
+            FilterOperator::GreaterThanOrEquals {
+                field,
+                value: folder.fold_value(value),
+                span,
+            }
+        }
+        FilterOperator::LessThanOrEquals { field, value, span } => {
+            FilterOperator::LessThanOrEquals {
+                field,
+                value: folder.fold_value(value),
+                span,
+            }
+        }
+        FilterOperator::In {
+            field,
+            values,
+            span,
+        } => FilterOperator::In {
+            field,
+            values: fold_values(folder, values),
+            span,
+        },
+        FilterOperator::NotIn {
+            field,
+            values,
+            span,
+        } => FilterOperator::NotIn {
+            field,
+            values: fold_values(folder, values),
+            span,
+        },
+        FilterOperator::Regex {
+            field,
+            pattern,
+            options,
+            span,
+        } => FilterOperator::Regex {
+            field,
+            pattern: folder.fold_value(pattern),
+            options,
+            span,
+        },
+        FilterOperator::ElemMatch {
+            field,
+            predicates,
+            span,
+        } => FilterOperator::ElemMatch {
+            field,
+            predicates: fold_filters(folder, predicates),
+            span,
+        },
+        FilterOperator::Size { field, size, span } => FilterOperator::Size {
+            field,
+            size: folder.fold_value(size),
+            span,
+        },
+        FilterOperator::All {
+            field,
+            values,
+            span,
+        } => FilterOperator::All {
+            field,
+            values: fold_values(folder, values),
+            span,
+        },
+        FilterOperator::Mod {
+            field,
+            divisor,
+            remainder,
+            span,
+        } => FilterOperator::Mod {
+            field,
+            divisor: folder.fold_value(divisor),
+            remainder: folder.fold_value(remainder),
+            span,
+        },
+        FilterOperator::BitsAllSet { field, mask, span } => FilterOperator::BitsAllSet {
+            field,
+            mask: folder.fold_value(mask),
+            span,
+        },
+        FilterOperator::BitsAnySet { field, mask, span } => FilterOperator::BitsAnySet {
+            field,
+            mask: folder.fold_value(mask),
+            span,
+        },
+        FilterOperator::BitsAllClear { field, mask, span } => FilterOperator::BitsAllClear {
+            field,
+            mask: folder.fold_value(mask),
+            span,
+        },
+        FilterOperator::BitsAnyClear { field, mask, span } => FilterOperator::BitsAnyClear {
+            field,
+            mask: folder.fold_value(mask),
+            span,
+        },
+        FilterOperator::GeoWithin { field, shape, span } => FilterOperator::GeoWithin {
+            field,
+            shape: folder.fold_geo_shape(shape),
+            span,
+        },
+        FilterOperator::GeoIntersects {
+            field,
+            geometry,
+            span,
+        } => FilterOperator::GeoIntersects {
+            field,
+            geometry: folder.fold_value(geometry),
+            span,
+        },
+        FilterOperator::Near {
+            field,
+            geometry,
+            max_distance,
+            min_distance,
+            span,
+        } => FilterOperator::Near {
+            field,
+            geometry: folder.fold_value(geometry),
+            max_distance: fold_optional_value(folder, max_distance),
+            min_distance: fold_optional_value(folder, min_distance),
+            span,
+        },
+        FilterOperator::NearSphere {
+            field,
+            geometry,
+            max_distance,
+            min_distance,
+            span,
+        } => FilterOperator::NearSphere {
+            field,
+            geometry: folder.fold_value(geometry),
+            max_distance: fold_optional_value(folder, max_distance),
+            min_distance: fold_optional_value(folder, min_distance),
+            span,
+        },
+        FilterOperator::Expr { expression, span } => FilterOperator::Expr {
+            expression: folder.fold_expression(expression),
+            span,
+        },
+        FilterOperator::JsonSchema { schema, span } => FilterOperator::JsonSchema {
+            schema: folder.fold_value(schema),
+            span,
+        },
+        FilterOperator::Text {
+            search,
+            language,
+            case_sensitive,
+            diacritic_sensitive,
+            span,
+        } => FilterOperator::Text {
+            search: folder.fold_value(search),
+            language,
+            case_sensitive,
+            diacritic_sensitive,
+            span,
+        },
+        FilterOperator::Where { code, span } => FilterOperator::Where {
+            code: folder.fold_value(code),
+            span,
+        },
+        FilterOperator::SampleRate { rate, span } => FilterOperator::SampleRate {
+            rate: folder.fold_value(rate),
+            span,
+        },
+        predicate @ (FilterOperator::Exists { .. }
+        | FilterOperator::Type { .. }
+        | FilterOperator::Comment { .. }) => predicate,
+    };
+}
+
+pub fn fold_geo_shape<F: Fold + ?Sized>(folder: &mut F, shape: GeoShape) -> GeoShape {
+    return match shape {
+        GeoShape::Geometry(value) => GeoShape::Geometry(folder.fold_value(value)),
+        GeoShape::Box(value) => GeoShape::Box(folder.fold_value(value)),
+        GeoShape::Polygon(value) => GeoShape::Polygon(folder.fold_value(value)),
+        GeoShape::Center(value) => GeoShape::Center(folder.fold_value(value)),
+        GeoShape::CenterSphere(value) => GeoShape::CenterSphere(folder.fold_value(value)),
+    };
+}
+
+pub fn fold_value<F: Fold + ?Sized>(folder: &mut F, value: Value) -> Value {
+    return match value {
+        Value::Object(fields, span) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name, folder.fold_value(value)))
+                .collect(),
+            span,
+        ),
+        Value::Array(values, span) => Value::Array(fold_values(folder, values), span),
+        value => value,
+    };
+}
+
+pub fn fold_expression<F: Fold + ?Sized>(folder: &mut F, expression: Expression) -> Expression {
+    return match expression {
+        Expression::Literal(value) => Expression::Literal(folder.fold_value(value)),
+        Expression::Object(fields) => Expression::Object(fold_expressions(folder, fields)),
+        Expression::Array(expressions) => Expression::Array(
+            expressions
+                .into_iter()
+                .map(|expression| folder.fold_expression(expression))
+                .collect(),
+        ),
+        Expression::Operator { name, arguments } => Expression::Operator {
+            name,
+            arguments: arguments
+                .into_iter()
+                .map(|expression| folder.fold_expression(expression))
+                .collect(),
+        },
+        expression => expression,
+    };
+}
+
+pub fn fold_stage<F: Fold + ?Sized>(folder: &mut F, stage: Stage) -> Stage {
+    return match stage {
+        Stage::Match { predicate } => Stage::Match {
+            predicate: folder.fold_filter(predicate),
+        },
+        Stage::Project { fields } => Stage::Project {
+            fields: fields
+                .into_iter()
+                .map(|(field, projection)| (field, folder.fold_projection(projection)))
+                .collect(),
+        },
+        Stage::Group { id, accumulators } => Stage::Group {
+            id: folder.fold_expression(id),
+            accumulators: fold_expressions(folder, accumulators),
+        },
+        Stage::Limit { limit } => Stage::Limit {
+            limit: folder.fold_value(limit),
+        },
+        Stage::Skip { skip } => Stage::Skip {
+            skip: folder.fold_value(skip),
+        },
+        Stage::Sample { size } => Stage::Sample {
+            size: folder.fold_value(size),
+        },
+        Stage::Lookup {
+            from,
+            local_field,
+            foreign_field,
+            let_variables,
+            pipeline,
+            as_field,
+        } => Stage::Lookup {
+            from,
+            local_field,
+            foreign_field,
+            let_variables: fold_expressions(folder, let_variables),
+            pipeline: fold_pipeline(folder, pipeline),
+            as_field,
+        },
+        Stage::GraphLookup {
+            from,
+            start_with,
+            connect_from_field,
+            connect_to_field,
+            as_field,
+            max_depth,
+            depth_field,
+            restrict_search_with_match,
+        } => Stage::GraphLookup {
+            from,
+            start_with: folder.fold_expression(start_with),
+            connect_from_field,
+            connect_to_field,
+            as_field,
+            max_depth: fold_optional_value(folder, max_depth),
+            depth_field,
+            restrict_search_with_match: restrict_search_with_match
+                .map(|predicate| Box::new(folder.fold_filter(*predicate))),
+        },
+        Stage::Facet { facets } => Stage::Facet {
+            facets: facets
+                .into_iter()
+                .map(|(name, pipeline)| (name, fold_pipeline(folder, pipeline)))
+                .collect(),
+        },
+        Stage::AddFields { fields } => Stage::AddFields {
+            fields: fold_expressions(folder, fields),
+        },
+        Stage::Set { fields } => Stage::Set {
+            fields: fold_expressions(folder, fields),
+        },
+        Stage::ReplaceRoot { new_root } => Stage::ReplaceRoot {
+            new_root: folder.fold_expression(new_root),
+        },
+        Stage::ReplaceWith { new_root } => Stage::ReplaceWith {
+            new_root: folder.fold_expression(new_root),
+        },
+        Stage::Bucket {
+            group_by,
+            boundaries,
+            default,
+            output,
+        } => Stage::Bucket {
+            group_by: folder.fold_expression(group_by),
+            boundaries: fold_values(folder, boundaries),
+            default: fold_optional_value(folder, default),
+            output: fold_expressions(folder, output),
+        },
+        Stage::Merge {
+            database,
+            collection,
+            on,
+            let_variables,
+            when_matched,
+            when_not_matched,
+        } => Stage::Merge {
+            database,
+            collection,
+            on,
+            let_variables: fold_expressions(folder, let_variables),
+            when_matched: when_matched.map(|when_matched| match when_matched {
+                MergeWhenMatched::Pipeline(pipeline) => {
+                    MergeWhenMatched::Pipeline(fold_pipeline(folder, pipeline))
+                }
+                when_matched => when_matched,
+            }),
+            when_not_matched,
+        },
+        Stage::Search { index, definition } => Stage::Search {
+            index,
+            definition: folder.fold_value(definition),
+        },
+        Stage::Other { name, definition } => Stage::Other {
+            name,
+            definition: folder.fold_value(definition),
+        },
+        stage @ (Stage::Sort { .. }
+        | Stage::Unwind { .. }
+        | Stage::Unset { .. }
+        | Stage::Count { .. }
+        | Stage::Out { .. }) => stage,
+    };
+}
+
+pub fn fold_projection<F: Fold + ?Sized>(
+    folder: &mut F,
+    projection: ProjectionField,
+) -> ProjectionField {
+    return match projection {
+        ProjectionField::Computed(expression) => {
+            ProjectionField::Computed(folder.fold_expression(expression))
+        }
+        ProjectionField::Slice { skip, limit } => ProjectionField::Slice {
+            skip: fold_optional_value(folder, skip),
+            limit: folder.fold_value(limit),
+        },
+        ProjectionField::ElemMatch(predicate) => {
+            ProjectionField::ElemMatch(folder.fold_filter(predicate))
+        }
+        projection => projection,
+    };
+}
+
+pub fn fold_update<F: Fold + ?Sized>(folder: &mut F, update: Update) -> Update {
+    return match update {
+        Update::Operators(operators) => Update::Operators(
+            operators
+                .into_iter()
+                .map(|operator| folder.fold_update_operator(operator))
+                .collect(),
+        ),
+        Update::Pipeline(pipeline) => Update::Pipeline(fold_pipeline(folder, pipeline)),
+    };
+}
+
+fn fold_array_update_value<F: Fold + ?Sized>(
+    folder: &mut F,
+    values: ArrayUpdateValue,
+) -> ArrayUpdateValue {
+    return match values {
+        ArrayUpdateValue::Single(value) => ArrayUpdateValue::Single(folder.fold_value(value)),
+        ArrayUpdateValue::Each(values) => ArrayUpdateValue::Each(fold_values(folder, values)),
+    };
+}
+
+pub fn fold_update_operator<F: Fold + ?Sized>(
+    folder: &mut F,
+    operator: UpdateOperator,
+) -> UpdateOperator {
+    return match operator {
+        UpdateOperator::Set { field, value } => UpdateOperator::Set {
+            field,
+            value: folder.fold_value(value),
+        },
+        UpdateOperator::SetOnInsert { field, value } => UpdateOperator::SetOnInsert {
+            field,
+            value: folder.fold_value(value),
+        },
+        UpdateOperator::Inc { field, amount } => UpdateOperator::Inc {
+            field,
+            amount: folder.fold_value(amount),
+        },
+        UpdateOperator::Mul { field, factor } => UpdateOperator::Mul {
+            field,
+            factor: folder.fold_value(factor),
+        },
+        UpdateOperator::Min { field, value } => UpdateOperator::Min {
+            field,
+            value: folder.fold_value(value),
+        },
+        UpdateOperator::Max { field, value } => UpdateOperator::Max {
+            field,
+            value: folder.fold_value(value),
+        },
+        UpdateOperator::Push {
+            field,
+            values,
+            position,
+            slice,
+            sort,
+        } => UpdateOperator::Push {
+            field,
+            values: fold_array_update_value(folder, values),
+            position: fold_boxed_value(folder, position),
+            slice: fold_boxed_value(folder, slice),
+            sort: fold_boxed_value(folder, sort),
+        },
+        UpdateOperator::AddToSet { field, values } => UpdateOperator::AddToSet {
+            field,
+            values: fold_array_update_value(folder, values),
+        },
+        UpdateOperator::Pull { field, condition } => UpdateOperator::Pull {
+            field,
+            condition: match condition {
+                PullCondition::Value(value) => PullCondition::Value(folder.fold_value(value)),
+                PullCondition::Predicate(predicate) => {
+                    PullCondition::Predicate(Box::new(folder.fold_filter(*predicate)))
+                }
+            },
+        },
+        UpdateOperator::PullAll { field, values } => UpdateOperator::PullAll {
+            field,
+            values: fold_values(folder, values),
+        },
+        UpdateOperator::Bit {
+            field,
+            operation,
+            value,
+        } => UpdateOperator::Bit {
+            field,
+            operation,
+            value: folder.fold_value(value),
+        },
+        operator @ (UpdateOperator::Unset { .. }
+        | UpdateOperator::Rename { .. }
+        | UpdateOperator::CurrentDate { .. }
+        | UpdateOperator::Pop { .. }) => operator,
+    };
+}
+
+pub fn fold_write_model<F: Fold + ?Sized>(folder: &mut F, model: WriteModel) -> WriteModel {
+    return match model {
+        WriteModel::InsertOne { document } => WriteModel::InsertOne {
+            document: folder.fold_value(document),
+        },
+        WriteModel::UpdateOne {
+            predicate,
+            update,
+            options,
+        } => WriteModel::UpdateOne {
+            predicate: folder.fold_filter(predicate),
+            update: folder.fold_update(update),
+            options: fold_update_options(folder, options),
+        },
+        WriteModel::UpdateMany {
+            predicate,
+            update,
+            options,
+        } => WriteModel::UpdateMany {
+            predicate: folder.fold_filter(predicate),
+            update: folder.fold_update(update),
+            options: fold_update_options(folder, options),
+        },
+        WriteModel::ReplaceOne {
+            predicate,
+            replacement,
+            upsert,
+        } => WriteModel::ReplaceOne {
+            predicate: folder.fold_filter(predicate),
+            replacement: folder.fold_value(replacement),
+            upsert,
+        },
+        WriteModel::DeleteOne { predicate } => WriteModel::DeleteOne {
+            predicate: folder.fold_filter(predicate),
+        },
+        WriteModel::DeleteMany { predicate } => WriteModel::DeleteMany {
+            predicate: folder.fold_filter(predicate),
+        },
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fold::{fold_value, Fold};
+    use crate::parser::parse_update;
+    use crate::span::Span;
+    use crate::update::{Update, UpdateOperator};
+    use crate::values::{Value, ValueType};
+
+    struct Bind;
+
+    impl Fold for Bind {
+        fn fold_value(&mut self, value: Value) -> Value {
+            return match value {
+                Value::Reference(_, _, span) => Value::Integer(1, span),
+                value => fold_value(self, value),
+            };
+        }
+    }
+
+    #[test]
+    fn rebuilds_the_tree_with_folded_nodes() {
+        let update = Update::Operators(vec![UpdateOperator::Set {
+            field: "counters".to_string(),
+            value: Value::Array(
+                vec![Value::Reference(
+                    "step".to_string(),
+                    ValueType::Any,
+                    Span::default(),
+                )],
+                Span::default(),
+            ),
+        }]);
+
+        assert_eq!(
+            Bind.fold_update(update),
+            parse_update(r#"{ $set: { counters: [1] } }"#).unwrap()
+        );
+    }
+}
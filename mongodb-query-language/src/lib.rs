@@ -1,3 +1,15 @@
+pub mod aggregation;
+pub mod ddl;
+pub mod evaluation;
 pub mod execution;
 pub mod filter;
+pub mod fold;
+pub mod normalization;
+pub mod parser;
+pub mod schema;
+pub mod serialization;
+pub mod span;
+pub mod update;
 pub mod values;
+pub mod visit;
+pub mod visit_mut;
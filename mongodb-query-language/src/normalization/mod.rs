@@ -0,0 +1,522 @@
+use std::cmp::Ordering;
+
+use bson::{Bson, Document};
+
+use crate::aggregation::Expression;
+use crate::filter::{FilterOperator, GeoShape};
+use crate::serialization::{to_document, ToBson};
+use crate::span::Span;
+use crate::values::{BsonType, Value, ValueType};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+pub fn normalize(predicate: FilterOperator) -> FilterOperator {
+    return match predicate {
+        FilterOperator::And { predicates, span } => {
+            let predicates = merge_ranges(flatten(predicates, true));
+            junction(predicates, span, true)
+        }
+        FilterOperator::Or { predicates, span } => {
+            junction(flatten(predicates, false), span, false)
+        }
+        FilterOperator::Nor { predicates, span } => {
+            let predicates: Vec<FilterOperator> = predicates.into_iter().map(normalize).collect();
+            if predicates
+                .iter()
+                .all(|predicate| negate(predicate.clone()).is_some())
+            {
+                let negated = predicates.into_iter().filter_map(negate).collect();
+                return normalize(FilterOperator::And {
+                    predicates: negated,
+                    span,
+                });
+            }
+
+            FilterOperator::Nor {
+                predicates: sorted(predicates),
+                span,
+            }
+        }
+        FilterOperator::Not { predicates, span } => {
+            let mut predicates: Vec<FilterOperator> =
+                predicates.into_iter().map(normalize).collect();
+            if predicates.len() == 1 {
+                if let Some(negated) = negate(predicates[0].clone()) {
+                    return with_span(negated, span);
+                }
+            }
+
+            predicates = sorted(predicates);
+            FilterOperator::Not { predicates, span }
+        }
+        FilterOperator::ElemMatch {
+            field,
+            predicates,
+            span,
+        } => FilterOperator::ElemMatch {
+            field,
+            predicates: sorted(merge_ranges(flatten(predicates, true))),
+            span,
+        },
+        FilterOperator::In {
+            field,
+            values,
+            span,
+        } => FilterOperator::In {
+            field,
+            values: sorted_values(values),
+            span,
+        },
+        FilterOperator::NotIn {
+            field,
+            values,
+            span,
+        } => FilterOperator::NotIn {
+            field,
+            values: sorted_values(values),
+            span,
+        },
+        FilterOperator::All {
+            field,
+            values,
+            span,
+        } => FilterOperator::All {
+            field,
+            values: sorted_values(values),
+            span,
+        },
+        other => other,
+    };
+}
+
+fn flatten(predicates: Vec<FilterOperator>, conjunction: bool) -> Vec<FilterOperator> {
+    let mut result = vec![];
+    for predicate in predicates.into_iter().map(normalize) {
+        match predicate {
+            FilterOperator::And { predicates, .. } if conjunction => result.extend(predicates),
+            FilterOperator::Or { predicates, .. } if !conjunction => result.extend(predicates),
+            predicate => result.push(predicate),
+        }
+    }
+
+    return result;
+}
+
+fn junction(predicates: Vec<FilterOperator>, span: Span, conjunction: bool) -> FilterOperator {
+    let mut predicates = sorted(predicates);
+    if predicates.len() == 1 {
+        return predicates.remove(0);
+    }
+
+    return match conjunction {
+        true => FilterOperator::And { predicates, span },
+        false => FilterOperator::Or { predicates, span },
+    };
+}
+
+fn negate(predicate: FilterOperator) -> Option<FilterOperator> {
+    let negated = match predicate {
+        FilterOperator::Equals { field, value, span } => {
+            FilterOperator::NotEquals { field, value, span }
+        }
+        FilterOperator::NotEquals { field, value, span } => {
+            FilterOperator::Equals { field, value, span }
+        }
+        FilterOperator::In {
+            field,
+            values,
+            span,
+        } => FilterOperator::NotIn {
+            field,
+            values,
+            span,
+        },
+        FilterOperator::NotIn {
+            field,
+            values,
+            span,
+        } => FilterOperator::In {
+            field,
+            values,
+            span,
+        },
+        FilterOperator::Exists {
+            field,
+            exists,
+            span,
+        } => FilterOperator::Exists {
+            field,
+            exists: !exists,
+            span,
+        },
+        FilterOperator::Not { mut predicates, .. } if predicates.len() == 1 => predicates.remove(0),
+        _ => return None,
+    };
+
+    return Some(negated);
+}
+
+fn with_span(mut predicate: FilterOperator, span: Span) -> FilterOperator {
+    if span.is_empty() {
+        return predicate;
+    }
+
+    match &mut predicate {
+        FilterOperator::Equals { span: inner, .. }
+        | FilterOperator::NotEquals { span: inner, .. }
+        | FilterOperator::In { span: inner, .. }
+        | FilterOperator::NotIn { span: inner, .. }
+        | FilterOperator::Exists { span: inner, .. } => *inner = span,
+        _ => {}
+    }
+
+    return predicate;
+}
+
+fn compare_values(left: &Value, right: &Value) -> Option<Ordering> {
+    let number = |value: &Value| match value {
+        Value::Integer(value, _) => Some(*value as f64),
+        Value::Int64(value, _) => Some(*value as f64),
+        Value::Floating(value, _) => Some(*value),
+        Value::Decimal128(value, _) => value.parse().ok(),
+        _ => None,
+    };
+
+    return match (left, right) {
+        (Value::String(left, _), Value::String(right, _)) => Some(left.cmp(right)),
+        (Value::Date(left, _), Value::Date(right, _)) => Some(left.cmp(right)),
+        _ => number(left)?.partial_cmp(&number(right)?),
+    };
+}
+
+enum Bound {
+    Lower,
+    Upper,
+}
+
+fn bound_of(predicate: &FilterOperator) -> Option<(Bound, &String, &Value, bool)> {
+    return match predicate {
+        FilterOperator::GreaterThan { field, value, .. } => {
+            Some((Bound::Lower, field, value, true))
+        }
+        FilterOperator::GreaterThanOrEquals { field, value, .. } => {
+            Some((Bound::Lower, field, value, false))
+        }
+        FilterOperator::LessThan { field, value, .. } => Some((Bound::Upper, field, value, true)),
+        FilterOperator::LessThanOrEquals { field, value, .. } => {
+            Some((Bound::Upper, field, value, false))
+        }
+        _ => None,
+    };
+}
+
+fn is_tighter(candidate: &FilterOperator, existing: &FilterOperator) -> Option<bool> {
+    let (bound, field, value, exclusive) = bound_of(candidate)?;
+    let (existing_bound, existing_field, existing_value, existing_exclusive) = bound_of(existing)?;
+
+    let same_side = matches!(
+        (&bound, &existing_bound),
+        (Bound::Lower, Bound::Lower) | (Bound::Upper, Bound::Upper)
+    );
+    if !same_side || field != existing_field {
+        return None;
+    }
+
+    return match (bound, compare_values(value, existing_value)?) {
+        (_, Ordering::Equal) => Some(exclusive && !existing_exclusive),
+        (Bound::Lower, ordering) => Some(ordering == Ordering::Greater),
+        (Bound::Upper, ordering) => Some(ordering == Ordering::Less),
+    };
+}
+
+fn merge_ranges(predicates: Vec<FilterOperator>) -> Vec<FilterOperator> {
+    let mut result: Vec<FilterOperator> = vec![];
+
+    'predicates: for predicate in predicates {
+        for existing in result.iter_mut() {
+            match is_tighter(&predicate, existing) {
+                Some(true) => {
+                    *existing = predicate;
+                    continue 'predicates;
+                }
+                Some(false) => continue 'predicates,
+                None => {}
+            }
+        }
+
+        result.push(predicate);
+    }
+
+    return result;
+}
+
+fn sort_key<T: ToBson>(node: &T) -> String {
+    return node.to_bson().into_canonical_extjson().to_string();
+}
+
+fn sorted(predicates: Vec<FilterOperator>) -> Vec<FilterOperator> {
+    let mut keyed: Vec<(String, FilterOperator)> = predicates
+        .into_iter()
+        .map(|predicate| (sort_key(&predicate), predicate))
+        .collect();
+    keyed.sort_by(|(left, _), (right, _)| left.cmp(right));
+    keyed.dedup_by(|(left, _), (right, _)| left == right);
+
+    return keyed.into_iter().map(|(_, predicate)| predicate).collect();
+}
+
+fn sorted_values(values: Vec<Value>) -> Vec<Value> {
+    let mut keyed: Vec<(String, Value)> = values
+        .into_iter()
+        .map(|value| (sort_key(&value), value))
+        .collect();
+    keyed.sort_by(|(left, _), (right, _)| left.cmp(right));
+    keyed.dedup_by(|(left, _), (right, _)| left == right);
+
+    return keyed.into_iter().map(|(_, value)| value).collect();
+}
+
+fn placeholder(value_type: &ValueType) -> String {
+    return match value_type {
+        ValueType::Any => "?".to_string(),
+        ValueType::Of(bson_type) if bson_type.is_numeric() => "?number".to_string(),
+        ValueType::Of(BsonType::Array) => "?array".to_string(),
+        other => format!("?{}", other.alias()),
+    };
+}
+
+fn shape_value(value: &Value) -> Value {
+    let shape = match value {
+        Value::Array(values, _) => {
+            let element_type = values
+                .iter()
+                .map(Value::value_type)
+                .fold(ValueType::Never, |joined, value_type| {
+                    joined.join(&value_type)
+                });
+            match element_type {
+                ValueType::Never => "?array<>".to_string(),
+                element_type => format!("?array<{}>", placeholder(&element_type)),
+            }
+        }
+        value => placeholder(&value.value_type()),
+    };
+
+    return Value::String(shape, value.span());
+}
+
+fn shape_values(values: &[Value]) -> Vec<Value> {
+    return sorted_values(values.iter().map(shape_value).collect());
+}
+
+fn shape_expression(expression: &Expression) -> Expression {
+    return match expression {
+        Expression::Literal(value) => Expression::Literal(shape_value(value)),
+        Expression::Object(fields) => Expression::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), shape_expression(value)))
+                .collect(),
+        ),
+        Expression::Array(values) => {
+            Expression::Array(values.iter().map(shape_expression).collect())
+        }
+        Expression::Operator { name, arguments } => Expression::Operator {
+            name: name.clone(),
+            arguments: arguments.iter().map(shape_expression).collect(),
+        },
+        other => other.clone(),
+    };
+}
+
+fn shape_geo(shape: &GeoShape) -> GeoShape {
+    return match shape {
+        GeoShape::Geometry(value) => GeoShape::Geometry(shape_value(value)),
+        GeoShape::Box(value) => GeoShape::Box(shape_value(value)),
+        GeoShape::Polygon(value) => GeoShape::Polygon(shape_value(value)),
+        GeoShape::Center(value) => GeoShape::Center(shape_value(value)),
+        GeoShape::CenterSphere(value) => GeoShape::CenterSphere(shape_value(value)),
+    };
+}
+
+fn shape_predicates(predicates: &[FilterOperator]) -> Vec<FilterOperator> {
+    return sorted(predicates.iter().filter_map(shape_predicate).collect());
+}
+
+fn shape_predicate(predicate: &FilterOperator) -> Option<FilterOperator> {
+    let optional = |value: &Option<Value>| value.as_ref().map(shape_value);
+    let mut shape = predicate.clone();
+
+    match &mut shape {
+        FilterOperator::And { predicates, .. }
+        | FilterOperator::Or { predicates, .. }
+        | FilterOperator::Nor { predicates, .. }
+        | FilterOperator::Not { predicates, .. }
+        | FilterOperator::ElemMatch { predicates, .. } => {
+            *predicates = shape_predicates(predicates)
+        }
+        FilterOperator::Equals { value, .. }
+        | FilterOperator::NotEquals { value, .. }
+        | FilterOperator::GreaterThan { value, .. }
+        | FilterOperator::LessThan { value, .. }
+        | FilterOperator::GreaterThanOrEquals { value, .. }
+        | FilterOperator::LessThanOrEquals { value, .. }
+        | FilterOperator::Size { size: value, .. }
+        | FilterOperator::BitsAllSet { mask: value, .. }
+        | FilterOperator::BitsAnySet { mask: value, .. }
+        | FilterOperator::BitsAllClear { mask: value, .. }
+        | FilterOperator::BitsAnyClear { mask: value, .. }
+        | FilterOperator::Regex { pattern: value, .. }
+        | FilterOperator::GeoIntersects {
+            geometry: value, ..
+        }
+        | FilterOperator::JsonSchema { schema: value, .. }
+        | FilterOperator::Text { search: value, .. }
+        | FilterOperator::Where { code: value, .. }
+        | FilterOperator::SampleRate { rate: value, .. } => *value = shape_value(value),
+        FilterOperator::In { values, .. }
+        | FilterOperator::NotIn { values, .. }
+        | FilterOperator::All { values, .. } => *values = shape_values(values),
+        FilterOperator::Mod {
+            divisor, remainder, ..
+        } => {
+            *divisor = shape_value(divisor);
+            *remainder = shape_value(remainder);
+        }
+        FilterOperator::GeoWithin { shape, .. } => *shape = shape_geo(shape),
+        FilterOperator::Near {
+            geometry,
+            max_distance,
+            min_distance,
+            ..
+        }
+        | FilterOperator::NearSphere {
+            geometry,
+            max_distance,
+            min_distance,
+            ..
+        } => {
+            *geometry = shape_value(geometry);
+            *max_distance = optional(max_distance);
+            *min_distance = optional(min_distance);
+        }
+        FilterOperator::Expr { expression, .. } => *expression = shape_expression(expression),
+        FilterOperator::Comment { .. } => return None,
+        FilterOperator::Exists { .. } | FilterOperator::Type { .. } => {}
+    }
+
+    return Some(shape);
+}
+
+pub fn query_shape(predicate: &FilterOperator) -> Document {
+    let normalized = normalize(predicate.clone());
+    return shape_predicate(&normalized)
+        .and_then(|shape| to_document(&shape).ok())
+        .unwrap_or_default();
+}
+
+pub fn query_shape_hash(predicate: &FilterOperator) -> String {
+    let shape = query_shape(predicate);
+    let canonical = Bson::Document(shape).into_canonical_extjson().to_string();
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in canonical.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+
+    return format!("{:016x}", hash);
+}
+
+#[cfg(test)]
+mod test {
+    use bson::doc;
+
+    use crate::filter::FilterOperator;
+    use crate::normalization::{normalize, query_shape, query_shape_hash};
+    use crate::parser::parse_filter;
+    use crate::span::Span;
+    use crate::values::Value;
+
+    #[test]
+    fn flattens_dedupes_and_sorts_predicates() {
+        let filter =
+            parse_filter(r#"{ $and: [ { name: "a" }, { $and: [ { age: 1 }, { name: "a" } ] } ] }"#)
+                .unwrap();
+
+        assert_eq!(
+            normalize(filter),
+            parse_filter(r#"{ age: 1, name: "a" }"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn pushes_down_negations() {
+        let filter = parse_filter(
+            r#"{ status: { $not: { $in: ["A", "B"] } }, $nor: [ { deleted: true } ] }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            normalize(filter),
+            parse_filter(r#"{ deleted: { $ne: true }, status: { $nin: ["A", "B"] } }"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn merges_range_predicates_on_the_same_field() {
+        let filter = parse_filter(
+            r#"{ $and: [ { age: { $gt: 18, $lt: 65 } }, { age: { $gte: 21, $lt: 70 } } ] }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            normalize(filter),
+            parse_filter(r#"{ age: { $gte: 21, $lt: 65 } }"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn keeps_spans_through_normalization() {
+        let span = Span {
+            start_byte: 10,
+            end_byte: 20,
+            ..Span::default()
+        };
+        let filter = FilterOperator::And {
+            predicates: vec![FilterOperator::Equals {
+                field: "name".to_string(),
+                value: Value::String("a".to_string(), span),
+                span,
+            }],
+            span: Span::default(),
+        };
+
+        let normalized = normalize(filter);
+        assert_eq!(normalized.span().start_byte, 10);
+    }
+
+    #[test]
+    fn computes_stable_query_shapes() {
+        let left =
+            parse_filter(r#"{ age: { $gt: 18 }, name: "a", tags: { $in: [1, 2] } }"#).unwrap();
+        let right =
+            parse_filter(r#"{ tags: { $in: [3] }, name: "b", age: { $gt: 21.5 }, $comment: "x" }"#)
+                .unwrap();
+
+        assert_eq!(
+            query_shape(&left),
+            doc! {
+                "age": { "$gt": "?number" },
+                "name": "?string",
+                "tags": { "$in": ["?number"] },
+            }
+        );
+        assert_eq!(query_shape_hash(&left), query_shape_hash(&right));
+        assert_ne!(
+            query_shape_hash(&left),
+            query_shape_hash(&parse_filter(r#"{ age: { $lt: 18 } }"#).unwrap())
+        );
+    }
+}
@@ -0,0 +1,662 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use bson::oid::ObjectId;
+use bson::spec::BinarySubtype;
+use bson::{Binary, Bson, DateTime, Document, Regex, Timestamp, Uuid};
+
+use crate::aggregation::Stage;
+use crate::filter::FilterOperator;
+use crate::serialization::{FromBson, SerializationError};
+use crate::update::Update;
+
+const EXTENDED_JSON_KEYS: [&str; 15] = [
+    "$oid",
+    "$date",
+    "$numberInt",
+    "$numberLong",
+    "$numberDouble",
+    "$numberDecimal",
+    "$binary",
+    "$uuid",
+    "$timestamp",
+    "$regularExpression",
+    "$minKey",
+    "$maxKey",
+    "$symbol",
+    "$code",
+    "$undefined",
+];
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct ParseError {
+    pub message: String,
+    pub offset: usize,
+    pub row: usize,
+    pub column: usize,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(
+            f,
+            "{} at line {}, column {}",
+            self.message,
+            self.row + 1,
+            self.column + 1
+        );
+    }
+}
+
+impl Error for ParseError {}
+
+struct Parser<'a> {
+    source: &'a [u8],
+    offset: usize,
+    path: Vec<String>,
+    positions: HashMap<Vec<String>, usize>,
+}
+
+pub fn parse<T: FromBson>(source: &str) -> Result<T, ParseError> {
+    let mut parser = Parser {
+        source: source.as_bytes(),
+        offset: 0,
+        path: vec![],
+        positions: HashMap::new(),
+    };
+
+    let bson = parser.parse_document()?;
+    return T::from_bson(&bson).map_err(|e| parser.locate(e));
+}
+
+pub fn parse_filter(source: &str) -> Result<FilterOperator, ParseError> {
+    return parse(source);
+}
+
+pub fn parse_update(source: &str) -> Result<Update, ParseError> {
+    return parse(source);
+}
+
+pub fn parse_pipeline(source: &str) -> Result<Vec<Stage>, ParseError> {
+    return parse(source);
+}
+
+fn is_identifier_start(byte: u8) -> bool {
+    return byte.is_ascii_alphabetic() || byte == b'_' || byte == b'$';
+}
+
+fn is_identifier_part(byte: u8) -> bool {
+    return is_identifier_start(byte) || byte.is_ascii_digit();
+}
+
+impl Parser<'_> {
+    fn error_at(&self, offset: usize, message: String) -> ParseError {
+        let before = &self.source[..offset.min(self.source.len())];
+        let row = before.iter().filter(|byte| **byte == b'\n').count();
+        let line_start = before
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map(|idx| idx + 1)
+            .unwrap_or(0);
+
+        return ParseError {
+            message,
+            offset,
+            row,
+            column: offset - line_start,
+        };
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        return self.error_at(self.offset, message);
+    }
+
+    fn locate(&self, error: SerializationError) -> ParseError {
+        let offset = (0..=error.path.len())
+            .rev()
+            .find_map(|len| self.positions.get(&error.path[..len]))
+            .copied()
+            .unwrap_or(0);
+
+        return self.error_at(offset, error.message);
+    }
+
+    fn peek(&self) -> Option<u8> {
+        return self.source.get(self.offset).copied();
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match (self.peek(), self.source.get(self.offset + 1)) {
+                (Some(byte), _) if byte.is_ascii_whitespace() => self.offset += 1,
+                (Some(b'/'), Some(b'/')) => {
+                    while !matches!(self.peek(), None | Some(b'\n')) {
+                        self.offset += 1;
+                    }
+                }
+                (Some(b'/'), Some(b'*')) => {
+                    self.offset += 2;
+                    while self.offset < self.source.len()
+                        && !self.source[self.offset..].starts_with(b"*/")
+                    {
+                        self.offset += 1;
+                    }
+                    self.offset = (self.offset + 2).min(self.source.len());
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(self.unexpected(&format!("'{}'", expected as char)));
+        }
+
+        self.offset += 1;
+        return Ok(());
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        return match self.peek() {
+            Some(byte) => self.error(format!(
+                "expected {} but found '{}'",
+                expected, byte as char
+            )),
+            None => self.error(format!("expected {} but found end of input", expected)),
+        };
+    }
+
+    fn parse_document(&mut self) -> Result<Bson, ParseError> {
+        let value = self.parse_value()?;
+        self.skip_whitespace();
+        if self.peek().is_some() {
+            return Err(self.error("unexpected trailing characters".to_string()));
+        }
+
+        return Ok(value);
+    }
+
+    fn parse_value(&mut self) -> Result<Bson, ParseError> {
+        self.skip_whitespace();
+        return match self.peek() {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') | Some(b'\'') => self.parse_string().map(Bson::String),
+            Some(b'/') => self.parse_regex(),
+            Some(byte) if byte == b'-' || byte == b'+' || byte == b'.' || byte.is_ascii_digit() => {
+                self.parse_number()
+            }
+            Some(byte) if is_identifier_start(byte) => self.parse_identifier_value(),
+            _ => Err(self.unexpected("a value")),
+        };
+    }
+
+    fn parse_object(&mut self) -> Result<Bson, ParseError> {
+        let start = self.offset;
+        self.expect(b'{')?;
+        let mut document = Document::new();
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(b'}') {
+                self.offset += 1;
+                break;
+            }
+
+            let key_offset = self.offset;
+            let key = match self.peek() {
+                Some(b'"') | Some(b'\'') => self.parse_string()?,
+                Some(byte) if is_identifier_start(byte) => self.parse_identifier(),
+                _ => return Err(self.unexpected("a field name")),
+            };
+
+            if document.contains_key(&key) {
+                return Err(self.error_at(key_offset, format!("duplicate field {}", key)));
+            }
+
+            self.path.push(key.clone());
+            self.positions.insert(self.path.clone(), key_offset);
+            self.expect(b':')?;
+            let value = self.parse_value()?;
+            self.path.pop();
+            document.insert(key, value);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.offset += 1,
+                Some(b'}') => {}
+                _ => return Err(self.unexpected("',' or '}'")),
+            }
+        }
+
+        let is_extended_json = document
+            .keys()
+            .next()
+            .map(|key| EXTENDED_JSON_KEYS.contains(&key.as_str()))
+            .unwrap_or(false);
+
+        if is_extended_json {
+            let json = Bson::Document(document).into_relaxed_extjson();
+            return Bson::try_from(json).map_err(|e| self.error_at(start, e.to_string()));
+        }
+
+        return Ok(Bson::Document(document));
+    }
+
+    fn parse_array(&mut self) -> Result<Bson, ParseError> {
+        self.expect(b'[')?;
+        let mut elements = vec![];
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(b']') {
+                self.offset += 1;
+                break;
+            }
+
+            self.path.push(elements.len().to_string());
+            self.positions.insert(self.path.clone(), self.offset);
+            elements.push(self.parse_value()?);
+            self.path.pop();
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.offset += 1,
+                Some(b']') => {}
+                _ => return Err(self.unexpected("',' or ']'")),
+            }
+        }
+
+        return Ok(Bson::Array(elements));
+    }
+
+    fn parse_string(&mut self) -> Result<String, ParseError> {
+        let start = self.offset;
+        let quote = self.source[self.offset];
+        self.offset += 1;
+        let mut bytes = vec![];
+
+        loop {
+            let Some(byte) = self.peek() else {
+                return Err(self.error_at(start, "unterminated string".to_string()));
+            };
+            self.offset += 1;
+
+            match byte {
+                _ if byte == quote => break,
+                b'\\' => {
+                    let Some(escaped) = self.peek() else {
+                        return Err(self.error_at(start, "unterminated string".to_string()));
+                    };
+                    self.offset += 1;
+                    match escaped {
+                        b'n' => bytes.push(b'\n'),
+                        b't' => bytes.push(b'\t'),
+                        b'r' => bytes.push(b'\r'),
+                        b'b' => bytes.push(0x08),
+                        b'f' => bytes.push(0x0c),
+                        b'u' => {
+                            let character = self.parse_unicode_escape()?;
+                            let mut buffer = [0; 4];
+                            bytes.extend(character.encode_utf8(&mut buffer).as_bytes());
+                        }
+                        other => bytes.push(other),
+                    }
+                }
+                _ => bytes.push(byte),
+            }
+        }
+
+        return String::from_utf8(bytes)
+            .map_err(|_| self.error_at(start, "invalid UTF-8".to_string()));
+    }
+
+    fn parse_hex_code_unit(&mut self) -> Result<u32, ParseError> {
+        let digits = self
+            .source
+            .get(self.offset..self.offset + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or(self.error("invalid unicode escape".to_string()))?;
+        self.offset += 4;
+        return Ok(digits);
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let start = self.offset;
+        let high = self.parse_hex_code_unit()?;
+        if (0xD800..0xDC00).contains(&high) && self.source[self.offset..].starts_with(b"\\u") {
+            self.offset += 2;
+            let low = self.parse_hex_code_unit()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error_at(start, "invalid unicode escape".to_string()));
+            }
+            let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+            return char::from_u32(code)
+                .ok_or(self.error_at(start, "invalid unicode escape".to_string()));
+        }
+
+        return char::from_u32(high)
+            .ok_or(self.error_at(start, "invalid unicode escape".to_string()));
+    }
+
+    fn parse_regex(&mut self) -> Result<Bson, ParseError> {
+        let start = self.offset;
+        self.offset += 1;
+        let mut in_class = false;
+
+        loop {
+            match self.peek() {
+                None | Some(b'\n') => {
+                    return Err(self.error_at(start, "unterminated regular expression".to_string()))
+                }
+                Some(b'\\') => self.offset += 2,
+                Some(b'[') => {
+                    in_class = true;
+                    self.offset += 1;
+                }
+                Some(b']') => {
+                    in_class = false;
+                    self.offset += 1;
+                }
+                Some(b'/') if !in_class => break,
+                Some(_) => self.offset += 1,
+            }
+        }
+
+        let pattern = String::from_utf8_lossy(&self.source[start + 1..self.offset]).to_string();
+        self.offset += 1;
+        let flags = self.parse_identifier();
+        let mut options: Vec<char> = flags.chars().filter(|flag| *flag != 'g').collect();
+        options.sort();
+
+        return Ok(Bson::RegularExpression(Regex {
+            pattern,
+            options: options.into_iter().collect(),
+        }));
+    }
+
+    fn parse_number(&mut self) -> Result<Bson, ParseError> {
+        let start = self.offset;
+        if matches!(self.peek(), Some(b'-') | Some(b'+')) {
+            self.offset += 1;
+        }
+
+        if self.peek().map(is_identifier_start).unwrap_or(false) {
+            let negative = self.source[start] == b'-';
+            return match self.parse_identifier().as_str() {
+                "Infinity" if negative => Ok(Bson::Double(f64::NEG_INFINITY)),
+                "Infinity" => Ok(Bson::Double(f64::INFINITY)),
+                "NaN" => Ok(Bson::Double(f64::NAN)),
+                _ => Err(self.error_at(start, "invalid number".to_string())),
+            };
+        }
+
+        let mut is_floating = false;
+        while let Some(byte) = self.peek() {
+            match byte {
+                b'0'..=b'9' => {}
+                b'.' | b'e' | b'E' => is_floating = true,
+                b'-' | b'+' if matches!(self.source[self.offset - 1], b'e' | b'E') => {}
+                _ => break,
+            }
+            self.offset += 1;
+        }
+
+        let text = std::str::from_utf8(&self.source[start..self.offset]).unwrap();
+        let invalid = || self.error_at(start, format!("invalid number {}", text));
+        if is_floating {
+            return text.parse().map(Bson::Double).map_err(|_| invalid());
+        }
+
+        return match text.parse::<i64>() {
+            Ok(number) => Ok(i32::try_from(number)
+                .map(Bson::Int32)
+                .unwrap_or(Bson::Int64(number))),
+            Err(_) => text.parse().map(Bson::Double).map_err(|_| invalid()),
+        };
+    }
+
+    fn parse_identifier(&mut self) -> String {
+        let start = self.offset;
+        while self.peek().map(is_identifier_part).unwrap_or(false) {
+            self.offset += 1;
+        }
+
+        return String::from_utf8_lossy(&self.source[start..self.offset]).to_string();
+    }
+
+    fn parse_arguments(&mut self) -> Result<Vec<Bson>, ParseError> {
+        self.skip_whitespace();
+        if self.peek() != Some(b'(') {
+            return Ok(vec![]);
+        }
+
+        self.offset += 1;
+        let mut arguments = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(b')') {
+                self.offset += 1;
+                return Ok(arguments);
+            }
+
+            arguments.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.offset += 1,
+                Some(b')') => {}
+                _ => return Err(self.unexpected("',' or ')'")),
+            }
+        }
+    }
+
+    fn parse_identifier_value(&mut self) -> Result<Bson, ParseError> {
+        let start = self.offset;
+        let mut name = self.parse_identifier();
+        if name == "new" {
+            self.skip_whitespace();
+            name = self.parse_identifier();
+        }
+
+        let arguments = self.parse_arguments()?;
+        let invalid = |message: &str| self.error_at(start, format!("{}: {}", name, message));
+
+        let value = match (name.as_str(), &arguments[..]) {
+            ("true", []) => Bson::Boolean(true),
+            ("false", []) => Bson::Boolean(false),
+            ("null", []) => Bson::Null,
+            ("undefined", []) => Bson::Undefined,
+            ("Infinity", []) => Bson::Double(f64::INFINITY),
+            ("NaN", []) => Bson::Double(f64::NAN),
+            ("MinKey", []) => Bson::MinKey,
+            ("MaxKey", []) => Bson::MaxKey,
+            ("ObjectId", [Bson::String(hex)]) => ObjectId::parse_str(hex)
+                .map(Bson::ObjectId)
+                .map_err(|e| invalid(&e.to_string()))?,
+            ("ObjectId", []) => Bson::ObjectId(ObjectId::new()),
+            ("ISODate" | "Date", [Bson::String(date)]) => {
+                let date = match date.len() {
+                    10 => format!("{}T00:00:00Z", date),
+                    _ => date.clone(),
+                };
+                DateTime::parse_rfc3339_str(date)
+                    .map(Bson::DateTime)
+                    .map_err(|e| invalid(&e.to_string()))?
+            }
+            ("ISODate" | "Date", [Bson::Int32(millis)]) => {
+                Bson::DateTime(DateTime::from_millis(*millis as i64))
+            }
+            ("ISODate" | "Date", [Bson::Int64(millis)]) => {
+                Bson::DateTime(DateTime::from_millis(*millis))
+            }
+            ("ISODate" | "Date", []) => Bson::DateTime(DateTime::now()),
+            ("NumberInt", [Bson::Int32(number)]) => Bson::Int32(*number),
+            ("NumberInt", [Bson::String(number)]) => number
+                .parse()
+                .map(Bson::Int32)
+                .map_err(|_| invalid("invalid int32"))?,
+            ("NumberLong", [Bson::Int32(number)]) => Bson::Int64(*number as i64),
+            ("NumberLong", [Bson::Int64(number)]) => Bson::Int64(*number),
+            ("NumberLong", [Bson::String(number)]) => number
+                .parse()
+                .map(Bson::Int64)
+                .map_err(|_| invalid("invalid int64"))?,
+            ("NumberDecimal", [Bson::String(number)]) => number
+                .parse()
+                .map(Bson::Decimal128)
+                .map_err(|_| invalid("invalid decimal"))?,
+            ("Timestamp", [Bson::Int32(time), Bson::Int32(increment)]) => {
+                Bson::Timestamp(Timestamp {
+                    time: *time as u32,
+                    increment: *increment as u32,
+                })
+            }
+            ("UUID", [Bson::String(uuid)]) => Uuid::parse_str(uuid)
+                .map(|uuid| Bson::Binary(Binary::from(uuid)))
+                .map_err(|e| invalid(&e.to_string()))?,
+            ("BinData", [Bson::Int32(subtype), Bson::String(base64)]) => {
+                Binary::from_base64(base64, BinarySubtype::from(*subtype as u8))
+                    .map(Bson::Binary)
+                    .map_err(|e| invalid(&e.to_string()))?
+            }
+            ("RegExp", [Bson::String(pattern)]) => Bson::RegularExpression(Regex {
+                pattern: pattern.clone(),
+                options: "".to_string(),
+            }),
+            ("RegExp", [Bson::String(pattern), Bson::String(options)]) => {
+                Bson::RegularExpression(Regex {
+                    pattern: pattern.clone(),
+                    options: options.clone(),
+                })
+            }
+            _ => return Err(invalid("unknown value or invalid arguments")),
+        };
+
+        return Ok(value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::aggregation::Stage;
+    use crate::filter::FilterOperator::{And, Equals, GreaterThan, GreaterThanOrEquals, Regex};
+    use crate::parser::{parse_filter, parse_pipeline, parse_update, ParseError};
+    use crate::span::Span;
+    use crate::update::{Update, UpdateOperator};
+    use crate::values::Value;
+
+    #[test]
+    fn parses_shell_syntax_filters() {
+        let filter = parse_filter(
+            r#"{
+                // adults only
+                age: { $gt: 18 },
+                'name': /^mongo/i,
+                _id: ObjectId("65300b2f8c3a4e0f1c2b3a4d"),
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            filter,
+            And {
+                predicates: vec![
+                    GreaterThan {
+                        field: "age".to_string(),
+                        value: Value::Integer(18, Span::default()),
+                        span: Span::default()
+                    },
+                    Regex {
+                        field: "name".to_string(),
+                        pattern: Value::String("^mongo".to_string(), Span::default()),
+                        options: Some("i".to_string()),
+                        span: Span::default()
+                    },
+                    Equals {
+                        field: "_id".to_string(),
+                        value: Value::ObjectId(
+                            "65300b2f8c3a4e0f1c2b3a4d".to_string(),
+                            Span::default()
+                        ),
+                        span: Span::default()
+                    },
+                ],
+                span: Span::default()
+            }
+        );
+    }
+
+    #[test]
+    fn parses_extended_json_wrappers() {
+        let filter =
+            parse_filter(r#"{ "createdAt": { "$gte": { "$date": "2023-10-17T00:00:00Z" } } }"#)
+                .unwrap();
+
+        assert_eq!(
+            filter,
+            GreaterThanOrEquals {
+                field: "createdAt".to_string(),
+                value: Value::Date(1697500800000, Span::default()),
+                span: Span::default()
+            }
+        );
+    }
+
+    #[test]
+    fn parses_pipelines_and_updates() {
+        let pipeline =
+            parse_pipeline(r#"[{ $match: { status: "A" } }, { $count: "total" }]"#).unwrap();
+        let update = parse_update(r#"{ $set: { "profile.name": "mongold" } }"#).unwrap();
+
+        assert_eq!(pipeline.len(), 2);
+        assert_eq!(
+            pipeline[1],
+            Stage::Count {
+                field: "total".to_string()
+            }
+        );
+        assert_eq!(
+            update,
+            Update::Operators(vec![UpdateOperator::Set {
+                field: "profile.name".to_string(),
+                value: Value::String("mongold".to_string(), Span::default())
+            }])
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_their_position() {
+        let error = parse_filter("{\n  age: { $gt 18 }\n}").unwrap_err();
+
+        assert_eq!(
+            error,
+            ParseError {
+                message: "expected ':' but found '1'".to_string(),
+                offset: 15,
+                row: 1,
+                column: 13,
+            }
+        );
+    }
+
+    #[test]
+    fn reports_semantic_errors_at_the_offending_field() {
+        let error = parse_filter("{\n  age: { $gt: 18, $foo: 1 }\n}").unwrap_err();
+
+        assert_eq!(error.message, "unknown query operator $foo");
+        assert_eq!((error.row, error.column), (1, 18));
+    }
+
+    #[test]
+    fn rejects_unpaired_surrogate_escapes() {
+        let mismatched = parse_filter(r#"{ a: "\uD800\u0041" }"#).unwrap_err();
+        let lone = parse_filter(r#"{ a: "\uD800" }"#).unwrap_err();
+
+        assert_eq!(mismatched.message, "invalid unicode escape");
+        assert_eq!(lone.message, "invalid unicode escape");
+    }
+}
@@ -0,0 +1,356 @@
+use crate::aggregation::Stage;
+use crate::execution::Execution;
+use crate::filter::FilterOperator;
+use crate::span::Span;
+use crate::update::{Update, UpdateOperator, WriteModel};
+use crate::values::{BsonType, Value, ValueType};
+use crate::visit::{visit_update, visit_write_model, Visitor};
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct SchemaField {
+    pub name: String,
+    pub value_type: ValueType,
+    pub schema: Option<DocumentSchema>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct DocumentSchema {
+    pub name: String,
+    pub fields: Vec<SchemaField>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum SchemaViolation {
+    UnknownField {
+        field: String,
+        span: Span,
+    },
+    MismatchedType {
+        field: String,
+        expected: ValueType,
+        actual: ValueType,
+        span: Span,
+    },
+}
+
+enum Lookup<'a> {
+    Field(&'a SchemaField),
+    Opaque,
+    Unknown,
+}
+
+fn is_positional(segment: &str) -> bool {
+    return segment.starts_with('$') || segment.parse::<usize>().is_ok();
+}
+
+impl DocumentSchema {
+    fn lookup(&self, path: &str) -> Lookup<'_> {
+        let mut current = self;
+        let mut segments = path.split('.').peekable();
+        while let Some(segment) = segments.next() {
+            if is_positional(segment) {
+                continue;
+            }
+
+            let Some(field) = current.fields.iter().find(|field| field.name == segment) else {
+                return match segment {
+                    "_id" if std::ptr::eq(current, self) => Lookup::Opaque,
+                    _ => Lookup::Unknown,
+                };
+            };
+
+            if segments.peek().is_none() {
+                return Lookup::Field(field);
+            }
+
+            match &field.schema {
+                Some(schema) => current = schema,
+                None => return Lookup::Opaque,
+            }
+        }
+
+        return Lookup::Opaque;
+    }
+
+    pub fn declares(&self, path: &str) -> bool {
+        return !matches!(self.lookup(path), Lookup::Unknown);
+    }
+
+    pub fn field_type(&self, path: &str) -> ValueType {
+        return match self.lookup(path) {
+            Lookup::Field(field) => field.value_type.clone(),
+            _ => ValueType::Any,
+        };
+    }
+
+    pub fn nested(&self, path: &str) -> Option<&DocumentSchema> {
+        return match self.lookup(path) {
+            Lookup::Field(field) => field.schema.as_ref(),
+            _ => None,
+        };
+    }
+
+    pub fn paths(&self) -> Vec<String> {
+        let mut result = vec![];
+        for field in &self.fields {
+            result.push(field.name.clone());
+            if let Some(schema) = &field.schema {
+                result.extend(
+                    schema
+                        .paths()
+                        .into_iter()
+                        .map(|path| format!("{}.{}", field.name, path)),
+                );
+            }
+        }
+
+        return result;
+    }
+
+    pub fn violations(&self, execution: &Execution) -> Vec<SchemaViolation> {
+        let mut checker = SchemaChecker {
+            schema: self,
+            span: execution.span(),
+            reshaped: false,
+            violations: vec![],
+        };
+        checker.visit_execution(execution);
+
+        return checker.violations;
+    }
+}
+
+fn numeric(value_type: &ValueType) -> bool {
+    return match value_type {
+        ValueType::Of(bson_type) => bson_type.is_numeric(),
+        ValueType::OneOf(types) => types.iter().all(BsonType::is_numeric),
+        _ => false,
+    };
+}
+
+struct SchemaChecker<'a> {
+    schema: &'a DocumentSchema,
+    span: Span,
+    reshaped: bool,
+    violations: Vec<SchemaViolation>,
+}
+
+impl<'a> SchemaChecker<'a> {
+    fn check_field(&mut self, schema: &DocumentSchema, field: &str, span: Span) -> bool {
+        if field.is_empty() || field.starts_with('$') || schema.declares(field) {
+            return true;
+        }
+
+        self.violations.push(SchemaViolation::UnknownField {
+            field: field.to_string(),
+            span,
+        });
+        return false;
+    }
+
+    fn check_value(&mut self, schema: &DocumentSchema, field: &str, value: &Value) {
+        let expected = schema.field_type(field);
+        let actual = value.value_type();
+        if expected == ValueType::Of(BsonType::Array)
+            || actual == ValueType::Of(BsonType::Null)
+            || (numeric(&expected) && numeric(&actual))
+            || expected.is_compatible_with(&actual)
+        {
+            return;
+        }
+
+        self.violations.push(SchemaViolation::MismatchedType {
+            field: field.to_string(),
+            expected,
+            actual,
+            span: value.span(),
+        });
+    }
+
+    fn check_filter(&mut self, schema: &DocumentSchema, predicate: &FilterOperator) {
+        let span = predicate.span();
+        match predicate {
+            FilterOperator::And { predicates, .. }
+            | FilterOperator::Or { predicates, .. }
+            | FilterOperator::Nor { predicates, .. }
+            | FilterOperator::Not { predicates, .. } => {
+                predicates
+                    .iter()
+                    .for_each(|predicate| self.check_filter(schema, predicate));
+                return;
+            }
+            _ => {}
+        }
+
+        let Some(field) = predicate.field() else {
+            return;
+        };
+        if !self.check_field(schema, field, span) {
+            return;
+        }
+
+        match predicate {
+            FilterOperator::Equals { value, .. }
+            | FilterOperator::NotEquals { value, .. }
+            | FilterOperator::GreaterThan { value, .. }
+            | FilterOperator::LessThan { value, .. }
+            | FilterOperator::GreaterThanOrEquals { value, .. }
+            | FilterOperator::LessThanOrEquals { value, .. } => {
+                self.check_value(schema, field, value)
+            }
+            FilterOperator::In { values, .. } | FilterOperator::NotIn { values, .. } => values
+                .iter()
+                .filter(|value| !value.is_array_reference())
+                .for_each(|value| self.check_value(schema, field, value)),
+            FilterOperator::ElemMatch { predicates, .. } => {
+                if let Some(element) = schema.nested(field) {
+                    predicates
+                        .iter()
+                        .for_each(|predicate| self.check_filter(element, predicate));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'a> Visitor for SchemaChecker<'a> {
+    fn visit_filter(&mut self, predicate: &FilterOperator) {
+        if !self.reshaped {
+            self.check_filter(self.schema, predicate);
+        }
+    }
+
+    fn visit_stage(&mut self, stage: &Stage) {
+        match stage {
+            Stage::Match { predicate } => self.visit_filter(predicate),
+            Stage::Sort { .. }
+            | Stage::Limit { .. }
+            | Stage::Skip { .. }
+            | Stage::Sample { .. } => {}
+            _ => self.reshaped = true,
+        }
+    }
+
+    // array filters and pipeline updates name fields the document schema does not declare
+    fn visit_update(&mut self, update: &Update) {
+        visit_update(self, update);
+        self.reshaped = true;
+    }
+
+    fn visit_write_model(&mut self, model: &WriteModel) {
+        self.reshaped = false;
+        visit_write_model(self, model);
+    }
+
+    fn visit_update_operator(&mut self, operator: &UpdateOperator) {
+        let schema = self.schema;
+        let span = self.span;
+        match operator {
+            UpdateOperator::Set { field, value }
+            | UpdateOperator::SetOnInsert { field, value }
+            | UpdateOperator::Min { field, value }
+            | UpdateOperator::Max { field, value } => {
+                if self.check_field(schema, field, span) {
+                    self.check_value(schema, field, value);
+                }
+            }
+            UpdateOperator::Unset { field }
+            | UpdateOperator::Inc { field, .. }
+            | UpdateOperator::Mul { field, .. }
+            | UpdateOperator::Rename { field, .. }
+            | UpdateOperator::CurrentDate { field, .. }
+            | UpdateOperator::Push { field, .. }
+            | UpdateOperator::AddToSet { field, .. }
+            | UpdateOperator::Pop { field, .. }
+            | UpdateOperator::Pull { field, .. }
+            | UpdateOperator::PullAll { field, .. }
+            | UpdateOperator::Bit { field, .. } => {
+                self.check_field(schema, field, span);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::execution::Execution::FindMany;
+    use crate::execution::{ExecutionContext, ExecutionNamespace, FindOptions};
+    use crate::parser::parse_filter;
+    use crate::schema::{DocumentSchema, SchemaField, SchemaViolation};
+    use crate::span::Span;
+    use crate::values::{BsonType, ValueType};
+
+    fn field(name: &str, bson_type: BsonType, schema: Option<DocumentSchema>) -> SchemaField {
+        return SchemaField {
+            name: name.to_string(),
+            value_type: ValueType::Of(bson_type),
+            schema,
+        };
+    }
+
+    #[test]
+    fn checks_field_paths_and_types_against_a_schema() {
+        let address = DocumentSchema {
+            name: "Address".to_string(),
+            fields: vec![field("city", BsonType::String, None)],
+        };
+        let user = DocumentSchema {
+            name: "User".to_string(),
+            fields: vec![
+                field("name", BsonType::String, None),
+                field("age", BsonType::Int, None),
+                field("address", BsonType::Object, Some(address.clone())),
+                field("visits", BsonType::Array, Some(address)),
+            ],
+        };
+
+        let execution = FindMany {
+            namespace: ExecutionNamespace::empty("users".to_string()),
+            predicate: parse_filter(
+                r#"{ "_id": 1, "age": "old", "address.city": "Paris", "nickname": 1,
+                     "visits": { "$elemMatch": { "town": "Rome" } }, "weight": { "$gt": 3.5 },
+                     "visits.0.city": "Oslo" }"#,
+            )
+            .unwrap(),
+            options: FindOptions::default(),
+            context: ExecutionContext::default(),
+            span: Span::default(),
+        };
+
+        assert_eq!(
+            user.paths(),
+            vec![
+                "name",
+                "age",
+                "address",
+                "address.city",
+                "visits",
+                "visits.city"
+            ]
+        );
+        assert_eq!(
+            user.violations(&execution),
+            vec![
+                SchemaViolation::MismatchedType {
+                    field: "age".to_string(),
+                    expected: ValueType::Of(BsonType::Int),
+                    actual: ValueType::Of(BsonType::String),
+                    span: Span::default(),
+                },
+                SchemaViolation::UnknownField {
+                    field: "nickname".to_string(),
+                    span: Span::default(),
+                },
+                SchemaViolation::UnknownField {
+                    field: "town".to_string(),
+                    span: Span::default(),
+                },
+                SchemaViolation::UnknownField {
+                    field: "weight".to_string(),
+                    span: Span::default(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,749 @@
+use bson::{doc, Bson, Document};
+
+use crate::aggregation::{
+    Expression, MergeWhenMatched, MergeWhenNotMatched, ProjectionField, SortDirection, Stage,
+};
+use crate::filter::FilterOperator;
+use crate::serialization::filter::is_operators;
+use crate::serialization::{
+    expect_bool, expect_document, expect_string, field_from_bson, fields_from_bson, fields_to_bson,
+    FromBson, SerializationError, ToBson,
+};
+use crate::values::Value;
+
+impl ToBson for Expression {
+    fn to_bson(&self) -> Bson {
+        return match self {
+            Expression::Literal(value) => match value {
+                Value::String(string, _) if string.starts_with('$') => {
+                    Bson::Document(doc! { "$literal": string.clone() })
+                }
+                Value::Object(_, _) | Value::Array(_, _) => {
+                    Bson::Document(doc! { "$literal": value.to_bson() })
+                }
+                _ => value.to_bson(),
+            },
+            Expression::FieldPath(path) => Bson::String(format!("${}", path)),
+            Expression::Variable(name) => Bson::String(format!("$${}", name)),
+            Expression::Object(fields) => Bson::Document(fields_to_bson(fields)),
+            Expression::Array(elements) => elements.to_bson(),
+            Expression::Operator { name, arguments } => match &arguments[..] {
+                [single] => Bson::Document(doc! { name: single.to_bson() }),
+                _ => Bson::Document(doc! { name: arguments.to_bson() }),
+            },
+        };
+    }
+}
+
+impl FromBson for Expression {
+    fn from_bson(bson: &Bson) -> Result<Self, SerializationError> {
+        return match bson {
+            Bson::String(path) if path.starts_with("$$") => {
+                Ok(Expression::Variable(path[2..].to_string()))
+            }
+            Bson::String(path) if path.starts_with('$') => {
+                Ok(Expression::FieldPath(path[1..].to_string()))
+            }
+            Bson::Array(_) => Vec::from_bson(bson).map(Expression::Array),
+            Bson::Document(document) if document.len() == 1 && is_operators(document) => {
+                let (name, argument) = document.iter().next().unwrap();
+                if name == "$literal" {
+                    return Value::from_bson(argument)
+                        .map(Expression::Literal)
+                        .map_err(|e| e.at(name));
+                }
+
+                let arguments = match argument {
+                    Bson::Array(_) => Vec::from_bson(argument),
+                    single => Expression::from_bson(single).map(|argument| vec![argument]),
+                }
+                .map_err(|e| e.at(name))?;
+
+                Ok(Expression::Operator {
+                    name: name.clone(),
+                    arguments,
+                })
+            }
+            Bson::Document(document) => fields_from_bson(document).map(Expression::Object),
+            literal => Value::from_bson(literal).map(Expression::Literal),
+        };
+    }
+}
+
+impl ToBson for ProjectionField {
+    fn to_bson(&self) -> Bson {
+        return match self {
+            ProjectionField::Include => Bson::Int32(1),
+            ProjectionField::Exclude => Bson::Int32(0),
+            ProjectionField::Computed(expression) => expression.to_bson(),
+            ProjectionField::Slice {
+                skip: Some(skip),
+                limit,
+            } => Bson::Document(doc! { "$slice": [skip.to_bson(), limit.to_bson()] }),
+            ProjectionField::Slice { skip: None, limit } => {
+                Bson::Document(doc! { "$slice": limit.to_bson() })
+            }
+            ProjectionField::ElemMatch(predicate) => {
+                Bson::Document(doc! { "$elemMatch": predicate.to_bson() })
+            }
+            ProjectionField::Meta(name) => Bson::Document(doc! { "$meta": name.clone() }),
+        };
+    }
+}
+
+fn is_number(bson: &Bson) -> bool {
+    return matches!(bson, Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_));
+}
+
+impl FromBson for ProjectionField {
+    fn from_bson(bson: &Bson) -> Result<Self, SerializationError> {
+        if let Bson::Document(document) = bson {
+            if let (1, Some(slice)) = (document.len(), document.get("$slice")) {
+                match slice {
+                    Bson::Array(parts) if parts.len() == 2 && is_number(&parts[0]) => {
+                        return Ok(ProjectionField::Slice {
+                            skip: Some(Value::from_bson(&parts[0])?),
+                            limit: Value::from_bson(&parts[1])?,
+                        });
+                    }
+                    Bson::Array(_) => {}
+                    limit => {
+                        return Ok(ProjectionField::Slice {
+                            skip: None,
+                            limit: Value::from_bson(limit)?,
+                        })
+                    }
+                }
+            }
+
+            if let (1, Some(predicate)) = (document.len(), document.get("$elemMatch")) {
+                return FilterOperator::from_bson(predicate)
+                    .map(ProjectionField::ElemMatch)
+                    .map_err(|e| e.at("$elemMatch"));
+            }
+
+            if let (1, Some(name)) = (document.len(), document.get("$meta")) {
+                return expect_string(name, "$meta").map(ProjectionField::Meta);
+            }
+        }
+
+        return match bson {
+            Bson::Boolean(true) => Ok(ProjectionField::Include),
+            Bson::Boolean(false) => Ok(ProjectionField::Exclude),
+            number if is_number(number) => Ok(match expect_bool(number, "a projection")? {
+                true => ProjectionField::Include,
+                false => ProjectionField::Exclude,
+            }),
+            computed => Expression::from_bson(computed).map(ProjectionField::Computed),
+        };
+    }
+}
+
+impl ToBson for SortDirection {
+    fn to_bson(&self) -> Bson {
+        return match self {
+            SortDirection::Ascending => Bson::Int32(1),
+            SortDirection::Descending => Bson::Int32(-1),
+            SortDirection::TextScore => Bson::Document(doc! { "$meta": "textScore" }),
+        };
+    }
+}
+
+impl FromBson for SortDirection {
+    fn from_bson(bson: &Bson) -> Result<Self, SerializationError> {
+        return match bson {
+            Bson::Int32(1) | Bson::Int64(1) => Ok(SortDirection::Ascending),
+            Bson::Int32(-1) | Bson::Int64(-1) => Ok(SortDirection::Descending),
+            Bson::Double(direction) if *direction == 1.0 => Ok(SortDirection::Ascending),
+            Bson::Double(direction) if *direction == -1.0 => Ok(SortDirection::Descending),
+            Bson::Document(document) if document.get_str("$meta") == Ok("textScore") => {
+                Ok(SortDirection::TextScore)
+            }
+            other => Err(SerializationError::new(format!(
+                "invalid sort direction {}",
+                other
+            ))),
+        };
+    }
+}
+
+fn optional_string(document: &Document, key: &str) -> Result<Option<String>, SerializationError> {
+    return document
+        .get(key)
+        .map(|value| expect_string(value, key).map_err(|e| e.at(key)))
+        .transpose();
+}
+
+fn required_string(document: &Document, key: &str) -> Result<String, SerializationError> {
+    return optional_string(document, key)?.ok_or(SerializationError::new(format!(
+        "missing required field {}",
+        key
+    )));
+}
+
+fn required<T: FromBson>(document: &Document, key: &str) -> Result<T, SerializationError> {
+    return field_from_bson(document, key)?.ok_or(SerializationError::new(format!(
+        "missing required field {}",
+        key
+    )));
+}
+
+fn strings_to_bson(strings: &[String]) -> Bson {
+    return match strings {
+        [single] => Bson::String(single.clone()),
+        _ => Bson::Array(strings.iter().map(|s| Bson::String(s.clone())).collect()),
+    };
+}
+
+fn strings_from_bson(bson: &Bson, what: &str) -> Result<Vec<String>, SerializationError> {
+    return match bson {
+        Bson::Array(elements) => elements
+            .iter()
+            .enumerate()
+            .map(|(idx, element)| expect_string(element, what).map_err(|e| e.at(&idx.to_string())))
+            .collect(),
+        single => expect_string(single, what).map(|string| vec![string]),
+    };
+}
+
+fn namespace_to_bson(database: &Option<String>, collection: &String) -> Bson {
+    return match database {
+        Some(database) => {
+            Bson::Document(doc! { "db": database.clone(), "coll": collection.clone() })
+        }
+        None => Bson::String(collection.clone()),
+    };
+}
+
+fn namespace_from_bson(bson: &Bson) -> Result<(Option<String>, String), SerializationError> {
+    return match bson {
+        Bson::Document(namespace) => Ok((
+            optional_string(namespace, "db")?,
+            required_string(namespace, "coll")?,
+        )),
+        collection => Ok((None, expect_string(collection, "a collection name")?)),
+    };
+}
+
+fn stage_body(stage: &Stage) -> Bson {
+    return match stage {
+        Stage::Match { predicate } => predicate.to_bson(),
+        Stage::Project { fields } => Bson::Document(fields_to_bson(fields)),
+        Stage::Group { id, accumulators } => {
+            let mut group = doc! { "_id": id.to_bson() };
+            group.extend(fields_to_bson(accumulators));
+            Bson::Document(group)
+        }
+        Stage::Sort { fields } => Bson::Document(fields_to_bson(fields)),
+        Stage::Limit { limit } => limit.to_bson(),
+        Stage::Skip { skip } => skip.to_bson(),
+        Stage::Sample { size } => Bson::Document(doc! { "size": size.to_bson() }),
+        Stage::Lookup {
+            from,
+            local_field,
+            foreign_field,
+            let_variables,
+            pipeline,
+            as_field,
+        } => {
+            let mut lookup = doc! { "from": from.clone() };
+            if let Some(local_field) = local_field {
+                lookup.insert("localField", local_field.clone());
+            }
+            if let Some(foreign_field) = foreign_field {
+                lookup.insert("foreignField", foreign_field.clone());
+            }
+            if !let_variables.is_empty() {
+                lookup.insert("let", fields_to_bson(let_variables));
+            }
+            if !pipeline.is_empty() {
+                lookup.insert("pipeline", pipeline.to_bson());
+            }
+            lookup.insert("as", as_field.clone());
+            Bson::Document(lookup)
+        }
+        Stage::GraphLookup {
+            from,
+            start_with,
+            connect_from_field,
+            connect_to_field,
+            as_field,
+            max_depth,
+            depth_field,
+            restrict_search_with_match,
+        } => {
+            let mut graph_lookup = doc! {
+                "from": from.clone(),
+                "startWith": start_with.to_bson(),
+                "connectFromField": connect_from_field.clone(),
+                "connectToField": connect_to_field.clone(),
+                "as": as_field.clone(),
+            };
+            if let Some(max_depth) = max_depth {
+                graph_lookup.insert("maxDepth", max_depth.to_bson());
+            }
+            if let Some(depth_field) = depth_field {
+                graph_lookup.insert("depthField", depth_field.clone());
+            }
+            if let Some(restrict_search_with_match) = restrict_search_with_match {
+                graph_lookup.insert(
+                    "restrictSearchWithMatch",
+                    restrict_search_with_match.to_bson(),
+                );
+            }
+            Bson::Document(graph_lookup)
+        }
+        Stage::Unwind {
+            path,
+            include_array_index: None,
+            preserve_null_and_empty_arrays: false,
+        } => Bson::String(format!("${}", path)),
+        Stage::Unwind {
+            path,
+            include_array_index,
+            preserve_null_and_empty_arrays,
+        } => {
+            let mut unwind = doc! { "path": format!("${}", path) };
+            if let Some(include_array_index) = include_array_index {
+                unwind.insert("includeArrayIndex", include_array_index.clone());
+            }
+            if *preserve_null_and_empty_arrays {
+                unwind.insert("preserveNullAndEmptyArrays", true);
+            }
+            Bson::Document(unwind)
+        }
+        Stage::Facet { facets } => Bson::Document(fields_to_bson(facets)),
+        Stage::AddFields { fields } | Stage::Set { fields } => {
+            Bson::Document(fields_to_bson(fields))
+        }
+        Stage::Unset { fields } => strings_to_bson(fields),
+        Stage::ReplaceRoot { new_root } => Bson::Document(doc! { "newRoot": new_root.to_bson() }),
+        Stage::ReplaceWith { new_root } => new_root.to_bson(),
+        Stage::Count { field } => Bson::String(field.clone()),
+        Stage::Bucket {
+            group_by,
+            boundaries,
+            default,
+            output,
+        } => {
+            let mut bucket = doc! {
+                "groupBy": group_by.to_bson(),
+                "boundaries": boundaries.to_bson(),
+            };
+            if let Some(default) = default {
+                bucket.insert("default", default.to_bson());
+            }
+            if !output.is_empty() {
+                bucket.insert("output", fields_to_bson(output));
+            }
+            Bson::Document(bucket)
+        }
+        Stage::Merge {
+            database,
+            collection,
+            on,
+            let_variables,
+            when_matched,
+            when_not_matched,
+        } => {
+            let mut merge = doc! { "into": namespace_to_bson(database, collection) };
+            if !on.is_empty() {
+                merge.insert("on", strings_to_bson(on));
+            }
+            if !let_variables.is_empty() {
+                merge.insert("let", fields_to_bson(let_variables));
+            }
+            if let Some(when_matched) = when_matched {
+                merge.insert(
+                    "whenMatched",
+                    match when_matched {
+                        MergeWhenMatched::Replace => Bson::String("replace".to_string()),
+                        MergeWhenMatched::KeepExisting => Bson::String("keepExisting".to_string()),
+                        MergeWhenMatched::Merge => Bson::String("merge".to_string()),
+                        MergeWhenMatched::Fail => Bson::String("fail".to_string()),
+                        MergeWhenMatched::Pipeline(pipeline) => pipeline.to_bson(),
+                    },
+                );
+            }
+            if let Some(when_not_matched) = when_not_matched {
+                merge.insert(
+                    "whenNotMatched",
+                    match when_not_matched {
+                        MergeWhenNotMatched::Insert => "insert",
+                        MergeWhenNotMatched::Discard => "discard",
+                        MergeWhenNotMatched::Fail => "fail",
+                    },
+                );
+            }
+            Bson::Document(merge)
+        }
+        Stage::Out {
+            database,
+            collection,
+        } => namespace_to_bson(database, collection),
+        Stage::Search { index, definition } => match (index, definition) {
+            (index, Value::Object(fields, _)) => {
+                let mut search = Document::new();
+                if let Some(index) = index {
+                    search.insert("index", index.clone());
+                }
+                search.extend(fields_to_bson(fields));
+                Bson::Document(search)
+            }
+            (_, definition) => definition.to_bson(),
+        },
+        Stage::Other { definition, .. } => definition.to_bson(),
+    };
+}
+
+fn stage_from_body(name: &str, body: &Bson) -> Result<Stage, SerializationError> {
+    let stage = match name {
+        "$match" => Stage::Match {
+            predicate: FilterOperator::from_bson(body)?,
+        },
+        "$project" => Stage::Project {
+            fields: fields_from_bson(expect_document(body, name)?)?,
+        },
+        "$group" => {
+            let group = expect_document(body, name)?;
+            let mut accumulators = fields_from_bson(group)?;
+            let Some(idx) = accumulators.iter().position(|(key, _)| key == "_id") else {
+                return Err(SerializationError::new(
+                    "missing required field _id".to_string(),
+                ));
+            };
+            Stage::Group {
+                id: accumulators.remove(idx).1,
+                accumulators,
+            }
+        }
+        "$sort" => Stage::Sort {
+            fields: fields_from_bson(expect_document(body, name)?)?,
+        },
+        "$limit" => Stage::Limit {
+            limit: Value::from_bson(body)?,
+        },
+        "$skip" => Stage::Skip {
+            skip: Value::from_bson(body)?,
+        },
+        "$sample" => Stage::Sample {
+            size: required(expect_document(body, name)?, "size")?,
+        },
+        "$lookup" => {
+            let lookup = expect_document(body, name)?;
+            Stage::Lookup {
+                from: required_string(lookup, "from")?,
+                local_field: optional_string(lookup, "localField")?,
+                foreign_field: optional_string(lookup, "foreignField")?,
+                let_variables: lookup
+                    .get("let")
+                    .map(|variables| {
+                        fields_from_bson(expect_document(variables, "let")?)
+                            .map_err(|e| e.at("let"))
+                    })
+                    .transpose()?
+                    .unwrap_or_default(),
+                pipeline: field_from_bson(lookup, "pipeline")?.unwrap_or_default(),
+                as_field: required_string(lookup, "as")?,
+            }
+        }
+        "$graphLookup" => {
+            let graph_lookup = expect_document(body, name)?;
+            Stage::GraphLookup {
+                from: required_string(graph_lookup, "from")?,
+                start_with: required(graph_lookup, "startWith")?,
+                connect_from_field: required_string(graph_lookup, "connectFromField")?,
+                connect_to_field: required_string(graph_lookup, "connectToField")?,
+                as_field: required_string(graph_lookup, "as")?,
+                max_depth: field_from_bson(graph_lookup, "maxDepth")?,
+                depth_field: optional_string(graph_lookup, "depthField")?,
+                restrict_search_with_match: field_from_bson(
+                    graph_lookup,
+                    "restrictSearchWithMatch",
+                )?
+                .map(Box::new),
+            }
+        }
+        "$unwind" => match body {
+            Bson::Document(unwind) => Stage::Unwind {
+                path: required_string(unwind, "path")?
+                    .trim_start_matches('$')
+                    .to_string(),
+                include_array_index: optional_string(unwind, "includeArrayIndex")?,
+                preserve_null_and_empty_arrays: unwind
+                    .get("preserveNullAndEmptyArrays")
+                    .map(|flag| expect_bool(flag, "preserveNullAndEmptyArrays"))
+                    .transpose()?
+                    .unwrap_or(false),
+            },
+            path => Stage::Unwind {
+                path: expect_string(path, "the unwind path")?
+                    .trim_start_matches('$')
+                    .to_string(),
+                include_array_index: None,
+                preserve_null_and_empty_arrays: false,
+            },
+        },
+        "$facet" => Stage::Facet {
+            facets: fields_from_bson(expect_document(body, name)?)?,
+        },
+        "$addFields" => Stage::AddFields {
+            fields: fields_from_bson(expect_document(body, name)?)?,
+        },
+        "$set" => Stage::Set {
+            fields: fields_from_bson(expect_document(body, name)?)?,
+        },
+        "$unset" => Stage::Unset {
+            fields: strings_from_bson(body, "an unset field")?,
+        },
+        "$replaceRoot" => Stage::ReplaceRoot {
+            new_root: required(expect_document(body, name)?, "newRoot")?,
+        },
+        "$replaceWith" => Stage::ReplaceWith {
+            new_root: Expression::from_bson(body)?,
+        },
+        "$count" => Stage::Count {
+            field: expect_string(body, name)?,
+        },
+        "$bucket" => {
+            let bucket = expect_document(body, name)?;
+            Stage::Bucket {
+                group_by: required(bucket, "groupBy")?,
+                boundaries: required(bucket, "boundaries")?,
+                default: field_from_bson(bucket, "default")?,
+                output: bucket
+                    .get("output")
+                    .map(|output| {
+                        fields_from_bson(expect_document(output, "output")?)
+                            .map_err(|e| e.at("output"))
+                    })
+                    .transpose()?
+                    .unwrap_or_default(),
+            }
+        }
+        "$merge" => {
+            let merge = match body {
+                Bson::Document(merge) => merge.clone(),
+                into => doc! { "into": into.clone() },
+            };
+            let Some(into) = merge.get("into") else {
+                return Err(SerializationError::new(
+                    "missing required field into".to_string(),
+                ));
+            };
+            let (database, collection) = namespace_from_bson(into).map_err(|e| e.at("into"))?;
+            Stage::Merge {
+                database,
+                collection,
+                on: merge
+                    .get("on")
+                    .map(|on| strings_from_bson(on, "on").map_err(|e| e.at("on")))
+                    .transpose()?
+                    .unwrap_or_default(),
+                let_variables: merge
+                    .get("let")
+                    .map(|variables| {
+                        fields_from_bson(expect_document(variables, "let")?)
+                            .map_err(|e| e.at("let"))
+                    })
+                    .transpose()?
+                    .unwrap_or_default(),
+                when_matched: merge
+                    .get("whenMatched")
+                    .map(|when_matched| match when_matched {
+                        Bson::Array(_) => {
+                            Vec::from_bson(when_matched).map(MergeWhenMatched::Pipeline)
+                        }
+                        mode => match expect_string(mode, "whenMatched")?.as_str() {
+                            "replace" => Ok(MergeWhenMatched::Replace),
+                            "keepExisting" => Ok(MergeWhenMatched::KeepExisting),
+                            "merge" => Ok(MergeWhenMatched::Merge),
+                            "fail" => Ok(MergeWhenMatched::Fail),
+                            other => Err(SerializationError::new(format!(
+                                "unknown whenMatched mode {}",
+                                other
+                            ))),
+                        },
+                    })
+                    .transpose()
+                    .map_err(|e| e.at("whenMatched"))?,
+                when_not_matched: optional_string(&merge, "whenNotMatched")?
+                    .map(|mode| match mode.as_str() {
+                        "insert" => Ok(MergeWhenNotMatched::Insert),
+                        "discard" => Ok(MergeWhenNotMatched::Discard),
+                        "fail" => Ok(MergeWhenNotMatched::Fail),
+                        other => Err(SerializationError::new(format!(
+                            "unknown whenNotMatched mode {}",
+                            other
+                        ))
+                        .at("whenNotMatched")),
+                    })
+                    .transpose()?,
+            }
+        }
+        "$out" => {
+            let (database, collection) = namespace_from_bson(body)?;
+            Stage::Out {
+                database,
+                collection,
+            }
+        }
+        "$search" => match body {
+            Bson::Document(search) => {
+                let mut definition = search.clone();
+                let index = definition
+                    .remove("index")
+                    .map(|index| expect_string(&index, "index").map_err(|e| e.at("index")))
+                    .transpose()?;
+                Stage::Search {
+                    index,
+                    definition: Value::from_bson(&Bson::Document(definition))?,
+                }
+            }
+            definition => Stage::Search {
+                index: None,
+                definition: Value::from_bson(definition)?,
+            },
+        },
+        other => Stage::Other {
+            name: other.to_string(),
+            definition: Value::from_bson(body)?,
+        },
+    };
+
+    return Ok(stage);
+}
+
+impl ToBson for Stage {
+    fn to_bson(&self) -> Bson {
+        return Bson::Document(doc! { self.name(): stage_body(self) });
+    }
+}
+
+impl FromBson for Stage {
+    fn from_bson(bson: &Bson) -> Result<Self, SerializationError> {
+        let stage = expect_document(bson, "a stage")?;
+        let (1, Some((name, body))) = (stage.len(), stage.iter().next()) else {
+            return Err(SerializationError::new(
+                "a stage must have exactly one field".to_string(),
+            ));
+        };
+
+        return stage_from_body(name, body).map_err(|e| e.at(name));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{bson, doc, Bson};
+
+    use crate::aggregation::{Expression, ProjectionField, SortDirection, Stage};
+    use crate::filter::FilterOperator;
+    use crate::serialization::{FromBson, ToBson};
+    use crate::span::Span;
+    use crate::values::Value;
+
+    #[test]
+    fn round_trips_a_pipeline() {
+        let pipeline = vec![
+            Stage::Match {
+                predicate: FilterOperator::Equals {
+                    field: "status".to_string(),
+                    value: Value::String("A".to_string(), Span::default()),
+                    span: Span::default(),
+                },
+            },
+            Stage::Lookup {
+                from: "orders".to_string(),
+                local_field: Some("_id".to_string()),
+                foreign_field: Some("customerId".to_string()),
+                let_variables: vec![],
+                pipeline: vec![Stage::Limit {
+                    limit: Value::Integer(5, Span::default()),
+                }],
+                as_field: "orders".to_string(),
+            },
+            Stage::Unwind {
+                path: "orders".to_string(),
+                include_array_index: None,
+                preserve_null_and_empty_arrays: false,
+            },
+            Stage::Group {
+                id: Expression::FieldPath("status".to_string()),
+                accumulators: vec![(
+                    "total".to_string(),
+                    Expression::Operator {
+                        name: "$sum".to_string(),
+                        arguments: vec![Expression::FieldPath("orders.amount".to_string())],
+                    },
+                )],
+            },
+            Stage::Sort {
+                fields: vec![("total".to_string(), SortDirection::Descending)],
+            },
+            Stage::Project {
+                fields: vec![
+                    ("_id".to_string(), ProjectionField::Exclude),
+                    (
+                        "label".to_string(),
+                        ProjectionField::Computed(Expression::Literal(Value::String(
+                            "$total".to_string(),
+                            Span::default(),
+                        ))),
+                    ),
+                ],
+            },
+            Stage::Merge {
+                database: Some("reporting".to_string()),
+                collection: "totals".to_string(),
+                on: vec!["_id".to_string()],
+                let_variables: vec![],
+                when_matched: None,
+                when_not_matched: None,
+            },
+        ];
+
+        assert_eq!(
+            Vec::<Stage>::from_bson(&pipeline.to_bson()).unwrap(),
+            pipeline
+        );
+    }
+
+    #[test]
+    fn renders_stages_in_their_shortest_form() {
+        let unwind = Stage::Unwind {
+            path: "tags".to_string(),
+            include_array_index: None,
+            preserve_null_and_empty_arrays: false,
+        };
+        let out = Stage::Out {
+            database: None,
+            collection: "archive".to_string(),
+        };
+
+        assert_eq!(
+            unwind.to_bson(),
+            Bson::Document(doc! { "$unwind": "$tags" })
+        );
+        assert_eq!(out.to_bson(), Bson::Document(doc! { "$out": "archive" }));
+    }
+
+    #[test]
+    fn keeps_unknown_stages_as_other() {
+        let stage = Stage::from_bson(&bson!({ "$densify": { "field": "ts" } })).unwrap();
+
+        assert_eq!(
+            stage,
+            Stage::Other {
+                name: "$densify".to_string(),
+                definition: Value::Object(
+                    vec![(
+                        "field".to_string(),
+                        Value::String("ts".to_string(), Span::default())
+                    )],
+                    Span::default()
+                ),
+            }
+        );
+    }
+}
@@ -0,0 +1,1013 @@
+use bson::{doc, Bson, Document, Regex};
+
+use crate::aggregation::Expression;
+use crate::filter::{FilterOperator, GeoShape};
+use crate::serialization::{
+    expect_bool, expect_document, expect_string, field_from_bson, FromBson, SerializationError,
+    ToBson,
+};
+use crate::span::Span;
+use crate::values::{BsonType, Value};
+
+const TOP_LEVEL_OPERATORS: [&str; 9] = [
+    "$and",
+    "$or",
+    "$nor",
+    "$expr",
+    "$jsonSchema",
+    "$text",
+    "$where",
+    "$comment",
+    "$sampleRate",
+];
+
+pub(crate) fn is_operators(document: &Document) -> bool {
+    return !document.is_empty() && document.keys().all(|key| key.starts_with('$'));
+}
+
+fn is_operator_document(bson: &Bson) -> bool {
+    return match bson {
+        Bson::Document(document) => is_operators(document),
+        _ => false,
+    };
+}
+
+fn insert_optional(document: &mut Document, key: &str, value: &Option<Value>) {
+    if let Some(value) = value {
+        document.insert(key, value.to_bson());
+    }
+}
+
+fn operand_to_bson(values: &Vec<Value>) -> Bson {
+    return match &values[..] {
+        [list] if list.is_array_reference() => list.to_bson(),
+        _ => values.to_bson(),
+    };
+}
+
+fn operand_from_bson(argument: &Bson) -> Result<Vec<Value>, SerializationError> {
+    if let Bson::String(_) = argument {
+        let list = Value::from_bson(argument)?;
+        if list.is_array_reference() {
+            return Ok(vec![list]);
+        }
+    }
+
+    return Vec::from_bson(argument);
+}
+
+fn types_to_bson(types: &[BsonType]) -> Bson {
+    let aliases: Vec<Bson> = types
+        .iter()
+        .map(|bson_type| Bson::String(bson_type.alias().to_string()))
+        .collect();
+
+    return match &aliases[..] {
+        [single] => single.clone(),
+        _ => Bson::Array(aliases),
+    };
+}
+
+fn geo_shape_to_bson(shape: &GeoShape) -> Document {
+    let (key, value) = match shape {
+        GeoShape::Geometry(value) => ("$geometry", value),
+        GeoShape::Box(value) => ("$box", value),
+        GeoShape::Polygon(value) => ("$polygon", value),
+        GeoShape::Center(value) => ("$center", value),
+        GeoShape::CenterSphere(value) => ("$centerSphere", value),
+    };
+
+    return doc! { key: value.to_bson() };
+}
+
+fn is_legacy_coordinate(geometry: &Value) -> bool {
+    return match geometry {
+        Value::Array(_, _) => true,
+        Value::Object(fields, _) => !fields.iter().any(|(key, _)| key == "type"),
+        _ => false,
+    };
+}
+
+fn near_operators(
+    operator: &str,
+    geometry: &Value,
+    max_distance: &Option<Value>,
+    min_distance: &Option<Value>,
+) -> Document {
+    if is_legacy_coordinate(geometry) {
+        let mut operators = doc! { operator: geometry.to_bson() };
+        insert_optional(&mut operators, "$maxDistance", max_distance);
+        insert_optional(&mut operators, "$minDistance", min_distance);
+        return operators;
+    }
+
+    let mut near = doc! { "$geometry": geometry.to_bson() };
+    insert_optional(&mut near, "$maxDistance", max_distance);
+    insert_optional(&mut near, "$minDistance", min_distance);
+    return doc! { operator: near };
+}
+
+pub(crate) fn field_operators(predicate: &FilterOperator) -> Option<(String, Document)> {
+    let (field, operators) = match predicate {
+        FilterOperator::Equals { field, value, .. } => (field, doc! { "$eq": value.to_bson() }),
+        FilterOperator::NotEquals { field, value, .. } => (field, doc! { "$ne": value.to_bson() }),
+        FilterOperator::GreaterThan { field, value, .. } => {
+            (field, doc! { "$gt": value.to_bson() })
+        }
+        FilterOperator::LessThan { field, value, .. } => (field, doc! { "$lt": value.to_bson() }),
+        FilterOperator::GreaterThanOrEquals { field, value, .. } => {
+            (field, doc! { "$gte": value.to_bson() })
+        }
+        FilterOperator::LessThanOrEquals { field, value, .. } => {
+            (field, doc! { "$lte": value.to_bson() })
+        }
+        FilterOperator::In { field, values, .. } => {
+            (field, doc! { "$in": operand_to_bson(values) })
+        }
+        FilterOperator::NotIn { field, values, .. } => {
+            (field, doc! { "$nin": operand_to_bson(values) })
+        }
+        FilterOperator::All { field, values, .. } => {
+            (field, doc! { "$all": operand_to_bson(values) })
+        }
+        FilterOperator::Exists { field, exists, .. } => (field, doc! { "$exists": *exists }),
+        FilterOperator::Type { field, types, .. } => {
+            (field, doc! { "$type": types_to_bson(types) })
+        }
+        FilterOperator::Regex {
+            field,
+            pattern,
+            options,
+            ..
+        } => {
+            let mut operators = doc! { "$regex": pattern.to_bson() };
+            if let Some(options) = options {
+                operators.insert("$options", options.clone());
+            }
+            (field, operators)
+        }
+        FilterOperator::ElemMatch {
+            field, predicates, ..
+        } => (
+            field,
+            doc! { "$elemMatch": element_predicates_to_document(predicates) },
+        ),
+        FilterOperator::Size { field, size, .. } => (field, doc! { "$size": size.to_bson() }),
+        FilterOperator::Mod {
+            field,
+            divisor,
+            remainder,
+            ..
+        } => (
+            field,
+            doc! { "$mod": [divisor.to_bson(), remainder.to_bson()] },
+        ),
+        FilterOperator::BitsAllSet { field, mask, .. } => {
+            (field, doc! { "$bitsAllSet": mask.to_bson() })
+        }
+        FilterOperator::BitsAnySet { field, mask, .. } => {
+            (field, doc! { "$bitsAnySet": mask.to_bson() })
+        }
+        FilterOperator::BitsAllClear { field, mask, .. } => {
+            (field, doc! { "$bitsAllClear": mask.to_bson() })
+        }
+        FilterOperator::BitsAnyClear { field, mask, .. } => {
+            (field, doc! { "$bitsAnyClear": mask.to_bson() })
+        }
+        FilterOperator::GeoWithin { field, shape, .. } => {
+            (field, doc! { "$geoWithin": geo_shape_to_bson(shape) })
+        }
+        FilterOperator::GeoIntersects {
+            field, geometry, ..
+        } => (
+            field,
+            doc! { "$geoIntersects": { "$geometry": geometry.to_bson() } },
+        ),
+        FilterOperator::Near {
+            field,
+            geometry,
+            max_distance,
+            min_distance,
+            ..
+        } => (
+            field,
+            near_operators("$near", geometry, max_distance, min_distance),
+        ),
+        FilterOperator::NearSphere {
+            field,
+            geometry,
+            max_distance,
+            min_distance,
+            ..
+        } => (
+            field,
+            near_operators("$nearSphere", geometry, max_distance, min_distance),
+        ),
+        FilterOperator::Not { predicates, .. } => {
+            let (field, operators) = merged_field_operators(predicates)?;
+            return Some((field, doc! { "$not": operators }));
+        }
+        _ => return None,
+    };
+
+    return Some((field.clone(), operators));
+}
+
+fn merged_field_operators(predicates: &[FilterOperator]) -> Option<(String, Document)> {
+    let mut result: Option<(String, Document)> = None;
+
+    for predicate in predicates {
+        let (field, operators) = field_operators(predicate)?;
+        match &mut result {
+            None => result = Some((field, operators)),
+            Some((merged_field, merged)) => {
+                if *merged_field != field || operators.keys().any(|key| merged.contains_key(key)) {
+                    return None;
+                }
+                merged.extend(operators);
+            }
+        }
+    }
+
+    return result;
+}
+
+fn implicit_conjunction(predicates: &[FilterOperator]) -> Option<Document> {
+    let mut result = Document::new();
+
+    for predicate in predicates {
+        for (key, value) in filter_to_document(predicate) {
+            let mergeable = match (result.get(&key), &value) {
+                (None, _) => {
+                    result.insert(key, value);
+                    continue;
+                }
+                (Some(Bson::Document(existing)), Bson::Document(operators)) => {
+                    !key.starts_with('$')
+                        && is_operators(existing)
+                        && is_operators(operators)
+                        && !operators.keys().any(|k| existing.contains_key(k))
+                }
+                _ => false,
+            };
+
+            if !mergeable {
+                return None;
+            }
+
+            if let (Some(Bson::Document(existing)), Bson::Document(operators)) =
+                (result.get_mut(&key), value)
+            {
+                existing.extend(operators);
+            }
+        }
+    }
+
+    return Some(result);
+}
+
+fn conjunction_to_document(predicates: &[FilterOperator]) -> Document {
+    if predicates.is_empty() {
+        return Document::new();
+    }
+
+    if predicates.len() > 1 {
+        if let Some(document) = implicit_conjunction(predicates) {
+            return document;
+        }
+    }
+
+    return doc! { "$and": predicates_to_bson(predicates) };
+}
+
+fn predicates_to_bson(predicates: &[FilterOperator]) -> Bson {
+    return Bson::Array(
+        predicates
+            .iter()
+            .map(|predicate| Bson::Document(filter_to_document(predicate)))
+            .collect(),
+    );
+}
+
+pub(crate) fn element_predicates_to_document(predicates: &[FilterOperator]) -> Document {
+    if let Some((field, operators)) = merged_field_operators(predicates) {
+        if field.is_empty() {
+            return operators;
+        }
+    }
+
+    return match predicates {
+        [single] => filter_to_document(single),
+        _ => conjunction_to_document(predicates),
+    };
+}
+
+pub(crate) fn element_predicates_from_document(
+    document: &Document,
+) -> Result<Vec<FilterOperator>, SerializationError> {
+    if is_operators(document)
+        && !document
+            .keys()
+            .any(|key| TOP_LEVEL_OPERATORS.contains(&key.as_str()))
+    {
+        return field_predicates("", &Bson::Document(document.clone()));
+    }
+
+    return match filter_from_document(document)? {
+        FilterOperator::And { predicates, .. } => Ok(predicates),
+        predicate => Ok(vec![predicate]),
+    };
+}
+
+pub(crate) fn element_predicate_from_document(
+    document: &Document,
+) -> Result<FilterOperator, SerializationError> {
+    let mut predicates = element_predicates_from_document(document)?;
+    if predicates.len() == 1 {
+        return Ok(predicates.remove(0));
+    }
+
+    return Ok(FilterOperator::And {
+        predicates,
+        span: Span::default(),
+    });
+}
+
+pub(crate) fn filter_to_document(predicate: &FilterOperator) -> Document {
+    return match predicate {
+        FilterOperator::Equals { field, value, .. } => {
+            let value = value.to_bson();
+            if is_operator_document(&value) || matches!(value, Bson::RegularExpression(_)) {
+                doc! { field: { "$eq": value } }
+            } else {
+                doc! { field: value }
+            }
+        }
+        FilterOperator::And { predicates, .. } => conjunction_to_document(predicates),
+        FilterOperator::Or { predicates, .. } => doc! { "$or": predicates_to_bson(predicates) },
+        FilterOperator::Nor { predicates, .. } => doc! { "$nor": predicates_to_bson(predicates) },
+        FilterOperator::Not { predicates, .. } => match field_operators(predicate) {
+            Some((field, operators)) => doc! { field: operators },
+            None => doc! { "$nor": [conjunction_to_document(predicates)] },
+        },
+        FilterOperator::Expr { expression, .. } => doc! { "$expr": expression.to_bson() },
+        FilterOperator::JsonSchema { schema, .. } => doc! { "$jsonSchema": schema.to_bson() },
+        FilterOperator::Text {
+            search,
+            language,
+            case_sensitive,
+            diacritic_sensitive,
+            ..
+        } => {
+            let mut text = doc! { "$search": search.to_bson() };
+            if let Some(language) = language {
+                text.insert("$language", language.clone());
+            }
+            if let Some(case_sensitive) = case_sensitive {
+                text.insert("$caseSensitive", *case_sensitive);
+            }
+            if let Some(diacritic_sensitive) = diacritic_sensitive {
+                text.insert("$diacriticSensitive", *diacritic_sensitive);
+            }
+            doc! { "$text": text }
+        }
+        FilterOperator::Where { code, .. } => doc! { "$where": code.to_bson() },
+        FilterOperator::Comment { comment, .. } => doc! { "$comment": comment.clone() },
+        FilterOperator::SampleRate { rate, .. } => doc! { "$sampleRate": rate.to_bson() },
+        _ => field_operators(predicate)
+            .map(|(field, operators)| doc! { field: operators })
+            .unwrap_or_default(),
+    };
+}
+
+pub(crate) fn filter_from_document(
+    document: &Document,
+) -> Result<FilterOperator, SerializationError> {
+    let mut predicates = vec![];
+    for (key, value) in document {
+        predicates.extend(top_level_predicates(key, value).map_err(|e| e.at(key))?);
+    }
+
+    if predicates.len() == 1 {
+        return Ok(predicates.remove(0));
+    }
+
+    return Ok(FilterOperator::And {
+        predicates,
+        span: Span::default(),
+    });
+}
+
+fn top_level_predicates(
+    key: &str,
+    value: &Bson,
+) -> Result<Vec<FilterOperator>, SerializationError> {
+    let predicate = match key {
+        "$and" => FilterOperator::And {
+            predicates: Vec::from_bson(value)?,
+            span: Span::default(),
+        },
+        "$or" => FilterOperator::Or {
+            predicates: Vec::from_bson(value)?,
+            span: Span::default(),
+        },
+        "$nor" => FilterOperator::Nor {
+            predicates: Vec::from_bson(value)?,
+            span: Span::default(),
+        },
+        "$expr" => FilterOperator::Expr {
+            expression: Expression::from_bson(value)?,
+            span: Span::default(),
+        },
+        "$jsonSchema" => FilterOperator::JsonSchema {
+            schema: Value::from_bson(value)?,
+            span: Span::default(),
+        },
+        "$text" => text_from_bson(value)?,
+        "$where" => FilterOperator::Where {
+            code: match value {
+                Bson::JavaScriptCode(code) => Value::String(code.clone(), Span::default()),
+                other => Value::from_bson(other)?,
+            },
+            span: Span::default(),
+        },
+        "$comment" => FilterOperator::Comment {
+            comment: expect_string(value, "$comment")?,
+            span: Span::default(),
+        },
+        "$sampleRate" => FilterOperator::SampleRate {
+            rate: Value::from_bson(value)?,
+            span: Span::default(),
+        },
+        operator if operator.starts_with('$') => {
+            return Err(SerializationError::new(format!(
+                "unknown top-level operator {}",
+                operator
+            )))
+        }
+        field => return field_predicates(field, value),
+    };
+
+    return Ok(vec![predicate]);
+}
+
+fn text_from_bson(value: &Bson) -> Result<FilterOperator, SerializationError> {
+    let text = expect_document(value, "$text")?;
+    let Some(search) = field_from_bson::<Value>(text, "$search")? else {
+        return Err(SerializationError::new(
+            "$text requires a $search".to_string(),
+        ));
+    };
+
+    return Ok(FilterOperator::Text {
+        search,
+        language: text
+            .get("$language")
+            .map(|language| expect_string(language, "$language"))
+            .transpose()?,
+        case_sensitive: text
+            .get("$caseSensitive")
+            .map(|flag| expect_bool(flag, "$caseSensitive"))
+            .transpose()?,
+        diacritic_sensitive: text
+            .get("$diacriticSensitive")
+            .map(|flag| expect_bool(flag, "$diacriticSensitive"))
+            .transpose()?,
+        span: Span::default(),
+    });
+}
+
+fn regex_predicate(field: String, regex: &Regex) -> FilterOperator {
+    return FilterOperator::Regex {
+        field,
+        pattern: Value::String(regex.pattern.clone(), Span::default()),
+        options: Some(regex.options.clone()).filter(|options| !options.is_empty()),
+        span: Span::default(),
+    };
+}
+
+pub(crate) fn field_predicates(
+    field: &str,
+    value: &Bson,
+) -> Result<Vec<FilterOperator>, SerializationError> {
+    let field = field.to_string();
+    let operators = match value {
+        Bson::RegularExpression(regex) => return Ok(vec![regex_predicate(field, regex)]),
+        Bson::Document(operators) if is_operators(operators) => operators,
+        _ => {
+            return Ok(vec![FilterOperator::Equals {
+                field,
+                value: Value::from_bson(value)?,
+                span: Span::default(),
+            }])
+        }
+    };
+
+    let mut result = vec![];
+    for (operator, argument) in operators {
+        let predicate =
+            field_operator(&field, operator, argument, operators).map_err(|e| e.at(operator))?;
+        result.extend(predicate);
+    }
+
+    return Ok(result);
+}
+
+fn field_operator(
+    field: &String,
+    operator: &str,
+    argument: &Bson,
+    siblings: &Document,
+) -> Result<Option<FilterOperator>, SerializationError> {
+    let field = field.clone();
+    let predicate = match operator {
+        "$eq" => FilterOperator::Equals {
+            field,
+            value: Value::from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$ne" => FilterOperator::NotEquals {
+            field,
+            value: Value::from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$gt" => FilterOperator::GreaterThan {
+            field,
+            value: Value::from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$gte" => FilterOperator::GreaterThanOrEquals {
+            field,
+            value: Value::from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$lt" => FilterOperator::LessThan {
+            field,
+            value: Value::from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$lte" => FilterOperator::LessThanOrEquals {
+            field,
+            value: Value::from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$in" => FilterOperator::In {
+            field,
+            values: operand_from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$nin" => FilterOperator::NotIn {
+            field,
+            values: operand_from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$all" => FilterOperator::All {
+            field,
+            values: operand_from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$exists" => FilterOperator::Exists {
+            field,
+            exists: expect_bool(argument, operator)?,
+            span: Span::default(),
+        },
+        "$type" => FilterOperator::Type {
+            field,
+            types: types_from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$regex" => match argument {
+            Bson::RegularExpression(regex) => regex_predicate(field, regex),
+            pattern => FilterOperator::Regex {
+                field,
+                pattern: Value::from_bson(pattern)?,
+                options: siblings
+                    .get("$options")
+                    .map(|options| expect_string(options, "$options"))
+                    .transpose()?,
+                span: Span::default(),
+            },
+        },
+        "$options" if siblings.contains_key("$regex") => return Ok(None),
+        "$elemMatch" => FilterOperator::ElemMatch {
+            field,
+            predicates: element_predicates_from_document(expect_document(argument, operator)?)?,
+            span: Span::default(),
+        },
+        "$size" => FilterOperator::Size {
+            field,
+            size: Value::from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$mod" => match argument {
+            Bson::Array(parts) if parts.len() == 2 => FilterOperator::Mod {
+                field,
+                divisor: Value::from_bson(&parts[0])?,
+                remainder: Value::from_bson(&parts[1])?,
+                span: Span::default(),
+            },
+            _ => {
+                return Err(SerializationError::new(
+                    "expected [divisor, remainder]".to_string(),
+                ))
+            }
+        },
+        "$bitsAllSet" => FilterOperator::BitsAllSet {
+            field,
+            mask: Value::from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$bitsAnySet" => FilterOperator::BitsAnySet {
+            field,
+            mask: Value::from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$bitsAllClear" => FilterOperator::BitsAllClear {
+            field,
+            mask: Value::from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$bitsAnyClear" => FilterOperator::BitsAnyClear {
+            field,
+            mask: Value::from_bson(argument)?,
+            span: Span::default(),
+        },
+        "$not" => FilterOperator::Not {
+            predicates: field_predicates(&field, argument)?,
+            span: Span::default(),
+        },
+        "$geoWithin" => FilterOperator::GeoWithin {
+            field,
+            shape: geo_shape_from_bson(expect_document(argument, operator)?)?,
+            span: Span::default(),
+        },
+        "$geoIntersects" => {
+            let geo_intersects = expect_document(argument, operator)?;
+            let Some(geometry) = field_from_bson(geo_intersects, "$geometry")? else {
+                return Err(SerializationError::new(
+                    "$geoIntersects requires a $geometry".to_string(),
+                ));
+            };
+            FilterOperator::GeoIntersects {
+                field,
+                geometry,
+                span: Span::default(),
+            }
+        }
+        "$near" | "$nearSphere" => near_from_bson(field, operator, argument, siblings)?,
+        "$maxDistance" | "$minDistance"
+            if siblings.contains_key("$near") || siblings.contains_key("$nearSphere") =>
+        {
+            return Ok(None)
+        }
+        _ => {
+            return Err(SerializationError::new(format!(
+                "unknown query operator {}",
+                operator
+            )))
+        }
+    };
+
+    return Ok(Some(predicate));
+}
+
+fn types_from_bson(argument: &Bson) -> Result<Vec<BsonType>, SerializationError> {
+    let bson_type = match argument {
+        Bson::Array(elements) => {
+            let mut types = vec![];
+            for (idx, element) in elements.iter().enumerate() {
+                types.extend(types_from_bson(element).map_err(|e| e.at(&idx.to_string()))?);
+            }
+            return Ok(types);
+        }
+        Bson::String(alias) => BsonType::from_alias(alias),
+        Bson::Int32(code) => BsonType::from_code(*code),
+        Bson::Int64(code) => i32::try_from(*code).ok().and_then(BsonType::from_code),
+        Bson::Double(code) => BsonType::from_code(*code as i32),
+        _ => None,
+    };
+
+    return bson_type
+        .map(|bson_type| vec![bson_type])
+        .ok_or(SerializationError::new(format!(
+            "unknown BSON type {}",
+            argument
+        )));
+}
+
+fn geo_shape_from_bson(shape: &Document) -> Result<GeoShape, SerializationError> {
+    let Some((key, value)) = shape.iter().next() else {
+        return Err(SerializationError::new("expected a geo shape".to_string()));
+    };
+
+    let value = Value::from_bson(value).map_err(|e| e.at(key))?;
+    return match key.as_str() {
+        "$geometry" => Ok(GeoShape::Geometry(value)),
+        "$box" => Ok(GeoShape::Box(value)),
+        "$polygon" => Ok(GeoShape::Polygon(value)),
+        "$center" => Ok(GeoShape::Center(value)),
+        "$centerSphere" => Ok(GeoShape::CenterSphere(value)),
+        other => Err(SerializationError::new(format!(
+            "unknown geo shape {}",
+            other
+        ))),
+    };
+}
+
+fn near_from_bson(
+    field: String,
+    operator: &str,
+    argument: &Bson,
+    siblings: &Document,
+) -> Result<FilterOperator, SerializationError> {
+    let (geometry, max_distance, min_distance) = match argument {
+        Bson::Document(near) if near.contains_key("$geometry") => (
+            field_from_bson(near, "$geometry")?.unwrap(),
+            field_from_bson(near, "$maxDistance")?,
+            field_from_bson(near, "$minDistance")?,
+        ),
+        legacy => (
+            Value::from_bson(legacy)?,
+            field_from_bson(siblings, "$maxDistance")?,
+            field_from_bson(siblings, "$minDistance")?,
+        ),
+    };
+
+    if operator == "$near" {
+        return Ok(FilterOperator::Near {
+            field,
+            geometry,
+            max_distance,
+            min_distance,
+            span: Span::default(),
+        });
+    }
+
+    return Ok(FilterOperator::NearSphere {
+        field,
+        geometry,
+        max_distance,
+        min_distance,
+        span: Span::default(),
+    });
+}
+
+impl ToBson for FilterOperator {
+    fn to_bson(&self) -> Bson {
+        return Bson::Document(filter_to_document(self));
+    }
+}
+
+impl FromBson for FilterOperator {
+    fn from_bson(bson: &Bson) -> Result<Self, SerializationError> {
+        return filter_from_document(expect_document(bson, "a filter")?);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{doc, Bson};
+    use serde_json::json;
+
+    use crate::aggregation::Expression;
+    use crate::filter::FilterOperator::*;
+    use crate::filter::{FilterOperator, GeoShape};
+    use crate::serialization::{
+        from_document, from_extended_json, to_document, to_extended_json, ExtendedJsonMode,
+        FromBson, ToBson,
+    };
+    use crate::span::Span;
+    use crate::values::{BsonType, Value, ValueType};
+
+    #[test]
+    fn renders_conjunctions_as_implicit_documents() {
+        let filter = And {
+            predicates: vec![
+                GreaterThan {
+                    field: "age".to_string(),
+                    value: Value::Integer(18, Span::default()),
+                    span: Span::default(),
+                },
+                LessThan {
+                    field: "age".to_string(),
+                    value: Value::Integer(65, Span::default()),
+                    span: Span::default(),
+                },
+                Equals {
+                    field: "status".to_string(),
+                    value: Value::Reference("status".to_string(), ValueType::Any, Span::default()),
+                    span: Span::default(),
+                },
+            ],
+            span: Span::default(),
+        };
+
+        assert_eq!(
+            to_document(&filter).unwrap(),
+            doc! { "age": { "$gt": 18, "$lt": 65 }, "status": "{{status:any}}" }
+        );
+        assert_eq!(
+            from_document::<FilterOperator>(&to_document(&filter).unwrap()).unwrap(),
+            filter
+        );
+    }
+
+    #[test]
+    fn renders_list_references_as_the_whole_operand() {
+        let statuses = || {
+            vec![Value::Reference(
+                "statuses".to_string(),
+                ValueType::Of(BsonType::Array),
+                Span::default(),
+            )]
+        };
+        let filter = And {
+            predicates: vec![
+                In {
+                    field: "status".to_string(),
+                    values: statuses(),
+                    span: Span::default(),
+                },
+                NotIn {
+                    field: "region".to_string(),
+                    values: statuses(),
+                    span: Span::default(),
+                },
+            ],
+            span: Span::default(),
+        };
+
+        assert_eq!(
+            to_document(&filter).unwrap(),
+            doc! {
+                "status": { "$in": "{{statuses:array}}" },
+                "region": { "$nin": "{{statuses:array}}" },
+            }
+        );
+        assert_eq!(
+            from_document::<FilterOperator>(&to_document(&filter).unwrap()).unwrap(),
+            filter
+        );
+        assert!(from_document::<FilterOperator>(&doc! { "status": { "$in": "A" } }).is_err());
+    }
+
+    #[test]
+    fn round_trips_every_operator_family() {
+        let filter = And {
+            predicates: vec![
+                Or {
+                    predicates: vec![
+                        In {
+                            field: "status".to_string(),
+                            values: vec![Value::String("A".to_string(), Span::default())],
+                            span: Span::default(),
+                        },
+                        Nor {
+                            predicates: vec![Exists {
+                                field: "deletedAt".to_string(),
+                                exists: true,
+                                span: Span::default(),
+                            }],
+                            span: Span::default(),
+                        },
+                    ],
+                    span: Span::default(),
+                },
+                Not {
+                    predicates: vec![Regex {
+                        field: "name".to_string(),
+                        pattern: Value::String("^a".to_string(), Span::default()),
+                        options: Some("i".to_string()),
+                        span: Span::default(),
+                    }],
+                    span: Span::default(),
+                },
+                Type {
+                    field: "score".to_string(),
+                    types: vec![BsonType::Int, BsonType::Double],
+                    span: Span::default(),
+                },
+                ElemMatch {
+                    field: "results".to_string(),
+                    predicates: vec![
+                        GreaterThanOrEquals {
+                            field: "".to_string(),
+                            value: Value::Integer(80, Span::default()),
+                            span: Span::default(),
+                        },
+                        LessThan {
+                            field: "".to_string(),
+                            value: Value::Integer(85, Span::default()),
+                            span: Span::default(),
+                        },
+                    ],
+                    span: Span::default(),
+                },
+                Mod {
+                    field: "qty".to_string(),
+                    divisor: Value::Integer(4, Span::default()),
+                    remainder: Value::Integer(0, Span::default()),
+                    span: Span::default(),
+                },
+                GeoWithin {
+                    field: "location".to_string(),
+                    shape: GeoShape::CenterSphere(Value::Array(
+                        vec![
+                            Value::Array(
+                                vec![
+                                    Value::Integer(-88, Span::default()),
+                                    Value::Integer(30, Span::default()),
+                                ],
+                                Span::default(),
+                            ),
+                            Value::Floating(0.5, Span::default()),
+                        ],
+                        Span::default(),
+                    )),
+                    span: Span::default(),
+                },
+                Near {
+                    field: "point".to_string(),
+                    geometry: Value::Array(
+                        vec![
+                            Value::Integer(1, Span::default()),
+                            Value::Integer(2, Span::default()),
+                        ],
+                        Span::default(),
+                    ),
+                    max_distance: Some(Value::Integer(10, Span::default())),
+                    min_distance: None,
+                    span: Span::default(),
+                },
+                Expr {
+                    expression: Expression::Operator {
+                        name: "$gt".to_string(),
+                        arguments: vec![
+                            Expression::FieldPath("spent".to_string()),
+                            Expression::FieldPath("budget".to_string()),
+                        ],
+                    },
+                    span: Span::default(),
+                },
+                Text {
+                    search: Value::String("coffee".to_string(), Span::default()),
+                    language: Some("en".to_string()),
+                    case_sensitive: None,
+                    diacritic_sensitive: Some(false),
+                    span: Span::default(),
+                },
+            ],
+            span: Span::default(),
+        };
+
+        assert_eq!(
+            FilterOperator::from_bson(&filter.to_bson()).unwrap(),
+            filter
+        );
+    }
+
+    #[test]
+    fn parses_shell_style_regular_expressions() {
+        let filter = doc! { "name": Bson::RegularExpression(bson::Regex {
+            pattern: "^mongo".to_string(),
+            options: "i".to_string(),
+        }) };
+
+        assert_eq!(
+            from_document::<FilterOperator>(&filter).unwrap(),
+            Regex {
+                field: "name".to_string(),
+                pattern: Value::String("^mongo".to_string(), Span::default()),
+                options: Some("i".to_string()),
+                span: Span::default()
+            }
+        );
+    }
+
+    #[test]
+    fn converts_to_and_from_canonical_extended_json() {
+        let filter = Equals {
+            field: "age".to_string(),
+            value: Value::Integer(18, Span::default()),
+            span: Span::default(),
+        };
+
+        let json = to_extended_json(&filter, ExtendedJsonMode::Canonical);
+        assert_eq!(json, json!({ "age": { "$numberInt": "18" } }));
+        assert_eq!(
+            to_extended_json(&filter, ExtendedJsonMode::Relaxed),
+            json!({ "age": 18 })
+        );
+        assert_eq!(from_extended_json::<FilterOperator>(json).unwrap(), filter);
+    }
+
+    #[test]
+    fn reports_the_path_of_unknown_operators() {
+        let error = from_document::<FilterOperator>(&doc! {
+            "$or": [{ "age": { "$gt": 1 } }, { "age": { "$foo": 1 } }]
+        })
+        .unwrap_err();
+
+        assert_eq!(error.path, vec!["$or", "1", "age", "$foo"]);
+    }
+}
@@ -0,0 +1,168 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use bson::{Bson, Document};
+
+mod aggregation;
+mod filter;
+mod update;
+mod values;
+
+pub use values::reference_placeholder;
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct SerializationError {
+    pub path: Vec<String>,
+    pub message: String,
+}
+
+impl SerializationError {
+    pub fn new(message: String) -> SerializationError {
+        return SerializationError {
+            path: vec![],
+            message,
+        };
+    }
+
+    pub fn at(mut self, segment: &str) -> SerializationError {
+        self.path.insert(0, segment.to_string());
+        return self;
+    }
+}
+
+impl Display for SerializationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            return write!(f, "{}", self.message);
+        }
+
+        return write!(f, "{}: {}", self.path.join("."), self.message);
+    }
+}
+
+impl Error for SerializationError {}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum ExtendedJsonMode {
+    Canonical,
+    Relaxed,
+}
+
+pub trait ToBson {
+    fn to_bson(&self) -> Bson;
+}
+
+pub trait FromBson: Sized {
+    fn from_bson(bson: &Bson) -> Result<Self, SerializationError>;
+}
+
+impl<T: ToBson> ToBson for Vec<T> {
+    fn to_bson(&self) -> Bson {
+        return Bson::Array(self.iter().map(ToBson::to_bson).collect());
+    }
+}
+
+impl<T: FromBson> FromBson for Vec<T> {
+    fn from_bson(bson: &Bson) -> Result<Self, SerializationError> {
+        let Bson::Array(elements) = bson else {
+            return Err(SerializationError::new("expected an array".to_string()));
+        };
+
+        return elements
+            .iter()
+            .enumerate()
+            .map(|(idx, element)| T::from_bson(element).map_err(|e| e.at(&idx.to_string())))
+            .collect();
+    }
+}
+
+pub fn to_document<T: ToBson>(node: &T) -> Result<Document, SerializationError> {
+    return match node.to_bson() {
+        Bson::Document(document) => Ok(document),
+        _ => Err(SerializationError::new(
+            "node does not render to a document".to_string(),
+        )),
+    };
+}
+
+pub fn from_document<T: FromBson>(document: &Document) -> Result<T, SerializationError> {
+    return T::from_bson(&Bson::Document(document.clone()));
+}
+
+pub fn to_extended_json<T: ToBson>(node: &T, mode: ExtendedJsonMode) -> serde_json::Value {
+    return match mode {
+        ExtendedJsonMode::Canonical => node.to_bson().into_canonical_extjson(),
+        ExtendedJsonMode::Relaxed => node.to_bson().into_relaxed_extjson(),
+    };
+}
+
+pub fn from_extended_json<T: FromBson>(json: serde_json::Value) -> Result<T, SerializationError> {
+    let bson = Bson::try_from(json).map_err(|e| SerializationError::new(e.to_string()))?;
+    return T::from_bson(&bson);
+}
+
+pub(crate) fn expect_document<'a>(
+    bson: &'a Bson,
+    what: &str,
+) -> Result<&'a Document, SerializationError> {
+    return match bson {
+        Bson::Document(document) => Ok(document),
+        _ => Err(SerializationError::new(format!(
+            "expected {} to be a document",
+            what
+        ))),
+    };
+}
+
+pub(crate) fn expect_string(bson: &Bson, what: &str) -> Result<String, SerializationError> {
+    return match bson {
+        Bson::String(string) => Ok(string.clone()),
+        _ => Err(SerializationError::new(format!(
+            "expected {} to be a string",
+            what
+        ))),
+    };
+}
+
+pub(crate) fn expect_bool(bson: &Bson, what: &str) -> Result<bool, SerializationError> {
+    return match bson {
+        Bson::Boolean(value) => Ok(*value),
+        Bson::Int32(value) => Ok(*value != 0),
+        Bson::Int64(value) => Ok(*value != 0),
+        Bson::Double(value) => Ok(*value != 0.0),
+        _ => Err(SerializationError::new(format!(
+            "expected {} to be a boolean",
+            what
+        ))),
+    };
+}
+
+pub(crate) fn field_from_bson<T: FromBson>(
+    document: &Document,
+    key: &str,
+) -> Result<Option<T>, SerializationError> {
+    return document
+        .get(key)
+        .map(|value| T::from_bson(value).map_err(|e| e.at(key)))
+        .transpose();
+}
+
+pub(crate) fn fields_from_bson<T: FromBson>(
+    document: &Document,
+) -> Result<Vec<(String, T)>, SerializationError> {
+    return document
+        .iter()
+        .map(|(key, value)| {
+            T::from_bson(value)
+                .map(|parsed| (key.clone(), parsed))
+                .map_err(|e| e.at(key))
+        })
+        .collect();
+}
+
+pub(crate) fn fields_to_bson<T: ToBson>(fields: &[(String, T)]) -> Document {
+    return fields
+        .iter()
+        .map(|(key, value)| (key.clone(), value.to_bson()))
+        .collect();
+}
@@ -0,0 +1,364 @@
+use bson::{doc, Bson, Document};
+
+use crate::filter::FilterOperator;
+use crate::serialization::filter::{
+    element_predicate_from_document, element_predicates_to_document,
+};
+use crate::serialization::{
+    expect_document, expect_string, field_from_bson, FromBson, SerializationError, ToBson,
+};
+use crate::update::{ArrayUpdateValue, BitOperation, PullCondition, Update, UpdateOperator};
+use crate::values::Value;
+
+fn operator_and_field(operator: &UpdateOperator) -> (&str, &String, Bson) {
+    return match operator {
+        UpdateOperator::Set { field, value } => ("$set", field, value.to_bson()),
+        UpdateOperator::SetOnInsert { field, value } => ("$setOnInsert", field, value.to_bson()),
+        UpdateOperator::Unset { field } => ("$unset", field, Bson::String("".to_string())),
+        UpdateOperator::Inc { field, amount } => ("$inc", field, amount.to_bson()),
+        UpdateOperator::Mul { field, factor } => ("$mul", field, factor.to_bson()),
+        UpdateOperator::Min { field, value } => ("$min", field, value.to_bson()),
+        UpdateOperator::Max { field, value } => ("$max", field, value.to_bson()),
+        UpdateOperator::Rename { field, new_name } => {
+            ("$rename", field, Bson::String(new_name.clone()))
+        }
+        UpdateOperator::CurrentDate {
+            field,
+            as_timestamp,
+        } => (
+            "$currentDate",
+            field,
+            match as_timestamp {
+                true => Bson::Document(doc! { "$type": "timestamp" }),
+                false => Bson::Boolean(true),
+            },
+        ),
+        UpdateOperator::Push {
+            field,
+            values: ArrayUpdateValue::Single(value),
+            position: None,
+            slice: None,
+            sort: None,
+        } => ("$push", field, value.to_bson()),
+        UpdateOperator::Push {
+            field,
+            values,
+            position,
+            slice,
+            sort,
+        } => {
+            let mut push = doc! { "$each": each_to_bson(values) };
+            for (key, modifier) in [("$position", position), ("$slice", slice), ("$sort", sort)] {
+                if let Some(modifier) = modifier {
+                    push.insert(key, modifier.to_bson());
+                }
+            }
+            ("$push", field, Bson::Document(push))
+        }
+        UpdateOperator::AddToSet {
+            field,
+            values: ArrayUpdateValue::Single(value),
+        } => ("$addToSet", field, value.to_bson()),
+        UpdateOperator::AddToSet { field, values } => (
+            "$addToSet",
+            field,
+            Bson::Document(doc! { "$each": each_to_bson(values) }),
+        ),
+        UpdateOperator::Pop { field, first } => {
+            ("$pop", field, Bson::Int32(if *first { -1 } else { 1 }))
+        }
+        UpdateOperator::Pull { field, condition } => (
+            "$pull",
+            field,
+            match condition {
+                PullCondition::Value(value) => value.to_bson(),
+                PullCondition::Predicate(predicate) => match predicate.as_ref() {
+                    FilterOperator::And { predicates, .. } => {
+                        Bson::Document(element_predicates_to_document(predicates))
+                    }
+                    predicate => Bson::Document(element_predicates_to_document(
+                        std::slice::from_ref(predicate),
+                    )),
+                },
+            },
+        ),
+        UpdateOperator::PullAll { field, values } => ("$pullAll", field, values.to_bson()),
+        UpdateOperator::Bit {
+            field,
+            operation,
+            value,
+        } => (
+            "$bit",
+            field,
+            Bson::Document(doc! {
+                match operation {
+                    BitOperation::And => "and",
+                    BitOperation::Or => "or",
+                    BitOperation::Xor => "xor",
+                }: value.to_bson()
+            }),
+        ),
+    };
+}
+
+fn each_to_bson(values: &ArrayUpdateValue) -> Bson {
+    return match values {
+        ArrayUpdateValue::Single(value) => Bson::Array(vec![value.to_bson()]),
+        ArrayUpdateValue::Each(values) => values.to_bson(),
+    };
+}
+
+fn array_update_value_from_bson(
+    argument: &Bson,
+) -> Result<(ArrayUpdateValue, Option<&Document>), SerializationError> {
+    return match argument {
+        Bson::Document(modifiers) if modifiers.contains_key("$each") => Ok((
+            ArrayUpdateValue::Each(field_from_bson(modifiers, "$each")?.unwrap_or_default()),
+            Some(modifiers),
+        )),
+        single => Ok((ArrayUpdateValue::Single(Value::from_bson(single)?), None)),
+    };
+}
+
+fn update_operator_from_bson(
+    operator: &str,
+    field: &String,
+    argument: &Bson,
+) -> Result<UpdateOperator, SerializationError> {
+    let field = field.clone();
+    let update = match operator {
+        "$set" => UpdateOperator::Set {
+            field,
+            value: Value::from_bson(argument)?,
+        },
+        "$setOnInsert" => UpdateOperator::SetOnInsert {
+            field,
+            value: Value::from_bson(argument)?,
+        },
+        "$unset" => UpdateOperator::Unset { field },
+        "$inc" => UpdateOperator::Inc {
+            field,
+            amount: Value::from_bson(argument)?,
+        },
+        "$mul" => UpdateOperator::Mul {
+            field,
+            factor: Value::from_bson(argument)?,
+        },
+        "$min" => UpdateOperator::Min {
+            field,
+            value: Value::from_bson(argument)?,
+        },
+        "$max" => UpdateOperator::Max {
+            field,
+            value: Value::from_bson(argument)?,
+        },
+        "$rename" => UpdateOperator::Rename {
+            field,
+            new_name: expect_string(argument, operator)?,
+        },
+        "$currentDate" => UpdateOperator::CurrentDate {
+            field,
+            as_timestamp: match argument {
+                Bson::Document(current_date) => current_date.get_str("$type") == Ok("timestamp"),
+                _ => false,
+            },
+        },
+        "$push" => {
+            let (values, modifiers) = array_update_value_from_bson(argument)?;
+            let modifier = |key| {
+                modifiers
+                    .map(|modifiers| field_from_bson(modifiers, key))
+                    .transpose()
+                    .map(|modifier| modifier.flatten().map(Box::new))
+            };
+            UpdateOperator::Push {
+                field,
+                values,
+                position: modifier("$position")?,
+                slice: modifier("$slice")?,
+                sort: modifier("$sort")?,
+            }
+        }
+        "$addToSet" => UpdateOperator::AddToSet {
+            field,
+            values: array_update_value_from_bson(argument)?.0,
+        },
+        "$pop" => UpdateOperator::Pop {
+            field,
+            first: matches!(argument, Bson::Int32(-1) | Bson::Int64(-1))
+                || matches!(argument, Bson::Double(direction) if *direction == -1.0),
+        },
+        "$pull" => UpdateOperator::Pull {
+            field,
+            condition: match argument {
+                Bson::Document(condition) => {
+                    PullCondition::Predicate(Box::new(element_predicate_from_document(condition)?))
+                }
+                value => PullCondition::Value(Value::from_bson(value)?),
+            },
+        },
+        "$pullAll" => UpdateOperator::PullAll {
+            field,
+            values: Vec::from_bson(argument)?,
+        },
+        "$bit" => {
+            let bit = expect_document(argument, operator)?;
+            let Some((operation, value)) = bit.iter().next() else {
+                return Err(SerializationError::new(
+                    "$bit requires an operation".to_string(),
+                ));
+            };
+            UpdateOperator::Bit {
+                field,
+                operation: match operation.as_str() {
+                    "and" => BitOperation::And,
+                    "or" => BitOperation::Or,
+                    "xor" => BitOperation::Xor,
+                    other => {
+                        return Err(SerializationError::new(format!(
+                            "unknown bitwise operation {}",
+                            other
+                        )))
+                    }
+                },
+                value: Value::from_bson(value).map_err(|e| e.at(operation))?,
+            }
+        }
+        other => {
+            return Err(SerializationError::new(format!(
+                "unknown update operator {}",
+                other
+            )))
+        }
+    };
+
+    return Ok(update);
+}
+
+impl ToBson for UpdateOperator {
+    fn to_bson(&self) -> Bson {
+        let (operator, field, argument) = operator_and_field(self);
+        return Bson::Document(doc! { operator: { field: argument } });
+    }
+}
+
+impl ToBson for Update {
+    fn to_bson(&self) -> Bson {
+        return match self {
+            Update::Operators(operators) => {
+                let mut update = Document::new();
+                for operator in operators {
+                    let (name, field, argument) = operator_and_field(operator);
+                    if let Ok(fields) = update.get_document_mut(name) {
+                        fields.insert(field, argument);
+                    } else {
+                        update.insert(name, doc! { field: argument });
+                    }
+                }
+                Bson::Document(update)
+            }
+            Update::Pipeline(pipeline) => pipeline.to_bson(),
+        };
+    }
+}
+
+impl FromBson for Update {
+    fn from_bson(bson: &Bson) -> Result<Self, SerializationError> {
+        if let Bson::Array(_) = bson {
+            return Vec::from_bson(bson).map(Update::Pipeline);
+        }
+
+        let mut operators = vec![];
+        for (operator, fields) in expect_document(bson, "an update")? {
+            let fields = expect_document(fields, operator).map_err(|e| e.at(operator))?;
+            for (field, argument) in fields {
+                operators.push(
+                    update_operator_from_bson(operator, field, argument)
+                        .map_err(|e| e.at(field).at(operator))?,
+                );
+            }
+        }
+
+        return Ok(Update::Operators(operators));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{doc, Bson};
+
+    use crate::filter::FilterOperator;
+    use crate::serialization::{FromBson, ToBson};
+    use crate::span::Span;
+    use crate::update::{ArrayUpdateValue, PullCondition, Update, UpdateOperator};
+    use crate::values::Value;
+
+    #[test]
+    fn groups_operators_by_name() {
+        let update = Update::Operators(vec![
+            UpdateOperator::Set {
+                field: "name".to_string(),
+                value: Value::String("mongold".to_string(), Span::default()),
+            },
+            UpdateOperator::Set {
+                field: "age".to_string(),
+                value: Value::Integer(1, Span::default()),
+            },
+            UpdateOperator::Inc {
+                field: "visits".to_string(),
+                amount: Value::Integer(1, Span::default()),
+            },
+        ]);
+
+        assert_eq!(
+            update.to_bson(),
+            Bson::Document(doc! {
+                "$set": { "name": "mongold", "age": 1 },
+                "$inc": { "visits": 1 },
+            })
+        );
+        assert_eq!(Update::from_bson(&update.to_bson()).unwrap(), update);
+    }
+
+    #[test]
+    fn round_trips_array_operators() {
+        let update = Update::Operators(vec![
+            UpdateOperator::Push {
+                field: "scores".to_string(),
+                values: ArrayUpdateValue::Each(vec![
+                    Value::Integer(90, Span::default()),
+                    Value::Integer(92, Span::default()),
+                ]),
+                position: None,
+                slice: Some(Box::new(Value::Integer(-5, Span::default()))),
+                sort: Some(Box::new(Value::Integer(-1, Span::default()))),
+            },
+            UpdateOperator::AddToSet {
+                field: "tags".to_string(),
+                values: ArrayUpdateValue::Single(Value::String("new".to_string(), Span::default())),
+            },
+            UpdateOperator::Pull {
+                field: "votes".to_string(),
+                condition: PullCondition::Predicate(Box::new(
+                    FilterOperator::GreaterThanOrEquals {
+                        field: "".to_string(),
+                        value: Value::Integer(6, Span::default()),
+                        span: Span::default(),
+                    },
+                )),
+            },
+        ]);
+
+        assert_eq!(Update::from_bson(&update.to_bson()).unwrap(), update);
+    }
+
+    #[test]
+    fn reports_the_path_of_invalid_updates() {
+        let error = Update::from_bson(&Bson::Document(doc! {
+            "$rename": { "name": 1 }
+        }))
+        .unwrap_err();
+
+        assert_eq!(error.path, vec!["$rename", "name"]);
+    }
+}
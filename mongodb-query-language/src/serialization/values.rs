@@ -0,0 +1,217 @@
+use bson::oid::ObjectId;
+use bson::spec::BinarySubtype;
+use bson::{Binary, Bson, DateTime, Regex, Timestamp, Uuid};
+
+use crate::serialization::{
+    fields_from_bson, fields_to_bson, FromBson, SerializationError, ToBson,
+};
+use crate::span::Span;
+use crate::values::{Value, ValueType};
+
+pub fn reference_placeholder(name: &String, value_type: &ValueType) -> String {
+    return format!("{{{{{}:{}}}}}", name, value_type.alias());
+}
+
+pub(crate) fn reference_from_placeholder(placeholder: &str) -> Option<Value> {
+    let (name, value_type) = placeholder
+        .strip_prefix("{{")?
+        .strip_suffix("}}")?
+        .rsplit_once(':')?;
+
+    return Some(Value::Reference(
+        name.to_string(),
+        ValueType::from_alias(value_type)?,
+        Span::default(),
+    ));
+}
+
+impl ToBson for Value {
+    fn to_bson(&self) -> Bson {
+        return match self {
+            Value::String(value, _) => Bson::String(value.clone()),
+            Value::Integer(value, _) => Bson::Int32(*value),
+            Value::Int64(value, _) => Bson::Int64(*value),
+            Value::Floating(value, _) => Bson::Double(*value),
+            Value::Decimal128(value, _) => value
+                .parse()
+                .map(Bson::Decimal128)
+                .unwrap_or(Bson::String(value.clone())),
+            Value::Boolean(value, _) => Bson::Boolean(*value),
+            Value::Null(_) => Bson::Null,
+            Value::Date(millis, _) => Bson::DateTime(DateTime::from_millis(*millis)),
+            Value::Object(fields, _) => Bson::Document(fields_to_bson(fields)),
+            Value::Array(values, _) => values.to_bson(),
+            Value::ObjectId(hex, _) => ObjectId::parse_str(hex)
+                .map(Bson::ObjectId)
+                .unwrap_or(Bson::String(hex.clone())),
+            Value::Regex(pattern, options, _) => Bson::RegularExpression(Regex {
+                pattern: pattern.clone(),
+                options: options.clone(),
+            }),
+            Value::Binary(subtype, bytes, _) => Bson::Binary(Binary {
+                subtype: BinarySubtype::from(*subtype),
+                bytes: bytes.clone(),
+            }),
+            Value::Uuid(uuid, _) => Uuid::parse_str(uuid)
+                .map(Bson::from)
+                .unwrap_or(Bson::String(uuid.clone())),
+            Value::Timestamp(time, increment, _) => Bson::Timestamp(Timestamp {
+                time: *time,
+                increment: *increment,
+            }),
+            Value::MinKey(_) => Bson::MinKey,
+            Value::MaxKey(_) => Bson::MaxKey,
+            Value::Reference(name, value_type, _) => {
+                Bson::String(reference_placeholder(name, value_type))
+            }
+        };
+    }
+}
+
+impl FromBson for Value {
+    fn from_bson(bson: &Bson) -> Result<Self, SerializationError> {
+        let span = Span::default();
+        let value = match bson {
+            Bson::String(value) => {
+                reference_from_placeholder(value).unwrap_or(Value::String(value.clone(), span))
+            }
+            Bson::Int32(value) => Value::Integer(*value, span),
+            Bson::Int64(value) => Value::Int64(*value, span),
+            Bson::Double(value) => Value::Floating(*value, span),
+            Bson::Decimal128(value) => Value::Decimal128(value.to_string(), span),
+            Bson::Boolean(value) => Value::Boolean(*value, span),
+            Bson::Null => Value::Null(span),
+            Bson::DateTime(value) => Value::Date(value.timestamp_millis(), span),
+            Bson::Document(document) => Value::Object(fields_from_bson(document)?, span),
+            Bson::Array(_) => Value::Array(Vec::from_bson(bson)?, span),
+            Bson::ObjectId(value) => Value::ObjectId(value.to_hex(), span),
+            Bson::RegularExpression(regex) => {
+                Value::Regex(regex.pattern.clone(), regex.options.clone(), span)
+            }
+            Bson::Binary(binary) if binary.subtype == BinarySubtype::Uuid => binary
+                .to_uuid()
+                .map(|uuid| Value::Uuid(uuid.to_string(), span))
+                .map_err(|e| SerializationError::new(e.to_string()))?,
+            Bson::Binary(binary) => {
+                Value::Binary(u8::from(binary.subtype), binary.bytes.clone(), span)
+            }
+            Bson::Timestamp(timestamp) => {
+                Value::Timestamp(timestamp.time, timestamp.increment, span)
+            }
+            Bson::MinKey => Value::MinKey(span),
+            Bson::MaxKey => Value::MaxKey(span),
+            other => {
+                return Err(SerializationError::new(format!(
+                    "unsupported BSON type {:?}",
+                    other.element_type()
+                )))
+            }
+        };
+
+        return Ok(value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{bson, Bson};
+
+    use crate::serialization::{FromBson, ToBson};
+    use crate::span::Span;
+    use crate::values::{BsonType, Value, ValueType};
+
+    #[test]
+    fn references_are_rendered_as_placeholders() {
+        let value = Value::Reference(
+            "user.getId()".to_string(),
+            ValueType::Of(BsonType::ObjectId),
+            Span::default(),
+        );
+
+        assert_eq!(
+            value.to_bson(),
+            Bson::String("{{user.getId():objectId}}".to_string())
+        );
+        assert_eq!(Value::from_bson(&value.to_bson()).unwrap(), value);
+    }
+
+    #[test]
+    fn values_round_trip_through_bson() {
+        let value = Value::Object(
+            vec![
+                (
+                    "name".to_string(),
+                    Value::String("mongold".to_string(), Span::default()),
+                ),
+                ("age".to_string(), Value::Integer(42, Span::default())),
+                ("ratio".to_string(), Value::Floating(0.5, Span::default())),
+                (
+                    "price".to_string(),
+                    Value::Decimal128("10.25".to_string(), Span::default()),
+                ),
+                (
+                    "createdAt".to_string(),
+                    Value::Date(1697500800000, Span::default()),
+                ),
+                (
+                    "_id".to_string(),
+                    Value::ObjectId("65300b2f8c3a4e0f1c2b3a4d".to_string(), Span::default()),
+                ),
+                ("active".to_string(), Value::Boolean(true, Span::default())),
+                ("deletedAt".to_string(), Value::Null(Span::default())),
+                ("visits".to_string(), Value::Int64(1 << 40, Span::default())),
+                (
+                    "pattern".to_string(),
+                    Value::Regex("^a".to_string(), "i".to_string(), Span::default()),
+                ),
+                (
+                    "payload".to_string(),
+                    Value::Binary(0, vec![1, 2, 3], Span::default()),
+                ),
+                (
+                    "token".to_string(),
+                    Value::Uuid(
+                        "5c1b7d1e-9a3f-4c6b-8e2d-7f0a1b2c3d4e".to_string(),
+                        Span::default(),
+                    ),
+                ),
+                (
+                    "clusterTime".to_string(),
+                    Value::Timestamp(1697500800, 1, Span::default()),
+                ),
+                ("lower".to_string(), Value::MinKey(Span::default())),
+                ("upper".to_string(), Value::MaxKey(Span::default())),
+                (
+                    "tags".to_string(),
+                    Value::Array(
+                        vec![Value::String("a".to_string(), Span::default())],
+                        Span::default(),
+                    ),
+                ),
+            ],
+            Span::default(),
+        );
+
+        assert_eq!(Value::from_bson(&value.to_bson()).unwrap(), value);
+    }
+
+    #[test]
+    fn decimals_keep_all_significant_digits() {
+        let digits = "1234567890123456789012345678901234";
+        let bson: Bson = Bson::Decimal128(digits.parse().unwrap());
+        let value = Value::from_bson(&bson).unwrap();
+
+        assert_eq!(
+            value,
+            Value::Decimal128(digits.to_string(), Span::default())
+        );
+        assert_eq!(value.to_bson(), bson);
+    }
+
+    #[test]
+    fn unsupported_types_report_their_path() {
+        let error = Value::from_bson(&bson!({ "a": { "b": Bson::Undefined } })).unwrap_err();
+
+        assert_eq!(error.path, vec!["a".to_string(), "b".to_string()]);
+    }
+}
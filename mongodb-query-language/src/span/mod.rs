@@ -0,0 +1,106 @@
+use std::hash::{Hash, Hasher};
+
+use tree_sitter::{Node, Point};
+
+// Spans locate a node in its source file but are not part of its meaning: two
+// nodes that only differ in where they were written compare (and hash) equal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Span {
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub start_point: Point,
+    pub end_point: Point,
+}
+
+impl Span {
+    pub fn is_empty(&self) -> bool {
+        return self.start_byte == self.end_byte;
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        return self.start_point <= point && point <= self.end_point;
+    }
+
+    pub fn merge(&self, other: &Span) -> Span {
+        if self.is_empty() {
+            return *other;
+        }
+
+        if other.is_empty() {
+            return *self;
+        }
+
+        let (start_byte, start_point) = if self.start_byte <= other.start_byte {
+            (self.start_byte, self.start_point)
+        } else {
+            (other.start_byte, other.start_point)
+        };
+
+        let (end_byte, end_point) = if self.end_byte >= other.end_byte {
+            (self.end_byte, self.end_point)
+        } else {
+            (other.end_byte, other.end_point)
+        };
+
+        return Span {
+            start_byte,
+            end_byte,
+            start_point,
+            end_point,
+        };
+    }
+}
+
+impl From<Node<'_>> for Span {
+    fn from(node: Node<'_>) -> Self {
+        return Span {
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+            start_point: node.start_position(),
+            end_point: node.end_position(),
+        };
+    }
+}
+
+impl PartialEq for Span {
+    fn eq(&self, _other: &Self) -> bool {
+        return true;
+    }
+}
+
+impl Eq for Span {}
+
+impl Hash for Span {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+#[cfg(test)]
+mod test {
+    use tree_sitter::Point;
+
+    use crate::span::Span;
+
+    #[test]
+    fn merges_spans_into_their_enclosing_range() {
+        let left = Span {
+            start_byte: 4,
+            end_byte: 10,
+            start_point: Point::new(0, 4),
+            end_point: Point::new(0, 10),
+        };
+        let right = Span {
+            start_byte: 12,
+            end_byte: 30,
+            start_point: Point::new(0, 12),
+            end_point: Point::new(1, 8),
+        };
+
+        let merged = left.merge(&right);
+
+        assert_eq!(
+            (merged.start_byte, merged.end_byte, merged.end_point),
+            (4, 30, Point::new(1, 8))
+        );
+        assert_eq!(Span::default().merge(&left).start_byte, 4);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::aggregation::Stage;
+use crate::filter::FilterOperator;
+use crate::values::Value;
+
+#[derive(PartialEq, Debug)]
+pub enum ArrayUpdateValue {
+    Single(Value),
+    Each(Vec<Value>),
+}
+
+#[derive(PartialEq, Debug)]
+pub enum PullCondition {
+    Value(Value),
+    Predicate(Box<FilterOperator>),
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum BitOperation {
+    And,
+    Or,
+    Xor,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum UpdateOperator {
+    Set {
+        field: String,
+        value: Value,
+    },
+    SetOnInsert {
+        field: String,
+        value: Value,
+    },
+    Unset {
+        field: String,
+    },
+    Inc {
+        field: String,
+        amount: Value,
+    },
+    Mul {
+        field: String,
+        factor: Value,
+    },
+    Min {
+        field: String,
+        value: Value,
+    },
+    Max {
+        field: String,
+        value: Value,
+    },
+    Rename {
+        field: String,
+        new_name: String,
+    },
+    CurrentDate {
+        field: String,
+        as_timestamp: bool,
+    },
+    Push {
+        field: String,
+        values: ArrayUpdateValue,
+        position: Option<Box<Value>>,
+        slice: Option<Box<Value>>,
+        sort: Option<Box<Value>>,
+    },
+    AddToSet {
+        field: String,
+        values: ArrayUpdateValue,
+    },
+    Pop {
+        field: String,
+        first: bool,
+    },
+    Pull {
+        field: String,
+        condition: PullCondition,
+    },
+    PullAll {
+        field: String,
+        values: Vec<Value>,
+    },
+    Bit {
+        field: String,
+        operation: BitOperation,
+        value: Value,
+    },
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Update {
+    Operators(Vec<UpdateOperator>),
+    Pipeline(Vec<Stage>),
+}
+
+#[derive(PartialEq, Debug, Default)]
+pub struct UpdateOptions {
+    pub upsert: bool,
+    pub array_filters: Vec<FilterOperator>,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum WriteModel {
+    InsertOne {
+        document: Value,
+    },
+    UpdateOne {
+        predicate: FilterOperator,
+        update: Update,
+        options: UpdateOptions,
+    },
+    UpdateMany {
+        predicate: FilterOperator,
+        update: Update,
+        options: UpdateOptions,
+    },
+    ReplaceOne {
+        predicate: FilterOperator,
+        replacement: Value,
+        upsert: bool,
+    },
+    DeleteOne {
+        predicate: FilterOperator,
+    },
+    DeleteMany {
+        predicate: FilterOperator,
+    },
+}
@@ -1,12 +1,331 @@
-#[derive(PartialEq, Debug)]
+use crate::span::Span;
+
+#[derive(PartialEq, Debug, Clone)]
 pub enum Value {
-    String(String),
-    Integer(i32),
-    Floating(f32),
-    Decimal128(f64),
-    Date(i64),
-    Object(Vec<(String, Value)>),
-    Array(Vec<Value>),
-    ObjectId(String),
-    Reference(String, String),
+    String(String, Span),
+    Integer(i32, Span),
+    Int64(i64, Span),
+    Floating(f64, Span),
+    Decimal128(String, Span),
+    Boolean(bool, Span),
+    Null(Span),
+    Date(i64, Span),
+    Object(Vec<(String, Value)>, Span),
+    Array(Vec<Value>, Span),
+    ObjectId(String, Span),
+    Regex(String, String, Span),
+    Binary(u8, Vec<u8>, Span),
+    Uuid(String, Span),
+    Timestamp(u32, u32, Span),
+    MinKey(Span),
+    MaxKey(Span),
+    Reference(String, ValueType, Span),
+}
+
+impl Value {
+    pub fn span(&self) -> Span {
+        return match self {
+            Value::String(_, span)
+            | Value::Integer(_, span)
+            | Value::Int64(_, span)
+            | Value::Floating(_, span)
+            | Value::Decimal128(_, span)
+            | Value::Boolean(_, span)
+            | Value::Null(span)
+            | Value::Date(_, span)
+            | Value::Object(_, span)
+            | Value::Array(_, span)
+            | Value::ObjectId(_, span)
+            | Value::Regex(_, _, span)
+            | Value::Binary(_, _, span)
+            | Value::Uuid(_, span)
+            | Value::Timestamp(_, _, span)
+            | Value::MinKey(span)
+            | Value::MaxKey(span)
+            | Value::Reference(_, _, span) => *span,
+        };
+    }
+
+    pub fn value_type(&self) -> ValueType {
+        let bson_type = match self {
+            Value::String(..) => BsonType::String,
+            Value::Integer(..) => BsonType::Int,
+            Value::Int64(..) => BsonType::Long,
+            Value::Floating(..) => BsonType::Double,
+            Value::Decimal128(..) => BsonType::Decimal,
+            Value::Boolean(..) => BsonType::Bool,
+            Value::Null(..) => BsonType::Null,
+            Value::Date(..) => BsonType::Date,
+            Value::Object(..) => BsonType::Object,
+            Value::Array(..) => BsonType::Array,
+            Value::ObjectId(..) => BsonType::ObjectId,
+            Value::Regex(..) => BsonType::Regex,
+            Value::Binary(..) | Value::Uuid(..) => BsonType::BinData,
+            Value::Timestamp(..) => BsonType::Timestamp,
+            Value::MinKey(..) => BsonType::MinKey,
+            Value::MaxKey(..) => BsonType::MaxKey,
+            Value::Reference(_, value_type, _) => return value_type.clone(),
+        };
+
+        return ValueType::Of(bson_type);
+    }
+
+    pub fn is_array_reference(&self) -> bool {
+        return matches!(self, Value::Reference(_, ValueType::Of(BsonType::Array), _));
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub enum ValueType {
+    Any,
+    Never,
+    Of(BsonType),
+    OneOf(Vec<BsonType>),
+}
+
+impl ValueType {
+    fn types(&self) -> &[BsonType] {
+        return match self {
+            ValueType::Of(bson_type) => std::slice::from_ref(bson_type),
+            ValueType::OneOf(types) => types,
+            _ => &[],
+        };
+    }
+
+    fn from_types(mut types: Vec<BsonType>) -> ValueType {
+        types.dedup();
+        if types.len() > 1 && types.iter().all(BsonType::is_numeric) {
+            return ValueType::Of(BsonType::Number);
+        }
+
+        return match &types[..] {
+            [] => ValueType::Never,
+            [single] => ValueType::Of(*single),
+            _ => ValueType::OneOf(types),
+        };
+    }
+
+    pub fn join(&self, other: &ValueType) -> ValueType {
+        return match (self, other) {
+            (ValueType::Any, _) | (_, ValueType::Any) => ValueType::Any,
+            (ValueType::Never, other) | (other, ValueType::Never) => other.clone(),
+            _ => {
+                let mut types = self.types().to_vec();
+                for bson_type in other.types() {
+                    if !types.contains(bson_type) {
+                        types.push(*bson_type);
+                    }
+                }
+                ValueType::from_types(types)
+            }
+        };
+    }
+
+    pub fn is_compatible_with(&self, other: &ValueType) -> bool {
+        return match (self, other) {
+            (ValueType::Any, _) | (_, ValueType::Any) => true,
+            (ValueType::Never, _) | (_, ValueType::Never) => false,
+            _ => self
+                .types()
+                .iter()
+                .any(|left| other.types().iter().any(|right| left.overlaps(right))),
+        };
+    }
+
+    pub fn alias(&self) -> String {
+        return match self {
+            ValueType::Any => "any".to_string(),
+            ValueType::Never => "never".to_string(),
+            _ => self
+                .types()
+                .iter()
+                .map(|bson_type| bson_type.alias())
+                .collect::<Vec<&str>>()
+                .join("|"),
+        };
+    }
+
+    pub fn from_alias(alias: &str) -> Option<ValueType> {
+        return match alias {
+            "any" => Some(ValueType::Any),
+            "never" => Some(ValueType::Never),
+            _ => alias
+                .split('|')
+                .map(BsonType::from_alias)
+                .collect::<Option<Vec<BsonType>>>()
+                .map(ValueType::from_types),
+        };
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum BsonType {
+    Double,
+    String,
+    Object,
+    Array,
+    BinData,
+    Undefined,
+    ObjectId,
+    Bool,
+    Date,
+    Null,
+    Regex,
+    DbPointer,
+    JavaScript,
+    Symbol,
+    JavaScriptWithScope,
+    Int,
+    Timestamp,
+    Long,
+    Decimal,
+    MinKey,
+    MaxKey,
+    Number,
+}
+
+const ALL_BSON_TYPES: [BsonType; 22] = [
+    BsonType::Double,
+    BsonType::String,
+    BsonType::Object,
+    BsonType::Array,
+    BsonType::BinData,
+    BsonType::Undefined,
+    BsonType::ObjectId,
+    BsonType::Bool,
+    BsonType::Date,
+    BsonType::Null,
+    BsonType::Regex,
+    BsonType::DbPointer,
+    BsonType::JavaScript,
+    BsonType::Symbol,
+    BsonType::JavaScriptWithScope,
+    BsonType::Int,
+    BsonType::Timestamp,
+    BsonType::Long,
+    BsonType::Decimal,
+    BsonType::MinKey,
+    BsonType::MaxKey,
+    BsonType::Number,
+];
+
+impl BsonType {
+    pub fn alias(&self) -> &'static str {
+        return match self {
+            BsonType::Double => "double",
+            BsonType::String => "string",
+            BsonType::Object => "object",
+            BsonType::Array => "array",
+            BsonType::BinData => "binData",
+            BsonType::Undefined => "undefined",
+            BsonType::ObjectId => "objectId",
+            BsonType::Bool => "bool",
+            BsonType::Date => "date",
+            BsonType::Null => "null",
+            BsonType::Regex => "regex",
+            BsonType::DbPointer => "dbPointer",
+            BsonType::JavaScript => "javascript",
+            BsonType::Symbol => "symbol",
+            BsonType::JavaScriptWithScope => "javascriptWithScope",
+            BsonType::Int => "int",
+            BsonType::Timestamp => "timestamp",
+            BsonType::Long => "long",
+            BsonType::Decimal => "decimal",
+            BsonType::MinKey => "minKey",
+            BsonType::MaxKey => "maxKey",
+            BsonType::Number => "number",
+        };
+    }
+
+    pub fn code(&self) -> Option<i32> {
+        return match self {
+            BsonType::Double => Some(1),
+            BsonType::String => Some(2),
+            BsonType::Object => Some(3),
+            BsonType::Array => Some(4),
+            BsonType::BinData => Some(5),
+            BsonType::Undefined => Some(6),
+            BsonType::ObjectId => Some(7),
+            BsonType::Bool => Some(8),
+            BsonType::Date => Some(9),
+            BsonType::Null => Some(10),
+            BsonType::Regex => Some(11),
+            BsonType::DbPointer => Some(12),
+            BsonType::JavaScript => Some(13),
+            BsonType::Symbol => Some(14),
+            BsonType::JavaScriptWithScope => Some(15),
+            BsonType::Int => Some(16),
+            BsonType::Timestamp => Some(17),
+            BsonType::Long => Some(18),
+            BsonType::Decimal => Some(19),
+            BsonType::MinKey => Some(-1),
+            BsonType::MaxKey => Some(127),
+            BsonType::Number => None,
+        };
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        return matches!(
+            self,
+            BsonType::Double
+                | BsonType::Int
+                | BsonType::Long
+                | BsonType::Decimal
+                | BsonType::Number
+        );
+    }
+
+    pub fn overlaps(&self, other: &BsonType) -> bool {
+        return self == other
+            || (*self == BsonType::Number && other.is_numeric())
+            || (*other == BsonType::Number && self.is_numeric());
+    }
+
+    pub fn from_alias(alias: &str) -> Option<BsonType> {
+        return ALL_BSON_TYPES.into_iter().find(|t| t.alias() == alias);
+    }
+
+    pub fn from_code(code: i32) -> Option<BsonType> {
+        return ALL_BSON_TYPES.into_iter().find(|t| t.code() == Some(code));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::values::{BsonType, ValueType};
+
+    #[test]
+    fn resolves_types_by_alias_and_code() {
+        assert_eq!(BsonType::from_alias("objectId"), Some(BsonType::ObjectId));
+        assert_eq!(BsonType::from_alias("number"), Some(BsonType::Number));
+        assert_eq!(BsonType::from_alias("nope"), None);
+        assert_eq!(BsonType::from_code(2), Some(BsonType::String));
+        assert_eq!(BsonType::from_code(-1), Some(BsonType::MinKey));
+        assert_eq!(BsonType::from_code(127), Some(BsonType::MaxKey));
+    }
+
+    #[test]
+    fn joins_types_into_their_least_upper_bound() {
+        let string = ValueType::Of(BsonType::String);
+        let int = ValueType::Of(BsonType::Int);
+        let long = ValueType::Of(BsonType::Long);
+
+        assert_eq!(int.join(&long), ValueType::Of(BsonType::Number));
+        assert_eq!(
+            string.join(&int),
+            ValueType::OneOf(vec![BsonType::String, BsonType::Int])
+        );
+        assert_eq!(string.join(&ValueType::Never), string);
+        assert_eq!(string.join(&ValueType::Any), ValueType::Any);
+        assert_eq!(ValueType::from_alias("string|int"), Some(string.join(&int)));
+    }
+
+    #[test]
+    fn detects_incompatible_types() {
+        let object_id = ValueType::Of(BsonType::ObjectId);
+
+        assert!(!ValueType::Of(BsonType::String).is_compatible_with(&object_id));
+        assert!(ValueType::Any.is_compatible_with(&object_id));
+        assert!(ValueType::Of(BsonType::Number).is_compatible_with(&ValueType::Of(BsonType::Long)));
+    }
 }
@@ -0,0 +1,558 @@
+use crate::aggregation::{Expression, MergeWhenMatched, ProjectionField, Stage};
+use crate::ddl::IndexDefinition;
+use crate::execution::{Execution, FindOptions, Hint};
+use crate::filter::{FilterOperator, GeoShape};
+use crate::update::{ArrayUpdateValue, PullCondition, Update, UpdateOperator, WriteModel};
+use crate::values::Value;
+
+pub trait Visitor {
+    fn visit_execution(&mut self, execution: &Execution) {
+        visit_execution(self, execution);
+    }
+
+    fn visit_find_options(&mut self, options: &FindOptions) {
+        visit_find_options(self, options);
+    }
+
+    fn visit_filter(&mut self, predicate: &FilterOperator) {
+        visit_filter(self, predicate);
+    }
+
+    fn visit_geo_shape(&mut self, shape: &GeoShape) {
+        visit_geo_shape(self, shape);
+    }
+
+    fn visit_value(&mut self, value: &Value) {
+        visit_value(self, value);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        visit_expression(self, expression);
+    }
+
+    fn visit_stage(&mut self, stage: &Stage) {
+        visit_stage(self, stage);
+    }
+
+    fn visit_projection(&mut self, projection: &ProjectionField) {
+        visit_projection(self, projection);
+    }
+
+    fn visit_update(&mut self, update: &Update) {
+        visit_update(self, update);
+    }
+
+    fn visit_update_operator(&mut self, operator: &UpdateOperator) {
+        visit_update_operator(self, operator);
+    }
+
+    fn visit_write_model(&mut self, model: &WriteModel) {
+        visit_write_model(self, model);
+    }
+}
+
+pub fn visit_execution<V: Visitor + ?Sized>(visitor: &mut V, execution: &Execution) {
+    match execution {
+        Execution::FindOne {
+            predicate, options, ..
+        }
+        | Execution::FindMany {
+            predicate, options, ..
+        } => {
+            visitor.visit_filter(predicate);
+            visitor.visit_find_options(options);
+        }
+        Execution::Aggregate { pipeline, .. } => {
+            pipeline.iter().for_each(|stage| visitor.visit_stage(stage));
+        }
+        Execution::InsertOne { document, .. } => visitor.visit_value(document),
+        Execution::InsertMany { documents, .. } => visitor.visit_value(documents),
+        Execution::UpdateOne {
+            predicate,
+            update,
+            options,
+            ..
+        }
+        | Execution::UpdateMany {
+            predicate,
+            update,
+            options,
+            ..
+        }
+        | Execution::FindOneAndUpdate {
+            predicate,
+            update,
+            options,
+            ..
+        } => {
+            visitor.visit_filter(predicate);
+            visitor.visit_update(update);
+            options
+                .array_filters
+                .iter()
+                .for_each(|filter| visitor.visit_filter(filter));
+        }
+        Execution::ReplaceOne {
+            predicate,
+            replacement,
+            ..
+        }
+        | Execution::FindOneAndReplace {
+            predicate,
+            replacement,
+            ..
+        } => {
+            visitor.visit_filter(predicate);
+            visitor.visit_value(replacement);
+        }
+        Execution::DeleteOne { predicate, .. }
+        | Execution::DeleteMany { predicate, .. }
+        | Execution::FindOneAndDelete { predicate, .. } => visitor.visit_filter(predicate),
+        Execution::BulkWrite { operations, .. } => {
+            operations
+                .iter()
+                .for_each(|model| visitor.visit_write_model(model));
+        }
+        Execution::CreateIndexes { indexes, .. } => indexes
+            .iter()
+            .for_each(|index| visit_index_definition(visitor, index)),
+        Execution::DropIndex { index, .. } => {
+            if let Hint::Keys(keys) = index {
+                visitor.visit_value(keys);
+            }
+        }
+        Execution::CreateCollection { options, .. } => {
+            if let Some(validator) = &options.validator {
+                visitor.visit_filter(validator);
+            }
+            if let Some(clustered_index) = &options.clustered_index {
+                visit_index_definition(visitor, clustered_index);
+            }
+
+            [
+                &options.size_in_bytes,
+                &options.max_documents,
+                &options.expire_after_seconds,
+                &options.collation,
+            ]
+            .into_iter()
+            .flatten()
+            .for_each(|value| visitor.visit_value(value));
+        }
+        Execution::CreateView { pipeline, .. } => {
+            pipeline.iter().for_each(|stage| visitor.visit_stage(stage));
+        }
+        Execution::RunCommand { command, .. } => visitor.visit_value(command),
+    }
+}
+
+fn visit_index_definition<V: Visitor + ?Sized>(visitor: &mut V, index: &IndexDefinition) {
+    visitor.visit_value(&index.keys);
+    if let Some(partial_filter) = &index.options.partial_filter {
+        visitor.visit_filter(partial_filter);
+    }
+
+    [
+        &index.options.expire_after_seconds,
+        &index.options.collation,
+    ]
+    .into_iter()
+    .flatten()
+    .for_each(|value| visitor.visit_value(value));
+}
+
+pub fn visit_find_options<V: Visitor + ?Sized>(visitor: &mut V, options: &FindOptions) {
+    options
+        .projection
+        .iter()
+        .for_each(|(_, projection)| visitor.visit_projection(projection));
+
+    if let Some(Hint::Keys(keys)) = &options.hint {
+        visitor.visit_value(keys);
+    }
+
+    [
+        &options.skip,
+        &options.limit,
+        &options.collation,
+        &options.max_time_ms,
+    ]
+    .into_iter()
+    .flatten()
+    .for_each(|value| visitor.visit_value(value));
+}
+
+pub fn visit_filter<V: Visitor + ?Sized>(visitor: &mut V, predicate: &FilterOperator) {
+    match predicate {
+        FilterOperator::And { predicates, .. }
+        | FilterOperator::Or { predicates, .. }
+        | FilterOperator::Nor { predicates, .. }
+        | FilterOperator::Not { predicates, .. }
+        | FilterOperator::ElemMatch { predicates, .. } => {
+            predicates
+                .iter()
+                .for_each(|predicate| visitor.visit_filter(predicate));
+        }
+        FilterOperator::Equals { value, .. }
+        | FilterOperator::NotEquals { value, .. }
+        | FilterOperator::GreaterThan { value, .. }
+        | FilterOperator::LessThan { value, .. }
+        | FilterOperator::GreaterThanOrEquals { value, .. }
+        | FilterOperator::LessThanOrEquals { value, .. }
+        | FilterOperator::Regex { pattern: value, .. }
+        | FilterOperator::Size { size: value, .. }
+        | FilterOperator::BitsAllSet { mask: value, .. }
+        | FilterOperator::BitsAnySet { mask: value, .. }
+        | FilterOperator::BitsAllClear { mask: value, .. }
+        | FilterOperator::BitsAnyClear { mask: value, .. }
+        | FilterOperator::GeoIntersects {
+            geometry: value, ..
+        }
+        | FilterOperator::JsonSchema { schema: value, .. }
+        | FilterOperator::Text { search: value, .. }
+        | FilterOperator::Where { code: value, .. }
+        | FilterOperator::SampleRate { rate: value, .. } => visitor.visit_value(value),
+        FilterOperator::In { values, .. }
+        | FilterOperator::NotIn { values, .. }
+        | FilterOperator::All { values, .. } => {
+            values.iter().for_each(|value| visitor.visit_value(value));
+        }
+        FilterOperator::Mod {
+            divisor, remainder, ..
+        } => {
+            visitor.visit_value(divisor);
+            visitor.visit_value(remainder);
+        }
+        FilterOperator::GeoWithin { shape, .. } => visitor.visit_geo_shape(shape),
+        FilterOperator::Near {
+            geometry,
+            max_distance,
+            min_distance,
+            ..
+        }
+        | FilterOperator::NearSphere {
+            geometry,
+            max_distance,
+            min_distance,
+            ..
+        } => {
+            visitor.visit_value(geometry);
+            [max_distance, min_distance]
+                .into_iter()
+                .flatten()
+                .for_each(|value| visitor.visit_value(value));
+        }
+        FilterOperator::Expr { expression, .. } => visitor.visit_expression(expression),
+        FilterOperator::Exists { .. }
+        | FilterOperator::Type { .. }
+        | FilterOperator::Comment { .. } => {}
+    }
+}
+
+pub fn visit_geo_shape<V: Visitor + ?Sized>(visitor: &mut V, shape: &GeoShape) {
+    match shape {
+        GeoShape::Geometry(value)
+        | GeoShape::Box(value)
+        | GeoShape::Polygon(value)
+        | GeoShape::Center(value)
+        | GeoShape::CenterSphere(value) => visitor.visit_value(value),
+    }
+}
+
+pub fn visit_value<V: Visitor + ?Sized>(visitor: &mut V, value: &Value) {
+    match value {
+        Value::Object(fields, _) => {
+            fields
+                .iter()
+                .for_each(|(_, value)| visitor.visit_value(value));
+        }
+        Value::Array(values, _) => values.iter().for_each(|value| visitor.visit_value(value)),
+        _ => {}
+    }
+}
+
+pub fn visit_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &Expression) {
+    match expression {
+        Expression::Literal(value) => visitor.visit_value(value),
+        Expression::Object(fields) => {
+            fields
+                .iter()
+                .for_each(|(_, expression)| visitor.visit_expression(expression));
+        }
+        Expression::Array(expressions)
+        | Expression::Operator {
+            arguments: expressions,
+            ..
+        } => {
+            expressions
+                .iter()
+                .for_each(|expression| visitor.visit_expression(expression));
+        }
+        Expression::FieldPath(_) | Expression::Variable(_) => {}
+    }
+}
+
+pub fn visit_stage<V: Visitor + ?Sized>(visitor: &mut V, stage: &Stage) {
+    match stage {
+        Stage::Match { predicate } => visitor.visit_filter(predicate),
+        Stage::Project { fields } => {
+            fields
+                .iter()
+                .for_each(|(_, projection)| visitor.visit_projection(projection));
+        }
+        Stage::Group { id, accumulators } => {
+            visitor.visit_expression(id);
+            accumulators
+                .iter()
+                .for_each(|(_, expression)| visitor.visit_expression(expression));
+        }
+        Stage::Limit { limit: value }
+        | Stage::Skip { skip: value }
+        | Stage::Sample { size: value }
+        | Stage::Search {
+            definition: value, ..
+        }
+        | Stage::Other {
+            definition: value, ..
+        } => visitor.visit_value(value),
+        Stage::Lookup {
+            let_variables,
+            pipeline,
+            ..
+        } => {
+            let_variables
+                .iter()
+                .for_each(|(_, expression)| visitor.visit_expression(expression));
+            pipeline.iter().for_each(|stage| visitor.visit_stage(stage));
+        }
+        Stage::GraphLookup {
+            start_with,
+            max_depth,
+            restrict_search_with_match,
+            ..
+        } => {
+            visitor.visit_expression(start_with);
+            if let Some(max_depth) = max_depth {
+                visitor.visit_value(max_depth);
+            }
+            if let Some(predicate) = restrict_search_with_match {
+                visitor.visit_filter(predicate);
+            }
+        }
+        Stage::Facet { facets } => {
+            facets
+                .iter()
+                .flat_map(|(_, pipeline)| pipeline)
+                .for_each(|stage| visitor.visit_stage(stage));
+        }
+        Stage::AddFields { fields } | Stage::Set { fields } => {
+            fields
+                .iter()
+                .for_each(|(_, expression)| visitor.visit_expression(expression));
+        }
+        Stage::ReplaceRoot { new_root } | Stage::ReplaceWith { new_root } => {
+            visitor.visit_expression(new_root);
+        }
+        Stage::Bucket {
+            group_by,
+            boundaries,
+            default,
+            output,
+        } => {
+            visitor.visit_expression(group_by);
+            boundaries
+                .iter()
+                .chain(default)
+                .for_each(|value| visitor.visit_value(value));
+            output
+                .iter()
+                .for_each(|(_, expression)| visitor.visit_expression(expression));
+        }
+        Stage::Merge {
+            let_variables,
+            when_matched,
+            ..
+        } => {
+            let_variables
+                .iter()
+                .for_each(|(_, expression)| visitor.visit_expression(expression));
+            if let Some(MergeWhenMatched::Pipeline(pipeline)) = when_matched {
+                pipeline.iter().for_each(|stage| visitor.visit_stage(stage));
+            }
+        }
+        Stage::Sort { .. }
+        | Stage::Unwind { .. }
+        | Stage::Unset { .. }
+        | Stage::Count { .. }
+        | Stage::Out { .. } => {}
+    }
+}
+
+pub fn visit_projection<V: Visitor + ?Sized>(visitor: &mut V, projection: &ProjectionField) {
+    match projection {
+        ProjectionField::Computed(expression) => visitor.visit_expression(expression),
+        ProjectionField::Slice { skip, limit } => {
+            if let Some(skip) = skip {
+                visitor.visit_value(skip);
+            }
+            visitor.visit_value(limit);
+        }
+        ProjectionField::ElemMatch(predicate) => visitor.visit_filter(predicate),
+        ProjectionField::Include | ProjectionField::Exclude | ProjectionField::Meta(_) => {}
+    }
+}
+
+pub fn visit_update<V: Visitor + ?Sized>(visitor: &mut V, update: &Update) {
+    match update {
+        Update::Operators(operators) => {
+            operators
+                .iter()
+                .for_each(|operator| visitor.visit_update_operator(operator));
+        }
+        Update::Pipeline(pipeline) => pipeline.iter().for_each(|stage| visitor.visit_stage(stage)),
+    }
+}
+
+pub fn visit_update_operator<V: Visitor + ?Sized>(visitor: &mut V, operator: &UpdateOperator) {
+    match operator {
+        UpdateOperator::Set { value, .. }
+        | UpdateOperator::SetOnInsert { value, .. }
+        | UpdateOperator::Inc { amount: value, .. }
+        | UpdateOperator::Mul { factor: value, .. }
+        | UpdateOperator::Min { value, .. }
+        | UpdateOperator::Max { value, .. }
+        | UpdateOperator::Bit { value, .. } => visitor.visit_value(value),
+        UpdateOperator::Push {
+            values,
+            position,
+            slice,
+            sort,
+            ..
+        } => {
+            visit_array_update_value(visitor, values);
+            [position, slice, sort]
+                .into_iter()
+                .flatten()
+                .for_each(|value| visitor.visit_value(value));
+        }
+        UpdateOperator::AddToSet { values, .. } => visit_array_update_value(visitor, values),
+        UpdateOperator::Pull { condition, .. } => match condition {
+            PullCondition::Value(value) => visitor.visit_value(value),
+            PullCondition::Predicate(predicate) => visitor.visit_filter(predicate),
+        },
+        UpdateOperator::PullAll { values, .. } => {
+            values.iter().for_each(|value| visitor.visit_value(value));
+        }
+        UpdateOperator::Unset { .. }
+        | UpdateOperator::Rename { .. }
+        | UpdateOperator::CurrentDate { .. }
+        | UpdateOperator::Pop { .. } => {}
+    }
+}
+
+fn visit_array_update_value<V: Visitor + ?Sized>(visitor: &mut V, values: &ArrayUpdateValue) {
+    match values {
+        ArrayUpdateValue::Single(value) => visitor.visit_value(value),
+        ArrayUpdateValue::Each(values) => {
+            values.iter().for_each(|value| visitor.visit_value(value))
+        }
+    }
+}
+
+pub fn visit_write_model<V: Visitor + ?Sized>(visitor: &mut V, model: &WriteModel) {
+    match model {
+        WriteModel::InsertOne { document } => visitor.visit_value(document),
+        WriteModel::UpdateOne {
+            predicate,
+            update,
+            options,
+        }
+        | WriteModel::UpdateMany {
+            predicate,
+            update,
+            options,
+        } => {
+            visitor.visit_filter(predicate);
+            visitor.visit_update(update);
+            options
+                .array_filters
+                .iter()
+                .for_each(|filter| visitor.visit_filter(filter));
+        }
+        WriteModel::ReplaceOne {
+            predicate,
+            replacement,
+            ..
+        } => {
+            visitor.visit_filter(predicate);
+            visitor.visit_value(replacement);
+        }
+        WriteModel::DeleteOne { predicate } | WriteModel::DeleteMany { predicate } => {
+            visitor.visit_filter(predicate);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::execution::{Execution, ExecutionContext, ExecutionNamespace, FindOptions};
+    use crate::filter::FilterOperator;
+    use crate::parser::parse_filter;
+    use crate::span::Span;
+    use crate::values::{Value, ValueType};
+    use crate::visit::{visit_filter, Visitor};
+
+    #[derive(Default)]
+    struct References {
+        names: Vec<String>,
+        fields: Vec<String>,
+    }
+
+    impl Visitor for References {
+        fn visit_filter(&mut self, predicate: &FilterOperator) {
+            if let Some(field) = predicate.field() {
+                self.fields.push(field.clone());
+            }
+            visit_filter(self, predicate);
+        }
+
+        fn visit_value(&mut self, value: &Value) {
+            if let Value::Reference(name, _, _) = value {
+                self.names.push(name.clone());
+            }
+        }
+    }
+
+    #[test]
+    fn walks_every_node_reachable_from_an_execution() {
+        let mut predicate =
+            parse_filter(r#"{ $or: [ { a: 1 }, { b: { $in: [2, 3] } } ] }"#).unwrap();
+        if let FilterOperator::Or { predicates, .. } = &mut predicate {
+            predicates.push(FilterOperator::Equals {
+                field: "c".to_string(),
+                value: Value::Reference("id".to_string(), ValueType::Any, Span::default()),
+                span: Span::default(),
+            });
+        }
+        let options = FindOptions {
+            limit: Some(Value::Reference(
+                "limit".to_string(),
+                ValueType::Any,
+                Span::default(),
+            )),
+            ..Default::default()
+        };
+
+        let mut references = References::default();
+        references.visit_execution(&Execution::FindMany {
+            namespace: ExecutionNamespace::empty("users".to_string()),
+            predicate,
+            options,
+            context: ExecutionContext::default(),
+            span: Span::default(),
+        });
+
+        assert_eq!(references.fields, vec!["a", "b", "c"]);
+        assert_eq!(references.names, vec!["id", "limit"]);
+    }
+}
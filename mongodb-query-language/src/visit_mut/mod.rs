@@ -0,0 +1,557 @@
+use crate::aggregation::{Expression, MergeWhenMatched, ProjectionField, Stage};
+use crate::ddl::IndexDefinition;
+use crate::execution::{Execution, FindOptions, Hint};
+use crate::filter::{FilterOperator, GeoShape};
+use crate::update::{ArrayUpdateValue, PullCondition, Update, UpdateOperator, WriteModel};
+use crate::values::Value;
+
+pub trait VisitorMut {
+    fn visit_execution_mut(&mut self, execution: &mut Execution) {
+        visit_execution_mut(self, execution);
+    }
+
+    fn visit_find_options_mut(&mut self, options: &mut FindOptions) {
+        visit_find_options_mut(self, options);
+    }
+
+    fn visit_filter_mut(&mut self, predicate: &mut FilterOperator) {
+        visit_filter_mut(self, predicate);
+    }
+
+    fn visit_geo_shape_mut(&mut self, shape: &mut GeoShape) {
+        visit_geo_shape_mut(self, shape);
+    }
+
+    fn visit_value_mut(&mut self, value: &mut Value) {
+        visit_value_mut(self, value);
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        visit_expression_mut(self, expression);
+    }
+
+    fn visit_stage_mut(&mut self, stage: &mut Stage) {
+        visit_stage_mut(self, stage);
+    }
+
+    fn visit_projection_mut(&mut self, projection: &mut ProjectionField) {
+        visit_projection_mut(self, projection);
+    }
+
+    fn visit_update_mut(&mut self, update: &mut Update) {
+        visit_update_mut(self, update);
+    }
+
+    fn visit_update_operator_mut(&mut self, operator: &mut UpdateOperator) {
+        visit_update_operator_mut(self, operator);
+    }
+
+    fn visit_write_model_mut(&mut self, model: &mut WriteModel) {
+        visit_write_model_mut(self, model);
+    }
+}
+
+pub fn visit_execution_mut<V: VisitorMut + ?Sized>(visitor: &mut V, execution: &mut Execution) {
+    match execution {
+        Execution::FindOne {
+            predicate, options, ..
+        }
+        | Execution::FindMany {
+            predicate, options, ..
+        } => {
+            visitor.visit_filter_mut(predicate);
+            visitor.visit_find_options_mut(options);
+        }
+        Execution::Aggregate { pipeline, .. } => {
+            pipeline
+                .iter_mut()
+                .for_each(|stage| visitor.visit_stage_mut(stage));
+        }
+        Execution::InsertOne { document, .. } => visitor.visit_value_mut(document),
+        Execution::InsertMany { documents, .. } => visitor.visit_value_mut(documents),
+        Execution::UpdateOne {
+            predicate,
+            update,
+            options,
+            ..
+        }
+        | Execution::UpdateMany {
+            predicate,
+            update,
+            options,
+            ..
+        }
+        | Execution::FindOneAndUpdate {
+            predicate,
+            update,
+            options,
+            ..
+        } => {
+            visitor.visit_filter_mut(predicate);
+            visitor.visit_update_mut(update);
+            options
+                .array_filters
+                .iter_mut()
+                .for_each(|filter| visitor.visit_filter_mut(filter));
+        }
+        Execution::ReplaceOne {
+            predicate,
+            replacement,
+            ..
+        }
+        | Execution::FindOneAndReplace {
+            predicate,
+            replacement,
+            ..
+        } => {
+            visitor.visit_filter_mut(predicate);
+            visitor.visit_value_mut(replacement);
+        }
+        Execution::DeleteOne { predicate, .. }
+        | Execution::DeleteMany { predicate, .. }
+        | Execution::FindOneAndDelete { predicate, .. } => visitor.visit_filter_mut(predicate),
+        Execution::BulkWrite { operations, .. } => {
+            operations
+                .iter_mut()
+                .for_each(|model| visitor.visit_write_model_mut(model));
+        }
+        Execution::CreateIndexes { indexes, .. } => indexes
+            .iter_mut()
+            .for_each(|index| visit_index_definition_mut(visitor, index)),
+        Execution::DropIndex { index, .. } => {
+            if let Hint::Keys(keys) = index {
+                visitor.visit_value_mut(keys);
+            }
+        }
+        Execution::CreateCollection { options, .. } => {
+            if let Some(validator) = &mut options.validator {
+                visitor.visit_filter_mut(validator);
+            }
+            if let Some(clustered_index) = &mut options.clustered_index {
+                visit_index_definition_mut(visitor, clustered_index);
+            }
+
+            [
+                &mut options.size_in_bytes,
+                &mut options.max_documents,
+                &mut options.expire_after_seconds,
+                &mut options.collation,
+            ]
+            .into_iter()
+            .flatten()
+            .for_each(|value| visitor.visit_value_mut(value));
+        }
+        Execution::CreateView { pipeline, .. } => {
+            pipeline
+                .iter_mut()
+                .for_each(|stage| visitor.visit_stage_mut(stage));
+        }
+        Execution::RunCommand { command, .. } => visitor.visit_value_mut(command),
+    }
+}
+
+fn visit_index_definition_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    index: &mut IndexDefinition,
+) {
+    visitor.visit_value_mut(&mut index.keys);
+    if let Some(partial_filter) = &mut index.options.partial_filter {
+        visitor.visit_filter_mut(partial_filter);
+    }
+
+    [
+        &mut index.options.expire_after_seconds,
+        &mut index.options.collation,
+    ]
+    .into_iter()
+    .flatten()
+    .for_each(|value| visitor.visit_value_mut(value));
+}
+
+pub fn visit_find_options_mut<V: VisitorMut + ?Sized>(visitor: &mut V, options: &mut FindOptions) {
+    options
+        .projection
+        .iter_mut()
+        .for_each(|(_, projection)| visitor.visit_projection_mut(projection));
+
+    if let Some(Hint::Keys(keys)) = &mut options.hint {
+        visitor.visit_value_mut(keys);
+    }
+
+    [
+        &mut options.skip,
+        &mut options.limit,
+        &mut options.collation,
+        &mut options.max_time_ms,
+    ]
+    .into_iter()
+    .flatten()
+    .for_each(|value| visitor.visit_value_mut(value));
+}
+
+pub fn visit_filter_mut<V: VisitorMut + ?Sized>(visitor: &mut V, predicate: &mut FilterOperator) {
+    match predicate {
+        FilterOperator::And { predicates, .. }
+        | FilterOperator::Or { predicates, .. }
+        | FilterOperator::Nor { predicates, .. }
+        | FilterOperator::Not { predicates, .. }
+        | FilterOperator::ElemMatch { predicates, .. } => {
+            predicates
+                .iter_mut()
+                .for_each(|predicate| visitor.visit_filter_mut(predicate));
+        }
+        FilterOperator::Equals { value, .. }
+        | FilterOperator::NotEquals { value, .. }
+        | FilterOperator::GreaterThan { value, .. }
+        | FilterOperator::LessThan { value, .. }
+        | FilterOperator::GreaterThanOrEquals { value, .. }
+        | FilterOperator::LessThanOrEquals { value, .. }
+        | FilterOperator::Regex { pattern: value, .. }
+        | FilterOperator::Size { size: value, .. }
+        | FilterOperator::BitsAllSet { mask: value, .. }
+        | FilterOperator::BitsAnySet { mask: value, .. }
+        | FilterOperator::BitsAllClear { mask: value, .. }
+        | FilterOperator::BitsAnyClear { mask: value, .. }
+        | FilterOperator::GeoIntersects {
+            geometry: value, ..
+        }
+        | FilterOperator::JsonSchema { schema: value, .. }
+        | FilterOperator::Text { search: value, .. }
+        | FilterOperator::Where { code: value, .. }
+        | FilterOperator::SampleRate { rate: value, .. } => visitor.visit_value_mut(value),
+        FilterOperator::In { values, .. }
+        | FilterOperator::NotIn { values, .. }
+        | FilterOperator::All { values, .. } => {
+            values
+                .iter_mut()
+                .for_each(|value| visitor.visit_value_mut(value));
+        }
+        FilterOperator::Mod {
+            divisor, remainder, ..
+        } => {
+            visitor.visit_value_mut(divisor);
+            visitor.visit_value_mut(remainder);
+        }
+        FilterOperator::GeoWithin { shape, .. } => visitor.visit_geo_shape_mut(shape),
+        FilterOperator::Near {
+            geometry,
+            max_distance,
+            min_distance,
+            ..
+        }
+        | FilterOperator::NearSphere {
+            geometry,
+            max_distance,
+            min_distance,
+            ..
+        } => {
+            visitor.visit_value_mut(geometry);
+            [max_distance, min_distance]
+                .into_iter()
+                .flatten()
+                .for_each(|value| visitor.visit_value_mut(value));
+        }
+        FilterOperator::Expr { expression, .. } => visitor.visit_expression_mut(expression),
+        FilterOperator::Exists { .. }
+        | FilterOperator::Type { .. }
+        | FilterOperator::Comment { .. } => {}
+    }
+}
+
+pub fn visit_geo_shape_mut<V: VisitorMut + ?Sized>(visitor: &mut V, shape: &mut GeoShape) {
+    match shape {
+        GeoShape::Geometry(value)
+        | GeoShape::Box(value)
+        | GeoShape::Polygon(value)
+        | GeoShape::Center(value)
+        | GeoShape::CenterSphere(value) => visitor.visit_value_mut(value),
+    }
+}
+
+pub fn visit_value_mut<V: VisitorMut + ?Sized>(visitor: &mut V, value: &mut Value) {
+    match value {
+        Value::Object(fields, _) => {
+            fields
+                .iter_mut()
+                .for_each(|(_, value)| visitor.visit_value_mut(value));
+        }
+        Value::Array(values, _) => values
+            .iter_mut()
+            .for_each(|value| visitor.visit_value_mut(value)),
+        _ => {}
+    }
+}
+
+pub fn visit_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut Expression) {
+    match expression {
+        Expression::Literal(value) => visitor.visit_value_mut(value),
+        Expression::Object(fields) => {
+            fields
+                .iter_mut()
+                .for_each(|(_, expression)| visitor.visit_expression_mut(expression));
+        }
+        Expression::Array(expressions)
+        | Expression::Operator {
+            arguments: expressions,
+            ..
+        } => {
+            expressions
+                .iter_mut()
+                .for_each(|expression| visitor.visit_expression_mut(expression));
+        }
+        Expression::FieldPath(_) | Expression::Variable(_) => {}
+    }
+}
+
+pub fn visit_stage_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stage: &mut Stage) {
+    match stage {
+        Stage::Match { predicate } => visitor.visit_filter_mut(predicate),
+        Stage::Project { fields } => {
+            fields
+                .iter_mut()
+                .for_each(|(_, projection)| visitor.visit_projection_mut(projection));
+        }
+        Stage::Group { id, accumulators } => {
+            visitor.visit_expression_mut(id);
+            accumulators
+                .iter_mut()
+                .for_each(|(_, expression)| visitor.visit_expression_mut(expression));
+        }
+        Stage::Limit { limit: value }
+        | Stage::Skip { skip: value }
+        | Stage::Sample { size: value }
+        | Stage::Search {
+            definition: value, ..
+        }
+        | Stage::Other {
+            definition: value, ..
+        } => visitor.visit_value_mut(value),
+        Stage::Lookup {
+            let_variables,
+            pipeline,
+            ..
+        } => {
+            let_variables
+                .iter_mut()
+                .for_each(|(_, expression)| visitor.visit_expression_mut(expression));
+            pipeline
+                .iter_mut()
+                .for_each(|stage| visitor.visit_stage_mut(stage));
+        }
+        Stage::GraphLookup {
+            start_with,
+            max_depth,
+            restrict_search_with_match,
+            ..
+        } => {
+            visitor.visit_expression_mut(start_with);
+            if let Some(max_depth) = max_depth {
+                visitor.visit_value_mut(max_depth);
+            }
+            if let Some(predicate) = restrict_search_with_match {
+                visitor.visit_filter_mut(predicate);
+            }
+        }
+        Stage::Facet { facets } => {
+            facets
+                .iter_mut()
+                .flat_map(|(_, pipeline)| pipeline)
+                .for_each(|stage| visitor.visit_stage_mut(stage));
+        }
+        Stage::AddFields { fields } | Stage::Set { fields } => {
+            fields
+                .iter_mut()
+                .for_each(|(_, expression)| visitor.visit_expression_mut(expression));
+        }
+        Stage::ReplaceRoot { new_root } | Stage::ReplaceWith { new_root } => {
+            visitor.visit_expression_mut(new_root);
+        }
+        Stage::Bucket {
+            group_by,
+            boundaries,
+            default,
+            output,
+        } => {
+            visitor.visit_expression_mut(group_by);
+            boundaries
+                .iter_mut()
+                .chain(default)
+                .for_each(|value| visitor.visit_value_mut(value));
+            output
+                .iter_mut()
+                .for_each(|(_, expression)| visitor.visit_expression_mut(expression));
+        }
+        Stage::Merge {
+            let_variables,
+            when_matched,
+            ..
+        } => {
+            let_variables
+                .iter_mut()
+                .for_each(|(_, expression)| visitor.visit_expression_mut(expression));
+            if let Some(MergeWhenMatched::Pipeline(pipeline)) = when_matched {
+                pipeline
+                    .iter_mut()
+                    .for_each(|stage| visitor.visit_stage_mut(stage));
+            }
+        }
+        Stage::Sort { .. }
+        | Stage::Unwind { .. }
+        | Stage::Unset { .. }
+        | Stage::Count { .. }
+        | Stage::Out { .. } => {}
+    }
+}
+
+pub fn visit_projection_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    projection: &mut ProjectionField,
+) {
+    match projection {
+        ProjectionField::Computed(expression) => visitor.visit_expression_mut(expression),
+        ProjectionField::Slice { skip, limit } => {
+            if let Some(skip) = skip {
+                visitor.visit_value_mut(skip);
+            }
+            visitor.visit_value_mut(limit);
+        }
+        ProjectionField::ElemMatch(predicate) => visitor.visit_filter_mut(predicate),
+        ProjectionField::Include | ProjectionField::Exclude | ProjectionField::Meta(_) => {}
+    }
+}
+
+pub fn visit_update_mut<V: VisitorMut + ?Sized>(visitor: &mut V, update: &mut Update) {
+    match update {
+        Update::Operators(operators) => {
+            operators
+                .iter_mut()
+                .for_each(|operator| visitor.visit_update_operator_mut(operator));
+        }
+        Update::Pipeline(pipeline) => pipeline
+            .iter_mut()
+            .for_each(|stage| visitor.visit_stage_mut(stage)),
+    }
+}
+
+pub fn visit_update_operator_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    operator: &mut UpdateOperator,
+) {
+    match operator {
+        UpdateOperator::Set { value, .. }
+        | UpdateOperator::SetOnInsert { value, .. }
+        | UpdateOperator::Inc { amount: value, .. }
+        | UpdateOperator::Mul { factor: value, .. }
+        | UpdateOperator::Min { value, .. }
+        | UpdateOperator::Max { value, .. }
+        | UpdateOperator::Bit { value, .. } => visitor.visit_value_mut(value),
+        UpdateOperator::Push {
+            values,
+            position,
+            slice,
+            sort,
+            ..
+        } => {
+            visit_array_update_value_mut(visitor, values);
+            [position, slice, sort]
+                .into_iter()
+                .flatten()
+                .for_each(|value| visitor.visit_value_mut(value));
+        }
+        UpdateOperator::AddToSet { values, .. } => visit_array_update_value_mut(visitor, values),
+        UpdateOperator::Pull { condition, .. } => match condition {
+            PullCondition::Value(value) => visitor.visit_value_mut(value),
+            PullCondition::Predicate(predicate) => visitor.visit_filter_mut(predicate),
+        },
+        UpdateOperator::PullAll { values, .. } => {
+            values
+                .iter_mut()
+                .for_each(|value| visitor.visit_value_mut(value));
+        }
+        UpdateOperator::Unset { .. }
+        | UpdateOperator::Rename { .. }
+        | UpdateOperator::CurrentDate { .. }
+        | UpdateOperator::Pop { .. } => {}
+    }
+}
+
+fn visit_array_update_value_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    values: &mut ArrayUpdateValue,
+) {
+    match values {
+        ArrayUpdateValue::Single(value) => visitor.visit_value_mut(value),
+        ArrayUpdateValue::Each(values) => values
+            .iter_mut()
+            .for_each(|value| visitor.visit_value_mut(value)),
+    }
+}
+
+pub fn visit_write_model_mut<V: VisitorMut + ?Sized>(visitor: &mut V, model: &mut WriteModel) {
+    match model {
+        WriteModel::InsertOne { document } => visitor.visit_value_mut(document),
+        WriteModel::UpdateOne {
+            predicate,
+            update,
+            options,
+        }
+        | WriteModel::UpdateMany {
+            predicate,
+            update,
+            options,
+        } => {
+            visitor.visit_filter_mut(predicate);
+            visitor.visit_update_mut(update);
+            options
+                .array_filters
+                .iter_mut()
+                .for_each(|filter| visitor.visit_filter_mut(filter));
+        }
+        WriteModel::ReplaceOne {
+            predicate,
+            replacement,
+            ..
+        } => {
+            visitor.visit_filter_mut(predicate);
+            visitor.visit_value_mut(replacement);
+        }
+        WriteModel::DeleteOne { predicate } | WriteModel::DeleteMany { predicate } => {
+            visitor.visit_filter_mut(predicate);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::filter::FilterOperator;
+    use crate::parser::parse_filter;
+    use crate::visit_mut::{visit_filter_mut, VisitorMut};
+
+    struct Prefix(&'static str);
+
+    impl VisitorMut for Prefix {
+        fn visit_filter_mut(&mut self, predicate: &mut FilterOperator) {
+            match predicate {
+                FilterOperator::Equals { field, .. } | FilterOperator::In { field, .. } => {
+                    *field = format!("{}.{}", self.0, field);
+                }
+                _ => {}
+            }
+            visit_filter_mut(self, predicate);
+        }
+    }
+
+    #[test]
+    fn rewrites_nodes_in_place() {
+        let mut predicate =
+            parse_filter(r#"{ $or: [ { name: "a" }, { age: { $in: [1, 2] } } ] }"#).unwrap();
+
+        Prefix("user").visit_filter_mut(&mut predicate);
+
+        assert_eq!(
+            predicate,
+            parse_filter(r#"{ $or: [ { "user.name": "a" }, { "user.age": { $in: [1, 2] } } ] }"#)
+                .unwrap()
+        );
+    }
+}
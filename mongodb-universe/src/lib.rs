@@ -1,4 +1,5 @@
 #![feature(custom_test_frameworks)]
 pub mod connection;
 pub mod insights;
+pub mod planner;
 pub mod schema;
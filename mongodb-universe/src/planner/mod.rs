@@ -0,0 +1,821 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use mongodb_query_language::aggregation::{ProjectionField, SortDirection, Stage};
+use mongodb_query_language::execution::Execution;
+use mongodb_query_language::filter::FilterOperator;
+use mongodb_query_language::values::Value;
+
+use crate::schema::{DeclaredIndexes, SchemaRegularIndex, SchemaRegularIndexPredicate};
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum PlanStage {
+    CollectionScan,
+    IndexScan { index: String },
+    TextScan { index: String },
+    Or { branches: Vec<QueryPlan> },
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum PlanReason {
+    PrefixNotConstrained { field: String },
+    UnsupportedIndexKey { field: String, kind: String },
+    InexactBounds { field: String, operator: String },
+    SortNotProvided { field: String },
+    EsrViolation { field: String, precedes: String },
+    NotCovered { field: String },
+    TextIndexRequired,
+    OrBranchNotIndexed,
+    NotSelected { winner: String },
+}
+
+impl Display for PlanReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            PlanReason::PrefixNotConstrained { field } => write!(
+                f,
+                "the first key `{}` is not constrained by the query",
+                field
+            ),
+            PlanReason::UnsupportedIndexKey { field, kind } => write!(
+                f,
+                "the key `{}` is a {} key that can not be simulated",
+                field, kind
+            ),
+            PlanReason::InexactBounds { field, operator } => write!(
+                f,
+                "`{}` on `{}` scans loose index bounds and fetches every candidate",
+                operator, field
+            ),
+            PlanReason::SortNotProvided { field } => write!(
+                f,
+                "the index does not provide the order of `{}`, so results are sorted in memory",
+                field
+            ),
+            PlanReason::EsrViolation { field, precedes } => write!(
+                f,
+                "the range key `{}` precedes `{}`, keys should follow the equality, sort, range order",
+                field, precedes
+            ),
+            PlanReason::NotCovered { field } => {
+                write!(f, "`{}` is not part of the index, documents are fetched", field)
+            }
+            PlanReason::TextIndexRequired => write!(f, "$text requires a text index"),
+            PlanReason::OrBranchNotIndexed => write!(
+                f,
+                "a branch of $or can not use any index, so the whole query scans the collection"
+            ),
+            PlanReason::NotSelected { winner } => {
+                write!(f, "`{}` constrains more of the query", winner)
+            }
+        };
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct IndexCandidate {
+    pub index: String,
+    pub usable: bool,
+    pub bounded_keys: Vec<String>,
+    pub provides_sort: bool,
+    pub covers: bool,
+    pub esr_compliant: bool,
+    pub reasons: Vec<PlanReason>,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct QueryPlan {
+    pub stage: PlanStage,
+    pub in_memory_sort: bool,
+    pub covered: bool,
+    pub reasons: Vec<PlanReason>,
+    pub candidates: Vec<IndexCandidate>,
+}
+
+impl QueryPlan {
+    pub fn candidate(&self, index: &str) -> Option<&IndexCandidate> {
+        return self
+            .candidates
+            .iter()
+            .find(|candidate| candidate.index == index);
+    }
+
+    pub fn explain(&self, index: &str) -> Option<String> {
+        let candidate = self.candidate(index)?;
+        let reasons: Vec<String> = candidate.reasons.iter().map(|r| r.to_string()).collect();
+        if reasons.is_empty() {
+            return None;
+        }
+
+        let verb = match &self.stage {
+            PlanStage::IndexScan { index: chosen } if chosen == index => "uses",
+            _ => "will not use",
+        };
+
+        return Some(format!(
+            "this query {} `{}` because {}",
+            verb,
+            index,
+            reasons.join(", ")
+        ));
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, PartialOrd, Ord)]
+enum Bounds {
+    Point,
+    Points,
+    Range,
+    Inexact,
+    None,
+}
+
+struct FieldBounds {
+    bounds: Bounds,
+    operator: String,
+}
+
+pub fn plan(
+    predicate: &FilterOperator,
+    sort: &[(String, SortDirection)],
+    projection: &[(String, ProjectionField)],
+    indexes: &[SchemaRegularIndex],
+) -> QueryPlan {
+    let predicates = match predicate {
+        FilterOperator::And { predicates, .. } => predicates.iter().collect(),
+        predicate => vec![predicate],
+    };
+
+    if let [FilterOperator::Or { predicates, .. }] = &predicates[..] {
+        return plan_or(predicates, sort, indexes);
+    }
+
+    if predicates
+        .iter()
+        .any(|p| matches!(p, FilterOperator::Text { .. }))
+    {
+        return plan_text(indexes);
+    }
+
+    let mut fields: HashMap<&String, FieldBounds> = HashMap::new();
+    for predicate in &predicates {
+        let Some(field) = predicate.field().or_else(|| negated_field(predicate)) else {
+            continue;
+        };
+
+        let bounds = bounds_of(predicate);
+        if fields
+            .get(field)
+            .map(|b| bounds.bounds < b.bounds)
+            .unwrap_or(true)
+        {
+            fields.insert(field, bounds);
+        }
+    }
+
+    let mut candidates: Vec<IndexCandidate> = indexes
+        .iter()
+        .map(|index| candidate(index, &fields, sort, projection))
+        .collect();
+
+    let winner = candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| candidate.usable)
+        .max_by_key(|(idx, candidate)| score(&indexes[*idx], candidate))
+        .map(|(_, candidate)| candidate.clone());
+
+    let Some(winner) = winner else {
+        return QueryPlan {
+            stage: PlanStage::CollectionScan,
+            in_memory_sort: !sort.is_empty(),
+            covered: false,
+            reasons: vec![],
+            candidates,
+        };
+    };
+
+    for candidate in candidates.iter_mut() {
+        if candidate.usable && candidate.index != winner.index {
+            candidate.reasons.push(PlanReason::NotSelected {
+                winner: winner.index.clone(),
+            });
+        }
+    }
+
+    return QueryPlan {
+        stage: PlanStage::IndexScan {
+            index: winner.index.clone(),
+        },
+        in_memory_sort: !winner.provides_sort,
+        covered: winner.covers,
+        reasons: winner.reasons.clone(),
+        candidates,
+    };
+}
+
+pub fn plan_execution(
+    execution: &Execution,
+    live: &[SchemaRegularIndex],
+    declared: &DeclaredIndexes,
+) -> Option<QueryPlan> {
+    let mut indexes = live.to_vec();
+    for index in declared.of(execution.namespace()) {
+        if !indexes.iter().any(|existing| existing.name == index.name) {
+            indexes.push(index.clone());
+        }
+    }
+
+    return match execution {
+        Execution::FindOne {
+            predicate, options, ..
+        }
+        | Execution::FindMany {
+            predicate, options, ..
+        } => Some(plan(
+            predicate,
+            &options.sort,
+            &options.projection,
+            &indexes,
+        )),
+        Execution::Aggregate { pipeline, .. } => match pipeline.first() {
+            Some(Stage::Match { predicate }) => Some(plan(predicate, &[], &[], &indexes)),
+            _ => None,
+        },
+        Execution::UpdateOne { predicate, .. }
+        | Execution::UpdateMany { predicate, .. }
+        | Execution::ReplaceOne { predicate, .. }
+        | Execution::DeleteOne { predicate, .. }
+        | Execution::DeleteMany { predicate, .. }
+        | Execution::FindOneAndUpdate { predicate, .. }
+        | Execution::FindOneAndReplace { predicate, .. }
+        | Execution::FindOneAndDelete { predicate, .. } => {
+            Some(plan(predicate, &[], &[], &indexes))
+        }
+        _ => None,
+    };
+}
+
+fn plan_or(
+    predicates: &[FilterOperator],
+    sort: &[(String, SortDirection)],
+    indexes: &[SchemaRegularIndex],
+) -> QueryPlan {
+    let branches: Vec<QueryPlan> = predicates
+        .iter()
+        .map(|predicate| plan(predicate, &[], &[], indexes))
+        .collect();
+
+    if branches
+        .iter()
+        .any(|branch| branch.stage == PlanStage::CollectionScan)
+    {
+        return QueryPlan {
+            stage: PlanStage::CollectionScan,
+            in_memory_sort: !sort.is_empty(),
+            covered: false,
+            reasons: vec![PlanReason::OrBranchNotIndexed],
+            candidates: vec![],
+        };
+    }
+
+    return QueryPlan {
+        stage: PlanStage::Or { branches },
+        in_memory_sort: !sort.is_empty(),
+        covered: false,
+        reasons: vec![],
+        candidates: vec![],
+    };
+}
+
+fn plan_text(indexes: &[SchemaRegularIndex]) -> QueryPlan {
+    let text_index = indexes.iter().find(|index| {
+        index
+            .predicates
+            .iter()
+            .any(|key| matches!(key, SchemaRegularIndexPredicate::Text(_)))
+    });
+
+    return match text_index {
+        Some(index) => QueryPlan {
+            stage: PlanStage::TextScan {
+                index: index.name.clone(),
+            },
+            in_memory_sort: false,
+            covered: false,
+            reasons: vec![],
+            candidates: vec![],
+        },
+        None => QueryPlan {
+            stage: PlanStage::CollectionScan,
+            in_memory_sort: false,
+            covered: false,
+            reasons: vec![PlanReason::TextIndexRequired],
+            candidates: vec![],
+        },
+    };
+}
+
+fn negated_field(predicate: &FilterOperator) -> Option<&String> {
+    return match predicate {
+        FilterOperator::Not { predicates, .. } => predicates.first()?.field(),
+        _ => None,
+    };
+}
+
+fn bounds_of(predicate: &FilterOperator) -> FieldBounds {
+    let (bounds, operator) = match predicate {
+        FilterOperator::Equals {
+            value: Value::Regex(pattern, options, _),
+            ..
+        } => (regex_bounds(pattern, options), "$regex"),
+        FilterOperator::Equals { .. } => (Bounds::Point, "$eq"),
+        FilterOperator::In { values, .. }
+            if values.len() == 1 && !values[0].is_array_reference() =>
+        {
+            (Bounds::Point, "$in")
+        }
+        FilterOperator::In { .. } => (Bounds::Points, "$in"),
+        FilterOperator::All { .. } => (Bounds::Point, "$all"),
+        FilterOperator::GreaterThan { .. } => (Bounds::Range, "$gt"),
+        FilterOperator::GreaterThanOrEquals { .. } => (Bounds::Range, "$gte"),
+        FilterOperator::LessThan { .. } => (Bounds::Range, "$lt"),
+        FilterOperator::LessThanOrEquals { .. } => (Bounds::Range, "$lte"),
+        FilterOperator::Exists { exists: true, .. } => (Bounds::Range, "$exists"),
+        FilterOperator::Regex {
+            pattern: Value::String(pattern, _),
+            options,
+            ..
+        } => (
+            regex_bounds(pattern, options.as_deref().unwrap_or("")),
+            "$regex",
+        ),
+        FilterOperator::Regex { .. } => (Bounds::Inexact, "$regex"),
+        FilterOperator::NotEquals { .. } => (Bounds::Inexact, "$ne"),
+        FilterOperator::NotIn { .. } => (Bounds::Inexact, "$nin"),
+        FilterOperator::Exists { exists: false, .. } => (Bounds::Inexact, "$exists"),
+        FilterOperator::Not { .. } => (Bounds::Inexact, "$not"),
+        FilterOperator::Type { .. } => (Bounds::Inexact, "$type"),
+        FilterOperator::Mod { .. } => (Bounds::Inexact, "$mod"),
+        FilterOperator::ElemMatch { .. } => (Bounds::Inexact, "$elemMatch"),
+        FilterOperator::BitsAllSet { .. }
+        | FilterOperator::BitsAnySet { .. }
+        | FilterOperator::BitsAllClear { .. }
+        | FilterOperator::BitsAnyClear { .. } => (Bounds::Inexact, "bitwise operators"),
+        FilterOperator::Size { .. } => (Bounds::None, "$size"),
+        _ => (Bounds::None, "geospatial operators"),
+    };
+
+    return FieldBounds {
+        bounds,
+        operator: operator.to_string(),
+    };
+}
+
+// Only case sensitive regular expressions anchored at the start of the string
+// translate into tight index bounds.
+fn regex_bounds(pattern: &str, options: &str) -> Bounds {
+    if pattern.starts_with('^') && !pattern.starts_with("^.") && !options.contains('i') {
+        return Bounds::Range;
+    }
+
+    return Bounds::Inexact;
+}
+
+fn key_field(key: &SchemaRegularIndexPredicate) -> &String {
+    return match key {
+        SchemaRegularIndexPredicate::Ascending(field)
+        | SchemaRegularIndexPredicate::Descending(field)
+        | SchemaRegularIndexPredicate::Text(field)
+        | SchemaRegularIndexPredicate::Unknown(field, _) => field,
+    };
+}
+
+fn key_direction(key: &SchemaRegularIndexPredicate) -> Option<SortDirection> {
+    return match key {
+        SchemaRegularIndexPredicate::Ascending(_) => Some(SortDirection::Ascending),
+        SchemaRegularIndexPredicate::Descending(_) => Some(SortDirection::Descending),
+        _ => None,
+    };
+}
+
+fn candidate(
+    index: &SchemaRegularIndex,
+    fields: &HashMap<&String, FieldBounds>,
+    sort: &[(String, SortDirection)],
+    projection: &[(String, ProjectionField)],
+) -> IndexCandidate {
+    let mut reasons = vec![];
+
+    let mut bounded_keys = vec![];
+    for key in &index.predicates {
+        let field = key_field(key);
+        let kind = match key {
+            SchemaRegularIndexPredicate::Ascending(_)
+            | SchemaRegularIndexPredicate::Descending(_) => None,
+            SchemaRegularIndexPredicate::Text(_) => Some("text".to_string()),
+            SchemaRegularIndexPredicate::Unknown(_, kind) => Some(kind.clone()),
+        };
+
+        if let Some(kind) = kind {
+            reasons.push(PlanReason::UnsupportedIndexKey {
+                field: field.clone(),
+                kind,
+            });
+            break;
+        }
+
+        match fields.get(field) {
+            Some(bounds) if bounds.bounds != Bounds::None => {
+                if bounds.bounds == Bounds::Inexact {
+                    reasons.push(PlanReason::InexactBounds {
+                        field: field.clone(),
+                        operator: bounds.operator.clone(),
+                    });
+                }
+                bounded_keys.push(field.clone());
+            }
+            _ => break,
+        }
+    }
+
+    let provides_sort = provides_sort(index, fields, sort);
+    if !provides_sort {
+        if let Some((field, _)) = sort
+            .iter()
+            .find(|(field, _)| !index.predicates.iter().any(|key| key_field(key) == field))
+        {
+            reasons.push(PlanReason::SortNotProvided {
+                field: field.clone(),
+            });
+        } else if let Some((field, _)) = sort.first() {
+            reasons.push(PlanReason::SortNotProvided {
+                field: field.clone(),
+            });
+        }
+    }
+
+    let usable = !bounded_keys.is_empty() || (!sort.is_empty() && provides_sort);
+    if !usable {
+        if let Some(first) = index.predicates.first() {
+            if key_direction(first).is_some() {
+                reasons.insert(
+                    0,
+                    PlanReason::PrefixNotConstrained {
+                        field: key_field(first).clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    let esr = esr_violations(index, fields, sort);
+    let esr_compliant = esr.is_empty();
+    reasons.extend(esr);
+
+    let covers = match uncovered_field(index, fields, projection) {
+        Some(field) => {
+            if !projection.is_empty() {
+                reasons.push(PlanReason::NotCovered { field });
+            }
+            false
+        }
+        None => true,
+    };
+
+    return IndexCandidate {
+        index: index.name.clone(),
+        usable,
+        bounded_keys,
+        provides_sort,
+        covers,
+        esr_compliant,
+        reasons,
+    };
+}
+
+// An index provides a sort when the sort keys follow its keys in order, all in
+// the same or all in the opposite direction, skipping keys pinned to a single
+// value by an equality.
+fn provides_sort(
+    index: &SchemaRegularIndex,
+    fields: &HashMap<&String, FieldBounds>,
+    sort: &[(String, SortDirection)],
+) -> bool {
+    let mut position = 0;
+    let mut reversed = None;
+
+    for key in &index.predicates {
+        let Some((field, direction)) = sort.get(position) else {
+            break;
+        };
+
+        if key_field(key) == field {
+            let Some(key_direction) = key_direction(key) else {
+                return false;
+            };
+            if *direction == SortDirection::TextScore {
+                return false;
+            }
+
+            let is_reversed = key_direction != *direction;
+            if *reversed.get_or_insert(is_reversed) != is_reversed {
+                return false;
+            }
+            position += 1;
+        } else if !matches!(
+            fields.get(key_field(key)).map(|b| b.bounds),
+            Some(Bounds::Point)
+        ) {
+            return false;
+        }
+    }
+
+    return position == sort.len();
+}
+
+fn esr_violations(
+    index: &SchemaRegularIndex,
+    fields: &HashMap<&String, FieldBounds>,
+    sort: &[(String, SortDirection)],
+) -> Vec<PlanReason> {
+    let roles: Vec<(&String, u8)> = index
+        .predicates
+        .iter()
+        .map(key_field)
+        .filter_map(|field| {
+            let role = match fields.get(field).map(|b| b.bounds) {
+                Some(Bounds::Point | Bounds::Points) => 0,
+                _ if sort.iter().any(|(sorted, _)| sorted == field) => 1,
+                Some(Bounds::Range | Bounds::Inexact) => 2,
+                _ => return None,
+            };
+            Some((field, role))
+        })
+        .collect();
+
+    let mut violations = vec![];
+    for (idx, (field, role)) in roles.iter().enumerate() {
+        if *role != 2 {
+            continue;
+        }
+
+        if let Some((precedes, _)) = roles[idx + 1..].iter().find(|(_, other)| other < role) {
+            violations.push(PlanReason::EsrViolation {
+                field: field.to_string(),
+                precedes: precedes.to_string(),
+            });
+        }
+    }
+
+    return violations;
+}
+
+fn uncovered_field(
+    index: &SchemaRegularIndex,
+    fields: &HashMap<&String, FieldBounds>,
+    projection: &[(String, ProjectionField)],
+) -> Option<String> {
+    let indexed = |field: &String| index.predicates.iter().any(|key| key_field(key) == field);
+
+    let included: Vec<&String> = projection
+        .iter()
+        .filter(|(_, projected)| *projected != ProjectionField::Exclude)
+        .map(|(field, _)| field)
+        .collect();
+    if included.is_empty() {
+        return Some("*".to_string());
+    }
+
+    let excludes_id = projection
+        .iter()
+        .any(|(field, projected)| field == "_id" && *projected == ProjectionField::Exclude);
+    let id = "_id".to_string();
+    if !excludes_id && !indexed(&id) {
+        return Some(id);
+    }
+
+    for (field, projected) in projection {
+        if *projected != ProjectionField::Exclude
+            && (*projected != ProjectionField::Include || !indexed(field))
+        {
+            return Some(field.clone());
+        }
+    }
+
+    return fields
+        .keys()
+        .find(|field| !indexed(field))
+        .map(|field| field.to_string());
+}
+
+fn score(index: &SchemaRegularIndex, candidate: &IndexCandidate) -> (usize, bool, bool, bool, i64) {
+    return (
+        candidate.bounded_keys.len(),
+        candidate.provides_sort,
+        candidate.covers,
+        candidate.esr_compliant,
+        -(index.predicates.len() as i64),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use dialect_interface::FileResource;
+    use dialect_java_driver::Java;
+    use mongodb_query_language::aggregation::{ProjectionField, SortDirection};
+    use mongodb_query_language::ddl::{IndexDefinition, IndexOptions};
+    use mongodb_query_language::execution::Execution;
+    use mongodb_query_language::parser::{parse, parse_filter};
+    use mongodb_query_language::values::Value;
+
+    use crate::planner::{plan, plan_execution, PlanReason, PlanStage};
+    use crate::schema::SchemaRegularIndexPredicate::{Ascending, Descending};
+    use crate::schema::{DeclaredIndexes, SchemaRegularIndex};
+
+    fn indexes() -> Vec<SchemaRegularIndex> {
+        return vec![
+            SchemaRegularIndex {
+                name: "_id_".to_string(),
+                predicates: vec![Ascending("_id".to_string())],
+            },
+            SchemaRegularIndex {
+                name: "status_1_createdAt_-1".to_string(),
+                predicates: vec![
+                    Ascending("status".to_string()),
+                    Descending("createdAt".to_string()),
+                ],
+            },
+            SchemaRegularIndex {
+                name: "age_1_name_1".to_string(),
+                predicates: vec![Ascending("age".to_string()), Ascending("name".to_string())],
+            },
+        ];
+    }
+
+    #[test]
+    fn scans_the_collection_when_no_prefix_is_constrained() {
+        let filter = parse_filter(r#"{ name: "mongold" }"#).unwrap();
+
+        let plan = plan(&filter, &[], &[], &indexes());
+
+        assert_eq!(plan.stage, PlanStage::CollectionScan);
+        assert_eq!(
+            plan.explain("age_1_name_1").unwrap(),
+            "this query will not use `age_1_name_1` because the first key `age` is not constrained by the query"
+        );
+    }
+
+    #[test]
+    fn picks_the_index_that_also_provides_the_sort() {
+        let filter = parse_filter(r#"{ status: "A" }"#).unwrap();
+        let sort = vec![("createdAt".to_string(), SortDirection::Ascending)];
+
+        let plan = plan(&filter, &sort, &[], &indexes());
+
+        assert_eq!(
+            plan.stage,
+            PlanStage::IndexScan {
+                index: "status_1_createdAt_-1".to_string()
+            }
+        );
+        assert!(!plan.in_memory_sort);
+        assert!(!plan.covered);
+    }
+
+    #[test]
+    fn reports_esr_violations_and_in_memory_sorts() {
+        let filter = parse_filter(r#"{ age: { $gt: 18 } }"#).unwrap();
+        let sort = vec![("name".to_string(), SortDirection::Ascending)];
+
+        let plan = plan(&filter, &sort, &[], &indexes());
+        let candidate = plan.candidate("age_1_name_1").unwrap();
+
+        assert!(plan.in_memory_sort);
+        assert!(!candidate.esr_compliant);
+        assert!(candidate.reasons.contains(&PlanReason::EsrViolation {
+            field: "age".to_string(),
+            precedes: "name".to_string()
+        }));
+    }
+
+    #[test]
+    fn detects_covered_queries() {
+        let filter = parse_filter(r#"{ age: 42, name: { $regex: "^mon" } }"#).unwrap();
+        let projection = vec![
+            ("_id".to_string(), ProjectionField::Exclude),
+            ("name".to_string(), ProjectionField::Include),
+        ];
+
+        let plan = plan(&filter, &[], &projection, &indexes());
+
+        assert_eq!(
+            plan.stage,
+            PlanStage::IndexScan {
+                index: "age_1_name_1".to_string()
+            }
+        );
+        assert!(plan.covered);
+    }
+
+    #[test]
+    fn requires_every_or_branch_to_be_indexed() {
+        let indexed = parse_filter(r#"{ $or: [ { status: "A" }, { age: 1 } ] }"#).unwrap();
+        let unindexed = parse_filter(r#"{ $or: [ { status: "A" }, { name: "a" } ] }"#).unwrap();
+
+        assert!(matches!(
+            plan(&indexed, &[], &[], &indexes()).stage,
+            PlanStage::Or { .. }
+        ));
+        assert_eq!(
+            plan(&unindexed, &[], &[], &indexes()).reasons,
+            vec![PlanReason::OrBranchNotIndexed]
+        );
+    }
+
+    #[test]
+    fn plans_against_indexes_declared_in_code() {
+        let declared = IndexDefinition {
+            keys: parse::<Value>(r#"{ "owner": 1, "createdAt": -1 }"#).unwrap(),
+            options: IndexOptions::default(),
+        };
+        let filter = parse_filter(r#"{ owner: "ana" }"#).unwrap();
+
+        let plan = plan(
+            &filter,
+            &[],
+            &[],
+            &[SchemaRegularIndex::declared(&declared)],
+        );
+
+        assert_eq!(
+            plan.stage,
+            PlanStage::IndexScan {
+                index: "owner_1_createdAt_-1".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn plans_extracted_queries_against_indexes_created_in_the_codebase() {
+        let migrations = r#"
+        public class Migrations {
+            private final MongoDatabase database = client.getDatabase("shop");
+
+            public void migrate() {
+                MongoCollection<Document> orders = database.getCollection("orders");
+                orders.createIndex(Indexes.ascending("status"));
+                orders.createIndex(Indexes.compoundIndex(Indexes.ascending("owner"), Indexes.descending("createdAt")));
+                orders.dropIndex("status_1");
+            }
+        }
+        "#;
+        let repository = r#"
+        public class OrderRepository {
+            private final MongoCollection<Document> orders = client.getDatabase("shop").getCollection("orders");
+
+            public List<Document> byOwner(String owner) {
+                return orders.find(Filters.eq("owner", owner)).into(new ArrayList<>());
+            }
+
+            public List<Document> byStatus(String status) {
+                return orders.find(Filters.eq("status", status)).into(new ArrayList<>());
+            }
+        }
+        "#;
+
+        let java = Java::new();
+        let extractor = Java::extractor();
+        let executions: Vec<Execution> = [migrations, repository]
+            .into_iter()
+            .flat_map(|code| {
+                let file = FileResource::new(&code.to_string(), 0, Rc::clone(&java));
+                let extraction = extractor.extract(&file.borrow()).unwrap();
+                extraction.executions
+            })
+            .collect();
+        let declared = DeclaredIndexes::collect(&executions);
+        let live = vec![SchemaRegularIndex {
+            name: "_id_".to_string(),
+            predicates: vec![Ascending("_id".to_string())],
+        }];
+
+        let plans: Vec<PlanStage> = executions
+            .iter()
+            .filter_map(|execution| plan_execution(execution, &live, &declared))
+            .map(|plan| plan.stage)
+            .collect();
+
+        assert_eq!(
+            plans,
+            vec![
+                PlanStage::IndexScan {
+                    index: "owner_1_createdAt_-1".to_string()
+                },
+                PlanStage::CollectionScan,
+            ]
+        );
+    }
+}
@@ -1,9 +1,14 @@
+use std::collections::HashMap;
 use std::error::Error;
 
 use mongodb::bson::Document;
 use mongodb::Namespace;
+use mongodb_query_language::ddl::{IndexDefinition, IndexOptions};
+use mongodb_query_language::evaluation::{matching, EvaluationError};
+use mongodb_query_language::execution::{Execution, ExecutionNamespace, Hint};
+use mongodb_query_language::filter::FilterOperator;
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Debug, Clone)]
 pub enum SchemaRegularIndexPredicate {
     Ascending(String),
     Descending(String),
@@ -11,18 +16,126 @@ pub enum SchemaRegularIndexPredicate {
     Unknown(String, String),
 }
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Debug, Clone)]
 pub struct SchemaRegularIndex {
     pub name: String,
     pub predicates: Vec<SchemaRegularIndexPredicate>,
 }
 
+impl SchemaRegularIndex {
+    pub fn declared(index: &IndexDefinition) -> SchemaRegularIndex {
+        let predicates = index
+            .key_pattern()
+            .into_iter()
+            .map(|(field, kind)| match kind.as_str() {
+                "1" => SchemaRegularIndexPredicate::Ascending(field),
+                "-1" => SchemaRegularIndexPredicate::Descending(field),
+                "text" => SchemaRegularIndexPredicate::Text(field),
+                _ => SchemaRegularIndexPredicate::Unknown(field, kind),
+            })
+            .collect();
+
+        return SchemaRegularIndex {
+            name: index.name(),
+            predicates,
+        };
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct DeclaredIndexes {
+    namespaces: HashMap<(Option<String>, String), Vec<SchemaRegularIndex>>,
+}
+
+impl DeclaredIndexes {
+    pub fn collect<'a>(executions: impl IntoIterator<Item = &'a Execution>) -> DeclaredIndexes {
+        let mut result = DeclaredIndexes::default();
+        for execution in executions {
+            result.apply(execution);
+        }
+
+        return result;
+    }
+
+    pub fn apply(&mut self, execution: &Execution) {
+        let namespace = execution.namespace();
+        let Some(collection) = &namespace.collection else {
+            return;
+        };
+        let key = (namespace.database.clone(), collection.clone());
+
+        match execution {
+            Execution::CreateIndexes { indexes, .. } => {
+                for index in indexes {
+                    self.declare(&key, SchemaRegularIndex::declared(index));
+                }
+            }
+            Execution::CreateCollection { options, .. } => {
+                if let Some(index) = &options.clustered_index {
+                    self.declare(&key, SchemaRegularIndex::declared(index));
+                }
+            }
+            Execution::DropIndex { index, .. } => {
+                let Some(declared) = self.namespaces.get_mut(&key) else {
+                    return;
+                };
+
+                match index {
+                    Hint::Name(name) if name == "*" => declared.clear(),
+                    Hint::Name(name) => declared.retain(|index| &index.name != name),
+                    Hint::Keys(keys) => {
+                        let dropped = SchemaRegularIndex::declared(&IndexDefinition {
+                            keys: keys.clone(),
+                            options: IndexOptions::default(),
+                        });
+                        declared.retain(|index| index.predicates != dropped.predicates)
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn of(&self, namespace: &ExecutionNamespace) -> Vec<&SchemaRegularIndex> {
+        let Some(collection) = &namespace.collection else {
+            return vec![];
+        };
+
+        return self
+            .namespaces
+            .iter()
+            .filter(|((database, declared_collection), _)| {
+                declared_collection == collection
+                    && (database.is_none()
+                        || namespace.database.is_none()
+                        || database == &namespace.database)
+            })
+            .flat_map(|(_, indexes)| indexes.iter())
+            .collect();
+    }
+
+    fn declare(&mut self, key: &(Option<String>, String), index: SchemaRegularIndex) {
+        let declared = self.namespaces.entry(key.clone()).or_default();
+        declared.retain(|existing| existing.name != index.name);
+        declared.push(index);
+    }
+}
+
 pub struct Schema {
     pub regular_indexes: Vec<SchemaRegularIndex>,
     pub samples: Vec<Document>,
     pub normalized: Document,
 }
 
+impl Schema {
+    pub fn matching_samples(
+        &self,
+        predicate: &FilterOperator,
+    ) -> Result<Vec<&Document>, EvaluationError> {
+        return matching(predicate, &self.samples);
+    }
+}
+
 pub trait InferSchema {
     fn infer_schema(&self, namespace: &Namespace) -> Result<Schema, Box<dyn Error + Send + Sync>>;
 }